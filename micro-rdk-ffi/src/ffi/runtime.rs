@@ -286,9 +286,14 @@ pub unsafe extern "C" fn viam_server_start(ctx: *mut viam_server_context) -> via
         #[cfg(not(target_os = "espidf"))]
         {
             use micro_rdk::common::conn::network::ExternallyManagedNetwork;
-            match local_ip_address::local_ip().expect("error parsing local IP") {
-                std::net::IpAddr::V4(ip) => ExternallyManagedNetwork::new(ip),
-                _ => panic!("oops expected ipv4"),
+            // the IPv4 address is left unspecified on IPv6 only networks
+            let ip = match local_ip_address::local_ip() {
+                Ok(std::net::IpAddr::V4(ip)) => ip,
+                _ => std::net::Ipv4Addr::UNSPECIFIED,
+            };
+            match local_ip_address::local_ipv6() {
+                Ok(std::net::IpAddr::V6(ipv6)) => ExternallyManagedNetwork::new(ip).with_ipv6(ipv6),
+                _ => ExternallyManagedNetwork::new(ip),
             }
         }
         #[cfg(target_os = "espidf")]
@@ -376,7 +381,9 @@ pub unsafe extern "C" fn viam_server_start(ctx: *mut viam_server_context) -> via
         builder.build(
             NativeH2Connector::default(),
             Executor::new(),
-            NativeMdns::new("".to_owned(), network.get_ip()).unwrap(),
+            NativeMdns::new("".to_owned(), network.get_ip())
+                .unwrap()
+                .with_ipv6(network.get_ipv6()),
             Box::new(network),
         )
     };
@@ -13,7 +13,7 @@ mod native {
                 server::WebRtcConfiguration,
                 viam::ViamServerBuilder,
             },
-            credentials_storage::{RAMStorage, RobotConfigurationStorage, RobotCredentials},
+            credentials_storage::{RobotConfigurationStorage, RobotCredentials},
             exec::Executor,
             log::initialize_logger,
             provisioning::server::ProvisioningInfo,
@@ -22,23 +22,49 @@ mod native {
         },
         native::{
             certificate::WebRtcCertificate, conn::mdns::NativeMdns, dtls::NativeDtls,
-            tcp::NativeH2Connector,
+            file_storage::FileStorage, tcp::NativeH2Connector,
         },
     };
 
+    const ADDRESS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+    // the IPv4 address is left unspecified on IPv6 only networks
+    fn local_addresses() -> (std::net::Ipv4Addr, Option<std::net::Ipv6Addr>) {
+        let ip = match local_ip_address::local_ip() {
+            Ok(std::net::IpAddr::V4(ip)) => ip,
+            _ => std::net::Ipv4Addr::UNSPECIFIED,
+        };
+        let ipv6 = match local_ip_address::local_ipv6() {
+            Ok(std::net::IpAddr::V6(ipv6)) => Some(ipv6),
+            _ => None,
+        };
+        (ip, ipv6)
+    }
+
     pub(crate) fn main_native() {
         initialize_logger::<env_logger::Logger>();
 
         log::info!("micro-rdk-server started (native)");
 
-        let network = match local_ip_address::local_ip().expect("error parsing local IP") {
-            std::net::IpAddr::V4(ip) => ExternallyManagedNetwork::new(ip),
-            _ => panic!("oops expected ipv4"),
+        let (ip, ipv6) = local_addresses();
+        let network = match ipv6 {
+            Some(ipv6) => ExternallyManagedNetwork::new(ip).with_ipv6(ipv6),
+            None => ExternallyManagedNetwork::new(ip),
         };
+        // the addresses are polled so WebRTC sessions and mDNS follow a change of address
+        let watched = network.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(ADDRESS_POLL_INTERVAL);
+            let (ip, ipv6) = local_addresses();
+            watched.set_addresses(ip, ipv6);
+        });
 
         let registry = Box::<ComponentRegistry>::default();
 
-        let storage = RAMStorage::new();
+        // credentials and the last machine config are kept on disk so the server can restart
+        // without being provisioned again, and boot from its cached config while offline
+        let storage = FileStorage::open_default().expect("failed to open storage directory");
+        log::info!("using storage directory {:?}", storage.path());
 
         // At runtime, if the program does not detect credentials or configs in storage,
         // it will try to load statically compiled values.
@@ -74,7 +100,9 @@ mod native {
         let dtls = Box::new(NativeDtls::new(webrtc_certs.clone()));
         let webrtc_config = WebRtcConfiguration::new(webrtc_certs, dtls);
         let mut builder = ViamServerBuilder::new(storage);
-        let mdns = NativeMdns::new("".to_string(), network.get_ip()).unwrap();
+        let mdns = NativeMdns::new("".to_string(), network.get_ip())
+            .unwrap()
+            .with_ipv6(network.get_ipv6());
         builder
             .with_http2_server(NativeH2Connector::default(), 12346)
             .with_webrtc_configuration(webrtc_config)
@@ -0,0 +1,363 @@
+//! Aggregation of the readings produced by a `DataCollector` over a time window. When a
+//! capture method has an "aggregation" section, raw readings are not stored; instead one
+//! `SensorData` summarizing each window is. Every numeric field is replaced by a struct
+//! holding the configured functions of the values it took during the window, e.g.
+//! `{"readings": {"temp": 21.5}}` becomes `{"readings": {"temp": {"min": 20.0, "max": 23.0,
+//! "mean": 21.4, "last": 21.5}}, "sample_count": 600}`.
+//! Other fields keep the value of the last reading of the window.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::google::protobuf::{value::Kind as ValueKind, Struct, Timestamp, Value};
+use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData, SensorMetadata};
+
+use super::config::{AttributeError, Kind};
+
+/// Name of the field of aggregated readings holding the number of readings of the window
+pub const SAMPLE_COUNT_KEY: &str = "sample_count";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationFunction {
+    Min,
+    Max,
+    Mean,
+    Last,
+}
+
+impl AggregationFunction {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Mean => "mean",
+            Self::Last => "last",
+        }
+    }
+}
+
+impl TryFrom<&Kind> for AggregationFunction {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        let name: &str = value.try_into()?;
+        match name {
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "mean" => Ok(Self::Mean),
+            "last" => Ok(Self::Last),
+            _ => Err(AttributeError::ValidationError(format!(
+                "unknown aggregation function {}",
+                name
+            ))),
+        }
+    }
+}
+
+/// An AggregationConfig is a representation of the "aggregation" section of a capture
+/// method, "window_s" is the length of the window in seconds and "functions" defaults to
+/// all supported functions
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregationConfig {
+    pub window: Duration,
+    pub functions: Vec<AggregationFunction>,
+}
+
+impl TryFrom<&Kind> for AggregationConfig {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        let window_s: f64 = value
+            .get("window_s")?
+            .ok_or(AttributeError::KeyNotFound("window_s".to_string()))?
+            .try_into()?;
+        if window_s <= 0.0 {
+            return Err(AttributeError::ValidationError(
+                "aggregation window must be positive".to_string(),
+            ));
+        }
+        let functions: Vec<AggregationFunction> = match value.get("functions")? {
+            Some(functions) => functions.try_into()?,
+            None => vec![
+                AggregationFunction::Min,
+                AggregationFunction::Max,
+                AggregationFunction::Mean,
+                AggregationFunction::Last,
+            ],
+        };
+        if functions.is_empty() {
+            return Err(AttributeError::ValidationError(
+                "at least one aggregation function is required".to_string(),
+            ));
+        }
+        Ok(Self {
+            window: Duration::from_secs_f64(window_s),
+            functions,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct FieldStats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: usize,
+    last: f64,
+}
+
+impl FieldStats {
+    fn new(value: f64) -> Self {
+        Self {
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+            last: value,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+        self.last = value;
+    }
+
+    fn value(&self, function: AggregationFunction) -> f64 {
+        match function {
+            AggregationFunction::Min => self.min,
+            AggregationFunction::Max => self.max,
+            AggregationFunction::Mean => self.sum / self.count as f64,
+            AggregationFunction::Last => self.last,
+        }
+    }
+}
+
+fn to_duration(ts: Option<&Timestamp>) -> Duration {
+    ts.map(|ts| Duration::new(ts.seconds as u64, ts.nanos as u32))
+        .unwrap_or_default()
+}
+
+/// An Aggregator accumulates the readings of the current window and produces the
+/// aggregated reading once the window elapsed
+#[derive(Debug)]
+pub(crate) struct Aggregator {
+    config: AggregationConfig,
+    window_metadata: Option<SensorMetadata>,
+    last_data: Struct,
+    stats: HashMap<Vec<String>, FieldStats>,
+    sample_count: usize,
+}
+
+impl Aggregator {
+    pub(crate) fn new(config: AggregationConfig) -> Self {
+        Self {
+            config,
+            window_metadata: None,
+            last_data: Struct::default(),
+            stats: HashMap::new(),
+            sample_count: 0,
+        }
+    }
+
+    fn accumulate(&mut self, fields: &HashMap<String, Value>, path: &mut Vec<String>) {
+        for (key, value) in fields {
+            path.push(key.clone());
+            match value.kind.as_ref() {
+                Some(ValueKind::NumberValue(n)) => match self.stats.get_mut(path.as_slice()) {
+                    Some(stats) => stats.add(*n),
+                    None => {
+                        self.stats.insert(path.clone(), FieldStats::new(*n));
+                    }
+                },
+                Some(ValueKind::StructValue(s)) => self.accumulate(&s.fields, path),
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+
+    fn aggregate(&self, fields: &HashMap<String, Value>, path: &mut Vec<String>) -> Struct {
+        let fields = fields
+            .iter()
+            .map(|(key, value)| {
+                path.push(key.clone());
+                let kind = match (value.kind.as_ref(), self.stats.get(path.as_slice())) {
+                    (Some(ValueKind::NumberValue(_)), Some(stats)) => {
+                        Some(ValueKind::StructValue(Struct {
+                            fields: self
+                                .config
+                                .functions
+                                .iter()
+                                .map(|f| {
+                                    (
+                                        f.name().to_string(),
+                                        Value {
+                                            kind: Some(ValueKind::NumberValue(stats.value(*f))),
+                                        },
+                                    )
+                                })
+                                .collect(),
+                        }))
+                    }
+                    (Some(ValueKind::StructValue(s)), _) => {
+                        Some(ValueKind::StructValue(self.aggregate(&s.fields, path)))
+                    }
+                    (kind, _) => kind.cloned(),
+                };
+                path.pop();
+                (key.clone(), Value { kind })
+            })
+            .collect();
+        Struct { fields }
+    }
+
+    /// Adds a reading to the current window, returns the aggregated reading when the window
+    /// elapsed. Readings without structured data are passed through untouched.
+    pub(crate) fn add(&mut self, reading: SensorData) -> Option<SensorData> {
+        let data = match reading.data {
+            Some(Data::Struct(data)) => data,
+            _ => return Some(reading),
+        };
+        let metadata = reading.metadata.unwrap_or_default();
+        self.accumulate(&data.fields, &mut vec![]);
+        self.last_data = data;
+        self.sample_count += 1;
+        let window_metadata = self.window_metadata.get_or_insert(metadata.clone());
+        // the aggregated reading spans from the request of the first reading of the window
+        // to the reception of the last one
+        window_metadata.time_received = metadata.time_received;
+        if to_duration(metadata.time_requested.as_ref())
+            .saturating_sub(to_duration(window_metadata.time_requested.as_ref()))
+            < self.config.window
+        {
+            return None;
+        }
+        let mut aggregated = self.aggregate(&self.last_data.fields, &mut vec![]);
+        aggregated.fields.insert(
+            SAMPLE_COUNT_KEY.to_string(),
+            Value {
+                kind: Some(ValueKind::NumberValue(self.sample_count as f64)),
+            },
+        );
+        let metadata = self.window_metadata.take();
+        self.stats.clear();
+        self.sample_count = 0;
+        Some(SensorData {
+            metadata,
+            data: Some(Data::Struct(aggregated)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use super::{AggregationConfig, AggregationFunction, Aggregator, SAMPLE_COUNT_KEY};
+    use crate::common::config::{AttributeError, Kind};
+    use crate::google::protobuf::{value::Kind as ValueKind, Struct, Timestamp, Value};
+    use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData, SensorMetadata};
+
+    fn reading(secs: i64, value: f64) -> SensorData {
+        let readings = Struct {
+            fields: HashMap::from([(
+                "temp".to_string(),
+                Value {
+                    kind: Some(ValueKind::NumberValue(value)),
+                },
+            )]),
+        };
+        SensorData {
+            metadata: Some(SensorMetadata {
+                time_requested: Some(Timestamp {
+                    seconds: secs,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            }),
+            data: Some(Data::Struct(Struct {
+                fields: HashMap::from([(
+                    "readings".to_string(),
+                    Value {
+                        kind: Some(ValueKind::StructValue(readings)),
+                    },
+                )]),
+            })),
+        }
+    }
+
+    fn number(s: &Struct, key: &str) -> f64 {
+        match s.fields.get(key).and_then(|v| v.kind.as_ref()) {
+            Some(ValueKind::NumberValue(n)) => *n,
+            _ => panic!("field {} was not a number", key),
+        }
+    }
+
+    #[test_log::test]
+    fn test_aggregation_config() -> Result<(), AttributeError> {
+        let kind = Kind::StructValue(HashMap::from([
+            ("window_s".to_string(), Kind::NumberValue(60.0)),
+            (
+                "functions".to_string(),
+                Kind::VecValue(vec![Kind::StringValue("mean".to_string())]),
+            ),
+        ]));
+        let conf = AggregationConfig::try_from(&kind)?;
+        assert_eq!(conf.window, Duration::from_secs(60));
+        assert_eq!(conf.functions, vec![AggregationFunction::Mean]);
+
+        let kind = Kind::StructValue(HashMap::from([(
+            "window_s".to_string(),
+            Kind::NumberValue(0.0),
+        )]));
+        assert!(matches!(
+            AggregationConfig::try_from(&kind),
+            Err(AttributeError::ValidationError(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_aggregator() {
+        let mut aggregator = Aggregator::new(AggregationConfig {
+            window: Duration::from_secs(3),
+            functions: vec![
+                AggregationFunction::Min,
+                AggregationFunction::Max,
+                AggregationFunction::Mean,
+                AggregationFunction::Last,
+            ],
+        });
+        assert!(aggregator.add(reading(10, 4.0)).is_none());
+        assert!(aggregator.add(reading(11, 1.0)).is_none());
+        assert!(aggregator.add(reading(12, 2.0)).is_none());
+        let aggregated = aggregator.add(reading(13, 5.0)).unwrap();
+        assert_eq!(
+            aggregated.metadata.unwrap().time_requested.unwrap().seconds,
+            10
+        );
+        let Some(Data::Struct(data)) = aggregated.data else {
+            panic!("expected struct data")
+        };
+        assert_eq!(number(&data, SAMPLE_COUNT_KEY), 4.0);
+        let Some(ValueKind::StructValue(readings)) =
+            data.fields.get("readings").and_then(|v| v.kind.clone())
+        else {
+            panic!("expected readings")
+        };
+        let Some(ValueKind::StructValue(temp)) =
+            readings.fields.get("temp").and_then(|v| v.kind.clone())
+        else {
+            panic!("expected aggregated temp")
+        };
+        assert_eq!(number(&temp, "min"), 1.0);
+        assert_eq!(number(&temp, "max"), 5.0);
+        assert_eq!(number(&temp, "mean"), 3.0);
+        assert_eq!(number(&temp, "last"), 5.0);
+
+        // a new window starts after an aggregated reading was produced
+        assert!(aggregator.add(reading(14, 1.0)).is_none());
+    }
+}
@@ -15,7 +15,10 @@ use crate::proto::{
     common::v1::LogEntry,
     rpc::{
         v1::{AuthenticateRequest, AuthenticateResponse, Credentials},
-        webrtc::v1::{AnswerRequest, AnswerResponse, AnswerResponseErrorStage},
+        webrtc::v1::{
+            AnswerRequest, AnswerResponse, AnswerResponseErrorStage, OptionalWebRtcConfigRequest,
+            OptionalWebRtcConfigResponse,
+        },
     },
 };
 use bytes::{BufMut, Bytes, BytesMut};
@@ -47,10 +50,17 @@ use super::{
 };
 
 #[cfg(feature = "data")]
-use crate::proto::app::data_sync::v1::DataCaptureUploadRequest;
+use crate::proto::app::data_sync::v1::{
+    streaming_data_capture_upload_request::UploadPacket, DataCaptureUploadMetadata,
+    DataCaptureUploadRequest, StreamingDataCaptureUploadRequest,
+};
 
 pub const VIAM_FOUNDING_YEAR: i32 = 2020;
 
+/// Size in bytes of the chunks binary data is split into when streamed to app
+#[cfg(feature = "data")]
+pub(crate) const BINARY_UPLOAD_CHUNK_SIZE: usize = 16 * 1024;
+
 #[derive(Error, Debug)]
 pub enum AppClientError {
     #[error("wrong credentials")]
@@ -245,6 +255,31 @@ impl AppClient {
         }
     }
 
+    /// Returns the WebRTC options of the signaling server, including the ICE servers the robot
+    /// should use to answer calls
+    pub(crate) async fn get_webrtc_config(
+        &self,
+        rpc_host: String,
+    ) -> Result<OptionalWebRtcConfigResponse, AppClientError> {
+        let body = encode_request(OptionalWebRtcConfigRequest {})?;
+        let r = self
+            .grpc_client
+            .build_request(
+                "/proto.rpc.webrtc.v1.SignalingService/OptionalWebRTCConfig",
+                Some(&self.jwt),
+                &rpc_host,
+                BodyExt::boxed(Full::new(body).map_err(|never| match never {})),
+            )
+            .map_err(AppClientError::AppGrpcClientError)?;
+
+        let (mut r, _) = self.grpc_client.send_request(r).await?;
+        if r.is_empty() {
+            return Err(AppClientError::AppClientEmptyBody);
+        }
+        let r = r.split_off(5);
+        Ok(OptionalWebRtcConfigResponse::decode(r)?)
+    }
+
     pub fn robot_credentials(&self) -> RobotCredentials {
         self.robot_credentials.clone()
     }
@@ -417,6 +452,49 @@ impl AppClient {
         Ok(())
     }
 
+    /// Uploads binary data (e.g. a camera image) through the streaming data capture API, the
+    /// data is sent in chunks of [BINARY_UPLOAD_CHUNK_SIZE] bytes following the metadata so
+    /// that payloads too large for a single request can be uploaded
+    #[cfg(feature = "data")]
+    pub async fn upload_binary_data(
+        &self,
+        metadata: DataCaptureUploadMetadata,
+        data: Bytes,
+    ) -> Result<(), AppClientError> {
+        let metadata = encode_request(StreamingDataCaptureUploadRequest {
+            upload_packet: Some(UploadPacket::Metadata(metadata)),
+        })?;
+        // chunks are only encoded as the body is polled so the payload is not duplicated
+        // in memory
+        let chunks = (0..data.len())
+            .step_by(BINARY_UPLOAD_CHUNK_SIZE)
+            .map(move |start| {
+                let end = std::cmp::min(start + BINARY_UPLOAD_CHUNK_SIZE, data.len());
+                let packet = StreamingDataCaptureUploadRequest {
+                    upload_packet: Some(UploadPacket::Data(data[start..end].to_vec())),
+                }
+                .encode_to_vec();
+                let mut frame = BytesMut::with_capacity(packet.len() + 5);
+                frame.put_u8(0);
+                frame.put_u32(packet.len() as u32);
+                frame.put_slice(&packet);
+                frame.freeze()
+            });
+        let frames = futures_lite::stream::iter(std::iter::once(metadata).chain(chunks));
+        let r = self
+            .grpc_client
+            .build_request(
+                "/viam.app.datasync.v1.DataSyncService/StreamingDataCaptureUpload",
+                Some(&self.jwt),
+                "",
+                BodyExt::boxed(StreamBody::new(frames.map(|b| Ok(Frame::data(b))))),
+            )
+            .map_err(AppClientError::AppGrpcClientError)?;
+        self.grpc_client.send_request(r).await?;
+
+        Ok(())
+    }
+
     /// Obtains the Duration for which we should wait before next
     /// checking for a restart. If no Duration is returned, then the
     /// app has signaled that we should restart now.
@@ -0,0 +1,87 @@
+use super::{
+    actuator::{Actuator, ActuatorError},
+    config::AttributeError,
+    generic::DoCommand,
+    servo::ServoError,
+    status::Status,
+};
+use crate::proto::common::v1::Pose;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "arm";
+
+#[derive(Debug, Error)]
+pub enum ArmError {
+    #[error(transparent)]
+    ArmServoError(#[from] ServoError),
+    #[error(transparent)]
+    ArmActuatorError(#[from] ActuatorError),
+    #[error(transparent)]
+    ArmConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    ArmConfigurationError(&'static str),
+    #[error("invalid argument: {0}")]
+    ArmInvalidArgument(&'static str),
+    #[error("unimplemented: {0}")]
+    ArmMethodUnimplemented(&'static str),
+}
+
+pub trait Arm: Status + Actuator + DoCommand {
+    /// Gets the position of each joint of the arm, expressed in degrees and ordered
+    /// from the base of the arm to its end
+    fn get_joint_positions(&mut self) -> Result<Vec<f64>, ArmError>;
+
+    /// Moves each joint of the arm to the position (in degrees) at the same index
+    /// in `positions`, which must contain one entry per joint
+    fn move_to_joint_positions(&mut self, positions: &[f64]) -> Result<(), ArmError>;
+
+    /// Gets the pose of the end of the arm relative to its base. Requires a kinematic model
+    /// of the arm, which is why the default implementation returns an error
+    fn get_end_position(&mut self) -> Result<Pose, ArmError> {
+        Err(ArmError::ArmMethodUnimplemented("get_end_position"))
+    }
+
+    /// Moves the end of the arm to `pose`, see `get_end_position`
+    fn move_to_position(&mut self, _pose: &Pose) -> Result<(), ArmError> {
+        Err(ArmError::ArmMethodUnimplemented("move_to_position"))
+    }
+}
+
+pub type ArmType = Arc<Mutex<dyn Arm>>;
+
+impl<L> Arm for Mutex<L>
+where
+    L: ?Sized + Arm,
+{
+    fn get_joint_positions(&mut self) -> Result<Vec<f64>, ArmError> {
+        self.get_mut().unwrap().get_joint_positions()
+    }
+    fn move_to_joint_positions(&mut self, positions: &[f64]) -> Result<(), ArmError> {
+        self.get_mut().unwrap().move_to_joint_positions(positions)
+    }
+    fn get_end_position(&mut self) -> Result<Pose, ArmError> {
+        self.get_mut().unwrap().get_end_position()
+    }
+    fn move_to_position(&mut self, pose: &Pose) -> Result<(), ArmError> {
+        self.get_mut().unwrap().move_to_position(pose)
+    }
+}
+
+impl<A> Arm for Arc<Mutex<A>>
+where
+    A: ?Sized + Arm,
+{
+    fn get_joint_positions(&mut self) -> Result<Vec<f64>, ArmError> {
+        self.lock().unwrap().get_joint_positions()
+    }
+    fn move_to_joint_positions(&mut self, positions: &[f64]) -> Result<(), ArmError> {
+        self.lock().unwrap().move_to_joint_positions(positions)
+    }
+    fn get_end_position(&mut self) -> Result<Pose, ArmError> {
+        self.lock().unwrap().get_end_position()
+    }
+    fn move_to_position(&mut self, pose: &Pose) -> Result<(), ArmError> {
+        self.lock().unwrap().move_to_position(pose)
+    }
+}
@@ -7,12 +7,32 @@ use crate::common::actuator::Actuator;
 use crate::common::status::Status;
 use crate::proto::common::v1::Vector3;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
 pub static COMPONENT_NAME: &str = "base";
 
 pub trait Base: Status + Actuator + DoCommand {
     fn set_power(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError>;
+
+    /// Moves the base in a straight line for `distance_mm` at `mm_per_sec`, a negative distance
+    /// or speed moves the base backwards. The returned duration is the expected time to cover
+    /// the distance, the caller is responsible for stopping the base. None is returned when
+    /// the base isn't set in motion.
+    fn move_straight(
+        &mut self,
+        distance_mm: i64,
+        mm_per_sec: f64,
+    ) -> Result<Option<Duration>, BaseError>;
+
+    /// Turns the base in place by `angle_deg` at `degs_per_sec`, positive angles turn the base
+    /// counterclockwise (to the left). The returned duration follows the same semantics as
+    /// `move_straight`.
+    fn spin(&mut self, angle_deg: f64, degs_per_sec: f64) -> Result<Option<Duration>, BaseError>;
+
+    /// Drives the base at a linear velocity (`lin.y` in mm/sec) and angular
+    /// velocity (`ang.z` in degrees/sec) until another command is received.
+    fn set_velocity(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError>;
 }
 
 pub type BaseType = Arc<Mutex<dyn Base>>;
@@ -25,6 +45,8 @@ pub enum BaseError {
     BaseConfigAttributeError(#[from] AttributeError),
     #[error("config error: {0}")]
     BaseConfigError(&'static str),
+    #[error("invalid argument: {0}")]
+    BaseInvalidArgument(&'static str),
 }
 
 // TODO(RSDK-5648) - Store power from set_power call on struct and register as "fake" model
@@ -52,6 +74,21 @@ where
     fn set_power(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
         self.get_mut().unwrap().set_power(lin, ang)
     }
+    fn move_straight(
+        &mut self,
+        distance_mm: i64,
+        mm_per_sec: f64,
+    ) -> Result<Option<Duration>, BaseError> {
+        self.get_mut()
+            .unwrap()
+            .move_straight(distance_mm, mm_per_sec)
+    }
+    fn spin(&mut self, angle_deg: f64, degs_per_sec: f64) -> Result<Option<Duration>, BaseError> {
+        self.get_mut().unwrap().spin(angle_deg, degs_per_sec)
+    }
+    fn set_velocity(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
+        self.get_mut().unwrap().set_velocity(lin, ang)
+    }
 }
 
 impl<L> Base for Arc<Mutex<L>>
@@ -61,6 +98,19 @@ where
     fn set_power(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
         self.lock().unwrap().set_power(lin, ang)
     }
+    fn move_straight(
+        &mut self,
+        distance_mm: i64,
+        mm_per_sec: f64,
+    ) -> Result<Option<Duration>, BaseError> {
+        self.lock().unwrap().move_straight(distance_mm, mm_per_sec)
+    }
+    fn spin(&mut self, angle_deg: f64, degs_per_sec: f64) -> Result<Option<Duration>, BaseError> {
+        self.lock().unwrap().spin(angle_deg, degs_per_sec)
+    }
+    fn set_velocity(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
+        self.lock().unwrap().set_velocity(lin, ang)
+    }
 }
 
 #[cfg(feature = "builtin-components")]
@@ -72,6 +122,49 @@ impl Base for FakeBase {
         );
         Ok(())
     }
+    fn move_straight(
+        &mut self,
+        distance_mm: i64,
+        mm_per_sec: f64,
+    ) -> Result<Option<Duration>, BaseError> {
+        debug!(
+            "Moving straight {} mm at {} mm/sec",
+            distance_mm, mm_per_sec
+        );
+        if !mm_per_sec.is_finite() {
+            return Err(BaseError::BaseInvalidArgument("mm_per_sec must be finite"));
+        }
+        if distance_mm == 0 || mm_per_sec == 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(Duration::from_secs_f64(
+            (distance_mm as f64 / mm_per_sec).abs(),
+        )))
+    }
+    fn spin(&mut self, angle_deg: f64, degs_per_sec: f64) -> Result<Option<Duration>, BaseError> {
+        debug!(
+            "Spinning {} degrees at {} degrees/sec",
+            angle_deg, degs_per_sec
+        );
+        if !angle_deg.is_finite() || !degs_per_sec.is_finite() {
+            return Err(BaseError::BaseInvalidArgument(
+                "angle_deg and degs_per_sec must be finite",
+            ));
+        }
+        if angle_deg == 0.0 || degs_per_sec == 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(Duration::from_secs_f64(
+            (angle_deg / degs_per_sec).abs(),
+        )))
+    }
+    fn set_velocity(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
+        debug!(
+            "Setting velocity following lin vec {:?} and ang {:?}",
+            lin, ang
+        );
+        Ok(())
+    }
 }
 
 #[cfg(feature = "builtin-components")]
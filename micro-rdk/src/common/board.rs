@@ -9,11 +9,17 @@ use crate::{
 };
 
 use log::*;
-use std::{collections::HashMap, sync::Arc, sync::Mutex, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use super::{
     analog::{AnalogReaderType, FakeAnalogReader},
     config::ConfigType,
+    digital_interrupt::{DigitalInterruptConfig, InterruptEvents},
     generic::DoCommand,
     i2c::{FakeI2CHandle, FakeI2cConfig, I2CErrors, I2CHandle, I2cHandleType},
     registry::ComponentRegistry,
@@ -90,6 +96,29 @@ pub trait Board: Status + DoCommand {
         ))
     }
 
+    /// Sets the debounce window of the interrupt on a pin: events happening less than
+    /// `debounce` after the last debounced event aren't counted by
+    /// `get_debounced_interrupt_value`. Should error if the pin has not been configured as
+    /// an interrupt
+    fn set_digital_interrupt_debounce(
+        &mut self,
+        _pin: i32,
+        _debounce: Duration,
+    ) -> Result<(), BoardError> {
+        Err(BoardError::BoardMethodNotSupported(
+            "set_digital_interrupt_debounce",
+        ))
+    }
+
+    /// Return the amount of interrupt events on a pin, ignoring the events within the debounce
+    /// window of the previous counted one. Should error if the pin has not been configured as
+    /// an interrupt
+    fn get_debounced_interrupt_value(&self, _pin: i32) -> Result<u32, BoardError> {
+        Err(BoardError::BoardMethodNotSupported(
+            "get_debounced_interrupt_value",
+        ))
+    }
+
     /// Get the pin's given duty cycle, returns percentage as float between 0.0 and 1.0
     fn get_pwm_duty(&self, pin: i32) -> f64;
 
@@ -116,6 +145,10 @@ pub struct FakeBoard {
     i2cs: HashMap<String, Arc<Mutex<FakeI2CHandle>>>,
     pin_pwms: HashMap<i32, f64>,
     pin_pwm_freq: HashMap<i32, u64>,
+    pin_levels: HashMap<i32, bool>,
+    interrupt_events: HashMap<i32, InterruptEvents>,
+    // clock of the simulated interrupt events
+    created: Instant,
 }
 
 impl FakeBoard {
@@ -130,6 +163,23 @@ impl FakeBoard {
             i2cs,
             pin_pwms: HashMap::new(),
             pin_pwm_freq: HashMap::new(),
+            pin_levels: HashMap::new(),
+            interrupt_events: HashMap::new(),
+            created: Instant::now(),
+        }
+    }
+
+    /// Configures `pin` as a digital interrupt
+    pub fn add_digital_interrupt(&mut self, pin: i32) {
+        let _ = self
+            .interrupt_events
+            .insert(pin, InterruptEvents::default());
+    }
+
+    /// Simulates an event on the digital interrupt configured on `pin`
+    pub fn trigger_digital_interrupt(&mut self, pin: i32) {
+        if let Some(events) = self.interrupt_events.get(&pin) {
+            events.record(self.created.elapsed().as_millis() as u32);
         }
     }
 
@@ -165,11 +215,23 @@ impl FakeBoard {
             HashMap::new()
         };
 
+        let interrupt_events = cfg
+            .get_attribute::<Vec<DigitalInterruptConfig>>("digital_interrupts")
+            .map_or(HashMap::new(), |confs| {
+                confs
+                    .iter()
+                    .map(|conf| (conf.pin, InterruptEvents::default()))
+                    .collect()
+            });
+
         Ok(Arc::new(Mutex::new(FakeBoard {
             analogs,
             i2cs,
             pin_pwms: HashMap::new(),
             pin_pwm_freq: HashMap::new(),
+            pin_levels: HashMap::new(),
+            interrupt_events,
+            created: Instant::now(),
         })))
     }
 }
@@ -177,12 +239,14 @@ impl FakeBoard {
 impl Board for FakeBoard {
     fn set_gpio_pin_level(&mut self, pin: i32, is_high: bool) -> Result<(), BoardError> {
         info!("set pin {} to {}", pin, is_high);
+        self.pin_levels.insert(pin, is_high);
         Ok(())
     }
 
+    // pins that were never set read high
     fn get_gpio_level(&self, pin: i32) -> Result<bool, BoardError> {
         info!("get pin {}", pin);
-        Ok(true)
+        Ok(*self.pin_levels.get(&pin).unwrap_or(&true))
     }
 
     fn get_analog_reader_by_name(&self, name: String) -> Result<AnalogReaderType<u16>, BoardError> {
@@ -215,6 +279,31 @@ impl Board for FakeBoard {
         Err(BoardError::I2CBusNotFound(name))
     }
 
+    fn get_digital_interrupt_value(&self, pin: i32) -> Result<u32, BoardError> {
+        self.interrupt_events
+            .get(&pin)
+            .map(InterruptEvents::count)
+            .ok_or(BoardError::GpioPinError(pin as u32, "not an interrupt"))
+    }
+
+    fn set_digital_interrupt_debounce(
+        &mut self,
+        pin: i32,
+        debounce: Duration,
+    ) -> Result<(), BoardError> {
+        self.interrupt_events
+            .get(&pin)
+            .map(|events| events.set_debounce(debounce))
+            .ok_or(BoardError::GpioPinError(pin as u32, "not an interrupt"))
+    }
+
+    fn get_debounced_interrupt_value(&self, pin: i32) -> Result<u32, BoardError> {
+        self.interrupt_events
+            .get(&pin)
+            .map(InterruptEvents::debounced_count)
+            .ok_or(BoardError::GpioPinError(pin as u32, "not an interrupt"))
+    }
+
     fn get_pwm_duty(&self, pin: i32) -> f64 {
         *self.pin_pwms.get(&pin).unwrap_or(&0.0)
     }
@@ -295,6 +384,20 @@ where
         self.lock().unwrap().get_digital_interrupt_value(pin)
     }
 
+    fn set_digital_interrupt_debounce(
+        &mut self,
+        pin: i32,
+        debounce: Duration,
+    ) -> Result<(), BoardError> {
+        self.lock()
+            .unwrap()
+            .set_digital_interrupt_debounce(pin, debounce)
+    }
+
+    fn get_debounced_interrupt_value(&self, pin: i32) -> Result<u32, BoardError> {
+        self.lock().unwrap().get_debounced_interrupt_value(pin)
+    }
+
     fn get_pwm_duty(&self, pin: i32) -> f64 {
         self.lock().unwrap().get_pwm_duty(pin)
     }
@@ -0,0 +1,49 @@
+use super::{board::BoardError, config::AttributeError, generic::DoCommand, status::Status};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "button";
+
+#[derive(Debug, Error)]
+pub enum ButtonError {
+    #[error(transparent)]
+    ButtonBoardError(#[from] BoardError),
+    #[error(transparent)]
+    ButtonConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    ButtonConfigurationError(&'static str),
+}
+
+pub trait Button: Status + DoCommand {
+    /// Pushes the button, the push is counted as a press
+    fn push(&mut self) -> Result<(), ButtonError>;
+
+    /// Gets the number of times the button was pressed since it was configured
+    fn get_press_count(&mut self) -> Result<u32, ButtonError>;
+}
+
+pub type ButtonType = Arc<Mutex<dyn Button>>;
+
+impl<L> Button for Mutex<L>
+where
+    L: ?Sized + Button,
+{
+    fn push(&mut self) -> Result<(), ButtonError> {
+        self.get_mut().unwrap().push()
+    }
+    fn get_press_count(&mut self) -> Result<u32, ButtonError> {
+        self.get_mut().unwrap().get_press_count()
+    }
+}
+
+impl<A> Button for Arc<Mutex<A>>
+where
+    A: ?Sized + Button,
+{
+    fn push(&mut self) -> Result<(), ButtonError> {
+        self.lock().unwrap().push()
+    }
+    fn get_press_count(&mut self) -> Result<u32, ButtonError> {
+        self.lock().unwrap().get_press_count()
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     common::{
-        camera::{Camera, CameraError, CameraType},
+        camera::{
+            calibration_from_config, Camera, CameraDistortion, CameraError, CameraImage,
+            CameraIntrinsics, CameraProperties, CameraType, MIME_TYPE_JPEG,
+        },
         status::{Status, StatusError},
     },
     google,
@@ -25,17 +28,27 @@ pub(crate) fn register_models(registry: &mut ComponentRegistry) {
 }
 
 #[derive(DoCommand)]
-pub struct FakeCamera {}
+pub struct FakeCamera {
+    intrinsics: Option<CameraIntrinsics>,
+    distortion: Option<CameraDistortion>,
+}
 
 impl FakeCamera {
     pub fn new() -> Self {
-        FakeCamera {}
+        FakeCamera {
+            intrinsics: None,
+            distortion: None,
+        }
     }
     pub(crate) fn from_config(
-        _cfg: ConfigType,
+        cfg: ConfigType,
         _: Vec<Dependency>,
     ) -> Result<CameraType, CameraError> {
-        Ok(Arc::new(Mutex::new(FakeCamera::new())))
+        let (intrinsics, distortion) = calibration_from_config(&cfg)?;
+        Ok(Arc::new(Mutex::new(FakeCamera {
+            intrinsics,
+            distortion,
+        })))
     }
 }
 
@@ -46,8 +59,20 @@ impl Default for FakeCamera {
 }
 
 impl Camera for FakeCamera {
-    fn get_image(&mut self) -> Result<Bytes, CameraError> {
-        Ok(FAKE_JPEG.into())
+    fn get_image(&mut self) -> Result<CameraImage, CameraError> {
+        Ok(CameraImage::new(
+            MIME_TYPE_JPEG,
+            Bytes::from_static(FAKE_JPEG),
+        ))
+    }
+    fn get_properties(&mut self) -> Result<CameraProperties, CameraError> {
+        Ok(CameraProperties {
+            supports_pcd: false,
+            intrinsics: self.intrinsics,
+            distortion: self.distortion.clone(),
+            mime_types: vec![MIME_TYPE_JPEG.to_string()],
+            frame_rate: None,
+        })
     }
 }
 
@@ -62,6 +87,7 @@ impl Status for FakeCamera {
 #[cfg(all(test, feature = "native"))]
 mod tests {
     use std::{
+        collections::HashMap,
         convert::Infallible,
         net::{SocketAddr, TcpListener, TcpStream},
         sync::{Arc, Mutex},
@@ -74,7 +100,7 @@ mod tests {
     use crate::{
         common::{
             app_client::encode_request,
-            config::DynamicComponentConfig,
+            config::{DynamicComponentConfig, Kind},
             exec::Executor,
             grpc::{GrpcBody, GrpcError, GrpcServer},
             registry::ComponentRegistry,
@@ -82,7 +108,10 @@ mod tests {
         },
         google::api::HttpBody,
         native::tcp::NativeStream,
-        proto::component::camera::v1::{GetImageRequest, GetImageResponse, RenderFrameRequest},
+        proto::component::camera::v1::{
+            image::Format, GetImageRequest, GetImageResponse, GetImagesRequest, GetImagesResponse,
+            GetPointCloudRequest, GetPropertiesRequest, GetPropertiesResponse, RenderFrameRequest,
+        },
     };
 
     use http_body_util::{combinators::BoxBody, BodyExt, Collected, Full};
@@ -108,7 +137,17 @@ mod tests {
             namespace: "rdk".to_string(),
             r#type: "camera".to_string(),
             model: "rdk:builtin:fake".to_string(),
-            attributes: None,
+            attributes: Some(HashMap::from([(
+                "intrinsic_parameters".to_string(),
+                Kind::StructValue(HashMap::from([
+                    ("width_px".to_string(), Kind::NumberValue(211.0)),
+                    ("height_px".to_string(), Kind::NumberValue(201.0)),
+                    ("fx".to_string(), Kind::NumberValue(250.0)),
+                    ("fy".to_string(), Kind::NumberValue(250.0)),
+                    ("ppx".to_string(), Kind::NumberValue(105.5)),
+                    ("ppy".to_string(), Kind::NumberValue(100.5)),
+                ])),
+            )])),
             ..Default::default()
         }));
         let mut registry: Box<ComponentRegistry> = Box::default();
@@ -254,6 +293,82 @@ mod tests {
         Ok(())
     }
 
+    async fn test_get_images(
+        mut send_request: SendRequest<BoxBody<bytes::Bytes, Infallible>>,
+        host: &str,
+    ) -> Result<(), String> {
+        let get_images_path = "/viam.component.camera.v1.CameraService/GetImages";
+        let mut message = GetImagesRequest::default();
+        message.name = "camera".to_string();
+        let message = encode_request(message).unwrap();
+
+        assert!(send_request.ready().await.is_ok());
+        let req = build_request(host.to_string(), get_images_path.to_string(), message).await;
+
+        let resp = send_request.send_request(req).await;
+        assert!(resp.is_ok());
+        let body = check_response(resp.unwrap(), SUCCESS).await.unwrap();
+
+        let resp = GetImagesResponse::decode(body.to_bytes().split_off(5));
+        assert!(resp.is_ok());
+        let resp = resp.unwrap();
+        assert_eq!(resp.images.len(), 1);
+        assert_eq!(resp.images[0].source_name, "camera");
+        assert_eq!(resp.images[0].format, Format::Jpeg as i32);
+        assert_eq!(resp.images[0].image.len(), FAKE_JPEG.len());
+        assert!(resp
+            .response_metadata
+            .is_some_and(|md| md.captured_at.is_some()));
+
+        Ok(())
+    }
+
+    async fn test_get_properties(
+        mut send_request: SendRequest<BoxBody<bytes::Bytes, Infallible>>,
+        host: &str,
+    ) -> Result<(), String> {
+        let get_properties_path = "/viam.component.camera.v1.CameraService/GetProperties";
+        let mut message = GetPropertiesRequest::default();
+        message.name = "camera".to_string();
+        let message = encode_request(message).unwrap();
+
+        assert!(send_request.ready().await.is_ok());
+        let req = build_request(host.to_string(), get_properties_path.to_string(), message).await;
+
+        let resp = send_request.send_request(req).await;
+        assert!(resp.is_ok());
+        let body = check_response(resp.unwrap(), SUCCESS).await.unwrap();
+
+        let resp = GetPropertiesResponse::decode(body.to_bytes().split_off(5));
+        assert!(resp.is_ok());
+        let resp = resp.unwrap();
+        assert!(!resp.supports_pcd);
+        assert_eq!(resp.mime_types, vec!["image/jpeg".to_string()]);
+        assert!(resp.distortion_parameters.is_none());
+        let intrinsics = resp.intrinsic_parameters.unwrap();
+        assert_eq!(intrinsics.width_px, 211);
+        assert_eq!(intrinsics.height_px, 201);
+        assert_eq!(intrinsics.focal_x_px, 250.0);
+        assert_eq!(intrinsics.center_y_px, 100.5);
+
+        // the fake camera cannot produce point clouds
+        let get_point_cloud_path = "/viam.component.camera.v1.CameraService/GetPointCloud";
+        let mut message = GetPointCloudRequest::default();
+        message.name = "camera".to_string();
+        let message = encode_request(message).unwrap();
+
+        assert!(send_request.ready().await.is_ok());
+        let req = build_request(host.to_string(), get_point_cloud_path.to_string(), message).await;
+
+        let resp = send_request.send_request(req).await;
+        assert!(resp.is_ok());
+        let _body = check_response(resp.unwrap(), GrpcError::RpcUnimplemented as i32)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
     #[test_log::test]
     fn test_fake_camera() {
         let exec = Executor::default();
@@ -301,5 +416,11 @@ mod tests {
         let render_frame =
             exec.block_on(async { test_render_frame(send_request.clone(), &host).await });
         assert!(render_frame.is_ok());
+        let get_images =
+            exec.block_on(async { test_get_images(send_request.clone(), &host).await });
+        assert!(get_images.is_ok());
+        let get_properties =
+            exec.block_on(async { test_get_properties(send_request.clone(), &host).await });
+        assert!(get_properties.is_ok());
     }
 }
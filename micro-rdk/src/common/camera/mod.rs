@@ -1,11 +1,26 @@
-use super::{generic::DoCommand, registry::ComponentRegistry, status::Status};
+use super::{
+    config::{AttributeError, ConfigType, Kind},
+    generic::DoCommand,
+    registry::ComponentRegistry,
+    status::Status,
+};
+use crate::{
+    google::{api::HttpBody, protobuf::Timestamp},
+    proto::{
+        common::v1::ResponseMetadata,
+        component::camera::v1::{
+            self as camera, image::Format, GetImageResponse, GetPointCloudResponse,
+            GetPropertiesResponse,
+        },
+    },
+};
 use bytes::Bytes;
 use prost::EncodeError;
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 #[cfg(feature = "builtin-components")]
-mod fake_camera;
+pub(crate) mod fake_camera;
 
 #[allow(unused)]
 pub(crate) fn register_models(registry: &mut ComponentRegistry) {
@@ -21,12 +36,18 @@ pub(crate) fn register_models(registry: &mut ComponentRegistry) {
 pub(crate) type CameraType = Arc<Mutex<dyn Camera>>;
 pub static COMPONENT_NAME: &str = "camera";
 
+pub const MIME_TYPE_JPEG: &str = "image/jpeg";
+pub const MIME_TYPE_PNG: &str = "image/png";
+pub const MIME_TYPE_PCD: &str = "pointcloud/pcd";
+
 #[derive(Error, Debug)]
 pub enum CameraError {
     #[error("cannot build camera {0}")]
     InitError(#[from] Box<dyn std::error::Error + Sync + Send>),
     #[error("config error {0}")]
     ConfigError(&'static str),
+    #[error(transparent)]
+    ConfigAttributeError(#[from] AttributeError),
     #[error("frame of size {0} greater than internal buffer capacity {1}, consider reducing camera's frame_size")]
     ImageTooBig(usize, usize),
     #[error("failed to get image")]
@@ -39,20 +60,245 @@ pub enum CameraError {
     MessageEncodeError(#[from] EncodeError),
 }
 
+/// An encoded image along with its MIME type and the time at which it was captured
+#[derive(Clone, Debug)]
+pub struct CameraImage {
+    pub mime_type: String,
+    pub data: Bytes,
+    pub captured_at: Option<Timestamp>,
+}
+
+impl CameraImage {
+    /// Builds an image stamped with the current time
+    pub fn new(mime_type: impl Into<String>, data: Bytes) -> Self {
+        let now = chrono::offset::Local::now().fixed_offset();
+        Self {
+            mime_type: mime_type.into(),
+            data,
+            captured_at: Some(Timestamp {
+                seconds: now.timestamp(),
+                nanos: now.timestamp_subsec_nanos() as i32,
+            }),
+        }
+    }
+    fn format(&self) -> Format {
+        match self.mime_type.as_str() {
+            MIME_TYPE_JPEG => Format::Jpeg,
+            MIME_TYPE_PNG => Format::Png,
+            _ => Format::Unspecified,
+        }
+    }
+}
+
+impl From<CameraImage> for GetImageResponse {
+    fn from(value: CameraImage) -> Self {
+        Self {
+            mime_type: value.mime_type,
+            image: value.data,
+        }
+    }
+}
+
+impl From<CameraImage> for HttpBody {
+    fn from(value: CameraImage) -> Self {
+        Self {
+            content_type: value.mime_type,
+            data: value.data.to_vec(),
+            ..Default::default()
+        }
+    }
+}
+
+/// An image produced by one of the sources of a camera, an empty `source_name` refers
+/// to the camera itself
+#[derive(Clone, Debug)]
+pub struct NamedCameraImage {
+    pub source_name: String,
+    pub image: CameraImage,
+}
+
+impl From<NamedCameraImage> for camera::Image {
+    fn from(value: NamedCameraImage) -> Self {
+        Self {
+            source_name: value.source_name,
+            format: value.image.format().into(),
+            image: value.image.data,
+        }
+    }
+}
+
+/// An encoded point cloud along with its MIME type
+#[derive(Clone, Debug)]
+pub struct PointCloud {
+    pub mime_type: String,
+    pub data: Bytes,
+}
+
+impl From<PointCloud> for GetPointCloudResponse {
+    fn from(value: PointCloud) -> Self {
+        Self {
+            mime_type: value.mime_type,
+            point_cloud: value.data,
+        }
+    }
+}
+
+/// Pinhole model parameters of a camera, expressed in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraIntrinsics {
+    pub width_px: u32,
+    pub height_px: u32,
+    pub focal_x_px: f64,
+    pub focal_y_px: f64,
+    pub center_x_px: f64,
+    pub center_y_px: f64,
+}
+
+impl TryFrom<&Kind> for CameraIntrinsics {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        for key in ["width_px", "height_px", "fx", "fy", "ppx", "ppy"] {
+            if !value.contains_key(key)? {
+                return Err(AttributeError::KeyNotFound(key.to_string()));
+            }
+        }
+        Ok(Self {
+            width_px: value.get("width_px")?.unwrap().try_into()?,
+            height_px: value.get("height_px")?.unwrap().try_into()?,
+            focal_x_px: value.get("fx")?.unwrap().try_into()?,
+            focal_y_px: value.get("fy")?.unwrap().try_into()?,
+            center_x_px: value.get("ppx")?.unwrap().try_into()?,
+            center_y_px: value.get("ppy")?.unwrap().try_into()?,
+        })
+    }
+}
+
+impl From<CameraIntrinsics> for camera::IntrinsicParameters {
+    fn from(value: CameraIntrinsics) -> Self {
+        Self {
+            width_px: value.width_px,
+            height_px: value.height_px,
+            focal_x_px: value.focal_x_px,
+            focal_y_px: value.focal_y_px,
+            center_x_px: value.center_x_px,
+            center_y_px: value.center_y_px,
+        }
+    }
+}
+
+/// Lens distortion model (e.g. "brown_conrady") and its coefficients
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraDistortion {
+    pub model: String,
+    pub parameters: Vec<f64>,
+}
+
+/// Parses Brown-Conrady coefficients, keys missing from the config default to 0
+impl TryFrom<&Kind> for CameraDistortion {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        let mut parameters = Vec::with_capacity(5);
+        for key in ["rk1", "rk2", "rk3", "tp1", "tp2"] {
+            parameters.push(match value.get(key)? {
+                Some(val) => val.try_into()?,
+                None => 0.0,
+            });
+        }
+        Ok(Self {
+            model: "brown_conrady".to_string(),
+            parameters,
+        })
+    }
+}
+
+impl From<CameraDistortion> for camera::DistortionParameters {
+    fn from(value: CameraDistortion) -> Self {
+        Self {
+            model: value.model,
+            parameters: value.parameters,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CameraProperties {
+    pub supports_pcd: bool,
+    pub intrinsics: Option<CameraIntrinsics>,
+    pub distortion: Option<CameraDistortion>,
+    pub mime_types: Vec<String>,
+    pub frame_rate: Option<f32>,
+}
+
+impl From<CameraProperties> for GetPropertiesResponse {
+    fn from(value: CameraProperties) -> Self {
+        Self {
+            supports_pcd: value.supports_pcd,
+            intrinsic_parameters: value.intrinsics.map(Into::into),
+            distortion_parameters: value.distortion.map(Into::into),
+            mime_types: value.mime_types,
+            frame_rate: value.frame_rate,
+        }
+    }
+}
+
+/// Reads the optional `intrinsic_parameters` and `distortion_parameters` attributes of a
+/// camera config
+#[allow(dead_code)]
+pub(crate) fn calibration_from_config(
+    cfg: &ConfigType,
+) -> Result<(Option<CameraIntrinsics>, Option<CameraDistortion>), CameraError> {
+    let intrinsics = match cfg.get_attribute::<CameraIntrinsics>("intrinsic_parameters") {
+        Ok(intrinsics) => Some(intrinsics),
+        Err(AttributeError::KeyNotFound(key)) if key == "intrinsic_parameters" => None,
+        Err(err) => return Err(err.into()),
+    };
+    let distortion = match cfg.get_attribute::<CameraDistortion>("distortion_parameters") {
+        Ok(distortion) => Some(distortion),
+        Err(AttributeError::KeyNotFound(key)) if key == "distortion_parameters" => None,
+        Err(err) => return Err(err.into()),
+    };
+    Ok((intrinsics, distortion))
+}
+
+/// Builds the response of a GetImages request, the metadata carries the capture time of the
+/// first image
+pub(crate) fn get_images_response(
+    images: Vec<NamedCameraImage>,
+    camera_name: &str,
+) -> camera::GetImagesResponse {
+    let captured_at = images.first().and_then(|img| img.image.captured_at.clone());
+    camera::GetImagesResponse {
+        images: images
+            .into_iter()
+            .map(|mut img| {
+                if img.source_name.is_empty() {
+                    img.source_name = camera_name.to_string();
+                }
+                img.into()
+            })
+            .collect(),
+        response_metadata: Some(ResponseMetadata { captured_at }),
+    }
+}
+
 pub trait Camera: Status + DoCommand {
     /// Returns a structured image response from a camera of the underlying robot.
     /// A specific MIME type can be requested but may not necessarily be the same one returned
-    fn get_image(&mut self) -> Result<Bytes, CameraError> {
+    fn get_image(&mut self) -> Result<CameraImage, CameraError> {
         Err(CameraError::CameraMethodUnimplemented("get_image"))
     }
-    fn get_images(&mut self) -> Result<Bytes, CameraError> {
-        Err(CameraError::CameraMethodUnimplemented("get_images"))
+    /// Returns an image from every source of the camera, defaults to the output of `get_image`
+    fn get_images(&mut self) -> Result<Vec<NamedCameraImage>, CameraError> {
+        Ok(vec![NamedCameraImage {
+            source_name: String::new(),
+            image: self.get_image()?,
+        }])
     }
-    fn get_point_cloud(&mut self) -> Result<Bytes, CameraError> {
+    fn get_point_cloud(&mut self) -> Result<PointCloud, CameraError> {
         Err(CameraError::CameraMethodUnimplemented("get_point_cloud"))
     }
     /// Returns the camera intrinsic parameters and camera distortion parameters
-    fn get_properties(&mut self) -> Result<Bytes, CameraError> {
+    fn get_properties(&mut self) -> Result<CameraProperties, CameraError> {
         Err(CameraError::CameraMethodUnimplemented("get_properties"))
     }
 }
@@ -61,16 +307,16 @@ impl<L> Camera for Mutex<L>
 where
     L: ?Sized + Camera,
 {
-    fn get_image(&mut self) -> Result<Bytes, CameraError> {
+    fn get_image(&mut self) -> Result<CameraImage, CameraError> {
         self.get_mut().unwrap().get_image()
     }
-    fn get_images(&mut self) -> Result<Bytes, CameraError> {
+    fn get_images(&mut self) -> Result<Vec<NamedCameraImage>, CameraError> {
         self.get_mut().unwrap().get_images()
     }
-    fn get_point_cloud(&mut self) -> Result<Bytes, CameraError> {
+    fn get_point_cloud(&mut self) -> Result<PointCloud, CameraError> {
         self.get_mut().unwrap().get_point_cloud()
     }
-    fn get_properties(&mut self) -> Result<Bytes, CameraError> {
+    fn get_properties(&mut self) -> Result<CameraProperties, CameraError> {
         self.get_mut().unwrap().get_properties()
     }
 }
@@ -79,16 +325,16 @@ impl<L> Camera for Arc<Mutex<L>>
 where
     L: ?Sized + Camera,
 {
-    fn get_image(&mut self) -> Result<Bytes, CameraError> {
+    fn get_image(&mut self) -> Result<CameraImage, CameraError> {
         self.lock().unwrap().get_image()
     }
-    fn get_images(&mut self) -> Result<Bytes, CameraError> {
+    fn get_images(&mut self) -> Result<Vec<NamedCameraImage>, CameraError> {
         self.lock().unwrap().get_images()
     }
-    fn get_point_cloud(&mut self) -> Result<Bytes, CameraError> {
+    fn get_point_cloud(&mut self) -> Result<PointCloud, CameraError> {
         self.lock().unwrap().get_point_cloud()
     }
-    fn get_properties(&mut self) -> Result<Bytes, CameraError> {
+    fn get_properties(&mut self) -> Result<CameraProperties, CameraError> {
         self.lock().unwrap().get_properties()
     }
 }
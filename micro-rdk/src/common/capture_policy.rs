@@ -0,0 +1,497 @@
+//! Capture policies decide which of the readings produced by a `DataCollector` are
+//! written to the data store. By default every reading is stored, a policy configured
+//! in the "capture_policy" section of a capture method can instead only store readings
+//! when a value changes beyond a deadband, when a value crosses a threshold, or around
+//! the firing of a named trigger.
+//!
+//! Triggers are fired either by a sensor reading crossing a bound (see
+//! `SensorTriggerConfig`) or by a `{"fire_trigger": "<name>"}` DoCommand sent to the
+//! data manager service.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::google::protobuf::{value::Kind as ValueKind, Value};
+use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData};
+
+use super::{
+    config::{AttributeError, Kind},
+    sensor::{SensorError, SensorType},
+};
+
+/// Number of readings preceding a trigger that are kept in RAM by default
+pub const DEFAULT_PRE_TRIGGER_COUNT: usize = 0;
+/// Number of readings stored by default once a trigger fired
+pub const DEFAULT_POST_TRIGGER_COUNT: usize = 1;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CapturePolicy {
+    /// Every reading is stored
+    #[default]
+    Always,
+    /// A reading is stored when one of its values moved by more than `deadband` since
+    /// the last stored reading
+    Deadband {
+        field: Option<String>,
+        deadband: f64,
+    },
+    /// A reading is stored when one of its values crossed `threshold` (in either
+    /// direction) since the previous reading
+    Threshold {
+        field: Option<String>,
+        threshold: f64,
+    },
+    /// Readings are only stored when the named trigger fires, the last `pre_trigger_count`
+    /// readings are kept in RAM and stored along with the next `post_trigger_count` ones
+    Trigger {
+        trigger: String,
+        pre_trigger_count: usize,
+        post_trigger_count: usize,
+    },
+}
+
+impl CapturePolicy {
+    /// Deadband and threshold policies compare numeric values and can't be applied to
+    /// binary data
+    pub fn supports_binary_data(&self) -> bool {
+        matches!(self, Self::Always | Self::Trigger { .. })
+    }
+}
+
+fn optional<'a, T>(value: &'a Kind, key: &str) -> Result<Option<T>, AttributeError>
+where
+    T: TryFrom<&'a Kind, Error = AttributeError>,
+{
+    value.get(key)?.map(T::try_from).transpose()
+}
+
+fn required<'a, T>(value: &'a Kind, key: &str) -> Result<T, AttributeError>
+where
+    T: TryFrom<&'a Kind, Error = AttributeError>,
+{
+    optional(value, key)?.ok_or(AttributeError::KeyNotFound(key.to_string()))
+}
+
+impl TryFrom<&Kind> for CapturePolicy {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        let policy_type: String = required(value, "type")?;
+        let field: Option<String> = optional(value, "field")?;
+        match policy_type.as_str() {
+            "always" => Ok(Self::Always),
+            "deadband" => {
+                let deadband: f64 = required(value, "deadband")?;
+                if deadband < 0.0 {
+                    return Err(AttributeError::ValidationError(
+                        "deadband cannot be negative".to_string(),
+                    ));
+                }
+                Ok(Self::Deadband { field, deadband })
+            }
+            "threshold" => Ok(Self::Threshold {
+                field,
+                threshold: required(value, "threshold")?,
+            }),
+            "trigger" => {
+                let pre_trigger_count =
+                    optional(value, "pre_trigger_count")?.unwrap_or(DEFAULT_PRE_TRIGGER_COUNT);
+                let post_trigger_count =
+                    optional(value, "post_trigger_count")?.unwrap_or(DEFAULT_POST_TRIGGER_COUNT);
+                if pre_trigger_count + post_trigger_count == 0 {
+                    return Err(AttributeError::ValidationError(
+                        "triggered capture must store at least one reading".to_string(),
+                    ));
+                }
+                Ok(Self::Trigger {
+                    trigger: required(value, "trigger")?,
+                    pre_trigger_count,
+                    post_trigger_count,
+                })
+            }
+            _ => Err(AttributeError::ValidationError(format!(
+                "unknown capture policy type {}",
+                policy_type
+            ))),
+        }
+    }
+}
+
+/// Named triggers shared between the data manager, the collectors waiting on them and
+/// whatever fires them. Each trigger counts how many times it fired so that every
+/// collector observes each firing exactly once.
+#[derive(Clone, Debug, Default)]
+pub struct CaptureTriggers(Arc<Mutex<HashMap<String, u64>>>);
+
+impl CaptureTriggers {
+    pub fn fire(&self, name: &str) {
+        log::debug!("capture trigger {} fired", name);
+        *self.0.lock().unwrap().entry(name.to_string()).or_default() += 1;
+    }
+
+    pub fn fire_count(&self, name: &str) -> u64 {
+        self.0
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// A CaptureFilter holds the state a `CapturePolicy` needs to decide whether readings
+/// should be stored
+#[derive(Debug)]
+pub(crate) struct CaptureFilter {
+    policy: CapturePolicy,
+    triggers: CaptureTriggers,
+    last_values: Option<Vec<f64>>,
+    history: VecDeque<SensorData>,
+    seen_fire_count: u64,
+    remaining_post_trigger: usize,
+}
+
+impl Default for CaptureFilter {
+    fn default() -> Self {
+        Self::new(CapturePolicy::Always, CaptureTriggers::default())
+    }
+}
+
+// Looks up a number in a struct, nested fields are separated by dots (e.g. "readings.temp")
+fn lookup_number(fields: &HashMap<String, Value>, path: &str) -> Option<f64> {
+    let (key, rest) = match path.split_once('.') {
+        Some((key, rest)) => (key, Some(rest)),
+        None => (path, None),
+    };
+    match (fields.get(key)?.kind.as_ref()?, rest) {
+        (ValueKind::NumberValue(n), None) => Some(*n),
+        (ValueKind::BoolValue(b), None) => Some(if *b { 1.0 } else { 0.0 }),
+        (ValueKind::StructValue(s), Some(rest)) => lookup_number(&s.fields, rest),
+        _ => None,
+    }
+}
+
+// Collects every number of a struct, sorted by path so that readings can be compared
+fn all_numbers(fields: &HashMap<String, Value>, out: &mut Vec<(String, f64)>, prefix: &str) {
+    for (key, value) in fields {
+        match value.kind.as_ref() {
+            Some(ValueKind::NumberValue(n)) => out.push((format!("{}{}", prefix, key), *n)),
+            Some(ValueKind::StructValue(s)) => {
+                all_numbers(&s.fields, out, &format!("{}{}.", prefix, key))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn numeric_values(reading: &SensorData, field: Option<&str>) -> Vec<f64> {
+    let fields = match reading.data.as_ref() {
+        Some(Data::Struct(s)) => &s.fields,
+        _ => return vec![],
+    };
+    match field {
+        Some(path) => lookup_number(fields, path).into_iter().collect(),
+        None => {
+            let mut numbers = vec![];
+            all_numbers(fields, &mut numbers, "");
+            numbers.sort_by(|a, b| a.0.cmp(&b.0));
+            numbers.into_iter().map(|(_, n)| n).collect()
+        }
+    }
+}
+
+impl CaptureFilter {
+    pub(crate) fn new(policy: CapturePolicy, triggers: CaptureTriggers) -> Self {
+        // firings preceding the creation of the filter are ignored
+        let seen_fire_count = match &policy {
+            CapturePolicy::Trigger { trigger, .. } => triggers.fire_count(trigger),
+            _ => 0,
+        };
+        Self {
+            policy,
+            triggers,
+            last_values: None,
+            history: VecDeque::new(),
+            seen_fire_count,
+            remaining_post_trigger: 0,
+        }
+    }
+
+    pub(crate) fn policy(&self) -> &CapturePolicy {
+        &self.policy
+    }
+
+    /// Returns the readings that should be stored following a new reading, possibly none
+    /// or several when readings kept before a trigger are released
+    pub(crate) fn filter(&mut self, reading: SensorData) -> Vec<SensorData> {
+        match &self.policy {
+            CapturePolicy::Always => vec![reading],
+            CapturePolicy::Deadband { field, deadband } => {
+                let values = numeric_values(&reading, field.as_deref());
+                // readings without the compared values are stored rather than silently dropped
+                let store = values.is_empty()
+                    || match &self.last_values {
+                        Some(last) => {
+                            last.len() != values.len()
+                                || last
+                                    .iter()
+                                    .zip(&values)
+                                    .any(|(a, b)| (a - b).abs() > *deadband)
+                        }
+                        None => true,
+                    };
+                if store {
+                    self.last_values = Some(values);
+                    vec![reading]
+                } else {
+                    vec![]
+                }
+            }
+            CapturePolicy::Threshold { field, threshold } => {
+                let values = numeric_values(&reading, field.as_deref());
+                // the first reading has nothing to be compared to and never crosses
+                let crossed = match &self.last_values {
+                    Some(last) => last
+                        .iter()
+                        .zip(&values)
+                        .any(|(a, b)| (*a > *threshold) != (*b > *threshold)),
+                    None => false,
+                };
+                self.last_values = Some(values);
+                if crossed {
+                    vec![reading]
+                } else {
+                    vec![]
+                }
+            }
+            CapturePolicy::Trigger {
+                trigger,
+                pre_trigger_count,
+                post_trigger_count,
+            } => {
+                let mut out = vec![];
+                let fire_count = self.triggers.fire_count(trigger);
+                if fire_count != self.seen_fire_count {
+                    self.seen_fire_count = fire_count;
+                    out.extend(self.history.drain(..));
+                    self.remaining_post_trigger = *post_trigger_count;
+                }
+                if self.remaining_post_trigger > 0 {
+                    self.remaining_post_trigger -= 1;
+                    out.push(reading);
+                } else if *pre_trigger_count > 0 {
+                    if self.history.len() == *pre_trigger_count {
+                        self.history.pop_front();
+                    }
+                    self.history.push_back(reading);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// A SensorTriggerConfig is a representation of an element of the list of
+/// "capture_triggers" in the attributes of the data manager service, it fires the named
+/// trigger whenever a field of a sensor's readings goes above or below a bound
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorTriggerConfig {
+    pub name: String,
+    pub sensor: String,
+    pub field: String,
+    pub above: Option<f64>,
+    pub below: Option<f64>,
+}
+
+impl TryFrom<&Kind> for SensorTriggerConfig {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        let above = optional(value, "above")?;
+        let below = optional(value, "below")?;
+        if above.is_none() && below.is_none() {
+            return Err(AttributeError::ValidationError(
+                "sensor trigger requires an above or below bound".to_string(),
+            ));
+        }
+        Ok(Self {
+            name: required(value, "name")?,
+            sensor: required(value, "sensor")?,
+            field: required(value, "field")?,
+            above,
+            below,
+        })
+    }
+}
+
+/// Polls a sensor and fires a trigger when the configured condition starts holding
+pub(crate) struct SensorTrigger {
+    config: SensorTriggerConfig,
+    sensor: SensorType,
+    triggers: CaptureTriggers,
+    active: bool,
+}
+
+impl SensorTrigger {
+    pub(crate) fn new(
+        config: SensorTriggerConfig,
+        sensor: SensorType,
+        triggers: CaptureTriggers,
+    ) -> Self {
+        Self {
+            config,
+            sensor,
+            triggers,
+            active: false,
+        }
+    }
+
+    pub(crate) fn poll(&mut self) -> Result<(), SensorError> {
+        let readings = self.sensor.lock().unwrap().get_generic_readings()?;
+        let active = lookup_number(&readings, &self.config.field).is_some_and(|v| {
+            self.config.above.is_some_and(|a| v > a) || self.config.below.is_some_and(|b| v < b)
+        });
+        // only the transition fires the trigger, a condition that keeps holding doesn't
+        if active && !self.active {
+            self.triggers.fire(&self.config.name);
+        }
+        self.active = active;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::{
+        CaptureFilter, CapturePolicy, CaptureTriggers, SensorTrigger, SensorTriggerConfig,
+    };
+    use crate::common::config::{AttributeError, Kind};
+    use crate::common::sensor::FakeSensor;
+    use crate::google::protobuf::{value::Kind as ValueKind, Struct, Value};
+    use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData};
+
+    fn reading(value: f64) -> SensorData {
+        SensorData {
+            metadata: None,
+            data: Some(Data::Struct(Struct {
+                fields: HashMap::from([(
+                    "value".to_string(),
+                    Value {
+                        kind: Some(ValueKind::NumberValue(value)),
+                    },
+                )]),
+            })),
+        }
+    }
+
+    fn stored(filter: &mut CaptureFilter, values: &[f64]) -> usize {
+        values
+            .iter()
+            .map(|v| filter.filter(reading(*v)).len())
+            .sum()
+    }
+
+    #[test_log::test]
+    fn test_capture_policy_config() -> Result<(), AttributeError> {
+        let kind = Kind::StructValue(HashMap::from([
+            (
+                "type".to_string(),
+                Kind::StringValue("deadband".to_string()),
+            ),
+            ("deadband".to_string(), Kind::NumberValue(0.5)),
+        ]));
+        assert_eq!(
+            CapturePolicy::try_from(&kind)?,
+            CapturePolicy::Deadband {
+                field: None,
+                deadband: 0.5
+            }
+        );
+        let kind = Kind::StructValue(HashMap::from([
+            ("type".to_string(), Kind::StringValue("trigger".to_string())),
+            (
+                "trigger".to_string(),
+                Kind::StringValue("alarm".to_string()),
+            ),
+            ("pre_trigger_count".to_string(), Kind::NumberValue(5.0)),
+        ]));
+        assert_eq!(
+            CapturePolicy::try_from(&kind)?,
+            CapturePolicy::Trigger {
+                trigger: "alarm".to_string(),
+                pre_trigger_count: 5,
+                post_trigger_count: 1
+            }
+        );
+        let kind = Kind::StructValue(HashMap::from([(
+            "type".to_string(),
+            Kind::StringValue("threshold".to_string()),
+        )]));
+        assert!(matches!(
+            CapturePolicy::try_from(&kind),
+            Err(AttributeError::KeyNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_deadband_and_threshold() {
+        let mut filter = CaptureFilter::new(
+            CapturePolicy::Deadband {
+                field: Some("value".to_string()),
+                deadband: 1.0,
+            },
+            CaptureTriggers::default(),
+        );
+        assert_eq!(stored(&mut filter, &[10.0, 10.5, 10.9, 11.5, 11.0]), 2);
+
+        let mut filter = CaptureFilter::new(
+            CapturePolicy::Threshold {
+                field: None,
+                threshold: 5.0,
+            },
+            CaptureTriggers::default(),
+        );
+        assert_eq!(stored(&mut filter, &[1.0, 6.0, 7.0, 4.0, 3.0]), 2);
+    }
+
+    #[test_log::test]
+    fn test_triggered_capture() {
+        let triggers = CaptureTriggers::default();
+        let mut filter = CaptureFilter::new(
+            CapturePolicy::Trigger {
+                trigger: "alarm".to_string(),
+                pre_trigger_count: 2,
+                post_trigger_count: 2,
+            },
+            triggers.clone(),
+        );
+        assert_eq!(stored(&mut filter, &[1.0, 2.0, 3.0]), 0);
+        triggers.fire("alarm");
+        // two readings of history and the current one
+        assert_eq!(filter.filter(reading(4.0)).len(), 3);
+        assert_eq!(stored(&mut filter, &[5.0, 6.0, 7.0]), 1);
+        triggers.fire("other");
+        assert_eq!(stored(&mut filter, &[8.0]), 0);
+    }
+
+    #[test_log::test]
+    fn test_sensor_trigger() {
+        let triggers = CaptureTriggers::default();
+        let mut trigger = SensorTrigger::new(
+            SensorTriggerConfig {
+                name: "hot".to_string(),
+                sensor: "sensor".to_string(),
+                field: "fake_sensor".to_string(),
+                above: Some(40.0),
+                below: None,
+            },
+            Arc::new(Mutex::new(FakeSensor::new())),
+            triggers.clone(),
+        );
+        trigger.poll().unwrap();
+        trigger.poll().unwrap();
+        assert_eq!(triggers.fire_count("hot"), 1);
+    }
+}
@@ -5,19 +5,28 @@ use super::{
 use crate::{
     common::{
         config::AgentConfig, credentials_storage::RobotConfigurationStorage, grpc::ServerError,
+        registry::ComponentRegistry, robot::LocalRobot,
     },
     proto::app::v1::RobotConfig,
 };
 use async_io::Timer;
 use futures_lite::{Future, FutureExt};
-use std::{fmt::Debug, pin::Pin, time::Duration};
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 #[cfg(feature = "ota")]
 use crate::common::{exec::Executor, ota};
 
 pub struct ConfigMonitor<'a, Storage> {
-    curr_config: Box<RobotConfig>, //config for robot gotten from last robot startup, aka inputted from entry
+    curr_config: RefCell<Box<RobotConfig>>, //config the robot was last built or reconfigured from
     storage: Storage,
+    robot: Arc<Mutex<LocalRobot>>,
+    registry: RefCell<Box<ComponentRegistry>>,
     #[cfg(feature = "ota")]
     executor: Executor,
     restart_hook: Box<dyn Fn() + 'a>,
@@ -32,18 +41,33 @@ where
     pub fn new(
         curr_config: Box<RobotConfig>,
         storage: Storage,
+        robot: Arc<Mutex<LocalRobot>>,
+        registry: Box<ComponentRegistry>,
         #[cfg(feature = "ota")] executor: Executor,
         restart_hook: impl Fn() + 'a,
     ) -> Self {
         Self {
-            curr_config,
+            curr_config: RefCell::new(curr_config),
             storage,
+            robot,
+            registry: RefCell::new(registry),
             #[cfg(feature = "ota")]
             executor,
             restart_hook: Box::new(restart_hook),
         }
     }
 
+    // Configs are compared by revision when both carry one, configs without a revision fall
+    // back to a deep comparison
+    fn config_changed(&self, new_config: &RobotConfig) -> bool {
+        let curr_config = self.curr_config.borrow();
+        if curr_config.revision.is_empty() || new_config.revision.is_empty() {
+            *new_config != **curr_config
+        } else {
+            new_config.revision != curr_config.revision
+        }
+    }
+
     fn restart(&self) -> ! {
         log::warn!("machine configuration change detected - restarting micro-rdk");
         (self.restart_hook)();
@@ -64,15 +88,15 @@ where
         Duration::from_secs(10)
     }
 
-    // TODO(RSDK-8160): Update "restart on config" to compare config version instead of deep
-    // comparison of config response, which relies on RSDK-8023 adding config version
+    // When the robot was built from the cached configuration (app wasn't reachable at boot),
+    // the first successful invocation reconciles it with the configuration held by app
     fn invoke<'c, 'b: 'c>(
         &'b self,
         app_client: &'c AppClient,
     ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, AppClientError>> + 'c>> {
         Box::pin(async move {
             let mut reboot = false;
-            let (new_config, _cfg_received_datetime) = app_client
+            let (new_config, cfg_received_datetime) = app_client
                 .get_app_config(None)
                 .or(async {
                     let _ = Timer::after(Duration::from_secs(60)).await;
@@ -107,14 +131,35 @@ where
                     }
                 }
 
-                if *config != *self.curr_config {
-                    if let Err(e) = self.storage.reset_robot_configuration() {
+                if self.config_changed(config) {
+                    log::info!(
+                        "machine config changed from revision '{}' to '{}'",
+                        self.curr_config.borrow().revision,
+                        config.revision
+                    );
+                    // store the new config rather than erasing the cached one so the robot can
+                    // boot from it should app be unreachable at the next boot
+                    if let Err(e) = self.storage.store_robot_configuration(config) {
                         log::warn!(
-                            "failed to reset machine config after new config detected: {}",
+                            "failed to store machine config after new config detected: {:?}",
                             e
                         );
-                    } else {
-                        reboot = true;
+                    }
+                    let reconfigured = self.robot.lock().unwrap().reconfigure(
+                        &self.curr_config.borrow(),
+                        config,
+                        &mut self.registry.borrow_mut(),
+                        cfg_received_datetime,
+                    );
+                    match reconfigured {
+                        Ok(()) => {
+                            log::info!("machine config applied without restarting");
+                            *self.curr_config.borrow_mut() = Box::new(config.clone());
+                        }
+                        Err(e) => {
+                            log::warn!("couldn't apply machine config in place: {}", e);
+                            reboot = true;
+                        }
                     }
                 }
             }
@@ -1,3 +1,5 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -55,4 +57,10 @@ pub trait Mdns {
     fn set_hostname(&mut self, _: &str) -> Result<(), MdnsError> {
         Ok(())
     }
+
+    /// Updates the addresses advertised by services added afterwards. Implementations
+    /// following the network interface on their own can ignore it
+    fn set_addresses(&mut self, _: Ipv4Addr, _: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        Ok(())
+    }
 }
@@ -1,4 +1,7 @@
-use std::net::Ipv4Addr;
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::{Arc, Mutex},
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,47 +17,192 @@ pub enum NetworkError {
     NoIpConfigured,
 }
 
+/// Addresses of a network interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetworkAddresses {
+    pub ipv4: Ipv4Addr,
+    pub ipv6: Option<Ipv6Addr>,
+}
+
+impl NetworkAddresses {
+    pub fn new(ipv4: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Self {
+        Self { ipv4, ipv6 }
+    }
+}
+
+impl<T: Network + ?Sized> From<&T> for NetworkAddresses {
+    fn from(network: &T) -> Self {
+        Self::new(network.get_ip(), network.get_ipv6())
+    }
+}
+
+#[derive(Default)]
+struct AddressChangeNotifierInner {
+    current: Option<NetworkAddresses>,
+    subscribers: Vec<async_channel::Sender<NetworkAddresses>>,
+}
+
+/// Broadcasts the new addresses of a network interface to every subscriber when they change.
+/// Clones share their subscribers so a clone can be moved in an event handler
+#[derive(Clone, Default)]
+pub struct AddressChangeNotifier(Arc<Mutex<AddressChangeNotifierInner>>);
+
+impl AddressChangeNotifier {
+    /// Creates a notifier, only addresses different from `current` will be broadcast
+    pub fn new(current: NetworkAddresses) -> Self {
+        Self(Arc::new(Mutex::new(AddressChangeNotifierInner {
+            current: Some(current),
+            subscribers: vec![],
+        })))
+    }
+    /// Returns a receiver of the addresses broadcast from now on, subscribers that went away
+    /// are dropped first as addresses rarely change while connections come and go
+    pub fn subscribe(&self) -> async_channel::Receiver<NetworkAddresses> {
+        let (tx, rx) = async_channel::unbounded();
+        let mut inner = self.0.lock().unwrap();
+        inner.subscribers.retain(|tx| !tx.is_closed());
+        inner.subscribers.push(tx);
+        rx
+    }
+    /// Broadcasts `addresses` if they differ from the last ones notified, subscribers that
+    /// went away are dropped
+    pub fn notify(&self, addresses: NetworkAddresses) {
+        // a lost address isn't broadcast, subscribers are notified once a new one is obtained
+        if addresses.ipv4.is_unspecified() && addresses.ipv6.is_none() {
+            return;
+        }
+        let mut inner = self.0.lock().unwrap();
+        if inner.current.replace(addresses) == Some(addresses) {
+            return;
+        }
+        log::info!(
+            "network addresses changed to {} {:?}",
+            addresses.ipv4,
+            addresses.ipv6
+        );
+        inner
+            .subscribers
+            .retain(|tx| tx.try_send(addresses).is_ok());
+    }
+}
+
 /// Reflects the representation of a network's status.
 pub trait Network {
     /// Get the current IP address of the network interface.
     fn get_ip(&self) -> Ipv4Addr;
 
+    /// Get the current IPv6 address of the network interface, if it has one. Global addresses
+    /// are preferred over link-local ones.
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        None
+    }
+
     /// Returns whether the underlying network interface is connected, *not* if
     /// internet access is available
     fn is_connected(&self) -> Result<bool, NetworkError>;
+
+    /// Returns a receiver getting the new addresses of the network interface every time they
+    /// change (DHCP lease with a new address, roaming...). Networks unable to track their
+    /// addresses return None
+    fn subscribe_address_changes(&self) -> Option<async_channel::Receiver<NetworkAddresses>> {
+        None
+    }
 }
 
 impl<T: Network + ?Sized> Network for Box<T> {
     fn get_ip(&self) -> Ipv4Addr {
         (**self).get_ip()
     }
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        (**self).get_ipv6()
+    }
     fn is_connected(&self) -> Result<bool, NetworkError> {
         (**self).is_connected()
     }
+    fn subscribe_address_changes(&self) -> Option<async_channel::Receiver<NetworkAddresses>> {
+        (**self).subscribe_address_changes()
+    }
 }
 
 /// For networks managed outside of micro-rdk (for example, using micro-rdk as an ESP-IDF
 /// component in a separate project), this struct is meant to simply communicate the IP
-/// address statically. It will trivially always appear as connected because connectivity
-/// management is external
-#[repr(C)]
+/// address. It will trivially always appear as connected because connectivity
+/// management is external. Clones share their addresses, a clone kept by the external code
+/// can report a change of address with [`ExternallyManagedNetwork::set_addresses`]
+#[derive(Clone)]
 pub struct ExternallyManagedNetwork {
-    ip: Ipv4Addr,
+    addresses: Arc<Mutex<NetworkAddresses>>,
+    notifier: AddressChangeNotifier,
 }
 
 impl ExternallyManagedNetwork {
     pub fn new(ip: Ipv4Addr) -> Self {
-        Self { ip }
+        Self::from_addresses(NetworkAddresses::new(ip, None))
+    }
+    fn from_addresses(addresses: NetworkAddresses) -> Self {
+        Self {
+            addresses: Arc::new(Mutex::new(addresses)),
+            notifier: AddressChangeNotifier::new(addresses),
+        }
+    }
+    /// Sets the IPv6 address of the network, `ip` can be left unspecified on IPv6 only networks
+    pub fn with_ipv6(self, ipv6: Ipv6Addr) -> Self {
+        Self::from_addresses(NetworkAddresses::new(self.get_ip(), Some(ipv6)))
+    }
+    /// Updates the addresses of the network, subscribers are notified when they changed
+    pub fn set_addresses(&self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) {
+        let addresses = NetworkAddresses::new(ip, ipv6);
+        *self.addresses.lock().unwrap() = addresses;
+        self.notifier.notify(addresses);
     }
 }
 
 impl Network for ExternallyManagedNetwork {
-    // TODO: provide a way for an external managed network to communicate a change in IP
-    // address
     fn get_ip(&self) -> Ipv4Addr {
-        self.ip
+        self.addresses.lock().unwrap().ipv4
+    }
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        self.addresses.lock().unwrap().ipv6
     }
     fn is_connected(&self) -> Result<bool, NetworkError> {
         Ok(true)
     }
+    fn subscribe_address_changes(&self) -> Option<async_channel::Receiver<NetworkAddresses>> {
+        Some(self.notifier.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::{AddressChangeNotifier, ExternallyManagedNetwork, Network};
+
+    #[test_log::test]
+    fn test_address_changes() {
+        let network = ExternallyManagedNetwork::new(Ipv4Addr::new(10, 1, 2, 3));
+        let changes = network.subscribe_address_changes().unwrap();
+
+        // the same addresses aren't notified twice
+        network.set_addresses(Ipv4Addr::new(10, 1, 2, 3), None);
+        assert!(changes.try_recv().is_err());
+
+        let external = network.clone();
+        external.set_addresses(Ipv4Addr::new(10, 1, 2, 4), Some(Ipv6Addr::LOCALHOST));
+        let addresses = changes.try_recv().unwrap();
+        assert_eq!(addresses.ipv4, Ipv4Addr::new(10, 1, 2, 4));
+        assert_eq!(addresses.ipv6, Some(Ipv6Addr::LOCALHOST));
+        assert_eq!(network.get_ip(), Ipv4Addr::new(10, 1, 2, 4));
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[test_log::test]
+    fn test_subscribers_pruned() {
+        let notifier = AddressChangeNotifier::default();
+        for _ in 0..3 {
+            let _ = notifier.subscribe();
+        }
+        let _changes = notifier.subscribe();
+        assert_eq!(notifier.0.lock().unwrap().subscribers.len(), 1);
+    }
 }
@@ -6,11 +6,15 @@ use crate::common::{
         certificate::Certificate,
         dtls::DtlsBuilder,
         grpc::{WebRtcGrpcBody, WebRtcGrpcServer},
+        ice::IceServer,
         io::WebRtcTransport,
         sctp::SctpHandle,
     },
 };
 
+#[cfg(feature = "camera")]
+use crate::common::webrtc::media::StreamServer;
+
 use async_io::Timer;
 
 use futures_lite::prelude::*;
@@ -21,11 +25,21 @@ use std::{rc::Rc, time::Duration};
 pub struct WebRtcConfiguration {
     pub(crate) dtls: Box<dyn DtlsBuilder>,
     pub(crate) cert: Rc<Box<dyn Certificate>>,
+    pub(crate) ice_servers: Vec<IceServer>,
 }
 
 impl WebRtcConfiguration {
     pub fn new(cert: Rc<Box<dyn Certificate>>, dtls: Box<dyn DtlsBuilder>) -> Self {
-        Self { cert, dtls }
+        Self {
+            cert,
+            dtls,
+            ice_servers: vec![],
+        }
+    }
+    /// STUN and TURN servers used in addition to the ones provided by signaling
+    pub fn with_ice_servers(mut self, ice_servers: Vec<IceServer>) -> Self {
+        self.ice_servers = ice_servers;
+        self
     }
 }
 
@@ -33,7 +47,10 @@ pub(crate) struct WebRTCConnection {
     server: WebRtcGrpcServer<GrpcServer<WebRtcGrpcBody>>,
     _transport: WebRtcTransport,
     ice_agent: AtomicSync,
+    ice_disconnected: AtomicSync,
     sctp_handle: SctpHandle,
+    #[cfg(feature = "camera")]
+    stream_server: Option<StreamServer>,
 }
 
 impl Drop for WebRTCConnection {
@@ -48,22 +65,45 @@ impl WebRTCConnection {
         server: WebRtcGrpcServer<GrpcServer<WebRtcGrpcBody>>,
         transport: WebRtcTransport,
         ice_agent: AtomicSync,
+        ice_disconnected: AtomicSync,
         sctp_handle: SctpHandle,
     ) -> Self {
         Self {
             server,
             _transport: transport,
             ice_agent,
+            ice_disconnected,
             sctp_handle,
+            #[cfg(feature = "camera")]
+            stream_server: None,
         }
     }
+    /// Keeps the connection open while it streams video even if no request comes in
+    #[cfg(feature = "camera")]
+    pub(crate) fn with_stream_server(mut self, stream_server: StreamServer) -> Self {
+        let _ = self.stream_server.insert(stream_server);
+        self
+    }
+    /// Serves requests until the connection is idle for too long or the peer is gone, dropping
+    /// the connection then tears down the SCTP association and the ICE agent
     pub(crate) async fn run(&mut self) -> Result<(), ServerError> {
         loop {
+            let ice_disconnected = self.ice_disconnected.clone();
+            #[cfg(feature = "camera")]
+            let stream_server = self.stream_server.clone();
             let req = self
                 .server
                 .next_request()
+                .or(async {
+                    ice_disconnected.await;
+                    Err(WebRtcError::IceDisconnected)
+                })
                 .or(async {
                     Timer::after(Duration::from_secs(30)).await;
+                    #[cfg(feature = "camera")]
+                    while stream_server.as_ref().is_some_and(|s| s.is_streaming()) {
+                        Timer::after(Duration::from_secs(30)).await;
+                    }
                     Err(WebRtcError::OperationTimeout)
                 })
                 .await;
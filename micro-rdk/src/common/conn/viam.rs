@@ -38,6 +38,7 @@ use crate::common::robot::LocalRobot;
 use crate::common::webrtc::api::{SignalingTask, WebRtcApi, WebRtcError, WebRtcSignalingChannel};
 use crate::common::webrtc::certificate::Certificate;
 use crate::common::webrtc::dtls::DtlsBuilder;
+use crate::common::webrtc::ice::default_ice_servers;
 use crate::common::{
     credentials_storage::{RobotConfigurationStorage, WifiCredentialStorage},
     exec::Executor,
@@ -47,13 +48,18 @@ use crate::proto::app::v1::RobotConfig;
 
 use super::errors;
 use super::mdns::Mdns;
-use super::network::Network;
+use super::network::{Network, NetworkAddresses};
 use super::server::{IncomingConnectionManager, WebRtcConfiguration};
 use crate::common::provisioning::server::AsNetwork;
 
 #[cfg(feature = "ota")]
 use crate::common::credentials_storage::OtaMetadataStorage;
 
+// Time spent trying to reach app at boot before building the robot from the cached
+// configuration (offline mode). App client tasks keep trying to reach app in the background and
+// the config monitor reconciles the cached configuration once it succeeds.
+const APP_BOOT_TIMEOUT: Duration = Duration::from_secs(15);
+
 pub struct RobotCloudConfig {
     local_fqdn: String,
     name: String,
@@ -544,6 +550,10 @@ where
 
         let app_client = self
             .connect_to_app()
+            .or(async {
+                let _ = Timer::after(APP_BOOT_TIMEOUT).await;
+                Err(AppClientError::AppClientRequestTimeout)
+            })
             .await
             .inspect_err(|error| {
                 log::error!("couldn't connect to {} reason {:?}", app_address, error);
@@ -569,6 +579,10 @@ where
         let config = match app_client.as_ref() {
             Some(app) => app
                 .get_app_config(Some(network.get_ip()))
+                .or(async {
+                    let _ = Timer::after(APP_BOOT_TIMEOUT).await;
+                    Err(AppClientError::AppClientRequestTimeout)
+                })
                 .await
                 .inspect_err(|err| {
                     log::error!(
@@ -590,7 +604,10 @@ where
                         .map_or_else(|_| {
                             log::warn!("unable to obtain a cached machine configuration from storage - an empty machine will be created");
                             Box::default()
-                        }, Box::new),
+                        }, |config| {
+                            log::info!("starting in offline mode from cached machine configuration (revision '{}') - it will be reconciled once app server is reachable", config.revision);
+                            Box::new(config)
+                        }),
                     None,
                 )
             },
@@ -607,15 +624,6 @@ where
             );
         }
 
-        let config_monitor_task = Box::new(ConfigMonitor::new(
-            config.clone(),
-            self.storage.clone(),
-            #[cfg(feature = "ota")]
-            self.executor.clone(),
-            || std::process::exit(0),
-        ));
-        self.app_client_tasks.push(config_monitor_task);
-
         log::info!("building machine from configuration");
         let mut robot = LocalRobot::from_cloud_config(
             self.executor.clone(),
@@ -634,6 +642,17 @@ where
 
         let robot = Arc::new(Mutex::new(robot));
 
+        let config_monitor_task = Box::new(ConfigMonitor::new(
+            config.clone(),
+            self.storage.clone(),
+            robot.clone(),
+            self.component_registry.clone(),
+            #[cfg(feature = "ota")]
+            self.executor.clone(),
+            || std::process::exit(0),
+        ));
+        self.app_client_tasks.push(config_monitor_task);
+
         if self.http2_server.has_http2_server() && !self.http2_server_insecure {
             // Try to obtain and store a fresh TLS certificate. If this fails or we cannot reach
             // app, then we'll end up falling back on whatever TLS certificate was cached. Note:
@@ -716,9 +735,10 @@ where
         }
 
         let mut tasks: FuturesUnordered<_> = FuturesUnordered::new();
-        tasks.push(Either::Right(Box::pin(
-            self.run_app_client_tasks(app_client),
-        )));
+        tasks.push(Either::Right(Box::pin(self.run_app_client_tasks(
+            app_client,
+            network.subscribe_address_changes(),
+        ))));
         tasks.push(Either::Left(Box::pin(inner.run())));
 
         log::info!("viam server started");
@@ -761,9 +781,13 @@ where
 
     // run task forever reconnecting on demand
     // if a task returns an error, the app client will be dropped
+    // the app client is also dropped when the addresses of the network changed, its connection
+    // was made from the previous address and reconnecting announces the machine to signaling
+    // again
     async fn run_app_client_tasks(
         &self,
         mut app_client: Option<AppClient>,
+        address_changes: Option<Receiver<NetworkAddresses>>,
     ) -> Result<(), errors::ServerError> {
         let wait = Duration::from_secs(1); // should do exponential back off
         loop {
@@ -780,15 +804,26 @@ where
                         },
                     });
                 }
-                while let Some(res) = app_client_tasks.next().await {
-                    if let Err(err) = res {
-                        log::error!(
-                            "an app client task returned an error {:?} - dropping app client",
-                            err
-                        );
-                        break;
+                async {
+                    while let Some(res) = app_client_tasks.next().await {
+                        if let Err(err) = res {
+                            log::error!(
+                                "an app client task returned an error {:?} - dropping app client",
+                                err
+                            );
+                            break;
+                        }
                     }
                 }
+                .or(async {
+                    match address_changes.as_ref() {
+                        Some(changes) if changes.recv().await.is_ok() => {
+                            log::info!("network addresses changed - dropping app client");
+                        }
+                        _ => futures_lite::future::pending().await,
+                    }
+                })
+                .await;
             }
 
             // Explicitly release the app_client resources before sleeping, and before
@@ -878,6 +913,8 @@ struct RobotServer<'a, M> {
 pub(crate) enum IncomingConnection {
     HTTP2Connection(std::io::Result<(Async<TcpStream>, SocketAddr)>),
     WebRTCConnection(Result<Box<WebRtcSignalingChannel>, WebRtcError>),
+    // not a connection, the addresses of the network changed while waiting for one
+    AddressChanged(NetworkAddresses),
 }
 
 impl<'a, M> RobotServer<'a, M>
@@ -956,14 +993,20 @@ where
 
             IncomingConnection::WebRTCConnection(conn) => {
                 let sig = conn.map_err(|e| errors::ServerError::Other(e.into()))?;
-                let ip = self.network.get_ip();
                 if let WebRtcListener::WebRtc(conf) = self.webrtc_config {
+                    let mut ice_servers = sig.ice_servers().to_vec();
+                    ice_servers.extend(conf.ice_servers.iter().cloned());
+                    if ice_servers.is_empty() {
+                        ice_servers = default_ice_servers();
+                    }
                     let mut api = WebRtcApi::new(
                         self.executor.clone(),
                         sig,
                         conf.cert.clone(),
-                        ip,
+                        NetworkAddresses::from(self.network),
                         conf.dtls.make()?,
+                        ice_servers,
+                        self.network.subscribe_address_changes(),
                     );
                     let (answer, prio) = api.answer(0).await?;
                     let robot = self.robot.clone();
@@ -988,34 +1031,42 @@ where
                         .await;
                 }
             }
+            // handled by run
+            IncomingConnection::AddressChanged(_) => {}
+        }
+        Ok(())
+    }
+
+    // advertises the machine on the local network
+    fn advertise_services(&self) -> Result<(), errors::ServerError> {
+        if let Some(cfg) = self.robot_config.cloud.as_ref() {
+            let mut mdns = self.mdns.borrow_mut();
+            let cfg: RobotCloudConfig = cfg.into();
+            mdns.set_hostname(&cfg.name)
+                .map_err(|e| errors::ServerError::Other(e.into()))?;
+            mdns.add_service(
+                &cfg.local_fqdn.replace('.', "-"),
+                "_rpc",
+                "_tcp",
+                self.http2_server_port,
+                &[("grpc", ""), ("webrtc", "")],
+            )
+            .map_err(|e| errors::ServerError::Other(e.into()))?;
+            mdns.add_service(
+                &cfg.fqdn.replace('.', "-"),
+                "_rpc",
+                "_tcp",
+                self.http2_server_port,
+                &[("grpc", ""), ("webrtc", "")],
+            )
+            .map_err(|e| errors::ServerError::Other(e.into()))?;
         }
         Ok(())
     }
 
     async fn run(&mut self) -> Result<(), errors::ServerError> {
         let http2_listener = if let HTTP2Server::HTTP2Connector(_) = self.http2_server {
-            if let Some(cfg) = self.robot_config.cloud.as_ref() {
-                let mut mdns = self.mdns.borrow_mut();
-                let cfg: RobotCloudConfig = cfg.into();
-                mdns.set_hostname(&cfg.name)
-                    .map_err(|e| errors::ServerError::Other(e.into()))?;
-                mdns.add_service(
-                    &cfg.local_fqdn.replace('.', "-"),
-                    "_rpc",
-                    "_tcp",
-                    self.http2_server_port,
-                    &[("grpc", ""), ("webrtc", "")],
-                )
-                .map_err(|e| errors::ServerError::Other(e.into()))?;
-                mdns.add_service(
-                    &cfg.fqdn.replace('.', "-"),
-                    "_rpc",
-                    "_tcp",
-                    self.http2_server_port,
-                    &[("grpc", ""), ("webrtc", "")],
-                )
-                .map_err(|e| errors::ServerError::Other(e.into()))?;
-            }
+            self.advertise_services()?;
             Some(async_io::Async::new(TcpListener::bind(format!(
                 "0.0.0.0:{}",
                 self.http2_server_port
@@ -1023,6 +1074,17 @@ where
         } else {
             None
         };
+        // IPv6 clients are served by a second, IPv6 only, listener so it doesn't clash with the
+        // IPv4 one on the same port
+        let mut http2_listener_v6 = match (&http2_listener, self.network.get_ipv6()) {
+            (Some(_), Some(_)) => bind_ipv6_only_listener(self.http2_server_port)
+                .and_then(async_io::Async::new)
+                .inspect_err(|e| log::warn!("failed to listen for HTTP2 on IPv6: {}", e))
+                .ok(),
+            _ => None,
+        };
+        // WebRTC connections follow the address changes on their own
+        let address_changes = self.network.subscribe_address_changes();
 
         loop {
             let h2_conn: Pin<Box<dyn Future<Output = IncomingConnection>>> =
@@ -1033,9 +1095,14 @@ where
                 } else {
                     // safe to unwrap, always exists
                     Box::pin(async {
-                        IncomingConnection::HTTP2Connection(
-                            http2_listener.as_ref().unwrap().accept().await,
-                        )
+                        let v4 = http2_listener.as_ref().unwrap().accept();
+                        let v6 = async {
+                            match http2_listener_v6.as_ref() {
+                                Some(listener) => listener.accept().await,
+                                None => futures_lite::future::pending().await,
+                            }
+                        };
+                        IncomingConnection::HTTP2Connection(futures_lite::future::or(v4, v6).await)
                     })
                 };
 
@@ -1055,8 +1122,45 @@ where
                     })
                 };
 
+            let address_change = async {
+                match address_changes.as_ref() {
+                    Some(changes) => match changes.recv().await {
+                        Ok(addresses) => IncomingConnection::AddressChanged(addresses),
+                        Err(_) => futures_lite::future::pending().await,
+                    },
+                    None => futures_lite::future::pending().await,
+                }
+            };
+
             log::info!("machine server waiting for a new incoming connection");
-            let incoming = Box::pin(futures_lite::future::or(h2_conn, webrtc_conn)).await;
+            let incoming = Box::pin(futures_lite::future::or(
+                futures_lite::future::or(h2_conn, webrtc_conn),
+                address_change,
+            ))
+            .await;
+            if let IncomingConnection::AddressChanged(addresses) = incoming {
+                log::info!("advertising the machine on its new addresses");
+                if let Err(e) = self
+                    .mdns
+                    .borrow_mut()
+                    .set_addresses(addresses.ipv4, addresses.ipv6)
+                {
+                    log::warn!("failed to update the mdns addresses: {}", e);
+                }
+                if let Err(e) = self.advertise_services() {
+                    log::warn!("failed to advertise the machine: {:?}", e);
+                }
+                if http2_listener.is_some()
+                    && http2_listener_v6.is_none()
+                    && addresses.ipv6.is_some()
+                {
+                    http2_listener_v6 = bind_ipv6_only_listener(self.http2_server_port)
+                        .and_then(async_io::Async::new)
+                        .inspect_err(|e| log::warn!("failed to listen for HTTP2 on IPv6: {}", e))
+                        .ok();
+                }
+                continue;
+            }
             if let Err(e) = self.serve_incoming_connection(incoming).await {
                 log::error!("failed to serve incoming connection: {:?}", e)
             }
@@ -1064,6 +1168,19 @@ where
     }
 }
 
+fn bind_ipv6_only_listener(port: u16) -> std::io::Result<TcpListener> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::IPV6,
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )?;
+    socket.set_only_v6(true)?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::from((std::net::Ipv6Addr::UNSPECIFIED, port)).into())?;
+    socket.listen(128)?;
+    Ok(socket.into())
+}
+
 pin_project_lite::pin_project! {
     #[project = TaskRunnerStateProj]
     enum TaskRunnerState<'a> {
@@ -1,19 +1,43 @@
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::time::{Duration, Instant};
 
-use crate::google::protobuf::Timestamp;
-use crate::proto::app::data_sync::v1::{MimeType, SensorData, SensorMetadata};
+use crate::google::protobuf::{self, value::Kind as ValueKind, Any, Struct, Timestamp};
+use crate::proto::app::data_sync::v1::{
+    sensor_data::Data, DataType, MimeType, SensorData, SensorMetadata,
+};
+use crate::proto::component::encoder::v1::PositionType;
+use prost::Message;
 
 use super::{
+    aggregation::{AggregationConfig, Aggregator},
+    analog::{AnalogError, AnalogReader},
+    board::{Board, BoardError},
+    capture_policy::{CaptureFilter, CapturePolicy, CaptureTriggers},
     config::{AttributeError, Kind},
+    encoder::{Encoder, EncoderError, EncoderPositionType},
+    motor::{Motor, MotorError},
     movement_sensor::MovementSensor,
+    power_sensor::{PowerSensor, PowerSupplyType},
     robot::ResourceType,
     sensor::{Readings, SensorError},
+    servo::{Servo, ServoError},
 };
 
+#[cfg(feature = "camera")]
+use super::camera::{Camera, CameraError, MIME_TYPE_JPEG, MIME_TYPE_PNG};
+
 use thiserror::Error;
 
 pub(crate) const DEFAULT_CACHE_SIZE_KB: f64 = 8.0;
+/// Default cache size of the methods capturing binary data, large enough to hold a few images
+pub(crate) const DEFAULT_BINARY_CACHE_SIZE_KB: f64 = 32.0;
+
+/// Key of "additional_params" selecting the analog reader captured by the Analogs method
+pub const ANALOG_READER_NAME_KEY: &str = "reader_name";
+/// Key of "additional_params" selecting the digital interrupt captured by the
+/// DigitalInterrupts method, digital interrupts are named after their pin
+pub const DIGITAL_INTERRUPT_NAME_KEY: &str = "digital_interrupt_name";
 
 /// A DataCollectorConfig instance is a representation of an element
 /// of the list of "capture_methods" in the "attributes" section of a
@@ -26,6 +50,8 @@ pub struct DataCollectorConfig {
     pub capture_frequency_hz: f32,
     pub capacity: usize,
     pub disabled: bool,
+    pub capture_policy: CapturePolicy,
+    pub aggregation: Option<AggregationConfig>,
 }
 
 impl TryFrom<&Kind> for DataCollectorConfig {
@@ -48,9 +74,44 @@ impl TryFrom<&Kind> for DataCollectorConfig {
                 "capture_frequency_hz".to_string(),
             ))?
             .try_into()?;
+        let method = match method_str.as_str() {
+            "Readings" => CollectionMethod::Readings,
+            "AngularVelocity" => CollectionMethod::AngularVelocity,
+            "LinearAcceleration" => CollectionMethod::LinearAcceleration,
+            "LinearVelocity" => CollectionMethod::LinearVelocity,
+            "CompassHeading" => CollectionMethod::CompassHeading,
+            "Orientation" => CollectionMethod::Orientation,
+            "Position" => CollectionMethod::Position,
+            "IsPowered" => CollectionMethod::IsPowered,
+            "TicksCount" => CollectionMethod::TicksCount,
+            "Voltage" => CollectionMethod::Voltage,
+            "Current" => CollectionMethod::Current,
+            "Power" => CollectionMethod::Power,
+            "ReadImage" => CollectionMethod::ReadImage,
+            "Analogs" => {
+                CollectionMethod::Analogs(method_parameter(value, ANALOG_READER_NAME_KEY)?)
+            }
+            "DigitalInterrupts" => {
+                let name = method_parameter(value, DIGITAL_INTERRUPT_NAME_KEY)?;
+                if name.parse::<i32>().is_err() {
+                    return Err(AttributeError::ValidationError(format!(
+                        "digital interrupt name must be a pin number, got {}",
+                        name
+                    )));
+                }
+                CollectionMethod::DigitalInterrupts(name)
+            }
+            _ => {
+                return Err(AttributeError::ConversionImpossibleError);
+            }
+        };
+        let default_cache_size_kb = Kind::NumberValue(match method.data_type() {
+            DataType::BinarySensor => DEFAULT_BINARY_CACHE_SIZE_KB,
+            _ => DEFAULT_CACHE_SIZE_KB,
+        });
         let capacity_kb: f64 = value
             .get("cache_size_kb")?
-            .unwrap_or(&Kind::NumberValue(DEFAULT_CACHE_SIZE_KB))
+            .unwrap_or(&default_cache_size_kb)
             .try_into()?;
         let capacity = (capacity_kb * 1000.0) as usize;
         if (capacity < 1000) && !disabled {
@@ -58,25 +119,48 @@ impl TryFrom<&Kind> for DataCollectorConfig {
                 "cache size must be at least 1KB".to_string(),
             ));
         }
-        // TODO: RSDK-7127 - Collectors that take arguments (ex. Board Analogs)
-        let method = match method_str.as_str() {
-            "Readings" => CollectionMethod::Readings,
-            "AngularVelocity" => CollectionMethod::AngularVelocity,
-            "LinearAcceleration" => CollectionMethod::LinearAcceleration,
-            "LinearVelocity" => CollectionMethod::LinearVelocity,
-            _ => {
-                return Err(AttributeError::ConversionImpossibleError);
-            }
+        let capture_policy = match value.get("capture_policy")? {
+            Some(policy) => policy.try_into()?,
+            None => CapturePolicy::default(),
         };
+        if method.data_type() == DataType::BinarySensor && !capture_policy.supports_binary_data() {
+            return Err(AttributeError::ValidationError(format!(
+                "capture policy {:?} cannot be applied to method {}",
+                capture_policy, method
+            )));
+        }
+        let aggregation = value
+            .get("aggregation")?
+            .map(AggregationConfig::try_from)
+            .transpose()?;
+        if method.data_type() == DataType::BinarySensor && aggregation.is_some() {
+            return Err(AttributeError::ValidationError(format!(
+                "method {} cannot be aggregated",
+                method
+            )));
+        }
         Ok(DataCollectorConfig {
             method,
             capture_frequency_hz,
             capacity,
             disabled,
+            capture_policy,
+            aggregation,
         })
     }
 }
 
+// Reads a string argument of a collection method from the "additional_params" section of
+// its configuration
+fn method_parameter(value: &Kind, key: &str) -> Result<String, AttributeError> {
+    value
+        .get("additional_params")?
+        .ok_or(AttributeError::KeyNotFound("additional_params".to_string()))?
+        .get(key)?
+        .ok_or(AttributeError::KeyNotFound(key.to_string()))?
+        .try_into()
+}
+
 /// A CollectionMethod is an enum whose values are associated with
 /// a method on one or more component traits
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -86,7 +170,63 @@ pub enum CollectionMethod {
     AngularVelocity,
     LinearAcceleration,
     LinearVelocity,
-    // TODO: RSDK-7127 - Implement collectors for all other applicable components/methods
+    CompassHeading,
+    Orientation,
+    // Motor, MovementSensor and Servo methods
+    Position,
+    // Motor methods
+    IsPowered,
+    // Encoder methods
+    TicksCount,
+    // PowerSensor methods
+    Voltage,
+    Current,
+    Power,
+    // Board methods, selecting an analog reader or a digital interrupt by name
+    Analogs(String),
+    DigitalInterrupts(String),
+    // Camera methods
+    ReadImage,
+}
+
+impl CollectionMethod {
+    /// Returns the type of the data captured by the method, binary data is uploaded
+    /// separately from tabular readings
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Self::ReadImage => DataType::BinarySensor,
+            _ => DataType::TabularSensor,
+        }
+    }
+
+    /// Returns the name and value of the argument the method is called with, if any
+    pub fn parameter(&self) -> Option<(&'static str, &str)> {
+        match self {
+            Self::Analogs(name) => Some((ANALOG_READER_NAME_KEY, name)),
+            Self::DigitalInterrupts(name) => Some((DIGITAL_INTERRUPT_NAME_KEY, name)),
+            _ => None,
+        }
+    }
+
+    /// Arguments of the method as expected by the method_parameters field of an upload's
+    /// metadata
+    pub(crate) fn method_parameters(&self) -> HashMap<String, Any> {
+        self.parameter()
+            .map(|(key, value)| {
+                (
+                    key.to_string(),
+                    Any {
+                        type_url: "type.googleapis.com/google.protobuf.StringValue".to_string(),
+                        value: protobuf::StringValue {
+                            value: value.to_string(),
+                        }
+                        .encode_to_vec(),
+                    },
+                )
+            })
+            .into_iter()
+            .collect()
+    }
 }
 
 impl Display for CollectionMethod {
@@ -99,6 +239,17 @@ impl Display for CollectionMethod {
                 Self::AngularVelocity => "AngularVelocity",
                 Self::LinearAcceleration => "LinearAcceleration",
                 Self::LinearVelocity => "LinearVelocity",
+                Self::CompassHeading => "CompassHeading",
+                Self::Orientation => "Orientation",
+                Self::Position => "Position",
+                Self::IsPowered => "IsPowered",
+                Self::TicksCount => "TicksCount",
+                Self::Voltage => "Voltage",
+                Self::Current => "Current",
+                Self::Power => "Power",
+                Self::Analogs(_) => "Analogs",
+                Self::DigitalInterrupts(_) => "DigitalInterrupts",
+                Self::ReadImage => "ReadImage",
             },
             f,
         )
@@ -114,11 +265,19 @@ pub struct ResourceMethodKey {
 
 impl Display for ResourceMethodKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "ResourceMethodKey ({}:{}, {})",
-            self.component_type, self.r_name, &self.method
-        )
+        match self.method.parameter() {
+            // keeps collectors of the same method on different readers/interrupts apart
+            Some((key, value)) => write!(
+                f,
+                "ResourceMethodKey ({}:{}, {} {}={})",
+                self.component_type, self.r_name, &self.method, key, value
+            ),
+            None => write!(
+                f,
+                "ResourceMethodKey ({}:{}, {})",
+                self.component_type, self.r_name, &self.method
+            ),
+        }
     }
 }
 
@@ -132,6 +291,19 @@ pub enum DataCollectionError {
     UnsupportedCaptureFrequency,
     #[error(transparent)]
     SensorCollectionError(#[from] SensorError),
+    #[error(transparent)]
+    MotorCollectionError(#[from] MotorError),
+    #[error(transparent)]
+    EncoderCollectionError(#[from] EncoderError),
+    #[error(transparent)]
+    BoardCollectionError(#[from] BoardError),
+    #[error(transparent)]
+    AnalogCollectionError(#[from] AnalogError),
+    #[error(transparent)]
+    ServoCollectionError(#[from] ServoError),
+    #[cfg(feature = "camera")]
+    #[error(transparent)]
+    CameraCollectionError(#[from] CameraError),
 }
 
 /// A DataCollector represents an association between a data collection method and
@@ -144,6 +316,8 @@ pub struct DataCollector {
     method: CollectionMethod,
     time_interval: Duration,
     capacity: usize,
+    aggregator: Option<Aggregator>,
+    filter: CaptureFilter,
 }
 
 fn resource_method_pair_is_valid(resource: &ResourceType, method: &CollectionMethod) -> bool {
@@ -155,11 +329,50 @@ fn resource_method_pair_is_valid(resource: &ResourceType, method: &CollectionMet
                 | CollectionMethod::AngularVelocity
                 | CollectionMethod::LinearAcceleration
                 | CollectionMethod::LinearVelocity
+                | CollectionMethod::CompassHeading
+                | CollectionMethod::Orientation
+                | CollectionMethod::Position
+        ),
+        ResourceType::PowerSensor(_) => matches!(
+            method,
+            CollectionMethod::Readings
+                | CollectionMethod::Voltage
+                | CollectionMethod::Current
+                | CollectionMethod::Power
+        ),
+        ResourceType::Motor(_) => {
+            matches!(
+                method,
+                CollectionMethod::Position | CollectionMethod::IsPowered
+            )
+        }
+        ResourceType::Encoder(_) => matches!(method, CollectionMethod::TicksCount),
+        ResourceType::Board(_) => matches!(
+            method,
+            CollectionMethod::Analogs(_) | CollectionMethod::DigitalInterrupts(_)
         ),
+        ResourceType::Servo(_) => matches!(method, CollectionMethod::Position),
+        #[cfg(feature = "camera")]
+        ResourceType::Camera(_) => matches!(method, CollectionMethod::ReadImage),
         _ => false,
     }
 }
 
+// Fields of the data captured by a method are named after the ones of the corresponding
+// gRPC response
+fn to_struct<const N: usize>(fields: [(&str, ValueKind); N]) -> Struct {
+    Struct {
+        fields: fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), protobuf::Value { kind: Some(v) }))
+            .collect(),
+    }
+}
+
+fn data_struct<const N: usize>(fields: [(&str, ValueKind); N]) -> Data {
+    Data::Struct(to_struct(fields))
+}
+
 impl DataCollector {
     pub fn new(
         name: String,
@@ -187,6 +400,8 @@ impl DataCollector {
             method,
             time_interval,
             capacity,
+            aggregator: None,
+            filter: CaptureFilter::default(),
         })
     }
 
@@ -194,14 +409,37 @@ impl DataCollector {
         name: String,
         resource: ResourceType,
         conf: &DataCollectorConfig,
+        triggers: CaptureTriggers,
     ) -> Result<Self, DataCollectionError> {
-        Self::new(
+        let collector = Self::new(
             name,
             resource,
             conf.method.clone(),
             conf.capture_frequency_hz,
             conf.capacity,
-        )
+        )?
+        .with_capture_policy(conf.capture_policy.clone(), triggers);
+        Ok(match &conf.aggregation {
+            Some(aggregation) => collector.with_aggregation(aggregation.clone()),
+            None => collector,
+        })
+    }
+
+    /// Stores one reading summarizing each window of `aggregation` rather than every reading
+    pub fn with_aggregation(mut self, aggregation: AggregationConfig) -> Self {
+        self.aggregator = Some(Aggregator::new(aggregation));
+        self
+    }
+
+    /// Sets the policy deciding which readings are stored, `triggers` are the named
+    /// triggers a `CapturePolicy::Trigger` waits on
+    pub fn with_capture_policy(mut self, policy: CapturePolicy, triggers: CaptureTriggers) -> Self {
+        self.filter = CaptureFilter::new(policy, triggers);
+        self
+    }
+
+    pub fn capture_policy(&self) -> &CapturePolicy {
+        self.filter.policy()
     }
 
     pub fn name(&self) -> String {
@@ -224,23 +462,38 @@ impl DataCollector {
         self.capacity
     }
 
+    /// calls the method associated with the collector and returns the readings its
+    /// capture policy retains, there may be none or several of them. When the collector
+    /// aggregates readings, the policy is applied to the aggregated readings.
+    pub(crate) fn collect(
+        &mut self,
+        robot_start_time: Instant,
+    ) -> Result<Vec<SensorData>, DataCollectionError> {
+        let reading = self.call_method(robot_start_time)?;
+        let reading = match self.aggregator.as_mut() {
+            Some(aggregator) => match aggregator.add(reading) {
+                Some(aggregated) => aggregated,
+                None => return Ok(vec![]),
+            },
+            None => reading,
+        };
+        Ok(self.filter.filter(reading))
+    }
+
     /// calls the method associated with the collector and returns the resulting data
     pub(crate) fn call_method(
         &mut self,
         robot_start_time: Instant,
     ) -> Result<SensorData, DataCollectionError> {
         let reading_requested_ts = robot_start_time.elapsed();
-        let data = match &mut self.resource {
-            ResourceType::Sensor(ref mut res) => match self.method {
-                CollectionMethod::Readings => res.get_generic_readings()?.into(),
-                _ => {
-                    return Err(DataCollectionError::UnsupportedMethod(
-                        self.method.clone(),
-                        "sensor".to_string(),
-                    ))
-                }
-            },
-            ResourceType::MovementSensor(ref mut res) => match self.method {
+        // only binary data (camera images) has a mime type
+        #[cfg_attr(not(feature = "camera"), allow(unused_mut))]
+        let mut mime_type = MimeType::Unspecified;
+        let data = match (&mut self.resource, &self.method) {
+            (ResourceType::Sensor(ref mut res), CollectionMethod::Readings) => {
+                res.get_generic_readings()?.into()
+            }
+            (ResourceType::MovementSensor(ref mut res), method) => match method {
                 CollectionMethod::Readings => res.get_generic_readings()?.into(),
                 CollectionMethod::AngularVelocity => res
                     .get_angular_velocity()?
@@ -251,8 +504,34 @@ impl DataCollector {
                 CollectionMethod::LinearVelocity => {
                     res.get_linear_velocity()?.to_data_struct("linear_velocity")
                 }
-                #[allow(unreachable_patterns)]
-                // TODO: RSDK-7127 - remove when methods for other components are implemented
+                CollectionMethod::CompassHeading => {
+                    data_struct([("value", ValueKind::NumberValue(res.get_compass_heading()?))])
+                }
+                CollectionMethod::Orientation => {
+                    let orientation = res.get_orientation()?;
+                    data_struct([(
+                        "orientation",
+                        ValueKind::StructValue(to_struct([
+                            ("o_x", ValueKind::NumberValue(orientation.o_x)),
+                            ("o_y", ValueKind::NumberValue(orientation.o_y)),
+                            ("o_z", ValueKind::NumberValue(orientation.o_z)),
+                            ("theta", ValueKind::NumberValue(orientation.theta)),
+                        ])),
+                    )])
+                }
+                CollectionMethod::Position => {
+                    let position = res.get_position()?;
+                    data_struct([
+                        (
+                            "coordinate",
+                            ValueKind::StructValue(to_struct([
+                                ("latitude", ValueKind::NumberValue(position.lat)),
+                                ("longitude", ValueKind::NumberValue(position.lon)),
+                            ])),
+                        ),
+                        ("altitude_m", ValueKind::NumberValue(position.alt as f64)),
+                    ])
+                }
                 _ => {
                     return Err(DataCollectionError::UnsupportedMethod(
                         self.method.clone(),
@@ -260,7 +539,105 @@ impl DataCollector {
                     ))
                 }
             },
-            _ => return Err(DataCollectionError::NoSupportedMethods),
+            (ResourceType::PowerSensor(ref mut res), method) => match method {
+                CollectionMethod::Readings => res.get_generic_readings()?.into(),
+                CollectionMethod::Voltage => {
+                    let voltage = res.get_voltage()?;
+                    data_struct([
+                        ("volts", ValueKind::NumberValue(voltage.volts)),
+                        (
+                            "is_ac",
+                            ValueKind::BoolValue(matches!(
+                                voltage.power_supply_type,
+                                PowerSupplyType::AC
+                            )),
+                        ),
+                    ])
+                }
+                CollectionMethod::Current => {
+                    let current = res.get_current()?;
+                    data_struct([
+                        ("amperes", ValueKind::NumberValue(current.amperes)),
+                        (
+                            "is_ac",
+                            ValueKind::BoolValue(matches!(
+                                current.power_supply_type,
+                                PowerSupplyType::AC
+                            )),
+                        ),
+                    ])
+                }
+                CollectionMethod::Power => {
+                    data_struct([("watts", ValueKind::NumberValue(res.get_power()?))])
+                }
+                _ => {
+                    return Err(DataCollectionError::UnsupportedMethod(
+                        self.method.clone(),
+                        "power_sensor".to_string(),
+                    ))
+                }
+            },
+            (ResourceType::Motor(ref mut res), CollectionMethod::Position) => data_struct([(
+                "position",
+                ValueKind::NumberValue(res.get_position()? as f64),
+            )]),
+            (ResourceType::Motor(ref mut res), CollectionMethod::IsPowered) => {
+                let (is_on, power_pct) = res.is_powered()?;
+                data_struct([
+                    ("is_on", ValueKind::BoolValue(is_on)),
+                    ("power_pct", ValueKind::NumberValue(power_pct)),
+                ])
+            }
+            (ResourceType::Encoder(ref mut res), CollectionMethod::TicksCount) => {
+                // not every encoder resolves an unspecified position type, prefer ticks
+                let position_type = if res.get_properties().ticks_count_supported {
+                    EncoderPositionType::TICKS
+                } else {
+                    EncoderPositionType::DEGREES
+                };
+                let position = res.get_position(position_type)?;
+                data_struct([
+                    ("value", ValueKind::NumberValue(position.value as f64)),
+                    (
+                        "position_type",
+                        ValueKind::StringValue(
+                            PositionType::from(position.position_type)
+                                .as_str_name()
+                                .to_string(),
+                        ),
+                    ),
+                ])
+            }
+            (ResourceType::Board(ref mut res), CollectionMethod::Analogs(name)) => {
+                let value = res.get_analog_reader_by_name(name.clone())?.read()?;
+                data_struct([("value", ValueKind::NumberValue(value as f64))])
+            }
+            (ResourceType::Board(ref mut res), CollectionMethod::DigitalInterrupts(name)) => {
+                // the name was validated to be a pin number when parsing the config
+                let pin = name.parse::<i32>().unwrap_or_default();
+                let value = res.get_digital_interrupt_value(pin)?;
+                data_struct([("value", ValueKind::NumberValue(value as f64))])
+            }
+            (ResourceType::Servo(ref mut res), CollectionMethod::Position) => data_struct([(
+                "position_deg",
+                ValueKind::NumberValue(res.get_position()? as f64),
+            )]),
+            #[cfg(feature = "camera")]
+            (ResourceType::Camera(ref mut res), CollectionMethod::ReadImage) => {
+                let image = res.get_image()?;
+                mime_type = match image.mime_type.as_str() {
+                    MIME_TYPE_JPEG => MimeType::ImageJpeg,
+                    MIME_TYPE_PNG => MimeType::ImagePng,
+                    _ => MimeType::Unspecified,
+                };
+                Data::Binary(image.data.to_vec())
+            }
+            (res, method) => {
+                return Err(DataCollectionError::UnsupportedMethod(
+                    method.clone(),
+                    res.component_type(),
+                ))
+            }
         };
         let reading_received_ts = robot_start_time.elapsed();
         Ok(SensorData {
@@ -274,7 +651,7 @@ impl DataCollector {
                     nanos: reading_requested_ts.subsec_nanos() as i32,
                 }),
                 annotations: None,
-                mime_type: MimeType::Unspecified.into(),
+                mime_type: mime_type.into(),
             }),
             data: Some(data),
         })
@@ -297,9 +674,15 @@ mod tests {
 
     use super::{
         CollectionMethod, DataCollectionError, DataCollector, DataCollectorConfig,
-        DEFAULT_CACHE_SIZE_KB,
+        ResourceMethodKey, DEFAULT_BINARY_CACHE_SIZE_KB, DEFAULT_CACHE_SIZE_KB,
     };
+    use crate::common::analog::{AnalogReaderType, FakeAnalogReader};
+    use crate::common::board::FakeBoard;
+    use crate::common::capture_policy::CapturePolicy;
     use crate::common::config::{AttributeError, Kind};
+    use crate::common::encoder::FakeEncoder;
+    use crate::common::motor::{FakeMotor, Motor};
+    use crate::common::movement_sensor::FakeMovementSensor;
     use crate::common::robot::ResourceType;
     use crate::common::sensor::FakeSensor;
     use crate::google;
@@ -321,6 +704,19 @@ mod tests {
         assert_eq!(conf.capacity, (DEFAULT_CACHE_SIZE_KB * 1000.0) as usize);
         assert!(!conf.disabled);
 
+        let kind_map = HashMap::from([
+            (
+                "method".to_string(),
+                Kind::StringValue("ReadImage".to_string()),
+            ),
+            ("capture_frequency_hz".to_string(), Kind::NumberValue(1.0)),
+        ]);
+        let conf: DataCollectorConfig = (&Kind::StructValue(kind_map)).try_into()?;
+        assert_eq!(
+            conf.capacity,
+            (DEFAULT_BINARY_CACHE_SIZE_KB * 1000.0) as usize
+        );
+
         let kind_map = HashMap::from([
             (
                 "method".to_string(),
@@ -458,4 +854,246 @@ mod tests {
         };
         Ok(())
     }
+    #[test_log::test]
+    fn test_collector_config_capture_policy() -> Result<(), AttributeError> {
+        let conf_kind = |method: &str| {
+            Kind::StructValue(HashMap::from([
+                ("method".to_string(), Kind::StringValue(method.to_string())),
+                ("capture_frequency_hz".to_string(), Kind::NumberValue(10.0)),
+                (
+                    "capture_policy".to_string(),
+                    Kind::StructValue(HashMap::from([
+                        (
+                            "type".to_string(),
+                            Kind::StringValue("threshold".to_string()),
+                        ),
+                        ("threshold".to_string(), Kind::NumberValue(30.0)),
+                    ])),
+                ),
+            ]))
+        };
+        let conf = DataCollectorConfig::try_from(&conf_kind("Readings"))?;
+        assert_eq!(
+            conf.capture_policy,
+            CapturePolicy::Threshold {
+                field: None,
+                threshold: 30.0
+            }
+        );
+        // thresholds can't be evaluated on images
+        assert!(matches!(
+            DataCollectorConfig::try_from(&conf_kind("ReadImage")),
+            Err(AttributeError::ValidationError(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_parameterized_collector_config() -> Result<(), AttributeError> {
+        let analogs_kind = |params: Option<(&str, &str)>| {
+            let mut kind_map = HashMap::from([
+                (
+                    "method".to_string(),
+                    Kind::StringValue("Analogs".to_string()),
+                ),
+                ("capture_frequency_hz".to_string(), Kind::NumberValue(10.0)),
+            ]);
+            if let Some((key, value)) = params {
+                kind_map.insert(
+                    "additional_params".to_string(),
+                    Kind::StructValue(HashMap::from([(
+                        key.to_string(),
+                        Kind::StringValue(value.to_string()),
+                    )])),
+                );
+            }
+            Kind::StructValue(kind_map)
+        };
+        let conf = DataCollectorConfig::try_from(&analogs_kind(Some(("reader_name", "a1"))))?;
+        assert_eq!(conf.method, CollectionMethod::Analogs("a1".to_string()));
+        assert_eq!(conf.method.to_string(), "Analogs");
+        assert!(conf.method.method_parameters().contains_key("reader_name"));
+
+        assert!(matches!(
+            DataCollectorConfig::try_from(&analogs_kind(None)),
+            Err(AttributeError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            DataCollectorConfig::try_from(&analogs_kind(Some(("pin_name", "a1")))),
+            Err(AttributeError::KeyNotFound(_))
+        ));
+
+        let kind_map = HashMap::from([
+            (
+                "method".to_string(),
+                Kind::StringValue("DigitalInterrupts".to_string()),
+            ),
+            ("capture_frequency_hz".to_string(), Kind::NumberValue(10.0)),
+            (
+                "additional_params".to_string(),
+                Kind::StructValue(HashMap::from([(
+                    "digital_interrupt_name".to_string(),
+                    Kind::StringValue("not_a_pin".to_string()),
+                )])),
+            ),
+        ]);
+        assert!(matches!(
+            DataCollectorConfig::try_from(&Kind::StructValue(kind_map)),
+            Err(AttributeError::ValidationError(_))
+        ));
+
+        // collectors of the same method on different readers must not share a store
+        let key_1 = ResourceMethodKey {
+            r_name: "board".to_string(),
+            component_type: "rdk:component:board".to_string(),
+            method: CollectionMethod::Analogs("a1".to_string()),
+        };
+        let key_2 = ResourceMethodKey {
+            method: CollectionMethod::Analogs("a2".to_string()),
+            ..key_1.clone()
+        };
+        assert_ne!(key_1.to_string(), key_2.to_string());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_collect_component_methods() -> Result<(), DataCollectionError> {
+        let robot_start_time = Instant::now();
+        let number_field = |data: Option<Data>, key: &str| match data {
+            Some(Data::Struct(d)) => match d.fields.get(key).and_then(|v| v.kind.clone()) {
+                Some(google::protobuf::value::Kind::NumberValue(n)) => n,
+                _ => panic!("field {} was not a number", key),
+            },
+            _ => panic!("expected struct data"),
+        };
+
+        let mut motor = FakeMotor::new();
+        motor.set_power(0.5).unwrap();
+        let motor = ResourceType::Motor(Arc::new(Mutex::new(motor)));
+        let mut coll = DataCollector::new(
+            "motor".to_string(),
+            motor.clone(),
+            CollectionMethod::IsPowered,
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert_eq!(number_field(data, "power_pct"), 0.5);
+        let mut coll = DataCollector::new(
+            "motor".to_string(),
+            motor.clone(),
+            CollectionMethod::Position,
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert_eq!(number_field(data, "position"), 10.0);
+        assert!(matches!(
+            DataCollector::new(
+                "motor".to_string(),
+                motor,
+                CollectionMethod::Readings,
+                1.0,
+                1000
+            ),
+            Err(DataCollectionError::UnsupportedMethod(_, _))
+        ));
+
+        let analog: AnalogReaderType<u16> =
+            Arc::new(Mutex::new(FakeAnalogReader::new("a1".to_string(), 42)));
+        let mut board = FakeBoard::new(vec![analog]);
+        board.add_digital_interrupt(4);
+        board.trigger_digital_interrupt(4);
+        let board = ResourceType::Board(Arc::new(Mutex::new(board)));
+        let mut coll = DataCollector::new(
+            "board".to_string(),
+            board.clone(),
+            CollectionMethod::Analogs("a1".to_string()),
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert_eq!(number_field(data, "value"), 42.0);
+        let mut coll = DataCollector::new(
+            "board".to_string(),
+            board.clone(),
+            CollectionMethod::DigitalInterrupts("4".to_string()),
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert_eq!(number_field(data, "value"), 1.0);
+        let mut coll = DataCollector::new(
+            "board".to_string(),
+            board,
+            CollectionMethod::Analogs("a2".to_string()),
+            1.0,
+            1000,
+        )?;
+        assert!(matches!(
+            coll.call_method(robot_start_time),
+            Err(DataCollectionError::BoardCollectionError(_))
+        ));
+
+        let mut encoder = FakeEncoder::new();
+        encoder.angle_degrees = 180.0;
+        encoder.ticks_per_rotation = 10;
+        let mut coll = DataCollector::new(
+            "encoder".to_string(),
+            ResourceType::Encoder(Arc::new(Mutex::new(encoder))),
+            CollectionMethod::TicksCount,
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert_eq!(number_field(data, "value"), 5.0);
+
+        let movement_sensor =
+            ResourceType::MovementSensor(Arc::new(Mutex::new(FakeMovementSensor::new())));
+        let mut coll = DataCollector::new(
+            "movement_sensor".to_string(),
+            movement_sensor.clone(),
+            CollectionMethod::Position,
+            1.0,
+            1000,
+        )?;
+        let data = coll.call_method(robot_start_time)?.data;
+        assert!((number_field(data, "altitude_m") - 4572.2).abs() < 0.01);
+        let mut coll = DataCollector::new(
+            "movement_sensor".to_string(),
+            movement_sensor,
+            CollectionMethod::Orientation,
+            1.0,
+            1000,
+        )?;
+        assert!(matches!(
+            coll.call_method(robot_start_time),
+            Err(DataCollectionError::SensorCollectionError(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(all(feature = "camera", feature = "builtin-components"))]
+    #[test_log::test]
+    fn test_collect_camera_image() -> Result<(), DataCollectionError> {
+        use crate::common::camera::fake_camera::FakeCamera;
+        use crate::proto::app::data_sync::v1::{DataType, MimeType};
+
+        let camera = ResourceType::Camera(Arc::new(Mutex::new(FakeCamera::new())));
+        let mut coll = DataCollector::new(
+            "camera".to_string(),
+            camera,
+            CollectionMethod::ReadImage,
+            1.0,
+            1000,
+        )?;
+        assert_eq!(coll.method.data_type(), DataType::BinarySensor);
+        let reading = coll.call_method(Instant::now())?;
+        assert_eq!(
+            reading.metadata.unwrap().mime_type,
+            i32::from(MimeType::ImageJpeg)
+        );
+        assert!(matches!(reading.data, Some(Data::Binary(d)) if !d.is_empty()));
+        Ok(())
+    }
 }
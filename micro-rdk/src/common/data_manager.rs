@@ -1,17 +1,25 @@
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::time::{Duration, Instant};
 
 use crate::common::data_collector::{DataCollectionError, DataCollector};
 use crate::common::data_store::DataStore;
 use crate::google::protobuf::value::Kind;
-use crate::google::protobuf::{Struct, Timestamp};
+use crate::google::protobuf::{ListValue, Struct, Timestamp, Value};
 use crate::proto::app::data_sync::v1::{
-    DataCaptureUploadRequest, DataType, SensorData, UploadMetadata,
+    sensor_data::Data, DataCaptureUploadMetadata, DataCaptureUploadRequest, DataType, MimeType,
+    SensorData, UploadMetadata,
 };
 use crate::proto::app::v1::{RobotConfig, ServiceConfig};
 
 use super::app_client::{AppClient, AppClientError, PeriodicAppClientTask, VIAM_FOUNDING_YEAR};
+use super::capture_policy::{CaptureTriggers, SensorTrigger, SensorTriggerConfig};
+use super::config::{AttributeError, Kind as ConfigKind};
 use super::data_collector::ResourceMethodKey;
 use super::data_store::{DataStoreError, DataStoreReader, WriteMode};
 use super::robot::{LocalRobot, RobotError};
@@ -29,7 +37,10 @@ use thiserror::Error;
 // the smaller amount of available RAM, we've halved it
 static MAX_SENSOR_CONTENTS_SIZE: usize = 32000;
 
-type CollectedReadings = Vec<(ResourceMethodKey, Result<SensorData, DataCollectionError>)>;
+type CollectedReadings = Vec<(
+    ResourceMethodKey,
+    Result<Vec<SensorData>, DataCollectionError>,
+)>;
 
 /// Allow for a C project using micro-RDK as a library to implement a callback to be run
 /// whenever data has successfully been uploaded. The callback should be identical in signature
@@ -64,8 +75,25 @@ pub enum DataManagerError {
     MultipleConfigError,
     #[error(transparent)]
     InitializationRobotError(#[from] RobotError),
+    #[error("capture trigger configuration error: {0}")]
+    TriggerConfigError(#[from] AttributeError),
+    #[error("sensor {0} of capture trigger not found")]
+    TriggerSensorNotFound(String),
+    #[error("data store is busy, try again")]
+    StoreBusy,
+    #[error(transparent)]
+    SyncError(#[from] DataSyncError),
+    #[error("no collector {0} with method {1}")]
+    UnknownCollector(String, String),
+    #[error("unsupported command {0}")]
+    UnsupportedCommand(String),
+    #[error("cloud sync is disabled")]
+    SyncDisabled,
 }
 
+/// Subtype of the data manager service in resource names
+pub static SERVICE_NAME: &str = "data_manager";
+
 fn get_data_service_config(
     robot_config: &RobotConfig,
 ) -> Result<Option<ServiceConfig>, DataManagerError> {
@@ -84,6 +112,18 @@ fn get_data_service_config(
         .cloned())
 }
 
+/// Returns true when the data manager is configured to buffer data in flash (or in a file on
+/// native) with the `persistent_store` attribute rather than in RAM
+#[allow(dead_code)]
+pub(crate) fn uses_persistent_store(robot_config: &RobotConfig) -> bool {
+    get_data_service_config(robot_config)
+        .ok()
+        .flatten()
+        .and_then(|cfg| cfg.attributes)
+        .and_then(|attrs| attrs.fields.get("persistent_store").cloned())
+        .is_some_and(|v| matches!(v.kind, Some(Kind::BoolValue(true))))
+}
+
 fn get_data_sync_interval(attrs: &Struct) -> Result<Option<Duration>, DataManagerError> {
     Ok(
         // If cloud sync is disabled, we'll communicate this by having the sync interval be None
@@ -111,12 +151,93 @@ fn get_data_sync_interval(attrs: &Struct) -> Result<Option<Duration>, DataManage
     )
 }
 
+// Builds the sensor triggers listed in the "capture_triggers" attribute, they fire the named
+// triggers triggered capture policies wait on
+fn get_sensor_triggers(
+    attrs: &Struct,
+    robot: &LocalRobot,
+) -> Result<Vec<SensorTrigger>, DataManagerError> {
+    let configs: Vec<SensorTriggerConfig> = match attrs
+        .fields
+        .get("capture_triggers")
+        .and_then(|v| v.kind.as_ref())
+    {
+        Some(kind) => (&ConfigKind::try_from(kind)?).try_into()?,
+        None => return Ok(vec![]),
+    };
+    configs
+        .into_iter()
+        .map(|config| {
+            let sensor = robot
+                .get_sensor_by_name(config.sensor.clone())
+                .ok_or_else(|| DataManagerError::TriggerSensorNotFound(config.sensor.clone()))?;
+            Ok(SensorTrigger::new(config, sensor, robot.capture_triggers()))
+        })
+        .collect()
+}
+
+/// Collectors and sensor triggers built against the current resources of a robot, a running
+/// data manager swaps them in when the components they read from are rebuilt
+pub(crate) struct CaptureSources {
+    collectors: Vec<DataCollector>,
+    sensor_triggers: Vec<SensorTrigger>,
+}
+
+impl CaptureSources {
+    // Builds the capture sources described by the attributes of the data manager service
+    fn from_robot_and_attributes(
+        robot: &LocalRobot,
+        attrs: &Struct,
+    ) -> Result<Self, DataManagerError> {
+        let capture_disabled = attrs
+            .fields
+            .get("capture_disabled")
+            .map(|v| match v.kind {
+                Some(Kind::BoolValue(b)) => b,
+                _ => false,
+            })
+            .unwrap_or(false);
+        Ok(Self {
+            collectors: if capture_disabled {
+                vec![]
+            } else {
+                robot.data_collectors()?
+            },
+            sensor_triggers: get_sensor_triggers(attrs, robot)?,
+        })
+    }
+
+    /// Returns the capture sources of the data manager service configured in `cfg`, if any
+    pub(crate) fn from_robot_and_config(
+        robot: &LocalRobot,
+        cfg: &RobotConfig,
+    ) -> Result<Option<Self>, DataManagerError> {
+        match get_data_service_config(cfg)? {
+            Some(cfg) => {
+                let attrs = cfg.attributes.ok_or(DataManagerError::ConfigError)?;
+                Self::from_robot_and_attributes(robot, &attrs).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
 pub struct DataManager<StoreType> {
+    name: String,
     collectors: Vec<DataCollector>,
+    sensor_triggers: Vec<SensorTrigger>,
     store: Rc<AsyncMutex<StoreType>>,
     sync_interval: Option<Duration>,
     min_interval: Duration,
     robot_part_id: String,
+    capture_paused: Arc<AtomicBool>,
+    // requests to sync outside of the sync interval, see DataSyncTask::invoke
+    sync_requests: (async_channel::Sender<()>, async_channel::Receiver<()>),
+    // capture sources replacing the current ones, see DataManager::data_collection_task
+    capture_updates: (
+        async_channel::Sender<CaptureSources>,
+        async_channel::Receiver<CaptureSources>,
+    ),
 }
 
 impl<StoreType> DataManager<StoreType>
@@ -132,11 +253,16 @@ where
         let intervals = collectors.iter().map(|x| x.time_interval());
         let min_interval = intervals.min().ok_or(DataManagerError::NoCollectors)?;
         Ok(Self {
+            name: Default::default(),
             collectors,
+            sensor_triggers: vec![],
             store: Rc::new(AsyncMutex::new(store)),
             sync_interval,
             min_interval,
             robot_part_id,
+            capture_paused: Default::default(),
+            sync_requests: async_channel::bounded(1),
+            capture_updates: async_channel::unbounded(),
         })
     }
 
@@ -147,19 +273,10 @@ where
         if let Some(cfg) = get_data_service_config(cfg)? {
             let attrs = cfg.attributes.ok_or(DataManagerError::ConfigError)?;
             let sync_interval = get_data_sync_interval(&attrs)?;
-            let collectors = if attrs
-                .fields
-                .get("capture_disabled")
-                .map(|v| match v.kind {
-                    Some(Kind::BoolValue(b)) => b,
-                    _ => false,
-                })
-                .unwrap_or(false)
-            {
-                vec![]
-            } else {
-                robot.data_collectors()?
-            };
+            let CaptureSources {
+                collectors,
+                sensor_triggers,
+            } = CaptureSources::from_robot_and_attributes(robot, &attrs)?;
 
             // if there are no collectors and cloud sync is off, simply don't create a DataManager
             if collectors.is_empty() && sync_interval.is_none() {
@@ -170,8 +287,10 @@ where
                     .map(|c| (c.resource_method_key(), c.capacity()))
                     .collect();
                 let store = StoreType::from_resource_method_settings(collector_settings)?;
-                let data_manager_svc =
+                let mut data_manager_svc =
                     DataManager::new(collectors, store, sync_interval, robot.part_id.clone())?;
+                data_manager_svc.sensor_triggers = sensor_triggers;
+                data_manager_svc.name = cfg.name;
                 Ok(Some(data_manager_svc))
             }
         } else {
@@ -205,9 +324,28 @@ where
         intervals
     }
 
+    /// Returns the sender handing new capture sources to the collection task, the task keeps
+    /// the store and the sync task it started with, so the sources must capture the same
+    /// resource methods with the same capacities
+    pub(crate) fn capture_updates(&self) -> async_channel::Sender<CaptureSources> {
+        self.capture_updates.0.clone()
+    }
+
+    fn replace_capture_sources(&mut self, sources: CaptureSources) {
+        if let Some(min_interval) = sources.collectors.iter().map(|c| c.time_interval()).min() {
+            self.min_interval = min_interval;
+        }
+        self.collectors = sources.collectors;
+        self.sensor_triggers = sources.sensor_triggers;
+    }
+
     pub async fn data_collection_task(&mut self, robot_start_time: Instant) -> ! {
         let mut loop_counter: u64 = 0;
         loop {
+            // only the latest sources matter when several reconfigurations happened meanwhile
+            while let Ok(sources) = self.capture_updates.1.try_recv() {
+                self.replace_capture_sources(sources);
+            }
             if let Err(e) = self
                 .collect_data_inner(loop_counter, robot_start_time)
                 .await
@@ -227,6 +365,15 @@ where
         loop_counter: u64,
         robot_start_time: Instant,
     ) -> Result<(), DataManagerError> {
+        if self.capture_paused.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        // triggers are evaluated before collecting so that readings of this loop observe them
+        for trigger in self.sensor_triggers.iter_mut() {
+            if let Err(e) = trigger.poll() {
+                log::error!("failed to evaluate capture trigger reason {:?}", e);
+            }
+        }
         let min_interval_ms = self.min_interval_ms();
         for interval in self.collection_intervals() {
             if loop_counter % (interval / min_interval_ms) == 0 {
@@ -252,14 +399,18 @@ where
                     e
                 ),
                 Ok(data) => {
-                    if let Err(e) =
-                        store_guard.write_message(&collector_key, data, WriteMode::OverwriteOldest)
-                    {
-                        log::error!(
-                            "couldn't store data for collector {:?} error : {:?}",
-                            collector_key,
-                            e
-                        );
+                    for data in data {
+                        if let Err(e) = store_guard.write_message(
+                            &collector_key,
+                            data,
+                            WriteMode::OverwriteOldest,
+                        ) {
+                            log::error!(
+                                "couldn't store data for collector {:?} error : {:?}",
+                                collector_key,
+                                e
+                            );
+                        }
                     }
                 }
             }
@@ -288,15 +439,40 @@ where
                 (coll.time_interval().as_millis() as u64 / min_interval_ms)
                     == (time_interval_ms / min_interval_ms)
             })
-            .map(|coll| {
-                Ok((
-                    coll.resource_method_key(),
-                    coll.call_method(robot_start_time),
-                ))
-            })
+            .map(|coll| Ok((coll.resource_method_key(), coll.collect(robot_start_time))))
             .collect()
     }
 
+    pub fn get_query(&self, robot_start_time: Instant) -> DataStoreQuery<StoreType> {
+        DataStoreQuery {
+            store: self.store.clone(),
+            resource_method_keys: self
+                .collectors
+                .iter()
+                .map(|coll| coll.resource_method_key())
+                .collect(),
+            robot_start_time,
+        }
+    }
+
+    /// Returns the handle serving the data manager service for this data manager
+    pub fn get_service(
+        &self,
+        robot_start_time: Instant,
+        triggers: CaptureTriggers,
+    ) -> DataManagerService
+    where
+        StoreType: 'static,
+    {
+        DataManagerService {
+            name: self.name.clone(),
+            query: Box::new(self.get_query(robot_start_time)),
+            triggers,
+            capture_paused: self.capture_paused.clone(),
+            sync_requests: self.sync_interval.map(|_| self.sync_requests.0.clone()),
+        }
+    }
+
     pub fn get_sync_task(&self, robot_start_time: Instant) -> Option<DataSyncTask<StoreType>> {
         if let Some(sync_interval) = self.sync_interval {
             let resource_method_keys: Vec<ResourceMethodKey> = self
@@ -310,6 +486,7 @@ where
                 sync_interval,
                 part_id: self.part_id(),
                 robot_start_time,
+                sync_requests: self.sync_requests.1.clone(),
             })
         } else {
             None
@@ -329,6 +506,55 @@ pub enum DataSyncError {
     NoCurrentTime,
 }
 
+fn get_time_to_subtract(
+    stored_time: Timestamp,
+    robot_start_time: Instant,
+) -> Result<chrono::Duration, DataSyncError> {
+    let stored_time_dur = Duration::new(stored_time.seconds as u64, stored_time.nanos as u32);
+    let time_to_subtract = robot_start_time.elapsed() - stored_time_dur;
+    let time_to_subtract = chrono::Duration::new(
+        time_to_subtract.as_secs() as i64,
+        time_to_subtract.subsec_nanos(),
+    )
+    .ok_or(DataSyncError::TimeOutOfBoundsError)?;
+    Ok(time_to_subtract)
+}
+
+fn time_corrected_reading(
+    raw_msg: BytesMut,
+    robot_start_time: Instant,
+) -> Result<SensorData, DataSyncError> {
+    let mut msg = SensorData::decode(raw_msg)?;
+    // the timestamps of the stored data are measured as offsets from a starting
+    // instant (robot_start_time, acquired from DataSyncTask), so we adjust the
+    // timestamps on the parsed message based on the current time (if it is now available)
+    if let Some(metadata) = msg.metadata.as_mut() {
+        let current_dt = Local::now().fixed_offset();
+        // Viam was founded in 2020, so if the current time is set to any time before that
+        // we know that settimeofday was never called, or called with an improper datetime
+        if current_dt.year() < VIAM_FOUNDING_YEAR {
+            return Err(DataSyncError::NoCurrentTime);
+        }
+        if let Some(time_received) = metadata.time_received.clone() {
+            let time_to_subtract = get_time_to_subtract(time_received, robot_start_time)?;
+            let time_received = current_dt - time_to_subtract;
+            metadata.time_received = Some(Timestamp {
+                seconds: time_received.timestamp(),
+                nanos: time_received.timestamp_subsec_nanos() as i32,
+            });
+        }
+        if let Some(time_requested) = metadata.time_requested.clone() {
+            let time_to_subtract = get_time_to_subtract(time_requested, robot_start_time)?;
+            let time_requested = current_dt - time_to_subtract;
+            metadata.time_requested = Some(Timestamp {
+                seconds: time_requested.timestamp(),
+                nanos: time_requested.timestamp_subsec_nanos() as i32,
+            });
+        }
+    }
+    Ok(msg)
+}
+
 pub struct DataSyncTask<StoreType> {
     store: Rc<AsyncMutex<StoreType>>,
     resource_method_keys: Vec<ResourceMethodKey>,
@@ -337,6 +563,7 @@ pub struct DataSyncTask<StoreType> {
     // used for time correcting stored data before upload, see DataSyncTask::run
     // and create_time_corrected_reading below
     robot_start_time: Instant,
+    sync_requests: async_channel::Receiver<()>,
 }
 
 impl<StoreType> DataSyncTask<StoreType>
@@ -348,76 +575,153 @@ where
         self.store.lock().await
     }
 
-    fn get_time_to_subtract(
-        &self,
-        stored_time: Timestamp,
-    ) -> Result<chrono::Duration, DataSyncError> {
-        let stored_time_dur = Duration::new(stored_time.seconds as u64, stored_time.nanos as u32);
-        let time_to_subtract = self.robot_start_time.elapsed() - stored_time_dur;
-        let time_to_subtract = chrono::Duration::new(
-            time_to_subtract.as_secs() as i64,
-            time_to_subtract.subsec_nanos(),
-        )
-        .ok_or(DataSyncError::TimeOutOfBoundsError)?;
-        Ok(time_to_subtract)
+    fn get_time_corrected_reading(&self, raw_msg: BytesMut) -> Result<SensorData, DataSyncError> {
+        time_corrected_reading(raw_msg, self.robot_start_time)
     }
 
-    fn get_time_corrected_reading(&self, raw_msg: BytesMut) -> Result<SensorData, DataSyncError> {
-        let mut msg = SensorData::decode(raw_msg)?;
-        // the timestamps of the stored data are measured as offsets from a starting
-        // instant (robot_start_time, acquired from DataSyncTask), so we adjust the
-        // timestamps on the parsed message based on the current time (if it is now available)
-        if let Some(metadata) = msg.metadata.as_mut() {
-            let current_dt = Local::now().fixed_offset();
-            // Viam was founded in 2020, so if the current time is set to any time before that
-            // we know that settimeofday was never called, or called with an improper datetime
-            if current_dt.year() < VIAM_FOUNDING_YEAR {
-                return Err(DataSyncError::NoCurrentTime);
-            }
-            if let Some(time_received) = metadata.time_received.clone() {
-                let time_to_subtract = self.get_time_to_subtract(time_received)?;
-                let time_received = current_dt - time_to_subtract;
-                metadata.time_received = Some(Timestamp {
-                    seconds: time_received.timestamp(),
-                    nanos: time_received.timestamp_subsec_nanos() as i32,
-                });
+    async fn messages_in_store(&self, collector_key: &ResourceMethodKey) -> usize {
+        let store_lock = self.store.lock().await;
+        match store_lock.get_reader(collector_key) {
+            Ok(reader) => match reader.messages_remaining() {
+                Ok(num_msgs) => num_msgs,
+                Err(err) => {
+                    log::error!("could not get number of messages remaining in store for collector key ({:?}): {:?}", collector_key, err);
+                    0
+                }
+            },
+            Err(err) => {
+                log::error!(
+                    "error acquiring reader for collector key ({:?}): {:?}",
+                    collector_key,
+                    err
+                );
+                0
             }
-            if let Some(time_requested) = metadata.time_requested.clone() {
-                let time_to_subtract = self.get_time_to_subtract(time_requested)?;
-                let time_requested = current_dt - time_to_subtract;
-                metadata.time_requested = Some(Timestamp {
-                    seconds: time_requested.timestamp(),
-                    nanos: time_requested.timestamp_subsec_nanos() as i32,
-                });
+        }
+    }
+
+    fn upload_metadata(&self, collector_key: &ResourceMethodKey) -> UploadMetadata {
+        UploadMetadata {
+            part_id: self.part_id.clone(),
+            component_type: collector_key.component_type.clone(),
+            r#type: collector_key.method.data_type().into(),
+            component_name: collector_key.r_name.clone(),
+            method_name: collector_key.method.to_string(),
+            method_parameters: collector_key.method.method_parameters(),
+            ..Default::default()
+        }
+    }
+
+    // Binary data (e.g. camera images) can't be batched with tabular readings, each message is
+    // uploaded on its own through the streaming API which splits it into chunks
+    async fn upload_binary_messages(
+        &self,
+        collector_key: &ResourceMethodKey,
+        app_client: &AppClient,
+    ) -> Result<(), AppClientError> {
+        // only process the messages initially present in this region of the store
+        let total_messages = self.messages_in_store(collector_key).await;
+        for _ in 0..total_messages {
+            let store_lock = self.store.lock().await;
+            let mut reader = match store_lock.get_reader(collector_key) {
+                Ok(reader) => reader,
+                Err(err) => {
+                    log::error!(
+                        "error acquiring reader for collector key ({:?}): {:?}",
+                        collector_key,
+                        err
+                    );
+                    break;
+                }
+            };
+            let message = match reader.read_next_message() {
+                Ok(msg) if msg.is_empty() => break,
+                Ok(msg) => msg,
+                Err(err) => {
+                    log::error!(
+                        "error reading message from store for collector key ({:?}): {:?}",
+                        collector_key,
+                        err
+                    );
+                    break;
+                }
+            };
+            let (sensor_metadata, data) = match self.get_time_corrected_reading(message) {
+                Ok(SensorData {
+                    metadata,
+                    data: Some(Data::Binary(data)),
+                }) => (metadata, data),
+                Ok(_) => {
+                    log::error!(
+                        "discarding non binary message for collector key ({:?})",
+                        collector_key
+                    );
+                    reader.flush();
+                    continue;
+                }
+                Err(DataSyncError::NoCurrentTime) => {
+                    log::error!(
+                        "Could not calculate data timestamps, returning without flushing store"
+                    );
+                    return Ok(());
+                }
+                Err(err) => {
+                    log::error!(
+                        "error decoding binary data for collector key ({:?}): {:?}",
+                        collector_key,
+                        err
+                    );
+                    reader.flush();
+                    continue;
+                }
+            };
+            // the message is flushed before the upload for the same reason as in run
+            reader.flush();
+            std::mem::drop(store_lock);
+
+            let mut upload_metadata = self.upload_metadata(collector_key);
+            upload_metadata.file_extension = sensor_metadata
+                .as_ref()
+                .map(|m| match MimeType::try_from(m.mime_type) {
+                    Ok(MimeType::ImageJpeg) => ".jpeg",
+                    Ok(MimeType::ImagePng) => ".png",
+                    Ok(MimeType::ApplicationPcd) => ".pcd",
+                    _ => "",
+                })
+                .unwrap_or_default()
+                .to_string();
+            let metadata = DataCaptureUploadMetadata {
+                upload_metadata: Some(upload_metadata),
+                sensor_metadata,
+            };
+            let data_len = data.len();
+            app_client
+                .upload_binary_data(metadata, data.into())
+                .await
+                .inspect_err(|_| {
+                    log::error!(
+                        "error uploading binary data, data lost ({:?} bytes)",
+                        data_len
+                    )
+                })?;
+            #[cfg(feature = "data-upload-hook-unstable")]
+            unsafe {
+                micro_rdk_data_manager_post_upload_hook();
             }
         }
-        Ok(msg)
+        Ok(())
     }
 
     async fn run<'b>(&self, app_client: &'b AppClient) -> Result<(), AppClientError> {
         for collector_key in self.resource_method_keys.iter() {
+            if collector_key.method.data_type() == DataType::BinarySensor {
+                self.upload_binary_messages(collector_key, app_client)
+                    .await?;
+                continue;
+            }
             // Since a write may occur in between uploading consecutive chunks of data, we want to make
             // sure only to process the messages initially present in this region of the store.
-            let total_messages = {
-                let store_lock = self.store.lock().await;
-                match store_lock.get_reader(collector_key) {
-                    Ok(reader) => match reader.messages_remaining() {
-                        Ok(num_msgs) => num_msgs,
-                        Err(err) => {
-                            log::error!("could not get number of messages remaining in store for collector key ({:?}): {:?}", collector_key, err);
-                            0
-                        }
-                    },
-                    Err(err) => {
-                        log::error!(
-                            "error acquiring reader for collector key ({:?}): {:?}",
-                            collector_key,
-                            err
-                        );
-                        0
-                    }
-                }
-            };
+            let total_messages = self.messages_in_store(collector_key).await;
             if total_messages == 0 {
                 continue;
             }
@@ -554,14 +858,7 @@ where
                 if !upload_data.is_empty() {
                     let data_len = upload_data.len();
                     let upload_request = DataCaptureUploadRequest {
-                        metadata: Some(UploadMetadata {
-                            part_id: self.part_id.clone(),
-                            component_type: collector_key.component_type.clone(),
-                            r#type: DataType::TabularSensor.into(),
-                            component_name: collector_key.r_name.clone(),
-                            method_name: collector_key.method.to_string(),
-                            ..Default::default()
-                        }),
+                        metadata: Some(self.upload_metadata(collector_key)),
                         sensor_contents: upload_data,
                     };
                     match app_client.upload_data(upload_request).await {
@@ -606,7 +903,386 @@ where
         &'a self,
         app_client: &'b AppClient,
     ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, AppClientError>> + 'b>> {
-        Box::pin(async move { self.run(app_client).await.map(|_| None) })
+        Box::pin(async move {
+            self.run(app_client).await?;
+            // the task waits for the next sync itself rather than returning the sync interval
+            // so that a sync request from the data manager service can cut the wait short
+            futures_lite::future::or(
+                async {
+                    Timer::after(self.sync_interval).await;
+                },
+                async {
+                    let _ = self.sync_requests.recv().await;
+                },
+            )
+            .await;
+            Ok(Some(Duration::ZERO))
+        })
+    }
+}
+
+/// A stored message, its timestamps are only corrected to the current time when the time
+/// was set on the system, otherwise they are offsets from the start of the robot
+#[derive(Debug, Clone)]
+pub struct BufferedMessage {
+    pub data: SensorData,
+    pub time_corrected: bool,
+}
+
+/// Summary of the data buffered for a collector
+#[derive(Debug, Clone)]
+pub struct BufferedDataSummary {
+    pub collector_key: ResourceMethodKey,
+    pub messages: usize,
+    pub used_bytes: usize,
+    pub capacity_bytes: usize,
+    pub oldest: Option<BufferedMessage>,
+}
+
+/// Access to the data buffered by a data manager, so that it can be inspected (and cleared)
+/// locally while the device is offline
+pub trait BufferedDataQuery {
+    fn collector_keys(&self) -> &[ResourceMethodKey];
+    fn summaries(&self) -> Result<Vec<BufferedDataSummary>, DataManagerError>;
+    /// Returns up to `count` of the messages most recently stored for the collector, oldest
+    /// first, without consuming them
+    fn last_messages(
+        &self,
+        collector_key: &ResourceMethodKey,
+        count: usize,
+    ) -> Result<Vec<BufferedMessage>, DataManagerError>;
+    /// Drops every message buffered for every collector
+    fn clear(&self) -> Result<(), DataManagerError>;
+}
+
+pub struct DataStoreQuery<StoreType> {
+    store: Rc<AsyncMutex<StoreType>>,
+    resource_method_keys: Vec<ResourceMethodKey>,
+    robot_start_time: Instant,
+}
+
+impl<StoreType> DataStoreQuery<StoreType>
+where
+    StoreType: DataStore,
+{
+    fn buffered_message(&self, raw_msg: BytesMut) -> Result<BufferedMessage, DataManagerError> {
+        match time_corrected_reading(raw_msg.clone(), self.robot_start_time) {
+            Ok(data) => Ok(BufferedMessage {
+                data,
+                time_corrected: true,
+            }),
+            Err(DataSyncError::NoCurrentTime) => Ok(BufferedMessage {
+                data: SensorData::decode(raw_msg).map_err(DataSyncError::from)?,
+                time_corrected: false,
+            }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // Queries are served synchronously and don't wait for the collection or the sync tasks
+    // to release the store
+    fn store(&self) -> Result<futures_util::lock::MutexGuard<StoreType>, DataManagerError> {
+        self.store.try_lock().ok_or(DataManagerError::StoreBusy)
+    }
+}
+
+impl<StoreType> BufferedDataQuery for DataStoreQuery<StoreType>
+where
+    StoreType: DataStore,
+{
+    fn collector_keys(&self) -> &[ResourceMethodKey] {
+        &self.resource_method_keys
+    }
+
+    fn summaries(&self) -> Result<Vec<BufferedDataSummary>, DataManagerError> {
+        let store = self.store()?;
+        self.resource_method_keys
+            .iter()
+            .map(|collector_key| {
+                let (used_bytes, capacity_bytes) = store.region_usage(collector_key)?;
+                // the reader is dropped without flushing, leaving the messages in the store
+                let mut reader = store.get_reader(collector_key)?;
+                let messages = reader.messages_remaining()?;
+                let oldest = reader.read_next_message()?;
+                let oldest = if oldest.is_empty() {
+                    None
+                } else {
+                    Some(self.buffered_message(oldest)?)
+                };
+                Ok(BufferedDataSummary {
+                    collector_key: collector_key.clone(),
+                    messages,
+                    used_bytes,
+                    capacity_bytes,
+                    oldest,
+                })
+            })
+            .collect()
+    }
+
+    fn last_messages(
+        &self,
+        collector_key: &ResourceMethodKey,
+        count: usize,
+    ) -> Result<Vec<BufferedMessage>, DataManagerError> {
+        let store = self.store()?;
+        let mut reader = store.get_reader(collector_key)?;
+        let total = reader.messages_remaining()?;
+        let mut messages = Vec::with_capacity(count.min(total));
+        for idx in 0..total {
+            let msg = reader.read_next_message()?;
+            if idx + count >= total {
+                messages.push(self.buffered_message(msg)?);
+            }
+        }
+        Ok(messages)
+    }
+
+    fn clear(&self) -> Result<(), DataManagerError> {
+        let store = self.store()?;
+        for collector_key in self.resource_method_keys.iter() {
+            let mut reader = store.get_reader(collector_key)?;
+            while !reader.read_next_message()?.is_empty() {}
+            reader.flush();
+        }
+        Ok(())
+    }
+}
+
+fn value(kind: Kind) -> Value {
+    Value { kind: Some(kind) }
+}
+
+fn timestamp_value(ts: Option<&Timestamp>, time_corrected: bool) -> Value {
+    match ts {
+        Some(ts) if time_corrected => {
+            match chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32) {
+                Some(dt) => value(Kind::StringValue(dt.to_rfc3339())),
+                None => value(Kind::NullValue(0)),
+            }
+        }
+        // without a clock, the offset from the start of the robot is reported in seconds
+        Some(ts) => value(Kind::NumberValue(
+            ts.seconds as f64 + ts.nanos as f64 / 1_000_000_000.0,
+        )),
+        None => value(Kind::NullValue(0)),
+    }
+}
+
+impl From<&BufferedMessage> for Value {
+    fn from(msg: &BufferedMessage) -> Self {
+        let metadata = msg.data.metadata.as_ref();
+        let mut fields = HashMap::from([
+            (
+                "time_requested".to_string(),
+                timestamp_value(
+                    metadata.and_then(|m| m.time_requested.as_ref()),
+                    msg.time_corrected,
+                ),
+            ),
+            (
+                "time_received".to_string(),
+                timestamp_value(
+                    metadata.and_then(|m| m.time_received.as_ref()),
+                    msg.time_corrected,
+                ),
+            ),
+        ]);
+        match msg.data.data.as_ref() {
+            Some(Data::Struct(data)) => {
+                fields.insert("data".to_string(), value(Kind::StructValue(data.clone())));
+            }
+            // binary data isn't returned, only its size
+            Some(Data::Binary(data)) => {
+                fields.insert(
+                    "binary_size".to_string(),
+                    value(Kind::NumberValue(data.len() as f64)),
+                );
+            }
+            None => {}
+        }
+        value(Kind::StructValue(Struct { fields }))
+    }
+}
+
+impl From<&BufferedDataSummary> for Value {
+    fn from(summary: &BufferedDataSummary) -> Self {
+        let key = &summary.collector_key;
+        let mut fields = HashMap::from([
+            (
+                "resource".to_string(),
+                value(Kind::StringValue(key.r_name.clone())),
+            ),
+            (
+                "component_type".to_string(),
+                value(Kind::StringValue(key.component_type.clone())),
+            ),
+            (
+                "method".to_string(),
+                value(Kind::StringValue(key.method.to_string())),
+            ),
+            (
+                "messages".to_string(),
+                value(Kind::NumberValue(summary.messages as f64)),
+            ),
+            (
+                "used_bytes".to_string(),
+                value(Kind::NumberValue(summary.used_bytes as f64)),
+            ),
+            (
+                "capacity_bytes".to_string(),
+                value(Kind::NumberValue(summary.capacity_bytes as f64)),
+            ),
+        ]);
+        if let Some((_, parameter)) = key.method.parameter() {
+            fields.insert(
+                "parameter".to_string(),
+                value(Kind::StringValue(parameter.to_string())),
+            );
+        }
+        if let Some(oldest) = summary.oldest.as_ref() {
+            fields.insert(
+                "oldest_time_requested".to_string(),
+                timestamp_value(
+                    oldest
+                        .data
+                        .metadata
+                        .as_ref()
+                        .and_then(|m| m.time_requested.as_ref()),
+                    oldest.time_corrected,
+                ),
+            );
+        }
+        value(Kind::StructValue(Struct { fields }))
+    }
+}
+
+fn string_field<'a>(args: &'a Struct, key: &str) -> Option<&'a str> {
+    match args.fields.get(key).and_then(|v| v.kind.as_ref()) {
+        Some(Kind::StringValue(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+/// Handles the trigger and query commands of the data manager service DoCommand:
+/// - `{"fire_trigger": "<name>"}` fires a capture trigger
+/// - `{"list_collectors": {}}` lists the collectors with the fill level of their buffer and
+///   the timestamp of their oldest message
+/// - `{"last_messages": {"resource": "<name>", "method": "<method>", "count": <n>}}` returns
+///   the last n messages stored by a collector without consuming them, "parameter" selects
+///   among collectors of the same method (e.g. the analog reader of a board)
+pub(crate) fn data_manager_do_command(
+    command: &Struct,
+    triggers: &CaptureTriggers,
+    query: &dyn BufferedDataQuery,
+) -> Result<Struct, DataManagerError> {
+    if let Some(name) = string_field(command, "fire_trigger") {
+        triggers.fire(name);
+        return Ok(Struct::default());
+    }
+    let (name, args) = command
+        .fields
+        .iter()
+        .next()
+        .ok_or_else(|| DataManagerError::UnsupportedCommand("".to_string()))?;
+    let result = match name.as_str() {
+        "list_collectors" => query.summaries()?.iter().map(Value::from).collect(),
+        "last_messages" => {
+            let args = match args.kind.as_ref() {
+                Some(Kind::StructValue(args)) => args.clone(),
+                _ => Struct::default(),
+            };
+            let resource = string_field(&args, "resource").unwrap_or_default();
+            let method = string_field(&args, "method").unwrap_or_default();
+            let parameter = string_field(&args, "parameter");
+            let count = match args.fields.get("count").and_then(|v| v.kind.as_ref()) {
+                Some(Kind::NumberValue(n)) if *n >= 0.0 => *n as usize,
+                _ => 1,
+            };
+            let collector_key = query
+                .collector_keys()
+                .iter()
+                .find(|key| {
+                    key.r_name == resource
+                        && key.method.to_string() == method
+                        && parameter
+                            .map_or(true, |p| key.method.parameter().map(|(_, v)| v) == Some(p))
+                })
+                .ok_or_else(|| {
+                    DataManagerError::UnknownCollector(resource.to_string(), method.to_string())
+                })?;
+            query
+                .last_messages(collector_key, count)?
+                .iter()
+                .map(Value::from)
+                .collect()
+        }
+        name => return Err(DataManagerError::UnsupportedCommand(name.to_string())),
+    };
+    let key = if name == "list_collectors" {
+        "collectors"
+    } else {
+        "messages"
+    };
+    Ok(Struct {
+        fields: HashMap::from([(
+            key.to_string(),
+            value(Kind::ListValue(ListValue { values: result })),
+        )]),
+    })
+}
+
+/// The builtin data manager service, a handle on a running `DataManager` letting clients
+/// request an immediate sync and control capture
+pub struct DataManagerService {
+    name: String,
+    query: Box<dyn BufferedDataQuery>,
+    triggers: CaptureTriggers,
+    capture_paused: Arc<AtomicBool>,
+    // None when cloud sync is disabled
+    sync_requests: Option<async_channel::Sender<()>>,
+}
+
+impl DataManagerService {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Requests an upload of the buffered data without waiting for the sync interval to
+    /// elapse, the upload happens as soon as a connection to app is available
+    pub fn sync(&self) -> Result<(), DataManagerError> {
+        let sync_requests = self
+            .sync_requests
+            .as_ref()
+            .ok_or(DataManagerError::SyncDisabled)?;
+        // a full channel means a sync is already pending
+        let _ = sync_requests.try_send(());
+        Ok(())
+    }
+
+    pub fn capture_paused(&self) -> bool {
+        self.capture_paused.load(Ordering::Relaxed)
+    }
+
+    /// On top of the commands of `data_manager_do_command`:
+    /// - `{"pause_capture": {}}` and `{"resume_capture": {}}` stop and restart data capture
+    /// - `{"clear_buffers": {}}` drops all buffered data
+    pub fn do_command(&self, command: &Struct) -> Result<Struct, DataManagerError> {
+        let status = |paused: bool| Struct {
+            fields: HashMap::from([("capture_paused".to_string(), value(Kind::BoolValue(paused)))]),
+        };
+        if command.fields.contains_key("pause_capture") {
+            self.capture_paused.store(true, Ordering::Relaxed);
+            Ok(status(true))
+        } else if command.fields.contains_key("resume_capture") {
+            self.capture_paused.store(false, Ordering::Relaxed);
+            Ok(status(false))
+        } else if command.fields.contains_key("clear_buffers") {
+            self.query.clear()?;
+            Ok(Struct::default())
+        } else {
+            data_manager_do_command(command, &self.triggers, self.query.as_ref())
+        }
     }
 }
 
@@ -623,7 +1299,8 @@ mod tests {
     use prost::Message;
     use ringbuf::{LocalRb, Rb};
 
-    use super::DataManager;
+    use super::{data_manager_do_command, BufferedDataQuery, DataManager, DataManagerError};
+    use crate::common::capture_policy::CaptureTriggers;
     use crate::common::data_collector::DataCollectionError;
     use crate::common::data_store::{DataStoreReader, WriteMode};
     use crate::common::encoder::EncoderError;
@@ -631,7 +1308,7 @@ mod tests {
         data_collector::{
             CollectionMethod, DataCollector, ResourceMethodKey, DEFAULT_CACHE_SIZE_KB,
         },
-        data_store::{DataStore, DataStoreError},
+        data_store::{DataStore, DataStoreError, DefaultDataStore},
         robot::ResourceType,
         sensor::{
             GenericReadingsResult, Readings, Sensor, SensorError, SensorResult, SensorT,
@@ -640,7 +1317,7 @@ mod tests {
         status::{Status, StatusError},
     };
     use crate::google::protobuf::value::Kind;
-    use crate::google::protobuf::Struct;
+    use crate::google::protobuf::{Struct, Value};
     use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData};
 
     #[derive(DoCommand)]
@@ -841,7 +1518,7 @@ mod tests {
         let sensor_data: Vec<(ResourceMethodKey, SensorData)> = sensor_data
             .into_iter()
             .try_fold(vec![], |mut out, val| {
-                out.push((val.0, val.1?));
+                out.extend(val.1?.into_iter().map(|d| (val.0.clone(), d)));
                 Ok::<Vec<(ResourceMethodKey, SensorData)>, DataCollectionError>(out)
             })
             .unwrap();
@@ -951,7 +1628,7 @@ mod tests {
             .unwrap();
         let readings: Result<Vec<(ResourceMethodKey, SensorData)>, DataCollectionError> =
             readings.into_iter().try_fold(vec![], |mut out, val| {
-                out.push((val.0, val.1?));
+                out.extend(val.1?.into_iter().map(|d| (val.0.clone(), d)));
                 Ok::<Vec<(ResourceMethodKey, SensorData)>, DataCollectionError>(out)
             });
         assert!(readings.is_err());
@@ -1170,4 +1847,132 @@ mod tests {
             assert_eq!(read_data, expected_data);
         });
     }
+
+    #[test_log::test]
+    fn test_query_buffered_data() {
+        let robot_start_time = Instant::now();
+        let resource = ResourceType::Sensor(Arc::new(Mutex::new(TestSensor {})));
+        let data_coll = DataCollector::new(
+            "r1".to_string(),
+            resource,
+            CollectionMethod::Readings,
+            10.0,
+            (DEFAULT_CACHE_SIZE_KB * 1000.0) as usize,
+        )
+        .unwrap();
+        let coll_key = data_coll.resource_method_key();
+        let store = DefaultDataStore::new(vec![(coll_key.clone(), 1000)]).unwrap();
+        let mut manager = DataManager::new(vec![data_coll], store, None, "1".to_string()).unwrap();
+        async_io::block_on(async {
+            for i in 0..3 {
+                assert!(manager
+                    .collect_data_inner(i, robot_start_time)
+                    .await
+                    .is_ok());
+            }
+        });
+
+        let query = manager.get_query(robot_start_time);
+        let summaries = query.summaries().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].messages, 3);
+        assert_eq!(summaries[0].capacity_bytes, 1000);
+        assert!(summaries[0].used_bytes > 0);
+        assert!(summaries[0].oldest.is_some());
+        assert_eq!(query.last_messages(&coll_key, 2).unwrap().len(), 2);
+
+        let command = Struct {
+            fields: HashMap::from([(
+                "last_messages".to_string(),
+                Value {
+                    kind: Some(Kind::StructValue(Struct {
+                        fields: HashMap::from([
+                            (
+                                "resource".to_string(),
+                                Value {
+                                    kind: Some(Kind::StringValue("r1".to_string())),
+                                },
+                            ),
+                            (
+                                "method".to_string(),
+                                Value {
+                                    kind: Some(Kind::StringValue("Readings".to_string())),
+                                },
+                            ),
+                            (
+                                "count".to_string(),
+                                Value {
+                                    kind: Some(Kind::NumberValue(5.0)),
+                                },
+                            ),
+                        ]),
+                    })),
+                },
+            )]),
+        };
+        let res = data_manager_do_command(&command, &CaptureTriggers::default(), &query).unwrap();
+        match res.fields.get("messages").and_then(|v| v.kind.as_ref()) {
+            Some(Kind::ListValue(messages)) => assert_eq!(messages.values.len(), 3),
+            _ => panic!("expected a list of messages"),
+        }
+        // querying doesn't consume the messages
+        assert_eq!(query.summaries().unwrap()[0].messages, 3);
+    }
+
+    #[test_log::test]
+    fn test_data_manager_service() {
+        let robot_start_time = Instant::now();
+        let resource = ResourceType::Sensor(Arc::new(Mutex::new(TestSensor {})));
+        let data_coll = DataCollector::new(
+            "r1".to_string(),
+            resource,
+            CollectionMethod::Readings,
+            10.0,
+            (DEFAULT_CACHE_SIZE_KB * 1000.0) as usize,
+        )
+        .unwrap();
+        let coll_key = data_coll.resource_method_key();
+        let store = DefaultDataStore::new(vec![(coll_key.clone(), 1000)]).unwrap();
+        let mut manager = DataManager::new(vec![data_coll], store, None, "1".to_string()).unwrap();
+        let service = manager.get_service(robot_start_time, CaptureTriggers::default());
+        let command = |name: &str| Struct {
+            fields: HashMap::from([(
+                name.to_string(),
+                Value {
+                    kind: Some(Kind::StructValue(Struct::default())),
+                },
+            )]),
+        };
+        let query = manager.get_query(robot_start_time);
+
+        assert!(service.do_command(&command("pause_capture")).is_ok());
+        assert!(service.capture_paused());
+        async_io::block_on(async {
+            assert!(manager
+                .collect_data_inner(0, robot_start_time)
+                .await
+                .is_ok());
+        });
+        assert_eq!(query.summaries().unwrap()[0].messages, 0);
+
+        assert!(service.do_command(&command("resume_capture")).is_ok());
+        async_io::block_on(async {
+            for i in 0..2 {
+                assert!(manager
+                    .collect_data_inner(i, robot_start_time)
+                    .await
+                    .is_ok());
+            }
+        });
+        assert_eq!(query.summaries().unwrap()[0].messages, 2);
+
+        assert!(service.do_command(&command("clear_buffers")).is_ok());
+        assert_eq!(query.summaries().unwrap()[0].messages, 0);
+
+        // sync is disabled for this data manager
+        assert!(matches!(
+            service.sync(),
+            Err(DataManagerError::SyncDisabled)
+        ));
+    }
 }
@@ -52,6 +52,10 @@ pub enum DataStoreError {
     BufferInUse(ResourceMethodKey),
     #[error("unimplemented")]
     Unimplemented,
+    #[error("storage of {0} bytes is too small for {1} data collectors")]
+    InsufficientStorage(usize, usize),
+    #[error("storage error: {0}")]
+    StorageError(String),
 }
 
 /// A trait for an entity that is capable of reading from a store region without consuming
@@ -87,6 +91,15 @@ pub trait DataStore {
     // Gets a reader that should implement `DataStoreReader`
     fn get_reader(&self, collector_key: &ResourceMethodKey)
         -> Result<Self::Reader, DataStoreError>;
+
+    /// Returns the number of bytes in use and the capacity in bytes of the region of the
+    /// collector
+    fn region_usage(
+        &self,
+        _collector_key: &ResourceMethodKey,
+    ) -> Result<(usize, usize), DataStoreError> {
+        Err(DataStoreError::Unimplemented)
+    }
 }
 
 const MAX_ALLOWED_TOTAL_CAPACITY: usize = 64000;
@@ -313,6 +326,14 @@ impl DataStore for DefaultDataStore {
             buffer_registration,
         ))
     }
+
+    fn region_usage(
+        &self,
+        collector_key: &ResourceMethodKey,
+    ) -> Result<(usize, usize), DataStoreError> {
+        let buffer = &self.buffers[self.get_index_for_collector(collector_key)?];
+        Ok((buffer.capacity() - buffer.vacant_len(), buffer.capacity()))
+    }
 }
 
 #[cfg(test)]
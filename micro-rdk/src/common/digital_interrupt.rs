@@ -1,4 +1,6 @@
 use super::config::{AttributeError, Kind};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
 #[derive(Copy, Clone, Debug)]
 pub struct DigitalInterruptConfig {
@@ -15,3 +17,51 @@ impl TryFrom<&Kind> for DigitalInterruptConfig {
         Ok(DigitalInterruptConfig { pin })
     }
 }
+
+/// Events of a digital interrupt, recorded by the interrupt handler of the pin as they happen.
+///
+/// Next to the count of every event, a debounced count ignores the events happening less than
+/// the debounce window after the last event it counted, they are bounces of a contact.
+#[derive(Debug, Default)]
+pub struct InterruptEvents {
+    count: AtomicU32,
+    debounced_count: AtomicU32,
+    debounce_ms: AtomicU32,
+    // wrapping timestamp of the last event added to the debounced count
+    last_debounced_ms: AtomicU32,
+}
+
+impl InterruptEvents {
+    /// Records an event happening at `now_ms`, a wrapping timestamp in milliseconds from a
+    /// monotonic clock. Only atomics are used so it can be called from an interrupt handler.
+    #[inline(always)]
+    pub fn record(&self, now_ms: u32) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let debounced_count = self.debounced_count.load(Ordering::Relaxed);
+        let elapsed_ms = now_ms.wrapping_sub(self.last_debounced_ms.load(Ordering::Relaxed));
+        // the first event is always counted
+        if debounced_count == 0 || elapsed_ms >= self.debounce_ms.load(Ordering::Relaxed) {
+            self.last_debounced_ms.store(now_ms, Ordering::Relaxed);
+            self.debounced_count
+                .store(debounced_count.wrapping_add(1), Ordering::Relaxed);
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn debounced_count(&self) -> u32 {
+        self.debounced_count.load(Ordering::Relaxed)
+    }
+
+    pub fn set_debounce(&self, debounce: Duration) {
+        let debounce_ms = debounce.as_millis().try_into().unwrap_or(u32::MAX);
+        self.debounce_ms.store(debounce_ms, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.debounced_count.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,106 @@
+use super::{
+    actuator::{Actuator, ActuatorError},
+    board::BoardError,
+    config::AttributeError,
+    generic::DoCommand,
+    motor::MotorError,
+    status::Status,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "gantry";
+
+#[derive(Debug, Error)]
+pub enum GantryError {
+    #[error(transparent)]
+    GantryMotorError(#[from] MotorError),
+    #[error(transparent)]
+    GantryBoardError(#[from] BoardError),
+    #[error(transparent)]
+    GantryActuatorError(#[from] ActuatorError),
+    #[error(transparent)]
+    GantryConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    GantryConfigurationError(&'static str),
+    #[error("invalid argument: {0}")]
+    GantryInvalidArgument(&'static str),
+    #[error("gantry must be homed first")]
+    GantryNotHomed,
+    #[error("homing didn't complete within {0:?}")]
+    GantryHomingTimeout(Duration),
+}
+
+pub trait Gantry: Status + Actuator + DoCommand {
+    /// Gets the position of each axis of the gantry in millimeters
+    fn get_position(&mut self) -> Result<Vec<f64>, GantryError>;
+
+    /// Moves each axis to the position at the same index in `positions_mm`, at the speed
+    /// at the same index in `speeds_mm_per_sec` (a default speed is used if it is empty).
+    /// The returned duration is the expected time to reach the positions, the caller is
+    /// responsible for stopping the gantry.
+    fn move_to_position(
+        &mut self,
+        positions_mm: &[f64],
+        speeds_mm_per_sec: &[f64],
+    ) -> Result<Option<Duration>, GantryError>;
+
+    /// Runs the homing routine of the gantry, one step at a time. Returns None once the
+    /// gantry is homed, otherwise the caller should call `home` again after the returned
+    /// duration. A routine interrupted by `stop` or `move_to_position` starts over.
+    fn home(&mut self) -> Result<Option<Duration>, GantryError>;
+
+    /// Gets the length of each axis of the gantry in millimeters
+    fn get_lengths(&mut self) -> Result<Vec<f64>, GantryError>;
+}
+
+pub type GantryType = Arc<Mutex<dyn Gantry>>;
+
+impl<L> Gantry for Mutex<L>
+where
+    L: ?Sized + Gantry,
+{
+    fn get_position(&mut self) -> Result<Vec<f64>, GantryError> {
+        self.get_mut().unwrap().get_position()
+    }
+    fn move_to_position(
+        &mut self,
+        positions_mm: &[f64],
+        speeds_mm_per_sec: &[f64],
+    ) -> Result<Option<Duration>, GantryError> {
+        self.get_mut()
+            .unwrap()
+            .move_to_position(positions_mm, speeds_mm_per_sec)
+    }
+    fn home(&mut self) -> Result<Option<Duration>, GantryError> {
+        self.get_mut().unwrap().home()
+    }
+    fn get_lengths(&mut self) -> Result<Vec<f64>, GantryError> {
+        self.get_mut().unwrap().get_lengths()
+    }
+}
+
+impl<A> Gantry for Arc<Mutex<A>>
+where
+    A: ?Sized + Gantry,
+{
+    fn get_position(&mut self) -> Result<Vec<f64>, GantryError> {
+        self.lock().unwrap().get_position()
+    }
+    fn move_to_position(
+        &mut self,
+        positions_mm: &[f64],
+        speeds_mm_per_sec: &[f64],
+    ) -> Result<Option<Duration>, GantryError> {
+        self.lock()
+            .unwrap()
+            .move_to_position(positions_mm, speeds_mm_per_sec)
+    }
+    fn home(&mut self) -> Result<Option<Duration>, GantryError> {
+        self.lock().unwrap().home()
+    }
+    fn get_lengths(&mut self) -> Result<Vec<f64>, GantryError> {
+        self.lock().unwrap().get_lengths()
+    }
+}
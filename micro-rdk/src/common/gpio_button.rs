@@ -0,0 +1,163 @@
+//! A button wired to a GPIO pin of the board configured as a digital interrupt.
+//!
+//! Presses are the interrupt events of the pin, counted by the board as they happen: events
+//! happening less than `debounce_ms` after a press are considered bounces of the contact and
+//! ignored. The debounce window is set on the pin, buttons sharing a pin share it.
+//!
+//! ```json
+//! {
+//!   "name": "start",
+//!   "type": "button",
+//!   "model": "gpio",
+//!   "attributes": {
+//!     "pin": 15,
+//!     "debounce_ms": 50
+//!   }
+//! }
+//! ```
+//!
+//! The pin must be listed in the `digital_interrupts` of the board.
+
+use super::board::{Board, BoardType};
+use super::button::{Button, ButtonError, ButtonType};
+use super::config::ConfigType;
+use super::registry::{get_board_from_dependencies, ComponentRegistry, Dependency};
+use super::status::{Status, StatusError};
+use crate::google;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Debounce window used when `debounce_ms` isn't configured
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_button("gpio", &GpioButton::<BoardType>::from_config)
+        .is_err()
+    {
+        log::error!("gpio model is already registered")
+    }
+}
+
+#[derive(DoCommand)]
+pub struct GpioButton<B> {
+    board: B,
+    pin: i32,
+    // debounced interrupt count of the pin when the button was configured
+    initial_count: u32,
+    push_count: u32,
+}
+
+impl<B> GpioButton<B>
+where
+    B: Board,
+{
+    pub fn new(mut board: B, pin: i32, debounce: Duration) -> Result<Self, ButtonError> {
+        board.set_digital_interrupt_debounce(pin, debounce)?;
+        let initial_count = board.get_debounced_interrupt_value(pin)?;
+        Ok(Self {
+            board,
+            pin,
+            initial_count,
+            push_count: 0,
+        })
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<ButtonType, ButtonError> {
+        let board = get_board_from_dependencies(deps).ok_or(
+            ButtonError::ButtonConfigurationError("missing board dependency"),
+        )?;
+        let pin = cfg.get_attribute::<i32>("pin")?;
+        let debounce = cfg
+            .get_attribute::<u32>("debounce_ms")
+            .map_or(DEFAULT_DEBOUNCE, |ms| Duration::from_millis(ms.into()));
+        Ok(Arc::new(Mutex::new(GpioButton::new(board, pin, debounce)?)))
+    }
+
+    fn press_count(&self) -> Result<u32, ButtonError> {
+        let count = self.board.get_debounced_interrupt_value(self.pin)?;
+        Ok(count.wrapping_sub(self.initial_count) + self.push_count)
+    }
+}
+
+impl<B> Button for GpioButton<B>
+where
+    B: Board,
+{
+    fn push(&mut self) -> Result<(), ButtonError> {
+        self.push_count += 1;
+        Ok(())
+    }
+
+    fn get_press_count(&mut self) -> Result<u32, ButtonError> {
+        self.press_count()
+    }
+}
+
+impl<B> Status for GpioButton<B>
+where
+    B: Board,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "press_count".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::NumberValue(
+                    self.press_count().unwrap_or(self.push_count).into(),
+                )),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::board::{Board, FakeBoard};
+    use crate::common::button::{Button, ButtonError};
+    use crate::common::gpio_button::GpioButton;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test_log::test]
+    fn test_gpio_button() -> Result<(), ButtonError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        assert!(GpioButton::new(board.clone(), 15, Duration::ZERO).is_err());
+        board.lock().unwrap().add_digital_interrupt(15);
+
+        let mut button = GpioButton::new(board.clone(), 15, Duration::ZERO)?;
+        assert_eq!(button.get_press_count()?, 0);
+
+        // every event is a press, even between two queries
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        assert_eq!(button.get_press_count()?, 2);
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        assert_eq!(button.get_press_count()?, 3);
+
+        button.push()?;
+        assert_eq!(button.get_press_count()?, 4);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_gpio_button_debounce() -> Result<(), ButtonError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        board.lock().unwrap().add_digital_interrupt(15);
+        let mut button = GpioButton::new(board.clone(), 15, Duration::from_secs(60))?;
+
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        assert_eq!(button.get_press_count()?, 1);
+        // the contact bounces, the events are ignored as they happen
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        assert_eq!(button.get_press_count()?, 1);
+        assert_eq!(board.lock().unwrap().get_digital_interrupt_value(15)?, 3);
+        Ok(())
+    }
+}
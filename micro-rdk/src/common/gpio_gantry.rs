@@ -0,0 +1,714 @@
+//! Gantries built from motors reporting their position (e.g. a gpio motor with an encoder)
+//! and limit switches wired to GPIO pins of the board.
+//!
+//! A `single_axis` gantry drives one linear stage. Homing moves the stage towards its first
+//! limit switch, which becomes the zero position. When a second limit switch is configured
+//! the stage is then moved towards it to measure the length of the axis, otherwise the length
+//! is taken from `length_mm`. The motor is stopped and homing fails if it doesn't complete
+//! within `homing_timeout_ms`.
+//!
+//! The limit switches are polled every 10ms while homing, so the stage keeps moving for up to
+//! one polling interval after reaching a switch. The homing speed is capped at 100mm/s to keep
+//! this overshoot under 1mm, provided `home` is called again after the interval it returns.
+//!
+//! ```json
+//! {
+//!   "name": "x",
+//!   "type": "gantry",
+//!   "model": "single_axis",
+//!   "attributes": {
+//!     "motor": "x_motor",
+//!     "limit_pins": [12, 13],
+//!     "limit_pin_enabled_high": true,
+//!     "mm_per_rev": 8,
+//!     "gantry_mm_per_sec": 20,
+//!     "homing_timeout_ms": 30000
+//!   }
+//! }
+//! ```
+//!
+//! A `multi_axis` gantry combines gantries listed, in order, by `subaxes_list`.
+
+use super::actuator::{Actuator, ActuatorError};
+use super::board::{Board, BoardType};
+use super::config::ConfigType;
+use super::gantry::{Gantry, GantryError, GantryType, COMPONENT_NAME as GantryCompName};
+use super::motor::{Motor, MotorType, COMPONENT_NAME as MotorCompName};
+use super::registry::{get_board_from_dependencies, ComponentRegistry, Dependency, ResourceKey};
+use super::robot::Resource;
+use super::status::{Status, StatusError};
+use crate::google;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Interval at which the limit switches are checked while homing
+const HOMING_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Distance the stage may travel past a limit switch before it is noticed while homing
+const MAX_HOMING_OVERSHOOT_MM: f64 = 1.0;
+/// Speed of the gantry when `gantry_mm_per_sec` isn't configured
+const DEFAULT_SPEED_MM_PER_SEC: f64 = 10.0;
+/// Time allowed to the homing routine when `homing_timeout_ms` isn't configured
+const DEFAULT_HOMING_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_gantry(
+            "single_axis",
+            &SingleAxisGantry::<MotorType, BoardType>::from_config,
+        )
+        .is_err()
+    {
+        log::error!("single_axis model is already registered")
+    }
+    if registry
+        .register_dependency_getter(
+            GantryCompName,
+            "single_axis",
+            &SingleAxisGantry::<MotorType, BoardType>::dependencies_from_config,
+        )
+        .is_err()
+    {
+        log::error!("failed to register dependency getter for single_axis model")
+    }
+    if registry
+        .register_gantry("multi_axis", &MultiAxisGantry::<GantryType>::from_config)
+        .is_err()
+    {
+        log::error!("multi_axis model is already registered")
+    }
+    if registry
+        .register_dependency_getter(
+            GantryCompName,
+            "multi_axis",
+            &MultiAxisGantry::<GantryType>::dependencies_from_config,
+        )
+        .is_err()
+    {
+        log::error!("failed to register dependency getter for multi_axis model")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Homing {
+    Idle,
+    SeekingMin,
+    // the motor position at the first limit switch is kept until the second one is reached
+    SeekingMax(f64),
+}
+
+#[derive(DoCommand)]
+pub struct SingleAxisGantry<M, B> {
+    motor: M,
+    board: B,
+    limit_pins: Vec<i32>,
+    limit_pin_enabled_high: bool,
+    mm_per_rev: f64,
+    speed_mm_per_sec: f64,
+    length_mm: Option<f64>,
+    // motor position (in revolutions) at the zero position of the axis, None until homed
+    position_offset: Option<f64>,
+    homing: Homing,
+    // set by a move until the gantry is stopped, status readers can't query the motor itself
+    moving: bool,
+    homing_timeout: Duration,
+    // set when the homing routine starts, it fails once the deadline is reached
+    homing_deadline: Instant,
+}
+
+impl<M, B> SingleAxisGantry<M, B>
+where
+    M: Motor,
+    B: Board,
+{
+    pub fn new(
+        mut motor: M,
+        board: B,
+        limit_pins: Vec<i32>,
+        limit_pin_enabled_high: bool,
+        mm_per_rev: f64,
+        length_mm: Option<f64>,
+    ) -> Result<Self, GantryError> {
+        if limit_pins.is_empty() || limit_pins.len() > 2 {
+            return Err(GantryError::GantryConfigurationError(
+                "gantry requires one or two limit pins",
+            ));
+        }
+        if limit_pins.len() == 1 && length_mm.is_none() {
+            return Err(GantryError::GantryConfigurationError(
+                "length_mm is required with a single limit pin",
+            ));
+        }
+        if !(mm_per_rev.is_finite() && mm_per_rev > 0.0) {
+            return Err(GantryError::GantryConfigurationError(
+                "mm_per_rev must be positive",
+            ));
+        }
+        if !motor.get_properties().position_reporting {
+            return Err(GantryError::GantryConfigurationError(
+                "gantry motor must report its position",
+            ));
+        }
+        Ok(Self {
+            motor,
+            board,
+            limit_pins,
+            limit_pin_enabled_high,
+            mm_per_rev,
+            speed_mm_per_sec: DEFAULT_SPEED_MM_PER_SEC,
+            length_mm,
+            position_offset: None,
+            homing: Homing::Idle,
+            moving: false,
+            homing_timeout: DEFAULT_HOMING_TIMEOUT,
+            homing_deadline: Instant::now(),
+        })
+    }
+
+    pub fn with_speed(mut self, speed_mm_per_sec: f64) -> Self {
+        self.speed_mm_per_sec = speed_mm_per_sec;
+        self
+    }
+
+    pub fn with_homing_timeout(mut self, homing_timeout: Duration) -> Self {
+        self.homing_timeout = homing_timeout;
+        self
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<GantryType, GantryError> {
+        let motor_name = cfg.get_attribute::<String>("motor")?;
+        let mut motor: Option<MotorType> = None;
+        for Dependency(key, res) in &deps {
+            if let Resource::Motor(found) = res {
+                if key.1 == motor_name {
+                    motor = Some(found.clone());
+                }
+            }
+        }
+        let motor = motor.ok_or(GantryError::GantryConfigurationError(
+            "motor couldn't be found",
+        ))?;
+        let board = get_board_from_dependencies(deps).ok_or(
+            GantryError::GantryConfigurationError("missing board dependency"),
+        )?;
+        let mut gantry = SingleAxisGantry::new(
+            motor,
+            board,
+            cfg.get_attribute::<Vec<i32>>("limit_pins")?,
+            cfg.get_attribute::<bool>("limit_pin_enabled_high")
+                .unwrap_or(true),
+            cfg.get_attribute::<f64>("mm_per_rev")?,
+            cfg.get_attribute::<f64>("length_mm").ok(),
+        )?;
+        if let Ok(speed) = cfg.get_attribute::<f64>("gantry_mm_per_sec") {
+            gantry = gantry.with_speed(speed);
+        }
+        if let Ok(timeout) = cfg.get_attribute::<u64>("homing_timeout_ms") {
+            gantry = gantry.with_homing_timeout(Duration::from_millis(timeout));
+        }
+        Ok(Arc::new(Mutex::new(gantry)))
+    }
+
+    pub(crate) fn dependencies_from_config(cfg: ConfigType) -> Vec<ResourceKey> {
+        let mut r_keys = Vec::new();
+        if let Ok(motor_name) = cfg.get_attribute::<String>("motor") {
+            r_keys.push(ResourceKey::new(MotorCompName, motor_name));
+        }
+        r_keys
+    }
+
+    fn limit_hit(&self, idx: usize) -> Result<bool, GantryError> {
+        Ok(self.board.get_gpio_level(self.limit_pins[idx])? == self.limit_pin_enabled_high)
+    }
+
+    fn rpm(&self, speed_mm_per_sec: f64) -> f64 {
+        speed_mm_per_sec / self.mm_per_rev * 60.0
+    }
+
+    // the stage travels at most one polling interval past a limit switch, the homing speed is
+    // capped so this stays within MAX_HOMING_OVERSHOOT_MM
+    fn homing_rpm(&self) -> f64 {
+        let max_speed = MAX_HOMING_OVERSHOOT_MM / HOMING_POLL_INTERVAL.as_secs_f64();
+        self.rpm(self.speed_mm_per_sec.min(max_speed))
+    }
+
+    fn step_homing(&mut self) -> Result<Option<Duration>, GantryError> {
+        if self.homing == Homing::Idle {
+            self.homing_deadline = Instant::now() + self.homing_timeout;
+            self.homing = Homing::SeekingMin;
+            self.moving = false;
+            self.motor.set_rpm(-self.homing_rpm())?;
+        }
+        match self.homing {
+            Homing::SeekingMin if self.limit_hit(0)? => {
+                self.motor.stop()?;
+                let min = self.motor.get_position_revolutions()?;
+                if self.limit_pins.len() == 1 {
+                    self.position_offset = Some(min);
+                    self.homing = Homing::Idle;
+                    return Ok(None);
+                }
+                self.homing = Homing::SeekingMax(min);
+                self.motor.set_rpm(self.homing_rpm())?;
+            }
+            Homing::SeekingMax(min) if self.limit_hit(1)? => {
+                self.motor.stop()?;
+                let max = self.motor.get_position_revolutions()?;
+                self.length_mm = Some((max - min) * self.mm_per_rev);
+                self.position_offset = Some(min);
+                self.homing = Homing::Idle;
+                return Ok(None);
+            }
+            _ => {}
+        }
+        if Instant::now() >= self.homing_deadline {
+            return Err(GantryError::GantryHomingTimeout(self.homing_timeout));
+        }
+        Ok(Some(HOMING_POLL_INTERVAL))
+    }
+}
+
+impl<M, B> Gantry for SingleAxisGantry<M, B>
+where
+    M: Motor,
+    B: Board,
+{
+    fn get_position(&mut self) -> Result<Vec<f64>, GantryError> {
+        let offset = self.position_offset.ok_or(GantryError::GantryNotHomed)?;
+        let position = self.motor.get_position_revolutions()?;
+        Ok(vec![(position - offset) * self.mm_per_rev])
+    }
+
+    fn move_to_position(
+        &mut self,
+        positions_mm: &[f64],
+        speeds_mm_per_sec: &[f64],
+    ) -> Result<Option<Duration>, GantryError> {
+        self.homing = Homing::Idle;
+        let offset = self.position_offset.ok_or(GantryError::GantryNotHomed)?;
+        let length = self.length_mm.ok_or(GantryError::GantryNotHomed)?;
+        if positions_mm.len() != 1 || speeds_mm_per_sec.len() > 1 {
+            return Err(GantryError::GantryInvalidArgument(
+                "expected one position and at most one speed",
+            ));
+        }
+        let position = positions_mm[0];
+        if !(0.0..=length).contains(&position) {
+            return Err(GantryError::GantryInvalidArgument(
+                "position is out of the gantry range",
+            ));
+        }
+        let speed = speeds_mm_per_sec
+            .first()
+            .copied()
+            .unwrap_or(self.speed_mm_per_sec);
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(GantryError::GantryInvalidArgument("speed must be positive"));
+        }
+        let target = offset + position / self.mm_per_rev;
+        let duration = self.motor.go_to(self.rpm(speed), target)?;
+        self.moving = true;
+        Ok(duration)
+    }
+
+    fn home(&mut self) -> Result<Option<Duration>, GantryError> {
+        let res = self.step_homing();
+        if res.is_err() {
+            // the motor is never left running once homing failed, the routine starts over
+            self.homing = Homing::Idle;
+            let _ = self.motor.stop();
+        }
+        res
+    }
+
+    fn get_lengths(&mut self) -> Result<Vec<f64>, GantryError> {
+        Ok(vec![self.length_mm.ok_or(GantryError::GantryNotHomed)?])
+    }
+}
+
+impl<M, B> Actuator for SingleAxisGantry<M, B>
+where
+    M: Motor,
+    B: Board,
+{
+    fn is_moving(&mut self) -> Result<bool, ActuatorError> {
+        self.motor.is_moving()
+    }
+    fn stop(&mut self) -> Result<(), ActuatorError> {
+        self.homing = Homing::Idle;
+        self.motor.stop()?;
+        self.moving = false;
+        Ok(())
+    }
+}
+
+impl<M, B> Status for SingleAxisGantry<M, B>
+where
+    M: Motor,
+    B: Board,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "is_moving".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::BoolValue(
+                    self.moving || self.homing != Homing::Idle,
+                )),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[derive(DoCommand)]
+pub struct MultiAxisGantry<G> {
+    subaxes: Vec<G>,
+    // subaxes done with the homing routine in progress
+    homed: Vec<bool>,
+}
+
+impl<G> MultiAxisGantry<G>
+where
+    G: Gantry,
+{
+    pub fn new(subaxes: Vec<G>) -> Self {
+        let homed = vec![false; subaxes.len()];
+        Self { subaxes, homed }
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<GantryType, GantryError> {
+        let names = cfg.get_attribute::<Vec<String>>("subaxes_list")?;
+        if names.is_empty() {
+            return Err(GantryError::GantryConfigurationError(
+                "multi_axis requires at least one subaxis",
+            ));
+        }
+        let mut gantries: HashMap<String, GantryType> = HashMap::new();
+        for Dependency(key, res) in deps {
+            if let Resource::Gantry(gantry) = res {
+                let _ = gantries.insert(key.1, gantry);
+            }
+        }
+        let subaxes = names
+            .iter()
+            .map(|name| {
+                gantries
+                    .get(name)
+                    .cloned()
+                    .ok_or(GantryError::GantryConfigurationError(
+                        "subaxis couldn't be found",
+                    ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Arc::new(Mutex::new(MultiAxisGantry::new(subaxes))))
+    }
+
+    pub(crate) fn dependencies_from_config(cfg: ConfigType) -> Vec<ResourceKey> {
+        cfg.get_attribute::<Vec<String>>("subaxes_list")
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| ResourceKey::new(GantryCompName, name))
+            .collect()
+    }
+
+    fn reset_homing(&mut self) {
+        self.homed.iter_mut().for_each(|homed| *homed = false);
+    }
+}
+
+impl<G> Gantry for MultiAxisGantry<G>
+where
+    G: Gantry,
+{
+    fn get_position(&mut self) -> Result<Vec<f64>, GantryError> {
+        let mut positions = Vec::new();
+        for subaxis in self.subaxes.iter_mut() {
+            positions.extend(subaxis.get_position()?);
+        }
+        Ok(positions)
+    }
+
+    fn move_to_position(
+        &mut self,
+        positions_mm: &[f64],
+        speeds_mm_per_sec: &[f64],
+    ) -> Result<Option<Duration>, GantryError> {
+        self.reset_homing();
+        let axes = self
+            .subaxes
+            .iter_mut()
+            .map(|subaxis| subaxis.get_lengths().map(|lengths| lengths.len()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if positions_mm.len() != axes.iter().sum::<usize>()
+            || !(speeds_mm_per_sec.is_empty() || speeds_mm_per_sec.len() == positions_mm.len())
+        {
+            return Err(GantryError::GantryInvalidArgument(
+                "expected one position and speed per axis",
+            ));
+        }
+        let mut duration: Option<Duration> = None;
+        let mut start = 0;
+        for (subaxis, count) in self.subaxes.iter_mut().zip(axes) {
+            let speeds = if speeds_mm_per_sec.is_empty() {
+                speeds_mm_per_sec
+            } else {
+                &speeds_mm_per_sec[start..start + count]
+            };
+            let res = subaxis.move_to_position(&positions_mm[start..start + count], speeds);
+            let subaxis_duration = match res {
+                Ok(d) => d,
+                Err(err) => {
+                    let _ = self.stop();
+                    return Err(err);
+                }
+            };
+            duration = duration.max(subaxis_duration);
+            start += count;
+        }
+        Ok(duration)
+    }
+
+    fn home(&mut self) -> Result<Option<Duration>, GantryError> {
+        let mut next_poll: Option<Duration> = None;
+        for (subaxis, homed) in self.subaxes.iter_mut().zip(self.homed.iter_mut()) {
+            if *homed {
+                continue;
+            }
+            match subaxis.home() {
+                Ok(Some(poll)) => next_poll = Some(next_poll.map_or(poll, |p| p.min(poll))),
+                Ok(None) => *homed = true,
+                Err(err) => {
+                    let _ = self.stop();
+                    return Err(err);
+                }
+            }
+        }
+        if next_poll.is_none() {
+            self.reset_homing();
+        }
+        Ok(next_poll)
+    }
+
+    fn get_lengths(&mut self) -> Result<Vec<f64>, GantryError> {
+        let mut lengths = Vec::new();
+        for subaxis in self.subaxes.iter_mut() {
+            lengths.extend(subaxis.get_lengths()?);
+        }
+        Ok(lengths)
+    }
+}
+
+impl<G> Actuator for MultiAxisGantry<G>
+where
+    G: Gantry,
+{
+    fn is_moving(&mut self) -> Result<bool, ActuatorError> {
+        for subaxis in self.subaxes.iter_mut() {
+            if subaxis.is_moving()? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    fn stop(&mut self) -> Result<(), ActuatorError> {
+        self.reset_homing();
+        // every subaxis is stopped even if one of them fails
+        let mut result = Ok(());
+        for subaxis in self.subaxes.iter_mut() {
+            if let Err(err) = subaxis.stop() {
+                result = Err(err);
+            }
+        }
+        result
+    }
+}
+
+impl<G> Status for MultiAxisGantry<G>
+where
+    G: Gantry,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut is_moving = false;
+        for subaxis in self.subaxes.iter() {
+            is_moving |= subaxis.get_status()?.is_some_and(|status| {
+                status.fields.get("is_moving").and_then(|v| v.kind.as_ref())
+                    == Some(&google::protobuf::value::Kind::BoolValue(true))
+            });
+        }
+        let mut hm = HashMap::new();
+        hm.insert(
+            "is_moving".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::BoolValue(is_moving)),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::actuator::Actuator;
+    use crate::common::board::FakeBoard;
+    use crate::common::encoder::FakeIncrementalEncoder;
+    use crate::common::gantry::{Gantry, GantryError};
+    use crate::common::gpio_gantry::{MultiAxisGantry, SingleAxisGantry, HOMING_POLL_INTERVAL};
+    use crate::common::gpio_motor::EncodedMotor;
+    use crate::common::motor::{FakeMotor, Motor};
+    use crate::common::status::Status;
+    use crate::google::protobuf::value::Kind;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    type FakeGantry = SingleAxisGantry<Arc<Mutex<FakeMotor>>, Arc<Mutex<FakeBoard>>>;
+
+    // the fake board reports every GPIO pin as high
+    fn fake_gantry(limit_pin_enabled_high: bool) -> Result<FakeGantry, GantryError> {
+        SingleAxisGantry::new(
+            Arc::new(Mutex::new(FakeMotor::new())),
+            Arc::new(Mutex::new(FakeBoard::new(vec![]))),
+            vec![12],
+            limit_pin_enabled_high,
+            10.0,
+            Some(100.0),
+        )
+    }
+
+    fn status_is_moving<S: Status>(gantry: &S) -> bool {
+        gantry.get_status().unwrap().unwrap().fields["is_moving"].kind
+            == Some(Kind::BoolValue(true))
+    }
+
+    #[test_log::test]
+    fn test_single_axis_gantry() -> Result<(), GantryError> {
+        let mut gantry = fake_gantry(true)?;
+
+        assert!(matches!(
+            gantry.get_position(),
+            Err(GantryError::GantryNotHomed)
+        ));
+        assert!(gantry.move_to_position(&[10.0], &[]).is_err());
+
+        assert_eq!(gantry.home()?, None);
+        assert_eq!(gantry.get_position()?, vec![0.0]);
+        assert_eq!(gantry.get_lengths()?, vec![100.0]);
+
+        // 5 revolutions at 60 rpm
+        let dur = gantry.move_to_position(&[50.0], &[10.0])?;
+        assert_eq!(dur, Some(Duration::from_secs(5)));
+        assert!(gantry.is_moving()?);
+        assert!(status_is_moving(&gantry));
+        gantry.stop()?;
+        assert!(!status_is_moving(&gantry));
+
+        assert!(gantry.move_to_position(&[150.0], &[]).is_err());
+        assert!(gantry.move_to_position(&[50.0], &[-10.0]).is_err());
+        assert!(gantry.move_to_position(&[50.0, 10.0], &[]).is_err());
+
+        assert!(SingleAxisGantry::new(
+            Arc::new(Mutex::new(FakeMotor::new())),
+            Arc::new(Mutex::new(FakeBoard::new(vec![]))),
+            vec![12],
+            true,
+            10.0,
+            None,
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_single_axis_gantry_encoded_motor() -> Result<(), GantryError> {
+        // 250 ticks at 100 ticks per rotation, the axis is homed at 2.5 revolutions
+        let encoder = Arc::new(Mutex::new(FakeIncrementalEncoder { ticks: 250.0 }));
+        let motor = EncodedMotor::new(FakeMotor::new(), encoder.clone(), Some(100));
+        let mut gantry = SingleAxisGantry::new(
+            motor,
+            Arc::new(Mutex::new(FakeBoard::new(vec![]))),
+            vec![12],
+            true,
+            10.0,
+            Some(100.0),
+        )?;
+        assert_eq!(gantry.home()?, None);
+        assert_eq!(gantry.get_position()?, vec![0.0]);
+
+        encoder.lock().unwrap().ticks = 550.0;
+        assert_eq!(gantry.get_position()?, vec![30.0]);
+
+        // from 5.5 to 7.5 revolutions at 60 rpm
+        let dur = gantry.move_to_position(&[50.0], &[10.0])?;
+        assert_eq!(dur, Some(Duration::from_secs(2)));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_single_axis_gantry_homing() -> Result<(), GantryError> {
+        // the limit switch is never reached
+        let mut gantry = fake_gantry(false)?;
+        assert_eq!(gantry.home()?, Some(HOMING_POLL_INTERVAL));
+        assert_eq!(gantry.home()?, Some(HOMING_POLL_INTERVAL));
+        assert!(status_is_moving(&gantry));
+        gantry.stop()?;
+        assert!(!status_is_moving(&gantry));
+        assert!(gantry.get_position().is_err());
+
+        // homing fails and stops the motor once the timeout is reached
+        let motor = Arc::new(Mutex::new(FakeMotor::new()));
+        let mut gantry = SingleAxisGantry::new(
+            motor.clone(),
+            Arc::new(Mutex::new(FakeBoard::new(vec![]))),
+            vec![12],
+            false,
+            10.0,
+            Some(100.0),
+        )?
+        .with_homing_timeout(Duration::ZERO);
+        assert!(matches!(
+            gantry.home(),
+            Err(GantryError::GantryHomingTimeout(_))
+        ));
+        assert!(!motor.lock().unwrap().is_moving()?);
+        assert!(gantry.get_position().is_err());
+
+        // homing is capped at 100mm/s, i.e. 60rpm at 100mm per revolution, whatever the
+        // configured speed
+        let motor = Arc::new(Mutex::new(FakeMotor::new()));
+        let mut gantry = SingleAxisGantry::new(
+            motor.clone(),
+            Arc::new(Mutex::new(FakeBoard::new(vec![]))),
+            vec![12],
+            false,
+            100.0,
+            Some(1000.0),
+        )?
+        .with_speed(1000.0);
+        assert_eq!(gantry.home()?, Some(HOMING_POLL_INTERVAL));
+        assert_eq!(motor.lock().unwrap().is_powered()?, (true, -0.6));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_multi_axis_gantry() -> Result<(), GantryError> {
+        let mut gantry = MultiAxisGantry::new(vec![fake_gantry(true)?, fake_gantry(true)?]);
+
+        assert_eq!(gantry.home()?, None);
+        assert_eq!(gantry.get_position()?, vec![0.0, 0.0]);
+        assert_eq!(gantry.get_lengths()?, vec![100.0, 100.0]);
+
+        assert!(!status_is_moving(&gantry));
+        let dur = gantry.move_to_position(&[50.0, 20.0], &[10.0, 10.0])?;
+        assert_eq!(dur, Some(Duration::from_secs(5)));
+        assert!(status_is_moving(&gantry));
+        assert!(gantry.move_to_position(&[50.0], &[]).is_err());
+        assert!(gantry.move_to_position(&[50.0, 20.0], &[10.0]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+//! An input controller made of the analog readers and GPIO pins of the board, such as a
+//! joystick wired to an ADC and a few panel buttons.
+//!
+//! Axes report values from -1.0 to 1.0 mapped from the `min` to `max` raw readings of their
+//! analog reader, values within `deadzone` of the center read 0. Buttons report 1.0 while
+//! pressed, a button is pressed when its pin reads `active_high`. The controller is polled
+//! whenever it is queried, when a button pin is also configured as a digital interrupt of
+//! the board, presses released between two polls are reported too.
+//!
+//! ```json
+//! {
+//!   "name": "pendant",
+//!   "type": "input_controller",
+//!   "model": "gpio",
+//!   "attributes": {
+//!     "axes": [
+//!       { "control": "AbsoluteX", "analog_reader": "joy_x", "min": 0, "max": 4095, "deadzone": 0.05 }
+//!     ],
+//!     "buttons": [
+//!       { "control": "ButtonSouth", "pin": 15, "active_high": false }
+//!     ]
+//!   }
+//! }
+//! ```
+
+use super::analog::{AnalogError, AnalogReader, AnalogReaderType};
+use super::board::{Board, BoardType};
+use super::config::{AttributeError, ConfigType, Kind};
+use super::input_controller::{
+    InputController, InputControllerError, InputControllerType, BUTTON_PRESS, BUTTON_RELEASE,
+    POSITION_CHANGE_ABS,
+};
+use super::registry::{get_board_from_dependencies, ComponentRegistry, Dependency};
+use super::status::{Status, StatusError};
+use crate::google::{self, protobuf::Timestamp};
+use crate::proto::component::input_controller::v1::Event;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Number of events kept for `get_events_after`
+const EVENT_HISTORY_SIZE: usize = 32;
+/// Smallest change of an axis value reported as an event
+const AXIS_CHANGE_THRESHOLD: f64 = 0.01;
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_input_controller(
+            "gpio",
+            &GpioInputController::<BoardType, AnalogReaderType<u16>>::from_config,
+        )
+        .is_err()
+    {
+        log::error!("gpio model is already registered")
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct AxisConfig {
+    pub(crate) control: String,
+    pub(crate) analog_reader: String,
+    pub(crate) min: f64,
+    pub(crate) max: f64,
+    pub(crate) deadzone: f64,
+}
+
+impl TryFrom<&Kind> for AxisConfig {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        if !value.contains_key("control")? {
+            return Err(AttributeError::KeyNotFound("control".to_string()));
+        }
+        if !value.contains_key("analog_reader")? {
+            return Err(AttributeError::KeyNotFound("analog_reader".to_string()));
+        }
+        let control = value.get("control")?.unwrap().try_into()?;
+        let analog_reader = value.get("analog_reader")?.unwrap().try_into()?;
+        let min = match value.get("min")? {
+            Some(val) => val.try_into()?,
+            None => 0.0,
+        };
+        let max = match value.get("max")? {
+            Some(val) => val.try_into()?,
+            None => u16::MAX.into(),
+        };
+        let deadzone = match value.get("deadzone")? {
+            Some(val) => val.try_into()?,
+            None => 0.0,
+        };
+        Ok(Self {
+            control,
+            analog_reader,
+            min,
+            max,
+            deadzone,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ButtonConfig {
+    pub(crate) control: String,
+    pub(crate) pin: i32,
+    pub(crate) active_high: bool,
+}
+
+impl TryFrom<&Kind> for ButtonConfig {
+    type Error = AttributeError;
+    fn try_from(value: &Kind) -> Result<Self, Self::Error> {
+        if !value.contains_key("control")? {
+            return Err(AttributeError::KeyNotFound("control".to_string()));
+        }
+        if !value.contains_key("pin")? {
+            return Err(AttributeError::KeyNotFound("pin".to_string()));
+        }
+        let control = value.get("control")?.unwrap().try_into()?;
+        let pin = value.get("pin")?.unwrap().try_into()?;
+        let active_high = match value.get("active_high")? {
+            Some(val) => val.try_into()?,
+            None => true,
+        };
+        Ok(Self {
+            control,
+            pin,
+            active_high,
+        })
+    }
+}
+
+pub struct Axis<A> {
+    control: String,
+    reader: A,
+    min: f64,
+    max: f64,
+    deadzone: f64,
+}
+
+impl<A> Axis<A>
+where
+    A: AnalogReader<u16, Error = AnalogError>,
+{
+    pub fn new(control: String, reader: A, min: f64, max: f64, deadzone: f64) -> Self {
+        Self {
+            control,
+            reader,
+            min,
+            max,
+            deadzone,
+        }
+    }
+
+    fn read(&mut self) -> Result<f64, InputControllerError> {
+        let raw = self.reader.read()? as f64;
+        let value = (2.0 * (raw - self.min) / (self.max - self.min) - 1.0).clamp(-1.0, 1.0);
+        Ok(if value.abs() < self.deadzone {
+            0.0
+        } else {
+            value
+        })
+    }
+}
+
+struct ButtonInput {
+    control: String,
+    pin: i32,
+    active_high: bool,
+    // interrupt count of the pin when it was last polled, None if the pin isn't an interrupt
+    event_count: Option<u32>,
+}
+
+#[derive(DoCommand)]
+pub struct GpioInputController<B, A> {
+    board: B,
+    axes: Vec<Axis<A>>,
+    buttons: Vec<ButtonInput>,
+    latest: HashMap<String, Event>,
+    history: VecDeque<(u64, Event)>,
+    event_number: u64,
+}
+
+impl<B, A> GpioInputController<B, A>
+where
+    B: Board,
+    A: AnalogReader<u16, Error = AnalogError>,
+{
+    pub fn new(board: B, axes: Vec<Axis<A>>) -> Self {
+        Self {
+            board,
+            axes,
+            buttons: Vec::new(),
+            latest: HashMap::new(),
+            history: VecDeque::with_capacity(EVENT_HISTORY_SIZE),
+            event_number: 0,
+        }
+    }
+
+    /// Adds a button wired to `pin`, pressed when the pin reads `active_high`
+    pub fn with_button(mut self, control: String, pin: i32, active_high: bool) -> Self {
+        let event_count = self.board.get_digital_interrupt_value(pin).ok();
+        self.buttons.push(ButtonInput {
+            control,
+            pin,
+            active_high,
+            event_count,
+        });
+        self
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<InputControllerType, InputControllerError> {
+        let board = get_board_from_dependencies(deps).ok_or(
+            InputControllerError::InputControllerConfigurationError("missing board dependency"),
+        )?;
+        let axes = cfg
+            .get_attribute::<Vec<AxisConfig>>("axes")
+            .unwrap_or_default();
+        let buttons = cfg
+            .get_attribute::<Vec<ButtonConfig>>("buttons")
+            .unwrap_or_default();
+        if axes.is_empty() && buttons.is_empty() {
+            return Err(InputControllerError::InputControllerConfigurationError(
+                "input controller requires at least one axis or button",
+            ));
+        }
+        if axes.iter().any(|axis| axis.max <= axis.min) {
+            return Err(InputControllerError::InputControllerConfigurationError(
+                "axis max must be greater than min",
+            ));
+        }
+        let axes = axes
+            .into_iter()
+            .map(|axis| {
+                let reader = board.get_analog_reader_by_name(axis.analog_reader)?;
+                Ok(Axis::new(
+                    axis.control,
+                    reader,
+                    axis.min,
+                    axis.max,
+                    axis.deadzone,
+                ))
+            })
+            .collect::<Result<Vec<_>, InputControllerError>>()?;
+        let mut controller = GpioInputController::new(board, axes);
+        for button in buttons {
+            controller = controller.with_button(button.control, button.pin, button.active_high);
+        }
+        controller.poll()?;
+        Ok(Arc::new(Mutex::new(controller)))
+    }
+
+    fn emit(&mut self, control: &str, event: &str, value: f64) {
+        let now = chrono::offset::Local::now().fixed_offset();
+        let event = Event {
+            time: Some(Timestamp {
+                seconds: now.timestamp(),
+                nanos: now.timestamp_subsec_nanos() as i32,
+            }),
+            event: event.to_string(),
+            control: control.to_string(),
+            value,
+        };
+        self.event_number += 1;
+        if self.history.len() == EVENT_HISTORY_SIZE {
+            let _ = self.history.pop_front();
+        }
+        self.history.push_back((self.event_number, event.clone()));
+        let _ = self.latest.insert(control.to_string(), event);
+    }
+
+    fn emit_button(&mut self, control: &str, pressed: bool) {
+        if pressed {
+            self.emit(control, BUTTON_PRESS, 1.0);
+        } else {
+            self.emit(control, BUTTON_RELEASE, 0.0);
+        }
+    }
+
+    // reads every control and records an event for each one that changed
+    fn poll(&mut self) -> Result<(), InputControllerError> {
+        let mut changes = Vec::new();
+        for axis in self.axes.iter_mut() {
+            let value = axis.read()?;
+            let changed = self.latest.get(&axis.control).is_none_or(|last| {
+                (value - last.value).abs() >= AXIS_CHANGE_THRESHOLD
+                    || (value == 0.0 && last.value != 0.0)
+            });
+            if changed {
+                changes.push((axis.control.clone(), value));
+            }
+        }
+        for (control, value) in changes {
+            self.emit(&control, POSITION_CHANGE_ABS, value);
+        }
+
+        for idx in 0..self.buttons.len() {
+            let (pin, active_high, last_count) = {
+                let button = &self.buttons[idx];
+                (button.pin, button.active_high, button.event_count)
+            };
+            let pressed = self.board.get_gpio_level(pin)? == active_high;
+            let event_count = match last_count {
+                Some(_) => Some(self.board.get_digital_interrupt_value(pin)?),
+                None => None,
+            };
+            let control = self.buttons[idx].control.clone();
+            let was_pressed = self
+                .latest
+                .get(&control)
+                .map(|last| last.event == BUTTON_PRESS);
+            if was_pressed != Some(pressed) {
+                self.emit_button(&control, pressed);
+            } else if event_count != last_count {
+                // the button changed and came back between two polls
+                self.emit_button(&control, !pressed);
+                self.emit_button(&control, pressed);
+            }
+            self.buttons[idx].event_count = event_count;
+        }
+        Ok(())
+    }
+
+    fn controls(&self) -> impl Iterator<Item = &String> {
+        self.axes
+            .iter()
+            .map(|axis| &axis.control)
+            .chain(self.buttons.iter().map(|button| &button.control))
+    }
+}
+
+impl<B, A> InputController for GpioInputController<B, A>
+where
+    B: Board,
+    A: AnalogReader<u16, Error = AnalogError>,
+{
+    fn get_controls(&mut self) -> Result<Vec<String>, InputControllerError> {
+        Ok(self.controls().cloned().collect())
+    }
+
+    fn get_events(&mut self) -> Result<Vec<Event>, InputControllerError> {
+        self.poll()?;
+        Ok(self
+            .controls()
+            .filter_map(|control| self.latest.get(control).cloned())
+            .collect())
+    }
+
+    fn get_events_after(&mut self, after: u64) -> Result<Vec<(u64, Event)>, InputControllerError> {
+        self.poll()?;
+        Ok(self
+            .history
+            .iter()
+            .filter(|(number, _)| *number > after)
+            .cloned()
+            .collect())
+    }
+}
+
+impl<B, A> Status for GpioInputController<B, A>
+where
+    B: Board,
+    A: AnalogReader<u16, Error = AnalogError>,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let hm = self
+            .latest
+            .iter()
+            .map(|(control, event)| {
+                (
+                    control.clone(),
+                    google::protobuf::Value {
+                        kind: Some(google::protobuf::value::Kind::NumberValue(event.value)),
+                    },
+                )
+            })
+            .collect();
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::analog::{AnalogError, AnalogReader, AnalogResolution};
+    use crate::common::board::{Board, FakeBoard};
+    use crate::common::gpio_input_controller::{Axis, GpioInputController};
+    use crate::common::input_controller::{
+        InputController, InputControllerError, BUTTON_PRESS, BUTTON_RELEASE, POSITION_CHANGE_ABS,
+    };
+    use std::sync::{Arc, Mutex};
+
+    struct TestAnalogReader {
+        value: u16,
+    }
+
+    impl AnalogReader<u16> for TestAnalogReader {
+        type Error = AnalogError;
+        fn read(&mut self) -> Result<u16, Self::Error> {
+            Ok(self.value)
+        }
+        fn name(&self) -> String {
+            "joy_x".to_string()
+        }
+        fn resolution(&self) -> AnalogResolution {
+            Default::default()
+        }
+    }
+
+    #[test_log::test]
+    fn test_gpio_input_controller() -> Result<(), InputControllerError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        board.lock().unwrap().add_digital_interrupt(15);
+        let reader = Arc::new(Mutex::new(TestAnalogReader { value: 500 }));
+        let axis = Axis::new("AbsoluteX".to_string(), reader.clone(), 0.0, 1000.0, 0.1);
+        let mut controller = GpioInputController::new(board.clone(), vec![axis]).with_button(
+            "ButtonSouth".to_string(),
+            15,
+            false,
+        );
+
+        assert_eq!(
+            controller.get_controls()?,
+            vec!["AbsoluteX".to_string(), "ButtonSouth".to_string()]
+        );
+        // pins of the fake board read high until set
+        let events = controller.get_events()?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, POSITION_CHANGE_ABS);
+        assert_eq!(events[0].value, 0.0);
+        assert_eq!(events[1].event, BUTTON_RELEASE);
+
+        reader.lock().unwrap().value = 1000;
+        board.lock().unwrap().set_gpio_pin_level(15, false)?;
+        let events = controller.get_events_after(2)?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, 3);
+        assert_eq!(events[0].1.value, 1.0);
+        assert_eq!(events[1].1.event, BUTTON_PRESS);
+        assert!(controller.get_events_after(4)?.is_empty());
+
+        // within the deadzone
+        reader.lock().unwrap().value = 520;
+        assert_eq!(controller.get_events()?[0].value, 0.0);
+
+        // a press and release between two polls
+        board.lock().unwrap().set_gpio_pin_level(15, true)?;
+        assert_eq!(controller.get_events_after(5)?.len(), 1);
+        board.lock().unwrap().trigger_digital_interrupt(15);
+        let events = controller.get_events_after(6)?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].1.event, BUTTON_PRESS);
+        assert_eq!(events[1].1.event, BUTTON_RELEASE);
+        Ok(())
+    }
+}
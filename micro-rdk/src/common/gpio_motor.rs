@@ -96,6 +96,20 @@ pub(crate) fn gpio_motor_from_config(
             }
         };
     }
+    // positions are reported in revolutions, the ticks counted by the encoder are converted
+    let enc = enc.map(|enc| {
+        let ticks_per_rotation = cfg
+            .get_attribute::<u32>("ticks_per_rotation")
+            .ok()
+            .filter(|t| *t > 0);
+        if ticks_per_rotation.is_none() {
+            log::warn!(
+                "Motor, 'ticks_per_rotation' should be positive when an encoder is set, \
+                 revolutions are read from the angle of the encoder or counted in ticks"
+            );
+        }
+        (enc, ticks_per_rotation)
+    });
     let board = get_board_from_dependencies(deps)
         .ok_or(MotorError::ConfigError("missing board dependency"))?;
     let motor_type = if let Ok(pin_cfg) = cfg.get_attribute::<MotorPinsConfig>("pins") {
@@ -110,8 +124,8 @@ pub(crate) fn gpio_motor_from_config(
         }
         MotorPinType::AB => AbMotor::<BoardType>::from_config(cfg, board.clone())?.clone(),
     };
-    if let Some(enc) = enc {
-        let enc_motor = EncodedMotor::new(motor, enc.clone());
+    if let Some((enc, ticks_per_rotation)) = enc {
+        let enc_motor = EncodedMotor::new(motor, enc.clone(), ticks_per_rotation);
         return Ok(Arc::new(Mutex::new(enc_motor)));
     }
     Ok(motor)
@@ -127,6 +141,9 @@ const MOTOR_PWM_FREQUENCY: u64 = 1000;
 pub struct EncodedMotor<M, Enc> {
     motor: M,
     enc: Enc,
+    // converts the ticks counted by the encoder into revolutions of the motor, see
+    // `get_position_revolutions` when unknown
+    ticks_per_rotation: Option<u32>,
 }
 
 impl<M, Enc> EncodedMotor<M, Enc>
@@ -134,8 +151,12 @@ where
     M: Motor,
     Enc: Encoder,
 {
-    pub fn new(motor: M, enc: Enc) -> Self {
-        Self { motor, enc }
+    pub fn new(motor: M, enc: Enc, ticks_per_rotation: Option<u32>) -> Self {
+        Self {
+            motor,
+            enc,
+            ticks_per_rotation,
+        }
     }
 }
 
@@ -150,6 +171,16 @@ where
             .get_position(EncoderPositionType::UNSPECIFIED)?
             .value as i32)
     }
+    /// Without `ticks_per_rotation` the angle of the encoder is used when it reports one,
+    /// otherwise every tick counts as a revolution
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        if self.ticks_per_rotation.is_none() && self.enc.get_properties().angle_degrees_supported {
+            let degrees = self.enc.get_position(EncoderPositionType::DEGREES)?.value as f64;
+            return Ok(degrees / 360.0);
+        }
+        let ticks = self.enc.get_position(EncoderPositionType::TICKS)?.value as f64;
+        Ok(ticks / self.ticks_per_rotation.unwrap_or(1) as f64)
+    }
 
     /// Accepts percentage as a float, e.g. `0.5` equals `50%` power.
     fn set_power(&mut self, pct: f64) -> Result<(), MotorError> {
@@ -163,6 +194,9 @@ where
             position_reporting: true,
         }
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        self.motor.is_powered()
+    }
 }
 
 impl<M, Enc> Actuator for EncodedMotor<M, Enc>
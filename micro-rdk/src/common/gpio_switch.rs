@@ -0,0 +1,188 @@
+//! A multi-position switch driving GPIO output pins of the board, such as a relay or a
+//! selector built from several relays.
+//!
+//! Each position is a list of levels, one per pin in `pins`. When `positions` isn't configured
+//! position 0 sets every pin low and position `n` sets the `n`-th pin high and the others low,
+//! so a switch with a single pin is an off/on switch.
+//!
+//! ```json
+//! {
+//!   "name": "pump",
+//!   "type": "switch",
+//!   "model": "gpio",
+//!   "attributes": {
+//!     "pins": [25, 26],
+//!     "positions": [[false, false], [true, false], [true, true]],
+//!     "initial_position": 0
+//!   }
+//! }
+//! ```
+
+use super::board::{Board, BoardType};
+use super::config::ConfigType;
+use super::registry::{get_board_from_dependencies, ComponentRegistry, Dependency};
+use super::status::{Status, StatusError};
+use super::switch::{Switch, SwitchError, SwitchType};
+use crate::google;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_switch("gpio", &GpioSwitch::<BoardType>::from_config)
+        .is_err()
+    {
+        log::error!("gpio model is already registered")
+    }
+}
+
+#[derive(DoCommand)]
+pub struct GpioSwitch<B> {
+    board: B,
+    pins: Vec<i32>,
+    // levels of the pins for each position
+    positions: Vec<Vec<bool>>,
+    position: u32,
+}
+
+impl<B> GpioSwitch<B>
+where
+    B: Board,
+{
+    /// Creates a switch and sets it to `initial_position`
+    pub fn new(
+        board: B,
+        pins: Vec<i32>,
+        positions: Vec<Vec<bool>>,
+        initial_position: u32,
+    ) -> Result<Self, SwitchError> {
+        if pins.is_empty() {
+            return Err(SwitchError::SwitchConfigurationError(
+                "switch requires at least one pin",
+            ));
+        }
+        if positions.len() < 2 {
+            return Err(SwitchError::SwitchConfigurationError(
+                "switch requires at least two positions",
+            ));
+        }
+        if positions.iter().any(|levels| levels.len() != pins.len()) {
+            return Err(SwitchError::SwitchConfigurationError(
+                "each position requires one level per pin",
+            ));
+        }
+        let mut switch = Self {
+            board,
+            pins,
+            positions,
+            position: initial_position,
+        };
+        switch.set_position(initial_position)?;
+        Ok(switch)
+    }
+
+    /// Positions where 0 sets every pin low and `n` sets only the `n`-th pin high
+    pub fn default_positions(pin_count: usize) -> Vec<Vec<bool>> {
+        (0..=pin_count)
+            .map(|position| (1..=pin_count).map(|pin| pin == position).collect())
+            .collect()
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<SwitchType, SwitchError> {
+        let board = get_board_from_dependencies(deps).ok_or(
+            SwitchError::SwitchConfigurationError("missing board dependency"),
+        )?;
+        let pins = cfg.get_attribute::<Vec<i32>>("pins")?;
+        let positions = cfg
+            .get_attribute::<Vec<Vec<bool>>>("positions")
+            .unwrap_or_else(|_| Self::default_positions(pins.len()));
+        let initial_position = cfg.get_attribute::<u32>("initial_position").unwrap_or(0);
+        Ok(Arc::new(Mutex::new(GpioSwitch::new(
+            board,
+            pins,
+            positions,
+            initial_position,
+        )?)))
+    }
+}
+
+impl<B> Switch for GpioSwitch<B>
+where
+    B: Board,
+{
+    fn set_position(&mut self, position: u32) -> Result<(), SwitchError> {
+        let levels = self
+            .positions
+            .get(position as usize)
+            .ok_or(SwitchError::SwitchInvalidPosition(position))?;
+        for (pin, is_high) in self.pins.iter().zip(levels) {
+            self.board.set_gpio_pin_level(*pin, *is_high)?;
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    fn get_position(&mut self) -> Result<u32, SwitchError> {
+        Ok(self.position)
+    }
+
+    fn get_number_of_positions(&mut self) -> Result<u32, SwitchError> {
+        Ok(self.positions.len() as u32)
+    }
+}
+
+impl<B> Status for GpioSwitch<B>
+where
+    B: Board,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "position".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::NumberValue(
+                    self.position.into(),
+                )),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::board::{Board, FakeBoard};
+    use crate::common::gpio_switch::GpioSwitch;
+    use crate::common::switch::{Switch, SwitchError};
+    use std::sync::{Arc, Mutex};
+
+    #[test_log::test]
+    fn test_gpio_switch() -> Result<(), SwitchError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        let positions = GpioSwitch::<Arc<Mutex<FakeBoard>>>::default_positions(2);
+        assert_eq!(
+            positions,
+            vec![vec![false, false], vec![true, false], vec![false, true]]
+        );
+
+        let mut switch = GpioSwitch::new(board.clone(), vec![25, 26], positions, 0)?;
+        assert_eq!(switch.get_number_of_positions()?, 3);
+        assert!(!board.get_gpio_level(25)?);
+        assert!(!board.get_gpio_level(26)?);
+
+        switch.set_position(2)?;
+        assert_eq!(switch.get_position()?, 2);
+        assert!(!board.get_gpio_level(25)?);
+        assert!(board.get_gpio_level(26)?);
+
+        assert!(switch.set_position(3).is_err());
+        assert_eq!(switch.get_position()?, 2);
+
+        assert!(GpioSwitch::new(board.clone(), vec![25], vec![vec![true]], 0).is_err());
+        assert!(GpioSwitch::new(board, vec![25], vec![vec![false], vec![true]], 2).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use super::{
+    actuator::{Actuator, ActuatorError},
+    config::AttributeError,
+    generic::DoCommand,
+    sensor::SensorError,
+    servo::ServoError,
+    status::Status,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "gripper";
+
+#[derive(Debug, Error)]
+pub enum GripperError {
+    #[error(transparent)]
+    GripperServoError(#[from] ServoError),
+    #[error(transparent)]
+    GripperSensorError(#[from] SensorError),
+    #[error(transparent)]
+    GripperActuatorError(#[from] ActuatorError),
+    #[error(transparent)]
+    GripperConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    GripperConfigurationError(&'static str),
+}
+
+pub trait Gripper: Status + Actuator + DoCommand {
+    /// Opens the gripper
+    fn open(&mut self) -> Result<(), GripperError>;
+
+    /// Starts closing the gripper. The returned duration is the time the gripper needs
+    /// to close, the caller should wait for it to elapse before calling `is_holding`
+    fn grab(&mut self) -> Result<Option<Duration>, GripperError>;
+
+    /// Reports whether the gripper is holding something, only meaningful once
+    /// a grab has completed
+    fn is_holding(&mut self) -> Result<bool, GripperError>;
+}
+
+pub type GripperType = Arc<Mutex<dyn Gripper>>;
+
+impl<L> Gripper for Mutex<L>
+where
+    L: ?Sized + Gripper,
+{
+    fn open(&mut self) -> Result<(), GripperError> {
+        self.get_mut().unwrap().open()
+    }
+    fn grab(&mut self) -> Result<Option<Duration>, GripperError> {
+        self.get_mut().unwrap().grab()
+    }
+    fn is_holding(&mut self) -> Result<bool, GripperError> {
+        self.get_mut().unwrap().is_holding()
+    }
+}
+
+impl<A> Gripper for Arc<Mutex<A>>
+where
+    A: ?Sized + Gripper,
+{
+    fn open(&mut self) -> Result<(), GripperError> {
+        self.lock().unwrap().open()
+    }
+    fn grab(&mut self) -> Result<Option<Duration>, GripperError> {
+        self.lock().unwrap().grab()
+    }
+    fn is_holding(&mut self) -> Result<bool, GripperError> {
+        self.lock().unwrap().is_holding()
+    }
+}
@@ -9,7 +9,20 @@ use std::{
 
 use crate::{
     common::{
-        analog::AnalogReader, board::Board, motor::Motor, robot::LocalRobot,
+        actuator::Actuator,
+        analog::AnalogReader,
+        base::{self, BaseType},
+        board::Board,
+        exec::Executor,
+        gantry::{self, GantryType},
+        gripper::{self, GripperType},
+        input_controller,
+        motor::{self, Motor, MotorType},
+        pose_tracker::PoseTrackerError,
+        registry::ResourceKey,
+        robot::{LocalRobot, Operation},
+        sensor::SensorError,
+        sensors_service::SensorsServiceError,
         webrtc::grpc::WebRtcGrpcService,
     },
     google::rpc::Status,
@@ -34,6 +47,15 @@ use thiserror::Error;
 
 use super::webrtc::signaling_server::SignalingServer;
 
+#[cfg(feature = "camera")]
+use super::webrtc::media::{StreamError, StreamServer};
+
+#[cfg(feature = "data")]
+use super::data_manager::DataManagerError;
+
+/// Interval at which StreamEvents looks for new input controller events
+const STREAM_EVENTS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 #[derive(Clone, Debug)]
 pub struct GrpcBody {
     _marker: PhantomData<*const ()>,
@@ -111,6 +133,10 @@ impl Body for GrpcBody {
     }
 }
 
+/// Response of an RPC that only completes once the robot is done acting on the request (e.g. a
+/// motor running for a number of revolutions). Awaiting it doesn't block the executor.
+pub type DeferredResponse = Pin<Box<dyn Future<Output = Result<Bytes, ServerError>> + Send + Sync>>;
+
 pub trait GrpcResponse {
     fn put_data(&mut self, data: Bytes);
     fn insert_trailer(&mut self, key: &'static str, value: &'_ str);
@@ -123,11 +149,15 @@ pub struct GrpcServer<R> {
     _response: PhantomData<R>,
     robot: Arc<Mutex<LocalRobot>>,
     signaling_server: Option<Arc<SignalingServer>>,
+    #[cfg(feature = "camera")]
+    stream_server: Option<StreamServer>,
 }
 
 pub struct GrpcServerInner<'a> {
     robot: &'a Arc<Mutex<LocalRobot>>,
     signaling_server: &'a Option<Arc<SignalingServer>>,
+    #[cfg(feature = "camera")]
+    stream_server: &'a Option<StreamServer>,
 }
 
 // TODO(RSDK-9243): The generic parameter R isn't really used here and can probably be removed,
@@ -141,6 +171,8 @@ where
             _response: PhantomData,
             robot,
             signaling_server: None,
+            #[cfg(feature = "camera")]
+            stream_server: None,
         }
     }
 
@@ -149,6 +181,14 @@ where
     pub(crate) fn register_signaling_server(&mut self, signaling_server: Arc<SignalingServer>) {
         let _ = self.signaling_server.insert(signaling_server);
     }
+
+    /// Lets the StreamService add the cameras of the robot as video tracks of the WebRTC
+    /// connection this server answers on
+    #[cfg(feature = "camera")]
+    pub(crate) fn with_stream_server(mut self, stream_server: StreamServer) -> Self {
+        let _ = self.stream_server.insert(stream_server);
+        self
+    }
 }
 
 impl<'a> GrpcServerInner<'a> {
@@ -188,19 +228,35 @@ impl<'a> GrpcServerInner<'a> {
         Ok(rest)
     }
 
+    /// Returns true for the server streaming methods served by `handle_rpc_stream`, every
+    /// other method is unary
+    pub(crate) fn is_server_stream(path: &str) -> bool {
+        matches!(
+            path,
+            "/viam.robot.v1.RobotService/StreamStatus"
+                | "/viam.component.inputcontroller.v1.InputControllerService/StreamEvents"
+        )
+    }
+
     pub(crate) fn handle_rpc_stream(
         &mut self,
         path: &str,
         payload: &[u8],
-    ) -> Result<(Bytes, std::time::Instant), ServerError> {
+        position: &mut Option<u64>,
+    ) -> Result<(Option<Bytes>, std::time::Instant), ServerError> {
         match path {
-            "/viam.robot.v1.RobotService/StreamStatus" => self.robot_status_stream(payload),
+            "/viam.robot.v1.RobotService/StreamStatus" => self
+                .robot_status_stream(payload)
+                .map(|(status, next)| (Some(status), next)),
+            "/viam.component.inputcontroller.v1.InputControllerService/StreamEvents" => {
+                self.input_controller_stream_events(payload, position)
+            }
             _ => Err(ServerError::from(GrpcError::RpcUnavailable)),
         }
     }
 
     pub(crate) fn handle_request(
-        self,
+        mut self,
         path: &str,
         payload: &[u8],
     ) -> Pin<Box<dyn futures_lite::Stream<Item = Result<Bytes, ServerError>> + Sync + Send>> {
@@ -211,12 +267,45 @@ impl<'a> GrpcServerInner<'a> {
         // demand a better system.
         match path {
             "/proto.rpc.webrtc.v1.SignalingService/Call" => self.signaling_service_call(payload),
-            _ => Box::pin(futures_lite::stream::once(
-                self.handle_unary_request(path, payload),
-            )),
+            _ => match self.handle_deferred_request(path, payload) {
+                Some(response) => Box::pin(futures_lite::stream::once_future(response)),
+                None => Box::pin(futures_lite::stream::once(
+                    self.handle_unary_request(path, payload),
+                )),
+            },
         }
     }
 
+    /// Routes the unary RPCs whose response has to wait for the robot to complete an action,
+    /// returns None if `path` can be answered by `handle_unary_request`
+    pub(crate) fn handle_deferred_request(
+        &mut self,
+        path: &str,
+        payload: &[u8],
+    ) -> Option<DeferredResponse> {
+        let response = match path {
+            "/viam.component.motor.v1.MotorService/GoFor" => self.motor_go_for(payload),
+            "/viam.component.motor.v1.MotorService/GoTo" => self.motor_go_to(payload),
+            "/viam.component.base.v1.BaseService/MoveStraight" => self.base_move_straight(payload),
+            "/viam.component.base.v1.BaseService/Spin" => self.base_spin(payload),
+            "/viam.component.gripper.v1.GripperService/Grab" => self.gripper_grab(payload),
+            "/viam.component.gantry.v1.GantryService/MoveToPosition" => {
+                self.gantry_move_to_position(payload)
+            }
+            "/viam.component.gantry.v1.GantryService/Home" => self.gantry_home(payload),
+            #[cfg(feature = "camera")]
+            "/proto.stream.v1.StreamService/AddStream" => self.stream_add_stream(payload),
+            #[cfg(feature = "camera")]
+            "/proto.stream.v1.StreamService/RemoveStream" => self.stream_remove_stream(payload),
+            _ => return None,
+        };
+        let response: DeferredResponse = match response {
+            Ok(response) => response,
+            Err(err) => Box::pin(futures_lite::future::ready(Err(err))),
+        };
+        Some(response)
+    }
+
     pub(crate) fn handle_unary_request(
         mut self,
         path: &str,
@@ -225,8 +314,6 @@ impl<'a> GrpcServerInner<'a> {
         match path {
             "/viam.component.base.v1.BaseService/SetPower" => self.base_set_power(payload),
             "/viam.component.base.v1.BaseService/Stop" => self.base_stop(payload),
-            "/viam.component.base.v1.BaseService/MoveStraight" => self.base_move_straight(payload),
-            "/viam.component.base.v1.BaseService/Spin" => self.base_spin(payload),
             "/viam.component.base.v1.BaseService/SetVelocity" => self.base_set_velocity(payload),
             "/viam.component.base.v1.BaseService/IsMoving" => self.base_is_moving(payload),
             "/viam.component.board.v1.BoardService/GetDigitalInterruptValue" => {
@@ -255,17 +342,27 @@ impl<'a> GrpcServerInner<'a> {
             #[cfg(feature = "camera")]
             "/viam.component.camera.v1.CameraService/GetImage" => self.camera_get_image(payload),
             #[cfg(feature = "camera")]
+            "/viam.component.camera.v1.CameraService/GetImages" => self.camera_get_images(payload),
+            #[cfg(feature = "camera")]
+            "/viam.component.camera.v1.CameraService/GetPointCloud" => {
+                self.camera_get_point_cloud(payload)
+            }
+            #[cfg(feature = "camera")]
+            "/viam.component.camera.v1.CameraService/GetProperties" => {
+                self.camera_get_properties(payload)
+            }
+            #[cfg(feature = "camera")]
             "/viam.component.camera.v1.CameraService/RenderFrame" => {
                 self.camera_render_frame(payload)
             }
             #[cfg(feature = "camera")]
             "/viam.component.camera.v1.CameraService/DoCommand" => self.camera_do_command(payload),
+            #[cfg(feature = "camera")]
+            "/proto.stream.v1.StreamService/ListStreams" => self.stream_list_streams(payload),
             "/viam.component.motor.v1.MotorService/GetPosition" => self.motor_get_position(payload),
             "/viam.component.motor.v1.MotorService/GetProperties" => {
                 self.motor_get_properties(payload)
             }
-            "/viam.component.motor.v1.MotorService/GoFor" => self.motor_go_for(payload),
-            "/viam.component.motor.v1.MotorService/GoTo" => self.motor_go_to(payload),
             "/viam.component.motor.v1.MotorService/IsPowered" => self.motor_is_powered(payload),
             "/viam.component.motor.v1.MotorService/IsMoving" => self.motor_is_moving(payload),
             "/viam.component.motor.v1.MotorService/ResetZeroPosition" => {
@@ -281,6 +378,7 @@ impl<'a> GrpcServerInner<'a> {
             "/viam.robot.v1.RobotService/GetOperations" => self.robot_get_operations(payload),
             "/viam.robot.v1.RobotService/Shutdown" => self.robot_shutdown(payload),
             "/viam.robot.v1.RobotService/GetCloudMetadata" => self.robot_get_cloud_metadata(),
+            "/viam.robot.v1.RobotService/GetMachineStatus" => self.robot_get_machine_status(),
             "/proto.rpc.v1.AuthService/Authenticate" => self.auth_service_authentificate(payload),
             "/proto.rpc.webrtc.v1.SignalingService/OptionalWebRTCConfig" => {
                 self.signaling_service_optional_webrtc_config(payload)
@@ -349,11 +447,79 @@ impl<'a> GrpcServerInner<'a> {
             "/viam.component.powersensor.v1.PowerSensorService/DoCommand" => {
                 self.power_sensor_do_command(payload)
             }
+            "/viam.service.sensors.v1.SensorsService/GetSensors" => {
+                self.sensors_service_get_sensors(payload)
+            }
+            "/viam.service.sensors.v1.SensorsService/GetReadings" => {
+                self.sensors_service_get_readings(payload)
+            }
+            #[cfg(feature = "data")]
+            "/viam.service.datamanager.v1.DataManagerService/Sync" => {
+                self.data_manager_sync(payload)
+            }
+            #[cfg(feature = "data")]
+            "/viam.service.datamanager.v1.DataManagerService/DoCommand" => {
+                self.data_manager_do_command(payload)
+            }
             "/viam.component.servo.v1.ServoService/Move" => self.servo_move(payload),
             "/viam.component.servo.v1.ServoService/GetPosition" => self.servo_get_position(payload),
             "/viam.component.servo.v1.ServoService/IsMoving" => self.servo_is_moving(payload),
             "/viam.component.servo.v1.ServoService/Stop" => self.servo_stop(payload),
             "/viam.component.servo.v1.ServoService/DoCommand" => self.servo_do_command(payload),
+            "/viam.component.arm.v1.ArmService/GetEndPosition" => {
+                self.arm_get_end_position(payload)
+            }
+            "/viam.component.arm.v1.ArmService/MoveToPosition" => {
+                self.arm_move_to_position(payload)
+            }
+            "/viam.component.arm.v1.ArmService/GetJointPositions" => {
+                self.arm_get_joint_positions(payload)
+            }
+            "/viam.component.arm.v1.ArmService/MoveToJointPositions" => {
+                self.arm_move_to_joint_positions(payload)
+            }
+            "/viam.component.arm.v1.ArmService/Stop" => self.arm_stop(payload),
+            "/viam.component.arm.v1.ArmService/IsMoving" => self.arm_is_moving(payload),
+            "/viam.component.arm.v1.ArmService/DoCommand" => self.arm_do_command(payload),
+            "/viam.component.gripper.v1.GripperService/Open" => self.gripper_open(payload),
+            "/viam.component.gripper.v1.GripperService/Stop" => self.gripper_stop(payload),
+            "/viam.component.gripper.v1.GripperService/IsMoving" => self.gripper_is_moving(payload),
+            "/viam.component.gripper.v1.GripperService/DoCommand" => {
+                self.gripper_do_command(payload)
+            }
+            "/viam.component.gantry.v1.GantryService/GetPosition" => {
+                self.gantry_get_position(payload)
+            }
+            "/viam.component.gantry.v1.GantryService/GetLengths" => {
+                self.gantry_get_lengths(payload)
+            }
+            "/viam.component.gantry.v1.GantryService/Stop" => self.gantry_stop(payload),
+            "/viam.component.gantry.v1.GantryService/IsMoving" => self.gantry_is_moving(payload),
+            "/viam.component.gantry.v1.GantryService/DoCommand" => self.gantry_do_command(payload),
+            "/viam.component.button.v1.ButtonService/Push" => self.button_push(payload),
+            "/viam.component.button.v1.ButtonService/DoCommand" => self.button_do_command(payload),
+            "/viam.component.switch.v1.SwitchService/SetPosition" => {
+                self.switch_set_position(payload)
+            }
+            "/viam.component.switch.v1.SwitchService/GetPosition" => {
+                self.switch_get_position(payload)
+            }
+            "/viam.component.switch.v1.SwitchService/GetNumberOfPositions" => {
+                self.switch_get_number_of_positions(payload)
+            }
+            "/viam.component.switch.v1.SwitchService/DoCommand" => self.switch_do_command(payload),
+            "/viam.component.inputcontroller.v1.InputControllerService/GetControls" => {
+                self.input_controller_get_controls(payload)
+            }
+            "/viam.component.inputcontroller.v1.InputControllerService/GetEvents" => {
+                self.input_controller_get_events(payload)
+            }
+            "/viam.component.posetracker.v1.PoseTrackerService/GetPoses" => {
+                self.pose_tracker_get_poses(payload)
+            }
+            "/viam.component.posetracker.v1.PoseTrackerService/DoCommand" => {
+                self.pose_tracker_do_command(payload)
+            }
             _ => Err(ServerError::from(GrpcError::RpcUnimplemented)),
         }
     }
@@ -388,35 +554,122 @@ impl<'a> GrpcServerInner<'a> {
         GrpcServerInner::encode_message(props)
     }
 
-    fn motor_go_for(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        // TODO: internal go_for can't wait without blocking executor, must be waited from here.
-        // requires refactoring this function (and its callers) to be async
-        /*
+    fn motor_go_for(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
         let req = component::motor::v1::GoForRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
-            Some(m) => m,
-            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
-        };
-        let mut motor = motor.lock().unwrap();
+        let (motor, operation) = self.start_motor_operation(req.name)?;
+        let duration = motor
+            .lock()
+            .unwrap()
+            .go_for(req.rpm, req.revolutions)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::motor::v1::GoForResponse {};
+        Ok(GrpcServerInner::wait_for_actuator(
+            motor, operation, duration, resp,
+        ))
+    }
 
-        if let Some(dur) =  motor.go_for(req.rpm, req.revolutions).map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err)))? {
-            // async wait for duration
-        }
-        motor.lock().unwrap();
+    fn motor_go_to(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::motor::v1::GoToRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (motor, operation) = self.start_motor_operation(req.name)?;
+        let duration = motor
+            .lock()
+            .unwrap()
+            .go_to(req.rpm, req.position_revolutions)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::motor::v1::GoToResponse {};
+        Ok(GrpcServerInner::wait_for_actuator(
+            motor, operation, duration, resp,
+        ))
+    }
 
-        let resp = component::motor::v1::GoForResponse {};
-        GrpcServerInner::encode_message(resp)
-        */
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn start_motor_operation(
+        &mut self,
+        name: String,
+    ) -> Result<(MotorType, Operation), ServerError> {
+        let mut robot = self.robot.lock().unwrap();
+        let motor = robot
+            .get_motor_by_name(name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+        let operation = robot.start_operation(ResourceKey::new(motor::COMPONENT_NAME, name));
+        Ok((motor, operation))
     }
 
-    fn motor_go_to(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn cancel_motor_operation(&mut self, name: &str) {
+        self.robot
+            .lock()
+            .unwrap()
+            .cancel_operation(&ResourceKey::new(motor::COMPONENT_NAME, name));
+    }
+
+    // Stops the actuator once `duration` has elapsed, unless the operation was cancelled first (by
+    // a call to Stop or a newer command).
+    fn wait_for_actuator<A: ?Sized + Actuator + 'static, M: Message + 'static>(
+        actuator: Arc<Mutex<A>>,
+        operation: Operation,
+        duration: Option<Duration>,
+        resp: M,
+    ) -> DeferredResponse {
+        let duration = match duration {
+            Some(duration) => duration,
+            None => {
+                return Box::pin(futures_lite::future::ready(
+                    GrpcServerInner::encode_message(resp),
+                ))
+            }
+        };
+        GrpcServerInner::defer_response(async move {
+            if operation.wait_for(duration).await {
+                actuator
+                    .lock()
+                    .unwrap()
+                    .stop()
+                    .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+            }
+            GrpcServerInner::encode_message(resp)
+        })
     }
 
-    fn motor_is_powered(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    // Runs `response` in a task spawned on the local executor so it completes (e.g. stops the
+    // actuator at the end of a move) even if the caller goes away before the response is sent.
+    fn defer_response<F>(response: F) -> DeferredResponse
+    where
+        F: Future<Output = Result<Bytes, ServerError>> + 'static,
+    {
+        let (sender, receiver) = async_channel::bounded(1);
+        Executor::new()
+            .spawn(async move {
+                let _ = sender.send(response.await).await;
+            })
+            .detach();
+        Box::pin(async move {
+            receiver
+                .recv()
+                .await
+                .unwrap_or_else(|_| Err(ServerError::from(GrpcError::RpcAborted)))
+        })
+    }
+
+    fn motor_is_powered(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::motor::v1::IsPoweredRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
+            Some(m) => m,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let (is_on, power_pct) = motor
+            .lock()
+            .unwrap()
+            .is_powered()
+            .map_err(|err| match err {
+                motor::MotorError::MotorMethodUnimplemented(_) => {
+                    ServerError::new(GrpcError::RpcUnimplemented, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
+        let resp = component::motor::v1::IsPoweredResponse { is_on, power_pct };
+        GrpcServerInner::encode_message(resp)
     }
 
     fn motor_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
@@ -468,6 +721,7 @@ impl<'a> GrpcServerInner<'a> {
     fn motor_set_power(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
         let req = component::motor::v1::SetPowerRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_motor_operation(&req.name);
         let motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
             Some(m) => m,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
@@ -484,111 +738,821 @@ impl<'a> GrpcServerInner<'a> {
     fn motor_set_rpm(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
         let req = component::motor::v1::SetRpmRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let mut motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
-            Some(m) => m,
+        self.cancel_motor_operation(&req.name);
+        let mut motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
+            Some(m) => m,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        motor
+            .set_rpm(req.rpm)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::motor::v1::SetRpmResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn motor_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::motor::v1::StopRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_motor_operation(&req.name);
+        let motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
+            Some(m) => m,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        motor
+            .lock()
+            .unwrap()
+            .stop()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::motor::v1::StopResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn servo_move(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::servo::v1::MoveRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        servo
+            .lock()
+            .unwrap()
+            .move_to(req.angle_deg)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::servo::v1::MoveResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn servo_get_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::servo::v1::GetPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let pos = servo
+            .lock()
+            .unwrap()
+            .get_position()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::servo::v1::GetPositionResponse { position_deg: pos };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn servo_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::servo::v1::IsMovingRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::servo::v1::IsMovingResponse {
+            is_moving: servo
+                .lock()
+                .unwrap()
+                .is_moving()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn servo_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::servo::v1::StopRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+            Some(m) => m,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        servo
+            .lock()
+            .unwrap()
+            .stop()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::servo::v1::StopResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn servo_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+            Some(m) => m,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = servo
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_get_end_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::GetEndPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let pose = arm
+            .lock()
+            .unwrap()
+            .get_end_position()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::arm::v1::GetEndPositionResponse { pose: Some(pose) };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_move_to_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::MoveToPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let pose = req.to.ok_or(GrpcError::RpcInvalidArgument)?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        arm.lock()
+            .unwrap()
+            .move_to_position(&pose)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::arm::v1::MoveToPositionResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_get_joint_positions(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::GetJointPositionsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let values = arm
+            .lock()
+            .unwrap()
+            .get_joint_positions()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::arm::v1::GetJointPositionsResponse {
+            positions: Some(component::arm::v1::JointPositions { values }),
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_move_to_joint_positions(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::MoveToJointPositionsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let positions = req.positions.ok_or(GrpcError::RpcInvalidArgument)?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        arm.lock()
+            .unwrap()
+            .move_to_joint_positions(&positions.values)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::arm::v1::MoveToJointPositionsResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::StopRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        arm.lock()
+            .unwrap()
+            .stop()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::arm::v1::StopResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::arm::v1::IsMovingRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::arm::v1::IsMovingResponse {
+            is_moving: arm
+                .lock()
+                .unwrap()
+                .is_moving()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn arm_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let arm = match self.robot.lock().unwrap().get_arm_by_name(req.name) {
+            Some(a) => a,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = arm
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gripper_open(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gripper::v1::OpenRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_gripper_operation(&req.name);
+        let gripper = match self.robot.lock().unwrap().get_gripper_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        gripper
+            .lock()
+            .unwrap()
+            .open()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::gripper::v1::OpenResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gripper_grab(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::gripper::v1::GrabRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (gripper, operation) = self.start_gripper_operation(req.name)?;
+        let duration = gripper
+            .lock()
+            .unwrap()
+            .grab()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let grab_response = move |completed: bool| -> Result<Bytes, ServerError> {
+            // a grab interrupted by Open or Stop didn't succeed
+            let success =
+                completed
+                    && gripper.lock().unwrap().is_holding().map_err(|err| {
+                        ServerError::new(GrpcError::RpcInternal, Some(err.into()))
+                    })?;
+            let resp = component::gripper::v1::GrabResponse {
+                success,
+                extra: None,
+            };
+            GrpcServerInner::encode_message(resp)
+        };
+        Ok(match duration {
+            Some(duration) => GrpcServerInner::defer_response(async move {
+                let completed = operation.wait_for(duration).await;
+                grab_response(completed)
+            }),
+            None => Box::pin(futures_lite::future::ready(grab_response(true))),
+        })
+    }
+
+    fn start_gripper_operation(
+        &mut self,
+        name: String,
+    ) -> Result<(GripperType, Operation), ServerError> {
+        let mut robot = self.robot.lock().unwrap();
+        let gripper = robot
+            .get_gripper_by_name(name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+        let operation = robot.start_operation(ResourceKey::new(gripper::COMPONENT_NAME, name));
+        Ok((gripper, operation))
+    }
+
+    fn cancel_gripper_operation(&mut self, name: &str) {
+        self.robot
+            .lock()
+            .unwrap()
+            .cancel_operation(&ResourceKey::new(gripper::COMPONENT_NAME, name));
+    }
+
+    fn gripper_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gripper::v1::StopRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_gripper_operation(&req.name);
+        let gripper = match self.robot.lock().unwrap().get_gripper_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        gripper
+            .lock()
+            .unwrap()
+            .stop()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::gripper::v1::StopResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gripper_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gripper::v1::IsMovingRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gripper = match self.robot.lock().unwrap().get_gripper_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::gripper::v1::IsMovingResponse {
+            is_moving: gripper
+                .lock()
+                .unwrap()
+                .is_moving()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gripper_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gripper = match self.robot.lock().unwrap().get_gripper_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = gripper
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gantry_get_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gantry::v1::GetPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gantry = match self.robot.lock().unwrap().get_gantry_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::gantry::v1::GetPositionResponse {
+            positions_mm: gantry
+                .lock()
+                .unwrap()
+                .get_position()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gantry_move_to_position(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::gantry::v1::MoveToPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (gantry, operation) = self.start_gantry_operation(req.name)?;
+        let duration = gantry
+            .lock()
+            .unwrap()
+            .move_to_position(&req.positions_mm, &req.speeds_mm_per_sec)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::gantry::v1::MoveToPositionResponse {};
+        Ok(GrpcServerInner::wait_for_actuator(
+            gantry, operation, duration, resp,
+        ))
+    }
+
+    fn gantry_home(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::gantry::v1::HomeRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (gantry, operation) = self.start_gantry_operation(req.name)?;
+        // a failed step leaves the gantry stopped, whatever the model does on errors
+        let home = |gantry: &GantryType| {
+            let mut gantry = gantry.lock().unwrap();
+            gantry.home().map_err(|err| {
+                let _ = gantry.stop();
+                ServerError::new(GrpcError::RpcInternal, Some(err.into()))
+            })
+        };
+        let mut poll = match home(&gantry)? {
+            Some(poll) => poll,
+            None => {
+                let resp = component::gantry::v1::HomeResponse { homed: true };
+                return Ok(Box::pin(futures_lite::future::ready(
+                    GrpcServerInner::encode_message(resp),
+                )));
+            }
+        };
+        // the homing routine is stepped until it completes or the operation is cancelled,
+        // in which case the routine was already interrupted by the gantry being stopped or moved
+        Ok(GrpcServerInner::defer_response(async move {
+            loop {
+                if !operation.wait_for(poll).await {
+                    let resp = component::gantry::v1::HomeResponse { homed: false };
+                    return GrpcServerInner::encode_message(resp);
+                }
+                match home(&gantry)? {
+                    Some(next_poll) => poll = next_poll,
+                    None => break,
+                }
+            }
+            let resp = component::gantry::v1::HomeResponse { homed: true };
+            GrpcServerInner::encode_message(resp)
+        }))
+    }
+
+    fn gantry_get_lengths(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gantry::v1::GetLengthsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gantry = match self.robot.lock().unwrap().get_gantry_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::gantry::v1::GetLengthsResponse {
+            lengths_mm: gantry
+                .lock()
+                .unwrap()
+                .get_lengths()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn start_gantry_operation(
+        &mut self,
+        name: String,
+    ) -> Result<(GantryType, Operation), ServerError> {
+        let mut robot = self.robot.lock().unwrap();
+        let gantry = robot
+            .get_gantry_by_name(name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+        let operation = robot.start_operation(ResourceKey::new(gantry::COMPONENT_NAME, name));
+        Ok((gantry, operation))
+    }
+
+    fn cancel_gantry_operation(&mut self, name: &str) {
+        self.robot
+            .lock()
+            .unwrap()
+            .cancel_operation(&ResourceKey::new(gantry::COMPONENT_NAME, name));
+    }
+
+    fn gantry_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gantry::v1::StopRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_gantry_operation(&req.name);
+        let gantry = match self.robot.lock().unwrap().get_gantry_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        gantry
+            .lock()
+            .unwrap()
+            .stop()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::gantry::v1::StopResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gantry_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::gantry::v1::IsMovingRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gantry = match self.robot.lock().unwrap().get_gantry_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::gantry::v1::IsMovingResponse {
+            is_moving: gantry
+                .lock()
+                .unwrap()
+                .is_moving()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn gantry_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let gantry = match self.robot.lock().unwrap().get_gantry_by_name(req.name) {
+            Some(g) => g,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = gantry
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn button_push(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::button::v1::PushRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let button = match self.robot.lock().unwrap().get_button_by_name(req.name) {
+            Some(b) => b,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        button
+            .lock()
+            .unwrap()
+            .push()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::button::v1::PushResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn button_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let button = match self.robot.lock().unwrap().get_button_by_name(req.name) {
+            Some(b) => b,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = button
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn switch_set_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::switch::v1::SetPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let switch = match self.robot.lock().unwrap().get_switch_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        switch
+            .lock()
+            .unwrap()
+            .set_position(req.position)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::switch::v1::SetPositionResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn switch_get_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::switch::v1::GetPositionRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let switch = match self.robot.lock().unwrap().get_switch_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::switch::v1::GetPositionResponse {
+            position: switch
+                .lock()
+                .unwrap()
+                .get_position()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn switch_get_number_of_positions(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::switch::v1::GetNumberOfPositionsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let switch = match self.robot.lock().unwrap().get_switch_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::switch::v1::GetNumberOfPositionsResponse {
+            number_of_positions: switch
+                .lock()
+                .unwrap()
+                .get_number_of_positions()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn switch_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let switch = match self.robot.lock().unwrap().get_switch_by_name(req.name) {
+            Some(s) => s,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let res = switch
+            .lock()
+            .unwrap()
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn input_controller_get_controls(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::input_controller::v1::GetControlsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let controller = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_input_controller_by_name(req.controller)
+        {
+            Some(c) => c,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let resp = component::input_controller::v1::GetControlsResponse {
+            controls: controller
+                .lock()
+                .unwrap()
+                .get_controls()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    fn input_controller_get_events(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::input_controller::v1::GetEventsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let controller = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_input_controller_by_name(req.controller)
+        {
+            Some(c) => c,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
         };
-        motor
-            .set_rpm(req.rpm)
-            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
-        let resp = component::motor::v1::SetRpmResponse {};
+        let resp = component::input_controller::v1::GetEventsResponse {
+            events: controller
+                .lock()
+                .unwrap()
+                .get_events()
+                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
+        };
         GrpcServerInner::encode_message(resp)
     }
 
-    fn motor_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = component::motor::v1::StopRequest::decode(message)
+    // Sends the subscribed events one message at a time, `position` is the number of the last
+    // event looked at by the stream. The stream starts with the events following its first call.
+    fn input_controller_stream_events(
+        &mut self,
+        message: &[u8],
+        position: &mut Option<u64>,
+    ) -> Result<(Option<Bytes>, Instant), ServerError> {
+        let req = component::input_controller::v1::StreamEventsRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let motor = match self.robot.lock().unwrap().get_motor_by_name(req.name) {
-            Some(m) => m,
+        let controller = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_input_controller_by_name(req.controller)
+        {
+            Some(c) => c,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
         };
-        motor
+        let events = controller
             .lock()
             .unwrap()
-            .stop()
+            .get_events_after(position.unwrap_or(0))
             .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
-        let resp = component::motor::v1::StopResponse {};
-        GrpcServerInner::encode_message(resp)
+        if position.is_none() {
+            *position = Some(events.last().map_or(0, |(number, _)| *number));
+            return Ok((None, Instant::now() + STREAM_EVENTS_POLL_INTERVAL));
+        }
+        let subscribed = |event: &component::input_controller::v1::Event| {
+            req.events.iter().any(|sub| {
+                sub.control == event.control
+                    && (sub.events.contains(&event.event)
+                        || sub.events.iter().any(|e| e == input_controller::ALL_EVENTS))
+                    && !sub.cancelled_events.contains(&event.event)
+            })
+        };
+        for (number, event) in events {
+            *position = Some(number);
+            if subscribed(&event) {
+                let resp =
+                    component::input_controller::v1::StreamEventsResponse { event: Some(event) };
+                // the following events are sent right away
+                return GrpcServerInner::encode_message(resp)
+                    .map(|resp| (Some(resp), Instant::now()));
+            }
+        }
+        Ok((None, Instant::now() + STREAM_EVENTS_POLL_INTERVAL))
     }
 
-    fn servo_move(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = component::servo::v1::MoveRequest::decode(message)
+    fn pose_tracker_get_poses(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::pose_tracker::v1::GetPosesRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
-            Some(s) => s,
-            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
-        };
-        servo
+        let tracker = match self
+            .robot
             .lock()
             .unwrap()
-            .move_to(req.angle_deg)
-            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
-        let resp = component::servo::v1::MoveResponse {};
+            .get_pose_tracker_by_name(req.name)
+        {
+            Some(t) => t,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let body_poses =
+            tracker
+                .lock()
+                .unwrap()
+                .get_poses(&req.body_names)
+                .map_err(|err| match err {
+                    PoseTrackerError::PoseTrackerBodyNotFound(_) => {
+                        ServerError::new(GrpcError::RpcInvalidArgument, Some(err.into()))
+                    }
+                    _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+                })?;
+        let resp = component::pose_tracker::v1::GetPosesResponse { body_poses };
         GrpcServerInner::encode_message(resp)
     }
 
-    fn servo_get_position(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = component::servo::v1::GetPositionRequest::decode(message)
+    fn pose_tracker_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
-            Some(s) => s,
+        let tracker = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_pose_tracker_by_name(req.name)
+        {
+            Some(t) => t,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
         };
-        let pos = servo
+        let res = tracker
             .lock()
             .unwrap()
-            .get_position()
-            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
-        let resp = component::servo::v1::GetPositionResponse { position_deg: pos };
+            .do_command(req.command)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let resp = proto::common::v1::DoCommandResponse { result: res };
         GrpcServerInner::encode_message(resp)
     }
 
-    fn servo_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = component::servo::v1::IsMovingRequest::decode(message)
+    #[allow(deprecated)]
+    fn sensors_service_get_sensors(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::service::sensors::v1::GetSensorsRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
+        let service = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_sensors_service_by_name(req.name)
+        {
             Some(s) => s,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
         };
-        let resp = component::servo::v1::IsMovingResponse {
-            is_moving: servo
-                .lock()
-                .unwrap()
-                .is_moving()
-                .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?,
-        };
+        let resp = service.lock().unwrap().get_sensors();
         GrpcServerInner::encode_message(resp)
     }
 
-    fn servo_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = component::servo::v1::StopRequest::decode(message)
+    #[allow(deprecated)]
+    fn sensors_service_get_readings(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::service::sensors::v1::GetReadingsRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
-            Some(m) => m,
+        let service = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_sensors_service_by_name(req.name)
+        {
+            Some(s) => s,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
         };
-        servo
+        let resp = service
             .lock()
             .unwrap()
-            .stop()
-            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
-        let resp = component::servo::v1::StopResponse {};
+            .get_readings(req.sensor_names)
+            .map_err(|err| match err {
+                SensorsServiceError::SensorNotFound(_) => {
+                    ServerError::new(GrpcError::RpcInvalidArgument, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
         GrpcServerInner::encode_message(resp)
     }
 
-    fn servo_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        let req = proto::common::v1::DoCommandRequest::decode(message)
+    #[cfg(feature = "data")]
+    fn data_manager_sync(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::service::datamanager::v1::SyncRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let servo = match self.robot.lock().unwrap().get_servo_by_name(req.name) {
-            Some(m) => m,
-            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
-        };
-        let res = servo
-            .lock()
-            .unwrap()
-            .do_command(req.command)
+        let robot = self.robot.lock().unwrap();
+        let service = robot
+            .get_data_manager_service_by_name(&req.name)
+            .ok_or_else(|| ServerError::from(GrpcError::RpcUnavailable))?;
+        service
+            .sync()
+            .map_err(|err| ServerError::new(GrpcError::RpcFailedPrecondition, Some(err.into())))?;
+        let resp = proto::service::datamanager::v1::SyncResponse {};
+        GrpcServerInner::encode_message(resp)
+    }
+
+    #[cfg(feature = "data")]
+    fn data_manager_do_command(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = proto::common::v1::DoCommandRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
-        let resp = proto::common::v1::DoCommandResponse { result: res };
+        let robot = self.robot.lock().unwrap();
+        let service = robot
+            .get_data_manager_service_by_name(&req.name)
+            .ok_or_else(|| ServerError::from(GrpcError::RpcUnavailable))?;
+        let res = service
+            .do_command(&req.command.unwrap_or_default())
+            .map_err(|err| match err {
+                DataManagerError::UnsupportedCommand(_)
+                | DataManagerError::UnknownCollector(_, _) => {
+                    ServerError::new(GrpcError::RpcInvalidArgument, Some(err.into()))
+                }
+                DataManagerError::StoreBusy => {
+                    ServerError::new(GrpcError::RpcUnavailable, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
+        let resp = proto::common::v1::DoCommandResponse { result: Some(res) };
         GrpcServerInner::encode_message(resp)
     }
 
@@ -997,8 +1961,32 @@ impl<'a> GrpcServerInner<'a> {
         Err(ServerError::from(GrpcError::RpcUnimplemented))
     }
 
-    fn movement_sensor_get_orientation(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn movement_sensor_get_orientation(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::movement_sensor::v1::GetOrientationRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let m_sensor = match self
+            .robot
+            .lock()
+            .unwrap()
+            .get_movement_sensor_by_name(req.name)
+        {
+            Some(b) => b,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        let orientation = m_sensor
+            .lock()
+            .unwrap()
+            .get_orientation()
+            .map_err(|err| match err {
+                SensorError::SensorMethodUnimplemented(_) => {
+                    ServerError::new(GrpcError::RpcUnimplemented, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
+        let resp = component::movement_sensor::v1::GetOrientationResponse {
+            orientation: Some(orientation),
+        };
+        GrpcServerInner::encode_message(resp)
     }
 
     fn movement_sensor_get_readings(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
@@ -1044,16 +2032,69 @@ impl<'a> GrpcServerInner<'a> {
         GrpcServerInner::encode_message(resp)
     }
 
-    fn base_move_straight(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn base_move_straight(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::base::v1::MoveStraightRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (base, operation) = self.start_base_operation(req.name)?;
+        let duration = base
+            .lock()
+            .unwrap()
+            .move_straight(req.distance_mm, req.mm_per_sec)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::base::v1::MoveStraightResponse {};
+        Ok(GrpcServerInner::wait_for_actuator(
+            base, operation, duration, resp,
+        ))
     }
 
-    fn base_spin(&mut self, _message: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn base_spin(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = component::base::v1::SpinRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let (base, operation) = self.start_base_operation(req.name)?;
+        let duration = base
+            .lock()
+            .unwrap()
+            .spin(req.angle_deg, req.degs_per_sec)
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::base::v1::SpinResponse {};
+        Ok(GrpcServerInner::wait_for_actuator(
+            base, operation, duration, resp,
+        ))
     }
 
-    fn base_set_velocity(&mut self, _: &[u8]) -> Result<Bytes, ServerError> {
-        Err(ServerError::from(GrpcError::RpcUnimplemented))
+    fn start_base_operation(&mut self, name: String) -> Result<(BaseType, Operation), ServerError> {
+        let mut robot = self.robot.lock().unwrap();
+        let base = robot
+            .get_base_by_name(name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+        let operation = robot.start_operation(ResourceKey::new(base::COMPONENT_NAME, name));
+        Ok((base, operation))
+    }
+
+    fn cancel_base_operation(&mut self, name: &str) {
+        self.robot
+            .lock()
+            .unwrap()
+            .cancel_operation(&ResourceKey::new(base::COMPONENT_NAME, name));
+    }
+
+    fn base_set_velocity(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::base::v1::SetVelocityRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_base_operation(&req.name);
+        let base = match self.robot.lock().unwrap().get_base_by_name(req.name) {
+            Some(b) => b,
+            None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
+        };
+        base.lock()
+            .unwrap()
+            .set_velocity(
+                &req.linear.unwrap_or_default(),
+                &req.angular.unwrap_or_default(),
+            )
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+        let resp = component::base::v1::SetVelocityResponse {};
+        GrpcServerInner::encode_message(resp)
     }
 
     fn base_is_moving(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
@@ -1076,6 +2117,7 @@ impl<'a> GrpcServerInner<'a> {
     fn base_set_power(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
         let req = component::base::v1::SetPowerRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_base_operation(&req.name);
         let base = match self.robot.lock().unwrap().get_base_by_name(req.name) {
             Some(b) => b,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
@@ -1094,6 +2136,7 @@ impl<'a> GrpcServerInner<'a> {
     fn base_stop(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
         let req = component::base::v1::StopRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        self.cancel_base_operation(&req.name);
         let base = match self.robot.lock().unwrap().get_base_by_name(req.name) {
             Some(b) => b,
             None => return Err(ServerError::from(GrpcError::RpcUnavailable)),
@@ -1341,11 +2384,13 @@ impl<'a> GrpcServerInner<'a> {
         GrpcServerInner::encode_message(resp)
     }
 
+    fn robot_get_machine_status(&mut self) -> Result<Bytes, ServerError> {
+        let resp = self.robot.lock().unwrap().get_machine_status();
+        GrpcServerInner::encode_message(resp)
+    }
+
     #[cfg(feature = "camera")]
     fn camera_get_image(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
-        // TODO: Modify camera methods (ie `get_image`, `render_frame`) to return a data structure that can be passed into
-        // `encode_message`, rather than re-implementing `encode_message` here. See
-        // https://viam.atlassian.net/browse/RSDK-824
         let req = component::camera::v1::GetImageRequest::decode(message)
             .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
 
@@ -1362,10 +2407,83 @@ impl<'a> GrpcServerInner<'a> {
             .get_image()
             .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
 
-        let resp = component::camera::v1::GetImageResponse {
-            mime_type: "image/jpeg".to_string(),
-            image,
-        };
+        let resp: component::camera::v1::GetImageResponse = image.into();
+        GrpcServerInner::encode_message(resp)
+    }
+
+    #[cfg(feature = "camera")]
+    fn camera_get_images(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::camera::v1::GetImagesRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+
+        let camera = self
+            .robot
+            .lock()
+            .unwrap()
+            .get_camera_by_name(req.name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+
+        let images = camera
+            .lock()
+            .unwrap()
+            .get_images()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
+
+        let resp = crate::common::camera::get_images_response(images, &req.name);
+        GrpcServerInner::encode_message(resp)
+    }
+
+    #[cfg(feature = "camera")]
+    fn camera_get_point_cloud(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::camera::v1::GetPointCloudRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+
+        let camera = self
+            .robot
+            .lock()
+            .unwrap()
+            .get_camera_by_name(req.name)
+            .ok_or(GrpcError::RpcUnavailable)?;
+
+        let point_cloud = camera
+            .lock()
+            .unwrap()
+            .get_point_cloud()
+            .map_err(|err| match err {
+                crate::common::camera::CameraError::CameraMethodUnimplemented(_) => {
+                    ServerError::new(GrpcError::RpcUnimplemented, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
+
+        let resp: component::camera::v1::GetPointCloudResponse = point_cloud.into();
+        GrpcServerInner::encode_message(resp)
+    }
+
+    #[cfg(feature = "camera")]
+    fn camera_get_properties(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let req = component::camera::v1::GetPropertiesRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+
+        let camera = self
+            .robot
+            .lock()
+            .unwrap()
+            .get_camera_by_name(req.name)
+            .ok_or(GrpcError::RpcUnavailable)?;
+
+        let properties = camera
+            .lock()
+            .unwrap()
+            .get_properties()
+            .map_err(|err| match err {
+                crate::common::camera::CameraError::CameraMethodUnimplemented(_) => {
+                    ServerError::new(GrpcError::RpcUnimplemented, Some(err.into()))
+                }
+                _ => ServerError::new(GrpcError::RpcInternal, Some(err.into())),
+            })?;
+
+        let resp: component::camera::v1::GetPropertiesResponse = properties.into();
         GrpcServerInner::encode_message(resp)
     }
 
@@ -1387,11 +2505,7 @@ impl<'a> GrpcServerInner<'a> {
             .get_image()
             .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?;
 
-        let msg = crate::google::api::HttpBody {
-            content_type: "image/jpeg".to_string(),
-            data: image.to_vec(),
-            ..Default::default()
-        };
+        let msg: crate::google::api::HttpBody = image.into();
         GrpcServerInner::encode_message(msg)
     }
 
@@ -1412,6 +2526,79 @@ impl<'a> GrpcServerInner<'a> {
         GrpcServerInner::encode_message(resp)
     }
 
+    #[cfg(feature = "camera")]
+    fn stream_list_streams(&mut self, message: &[u8]) -> Result<Bytes, ServerError> {
+        let _ = proto::stream::v1::ListStreamsRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let names = self
+            .robot
+            .lock()
+            .unwrap()
+            .get_resource_names()
+            .map_err(|err| ServerError::new(GrpcError::RpcInternal, Some(err.into())))?
+            .into_iter()
+            .filter(|r| r.subtype == crate::common::camera::COMPONENT_NAME)
+            .map(|r| r.name)
+            .collect();
+        let resp = proto::stream::v1::ListStreamsResponse { names };
+        GrpcServerInner::encode_message(resp)
+    }
+
+    #[cfg(feature = "camera")]
+    fn stream_add_stream(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = proto::stream::v1::AddStreamRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        // streams can only be added to WebRTC connections
+        let streams = self
+            .stream_server
+            .clone()
+            .ok_or(GrpcError::RpcUnimplemented)?;
+        let camera = self
+            .robot
+            .lock()
+            .unwrap()
+            .get_camera_by_name(req.name.clone())
+            .ok_or(GrpcError::RpcUnavailable)?;
+        // the track is negotiated with the peer before the response is sent
+        Ok(GrpcServerInner::defer_response(async move {
+            streams
+                .add_stream(req.name, camera)
+                .await
+                .map_err(GrpcServerInner::stream_error)?;
+            GrpcServerInner::encode_message(proto::stream::v1::AddStreamResponse {})
+        }))
+    }
+
+    #[cfg(feature = "camera")]
+    fn stream_remove_stream(&mut self, message: &[u8]) -> Result<DeferredResponse, ServerError> {
+        let req = proto::stream::v1::RemoveStreamRequest::decode(message)
+            .map_err(|_| ServerError::from(GrpcError::RpcInvalidArgument))?;
+        let streams = self
+            .stream_server
+            .clone()
+            .ok_or(GrpcError::RpcUnimplemented)?;
+        Ok(GrpcServerInner::defer_response(async move {
+            streams
+                .remove_stream(req.name)
+                .await
+                .map_err(GrpcServerInner::stream_error)?;
+            GrpcServerInner::encode_message(proto::stream::v1::RemoveStreamResponse {})
+        }))
+    }
+
+    #[cfg(feature = "camera")]
+    fn stream_error(err: StreamError) -> ServerError {
+        let code = match err {
+            StreamError::StreamAlreadyAdded(_) => GrpcError::RpcAlreadyExists,
+            StreamError::StreamNotAdded(_) => GrpcError::RpcNotFound,
+            StreamError::StreamTooManyTracks(_) => GrpcError::RpcResourceExhausted,
+            StreamError::StreamRejected => GrpcError::RpcFailedPrecondition,
+            StreamError::StreamConnectionClosed => GrpcError::RpcAborted,
+            _ => GrpcError::RpcInternal,
+        };
+        ServerError::new(code, Some(err.into()))
+    }
+
     fn get_version(&mut self) -> Result<Bytes, ServerError> {
         let resp = proto::robot::v1::GetVersionResponse {
             platform: "viam-micro-server".to_string(),
@@ -1512,22 +2699,41 @@ where
         let grpc = GrpcServerInner {
             robot: &self.robot,
             signaling_server: &self.signaling_server,
+            #[cfg(feature = "camera")]
+            stream_server: &self.stream_server,
         };
         grpc.handle_unary_request(method, data)
             .map(|mut b| b.split_off(5))
     }
+    fn deferred_unary_rpc(&mut self, method: &str, data: &Bytes) -> Option<DeferredResponse> {
+        let mut grpc = GrpcServerInner {
+            robot: &self.robot,
+            signaling_server: &self.signaling_server,
+            #[cfg(feature = "camera")]
+            stream_server: &self.stream_server,
+        };
+        grpc.handle_deferred_request(method, data).map(|response| {
+            Box::pin(async move { response.await.map(|mut b| b.split_off(5)) }) as DeferredResponse
+        })
+    }
     fn server_stream_rpc(
         &mut self,
         method: &str,
         data: &Bytes,
-    ) -> Result<(Bytes, Instant), ServerError> {
+        position: &mut Option<u64>,
+    ) -> Result<(Option<Bytes>, Instant), ServerError> {
         log::debug!("stream req is {:?}, ", method);
         let mut grpc = GrpcServerInner {
             robot: &self.robot,
             signaling_server: &self.signaling_server,
+            #[cfg(feature = "camera")]
+            stream_server: &self.stream_server,
         };
-        grpc.handle_rpc_stream(method, data)
-            .map(|mut dur| (dur.0.split_off(5), dur.1))
+        grpc.handle_rpc_stream(method, data, position)
+            .map(|(message, next)| (message.map(|mut b| b.split_off(5)), next))
+    }
+    fn is_server_stream(&self, method: &str) -> bool {
+        GrpcServerInner::is_server_stream(method)
     }
 }
 
@@ -1561,6 +2767,8 @@ where
             let grpc = GrpcServerInner {
                 robot: &svc.robot,
                 signaling_server: &svc.signaling_server,
+                #[cfg(feature = "camera")]
+                stream_server: &svc.stream_server,
             };
 
             type Stream = dyn futures_lite::Stream<Item = Result<Bytes, ServerError>> + Send + Sync;
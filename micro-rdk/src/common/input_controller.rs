@@ -0,0 +1,72 @@
+use super::{
+    analog::AnalogError, board::BoardError, config::AttributeError, generic::DoCommand,
+    status::Status,
+};
+use crate::proto::component::input_controller::v1::Event;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "input_controller";
+
+pub static BUTTON_PRESS: &str = "ButtonPress";
+pub static BUTTON_RELEASE: &str = "ButtonRelease";
+pub static POSITION_CHANGE_ABS: &str = "PositionChangeAbs";
+/// Matches every event type when subscribing to events
+pub static ALL_EVENTS: &str = "AllEvents";
+
+#[derive(Debug, Error)]
+pub enum InputControllerError {
+    #[error(transparent)]
+    InputControllerBoardError(#[from] BoardError),
+    #[error(transparent)]
+    InputControllerAnalogError(#[from] AnalogError),
+    #[error(transparent)]
+    InputControllerConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    InputControllerConfigurationError(&'static str),
+}
+
+pub trait InputController: Status + DoCommand {
+    /// Gets the names of the controls (buttons and axes) of the controller
+    fn get_controls(&mut self) -> Result<Vec<String>, InputControllerError>;
+
+    /// Gets the most recent event of each control
+    fn get_events(&mut self) -> Result<Vec<Event>, InputControllerError>;
+
+    /// Gets the events that occurred after the event numbered `after`, oldest first and
+    /// along with their number. Events are numbered from 1 in order of occurrence, only the
+    /// most recent ones are kept so a caller falling behind misses events.
+    fn get_events_after(&mut self, after: u64) -> Result<Vec<(u64, Event)>, InputControllerError>;
+}
+
+pub type InputControllerType = Arc<Mutex<dyn InputController>>;
+
+impl<L> InputController for Mutex<L>
+where
+    L: ?Sized + InputController,
+{
+    fn get_controls(&mut self) -> Result<Vec<String>, InputControllerError> {
+        self.get_mut().unwrap().get_controls()
+    }
+    fn get_events(&mut self) -> Result<Vec<Event>, InputControllerError> {
+        self.get_mut().unwrap().get_events()
+    }
+    fn get_events_after(&mut self, after: u64) -> Result<Vec<(u64, Event)>, InputControllerError> {
+        self.get_mut().unwrap().get_events_after(after)
+    }
+}
+
+impl<A> InputController for Arc<Mutex<A>>
+where
+    A: ?Sized + InputController,
+{
+    fn get_controls(&mut self) -> Result<Vec<String>, InputControllerError> {
+        self.lock().unwrap().get_controls()
+    }
+    fn get_events(&mut self) -> Result<Vec<Event>, InputControllerError> {
+        self.lock().unwrap().get_events()
+    }
+    fn get_events_after(&mut self, after: u64) -> Result<Vec<(u64, Event)>, InputControllerError> {
+        self.lock().unwrap().get_events_after(after)
+    }
+}
@@ -3,14 +3,24 @@
 //!
 //! # Components
 //! - [actuator]
+//! - [arm]
 //! - [base]
 //! - [board]
+//! - [button]
 //! - [camera]
 //! - [encoder]
+//! - [gantry]
+//! - [gripper]
+//! - [input_controller]
 //! - [motor]
 //! - [movement_sensor]
+//! - [pose_tracker]
 //! - [sensor]
 //! - [servo]
+//! - [switch]
+//!
+//! # Services
+//! - [sensors_service]
 //!
 //! # Utils
 //! - [grpc]
@@ -22,17 +32,26 @@
 //!
 //! General Purpose Drivers
 //! - [adxl345]
+//! - [gpio_button]
+//! - [gpio_gantry]
+//! - [gpio_input_controller]
 //! - [gpio_motor]
+//! - [gpio_switch]
 //! - [ina]
+//! - [movement_sensor_pose_tracker]
 //! - [mpu6050]
+//! - [servo_arm]
+//! - [servo_gripper]
 
 pub mod actuator;
 #[cfg(feature = "builtin-components")]
 pub mod adxl345;
 pub mod analog;
 pub mod app_client;
+pub mod arm;
 pub mod base;
 pub mod board;
+pub mod button;
 #[cfg(feature = "camera")]
 pub mod camera;
 pub mod config;
@@ -41,31 +60,51 @@ pub mod credentials_storage;
 pub mod digital_interrupt;
 pub mod encoder;
 pub mod exec;
+pub mod gantry;
 pub mod generic;
 #[cfg(feature = "builtin-components")]
+pub mod gpio_button;
+#[cfg(feature = "builtin-components")]
+pub mod gpio_gantry;
+#[cfg(feature = "builtin-components")]
+pub mod gpio_input_controller;
+#[cfg(feature = "builtin-components")]
 pub mod gpio_motor;
 #[cfg(feature = "builtin-components")]
 pub mod gpio_servo;
+#[cfg(feature = "builtin-components")]
+pub mod gpio_switch;
+pub mod gripper;
 pub mod grpc;
 pub mod grpc_client;
 pub mod i2c;
 #[cfg(feature = "builtin-components")]
 pub mod ina;
+pub mod input_controller;
 pub mod log;
 pub mod math_utils;
 pub mod motor;
 pub mod movement_sensor;
 #[cfg(feature = "builtin-components")]
+pub mod movement_sensor_pose_tracker;
+#[cfg(feature = "builtin-components")]
 pub mod mpu6050;
 #[cfg(feature = "ota")]
 pub mod ota;
+pub mod pose_tracker;
 pub mod power_sensor;
 pub mod registry;
 pub mod restart_monitor;
 pub mod robot;
 pub mod sensor;
+pub mod sensors_service;
 pub mod servo;
+#[cfg(feature = "builtin-components")]
+pub mod servo_arm;
+#[cfg(feature = "builtin-components")]
+pub mod servo_gripper;
 pub mod status;
+pub mod switch;
 #[cfg(feature = "builtin-components")]
 pub mod wheeled_base;
 pub mod webrtc {
@@ -77,8 +116,14 @@ pub mod webrtc {
     pub mod grpc;
     pub mod ice;
     pub mod io;
+    #[cfg(feature = "camera")]
+    pub mod media;
+    #[cfg(feature = "camera")]
+    pub mod rtp;
     pub mod sctp;
     pub mod signaling_server;
+    pub mod srtp;
+    pub mod turn;
     pub mod udp_mux;
 }
 pub mod conn {
@@ -89,10 +134,16 @@ pub mod conn {
     pub mod viam;
 }
 #[cfg(feature = "data")]
+pub mod aggregation;
+#[cfg(feature = "data")]
+pub mod capture_policy;
+#[cfg(feature = "data")]
 pub mod data_collector;
 #[cfg(feature = "data")]
 pub mod data_manager;
 #[cfg(feature = "data")]
 pub mod data_store;
+#[cfg(feature = "data")]
+pub mod persistent_data_store;
 
 pub mod provisioning;
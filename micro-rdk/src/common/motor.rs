@@ -102,6 +102,13 @@ pub trait Motor: Status + Actuator + DoCommand {
     /// This method will return an error if position reporting is not supported.
     fn get_position(&mut self) -> Result<i32, MotorError>;
 
+    /// Reports the position of the motor in revolutions relative to its zero position, motors
+    /// whose `get_position` counts encoder ticks or degrees convert it. The default assumes
+    /// `get_position` is already expressed in revolutions.
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        Ok(self.get_position()? as f64)
+    }
+
     /// Instructs the motor to turn at a specified speed, which is expressed in RPM,
     /// for a specified number of rotations relative to its starting position.
     /// This method will return an error if position reporting is not supported.
@@ -109,6 +116,28 @@ pub trait Motor: Status + Actuator + DoCommand {
     /// If revolutions != 0, this will block until the number of revolutions has been completed or another operation comes in.
     fn go_for(&mut self, rpm: f64, revolutions: f64) -> Result<Option<Duration>, MotorError>;
 
+    /// Instructs the motor to turn at a specified speed, which is expressed in RPM,
+    /// to a position expressed in revolutions relative to its zero position.
+    /// The default implementation computes the distance to travel from
+    /// `get_position_revolutions` and delegates to `go_for`, the sign of `rpm` is ignored. The
+    /// returned duration is the expected time to reach the position, the caller is responsible
+    /// for stopping the motor.
+    fn go_to(
+        &mut self,
+        rpm: f64,
+        position_revolutions: f64,
+    ) -> Result<Option<Duration>, MotorError> {
+        if !self.get_properties().position_reporting {
+            return Err(MotorError::MissingEncoder);
+        }
+        let revolutions = position_revolutions - self.get_position_revolutions()?;
+        if revolutions == 0.0 {
+            // go_for interprets 0 revolutions as "run indefinitely"
+            return Ok(Some(Duration::ZERO));
+        }
+        self.go_for(rpm.abs(), revolutions)
+    }
+
     // Instructs the motor to turn at the specified RPM. The default behavior is to call go_for
     // with 0.0 revolutions, so this functionality must be overwritten if the implementation of go_for
     // is different from the behavior described above (or if custom logic is required)
@@ -120,6 +149,12 @@ pub trait Motor: Status + Actuator + DoCommand {
     /// Returns an instance of MotorSupportedProperties indicating the optional properties
     /// supported by this motor
     fn get_properties(&mut self) -> MotorSupportedProperties;
+
+    /// Returns whether the motor is powered along with the percentage of power applied
+    /// to it, between `-1.0` and `1.0`
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        Err(MotorError::MotorMethodUnimplemented("is_powered"))
+    }
 }
 
 pub type MotorType = Arc<Mutex<dyn Motor>>;
@@ -252,18 +287,31 @@ where
     fn get_position(&mut self) -> Result<i32, MotorError> {
         self.get_mut().unwrap().get_position()
     }
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        self.get_mut().unwrap().get_position_revolutions()
+    }
     fn set_power(&mut self, pct: f64) -> Result<(), MotorError> {
         self.get_mut().unwrap().set_power(pct)
     }
     fn go_for(&mut self, rpm: f64, revolutions: f64) -> Result<Option<Duration>, MotorError> {
         self.get_mut().unwrap().go_for(rpm, revolutions)
     }
+    fn go_to(
+        &mut self,
+        rpm: f64,
+        position_revolutions: f64,
+    ) -> Result<Option<Duration>, MotorError> {
+        self.get_mut().unwrap().go_to(rpm, position_revolutions)
+    }
     fn set_rpm(&mut self, rpm: f64) -> Result<(), MotorError> {
         self.get_mut().unwrap().set_rpm(rpm)
     }
     fn get_properties(&mut self) -> MotorSupportedProperties {
         self.get_mut().unwrap().get_properties()
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        self.get_mut().unwrap().is_powered()
+    }
 }
 
 impl<A> Motor for Arc<Mutex<A>>
@@ -273,18 +321,31 @@ where
     fn get_position(&mut self) -> Result<i32, MotorError> {
         self.lock().unwrap().get_position()
     }
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        self.lock().unwrap().get_position_revolutions()
+    }
     fn set_power(&mut self, pct: f64) -> Result<(), MotorError> {
         self.lock().unwrap().set_power(pct)
     }
     fn go_for(&mut self, rpm: f64, revolutions: f64) -> Result<Option<Duration>, MotorError> {
         self.lock().unwrap().go_for(rpm, revolutions)
     }
+    fn go_to(
+        &mut self,
+        rpm: f64,
+        position_revolutions: f64,
+    ) -> Result<Option<Duration>, MotorError> {
+        self.lock().unwrap().go_to(rpm, position_revolutions)
+    }
     fn set_rpm(&mut self, rpm: f64) -> Result<(), MotorError> {
         self.lock().unwrap().set_rpm(rpm)
     }
     fn get_properties(&mut self) -> MotorSupportedProperties {
         self.lock().unwrap().get_properties()
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        self.lock().unwrap().is_powered()
+    }
 }
 
 #[cfg(feature = "builtin-components")]
@@ -292,6 +353,9 @@ impl Motor for FakeMotor {
     fn get_position(&mut self) -> Result<i32, MotorError> {
         Ok(self.pos as i32)
     }
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        Ok(self.pos)
+    }
     fn set_power(&mut self, pct: f64) -> Result<(), MotorError> {
         log::debug!("setting power to {}", pct);
         self.power = pct;
@@ -308,6 +372,9 @@ impl Motor for FakeMotor {
             position_reporting: true,
         }
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        Ok((self.power != 0.0, self.power))
+    }
 }
 
 #[cfg(feature = "builtin-components")]
@@ -338,7 +405,7 @@ impl Actuator for FakeMotor {
         self.set_power(0.0).map_err(|_| ActuatorError::CouldntStop)
     }
     fn is_moving(&mut self) -> Result<bool, ActuatorError> {
-        Ok(self.power > 0.0)
+        Ok(self.power != 0.0)
     }
 }
 
@@ -396,6 +463,12 @@ impl Motor for FakeMotorWithDependency {
             None => Ok(0),
         }
     }
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        match &self.encoder {
+            Some(enc) => Ok(enc.get_position(EncoderPositionType::DEGREES)?.value as f64 / 360.0),
+            None => Ok(0.0),
+        }
+    }
     fn set_power(&mut self, pct: f64) -> Result<(), MotorError> {
         log::debug!("setting power to {}", pct);
         self.power = pct;
@@ -409,6 +482,9 @@ impl Motor for FakeMotorWithDependency {
             position_reporting: true,
         }
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        Ok((self.power != 0.0, self.power))
+    }
 }
 
 #[cfg(feature = "builtin-components")]
@@ -426,7 +502,7 @@ impl Actuator for FakeMotorWithDependency {
         Ok(())
     }
     fn is_moving(&mut self) -> Result<bool, ActuatorError> {
-        Ok(self.power > 0.0)
+        Ok(self.power != 0.0)
     }
 }
 
@@ -435,8 +511,61 @@ impl Actuator for FakeMotorWithDependency {
 mod tests {
     use std::collections::HashMap;
 
+    use std::time::Duration;
+
     use crate::common::config::{Component, DynamicComponentConfig, Kind};
-    use crate::common::motor::{ConfigType, FakeMotor, MotorPinType, MotorPinsConfig};
+    use crate::common::encoder::{FakeEncoder, FakeIncrementalEncoder};
+    use crate::common::gpio_motor::EncodedMotor;
+    use crate::common::motor::{
+        ConfigType, FakeMotor, FakeMotorWithDependency, Motor, MotorPinType, MotorPinsConfig,
+    };
+
+    #[test_log::test]
+    fn test_motor_go_to() {
+        // FakeMotor starts at position 10 with a max rpm of 100
+        let mut motor = FakeMotor::new();
+
+        let dur = motor.go_to(60.0, 12.0);
+        assert!(dur.is_ok());
+        assert_eq!(dur.unwrap(), Some(Duration::from_secs(2)));
+        assert_eq!(motor.power, 0.6);
+
+        // rpm sign is ignored, direction comes from the target position
+        let dur = motor.go_to(-60.0, 7.0);
+        assert!(dur.is_ok());
+        assert_eq!(dur.unwrap(), Some(Duration::from_secs(3)));
+        assert_eq!(motor.power, -0.6);
+
+        let dur = motor.go_to(60.0, 10.0);
+        assert!(dur.is_ok());
+        assert_eq!(dur.unwrap(), Some(Duration::ZERO));
+
+        // errors from go_for are forwarded
+        let mut motor = FakeMotorWithDependency::new(None);
+        assert!(motor.go_to(60.0, 10.0).is_err());
+    }
+
+    #[test_log::test]
+    fn test_encoded_motor_go_to() {
+        // 250 ticks at 100 ticks per rotation puts the motor at 2.5 revolutions
+        let encoder = FakeIncrementalEncoder { ticks: 250.0 };
+        let mut motor = EncodedMotor::new(FakeMotor::new(), encoder, Some(100));
+        assert_eq!(motor.get_position_revolutions().unwrap(), 2.5);
+
+        // without ticks per rotation the angle of the encoder is used
+        let mut encoder = FakeEncoder::new();
+        encoder.angle_degrees = 90.0;
+        let mut unscaled = EncodedMotor::new(FakeMotor::new(), encoder, None);
+        assert_eq!(unscaled.get_position_revolutions().unwrap(), 0.25);
+
+        let dur = motor.go_to(60.0, 4.0);
+        assert_eq!(dur.unwrap(), Some(Duration::from_millis(1500)));
+        assert_eq!(motor.is_powered().unwrap(), (true, 0.6));
+
+        let dur = motor.go_to(60.0, 1.0);
+        assert_eq!(dur.unwrap(), Some(Duration::from_millis(1500)));
+        assert_eq!(motor.is_powered().unwrap(), (true, -0.6));
+    }
     #[test_log::test]
     fn test_motor_config() {
         let robot_config: [Option<DynamicComponentConfig>; 1] = [Some(DynamicComponentConfig {
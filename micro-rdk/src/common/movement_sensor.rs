@@ -12,7 +12,7 @@ use super::sensor::{GenericReadingsResult, Readings, SensorError};
 use super::status::Status;
 use crate::google;
 use crate::google::protobuf::{value::Kind, Struct, Value};
-use crate::proto::common::v1::GeoPoint;
+use crate::proto::common::v1::{GeoPoint, Orientation};
 use crate::proto::component::movement_sensor;
 
 use std::collections::HashMap;
@@ -105,13 +105,18 @@ impl From<GeoPosition> for movement_sensor::v1::GetPositionResponse {
 }
 
 // A trait for implementing a movement sensor component driver. TODO: add
-// get_orientation and get_accuracy if/when they become supportable.
+// get_accuracy if/when it becomes supportable.
 pub trait MovementSensor: Status + Readings + DoCommand {
     fn get_position(&mut self) -> Result<GeoPosition, SensorError>;
     fn get_linear_velocity(&mut self) -> Result<Vector3, SensorError>;
     fn get_angular_velocity(&mut self) -> Result<Vector3, SensorError>;
     fn get_linear_acceleration(&mut self) -> Result<Vector3, SensorError>;
     fn get_compass_heading(&mut self) -> Result<f64, SensorError>;
+    // Orientation as an orientation vector, theta is expressed in degrees. None of the
+    // builtin drivers can report it
+    fn get_orientation(&mut self) -> Result<Orientation, SensorError> {
+        Err(SensorError::SensorMethodUnimplemented("get_orientation"))
+    }
     fn get_properties(&self) -> MovementSensorSupportedMethods;
 }
 
@@ -291,6 +296,10 @@ where
         self.get_mut().unwrap().get_compass_heading()
     }
 
+    fn get_orientation(&mut self) -> Result<Orientation, SensorError> {
+        self.get_mut().unwrap().get_orientation()
+    }
+
     fn get_properties(&self) -> MovementSensorSupportedMethods {
         self.lock().unwrap().get_properties()
     }
@@ -320,6 +329,10 @@ where
         self.lock().unwrap().get_compass_heading()
     }
 
+    fn get_orientation(&mut self) -> Result<Orientation, SensorError> {
+        self.lock().unwrap().get_orientation()
+    }
+
     fn get_properties(&self) -> MovementSensorSupportedMethods {
         self.lock().unwrap().get_properties()
     }
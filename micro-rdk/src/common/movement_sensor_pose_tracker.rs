@@ -0,0 +1,244 @@
+//! A pose tracker reporting the pose of a single body carrying a movement sensor, e.g. a
+//! tracking board with a GPS and a compass.
+//!
+//! The position of the sensor is converted to millimeters east (x), north (y) and up (z) of
+//! an origin, which defaults to the first position read from the sensor. The orientation is
+//! the rotation around the z axis given by the compass heading of the sensor. Sensors that
+//! don't support one of those methods report 0 for it.
+//!
+//! ```json
+//! {
+//!   "name": "tracker",
+//!   "type": "pose_tracker",
+//!   "model": "movement_sensor",
+//!   "attributes": {
+//!     "movement_sensor": "gps",
+//!     "body_name": "rover",
+//!     "origin_lat": 40.7,
+//!     "origin_lon": -74.0,
+//!     "origin_alt_m": 10
+//!   }
+//! }
+//! ```
+
+use super::config::ConfigType;
+use super::movement_sensor::{
+    GeoPosition, MovementSensor, MovementSensorType, COMPONENT_NAME as MovementSensorCompName,
+};
+use super::pose_tracker::{
+    PoseTracker, PoseTrackerError, PoseTrackerType, COMPONENT_NAME as PoseTrackerCompName,
+};
+use super::registry::{ComponentRegistry, Dependency, ResourceKey};
+use super::robot::Resource;
+use super::status::{Status, StatusError};
+use crate::google;
+use crate::proto::common::v1::{Pose, PoseInFrame};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Mean radius of the earth in meters
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+/// Frame in which poses are reported when `reference_frame` isn't configured
+const DEFAULT_REFERENCE_FRAME: &str = "world";
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_pose_tracker(
+            "movement_sensor",
+            &MovementSensorPoseTracker::<MovementSensorType>::from_config,
+        )
+        .is_err()
+    {
+        log::error!("movement_sensor model is already registered")
+    }
+    if registry
+        .register_dependency_getter(
+            PoseTrackerCompName,
+            "movement_sensor",
+            &MovementSensorPoseTracker::<MovementSensorType>::dependencies_from_config,
+        )
+        .is_err()
+    {
+        log::error!("failed to register dependency getter for movement_sensor model")
+    }
+}
+
+#[derive(DoCommand)]
+pub struct MovementSensorPoseTracker<S> {
+    sensor: S,
+    body_name: String,
+    reference_frame: String,
+    origin: Option<GeoPosition>,
+}
+
+impl<S> MovementSensorPoseTracker<S>
+where
+    S: MovementSensor,
+{
+    pub fn new(sensor: S, body_name: String) -> Result<Self, PoseTrackerError> {
+        let props = sensor.get_properties();
+        if !props.position_supported && !props.compass_heading_supported {
+            return Err(PoseTrackerError::PoseTrackerConfigurationError(
+                "movement sensor must support position or compass heading",
+            ));
+        }
+        Ok(Self {
+            sensor,
+            body_name,
+            reference_frame: DEFAULT_REFERENCE_FRAME.to_string(),
+            origin: None,
+        })
+    }
+
+    /// Measures positions from `origin` instead of the first position read from the sensor
+    pub fn with_origin(mut self, origin: GeoPosition) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    pub fn with_reference_frame(mut self, reference_frame: String) -> Self {
+        self.reference_frame = reference_frame;
+        self
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<PoseTrackerType, PoseTrackerError> {
+        let sensor_name = cfg.get_attribute::<String>("movement_sensor")?;
+        let mut sensor: Option<MovementSensorType> = None;
+        for Dependency(key, res) in deps {
+            if let Resource::MovementSensor(found) = res {
+                if key.1 == sensor_name {
+                    sensor = Some(found);
+                }
+            }
+        }
+        let sensor = sensor.ok_or(PoseTrackerError::PoseTrackerConfigurationError(
+            "movement sensor couldn't be found",
+        ))?;
+        let body_name = cfg
+            .get_attribute::<String>("body_name")
+            .unwrap_or(sensor_name);
+        let mut tracker = MovementSensorPoseTracker::new(sensor, body_name)?;
+        if let Ok(reference_frame) = cfg.get_attribute::<String>("reference_frame") {
+            tracker = tracker.with_reference_frame(reference_frame);
+        }
+        match (
+            cfg.get_attribute::<f64>("origin_lat"),
+            cfg.get_attribute::<f64>("origin_lon"),
+        ) {
+            (Ok(lat), Ok(lon)) => {
+                let alt = cfg.get_attribute::<f32>("origin_alt_m").unwrap_or(0.0);
+                tracker = tracker.with_origin(GeoPosition { lat, lon, alt });
+            }
+            (Err(_), Err(_)) => {}
+            _ => {
+                return Err(PoseTrackerError::PoseTrackerConfigurationError(
+                    "origin_lat and origin_lon must be configured together",
+                ))
+            }
+        }
+        Ok(Arc::new(Mutex::new(tracker)))
+    }
+
+    pub(crate) fn dependencies_from_config(cfg: ConfigType) -> Vec<ResourceKey> {
+        let mut r_keys = Vec::new();
+        if let Ok(sensor_name) = cfg.get_attribute::<String>("movement_sensor") {
+            r_keys.push(ResourceKey::new(MovementSensorCompName, sensor_name));
+        }
+        r_keys
+    }
+
+    fn get_pose(&mut self) -> Result<Pose, PoseTrackerError> {
+        let props = self.sensor.get_properties();
+        let mut pose = Pose {
+            o_z: 1.0,
+            ..Default::default()
+        };
+        if props.position_supported {
+            let position = self.sensor.get_position()?;
+            let origin = *self.origin.get_or_insert(position);
+            // equirectangular approximation, accurate enough over the range of a robot
+            let lat = origin.lat.to_radians();
+            pose.x = (position.lon - origin.lon).to_radians() * lat.cos() * EARTH_RADIUS_M * 1000.0;
+            pose.y = (position.lat - origin.lat).to_radians() * EARTH_RADIUS_M * 1000.0;
+            pose.z = (position.alt - origin.alt) as f64 * 1000.0;
+        }
+        if props.compass_heading_supported {
+            // headings go clockwise from north while rotations around z go counterclockwise
+            pose.theta = -self.sensor.get_compass_heading()?;
+        }
+        Ok(pose)
+    }
+}
+
+impl<S> PoseTracker for MovementSensorPoseTracker<S>
+where
+    S: MovementSensor,
+{
+    fn get_poses(
+        &mut self,
+        body_names: &[String],
+    ) -> Result<HashMap<String, PoseInFrame>, PoseTrackerError> {
+        if let Some(name) = body_names.iter().find(|name| **name != self.body_name) {
+            return Err(PoseTrackerError::PoseTrackerBodyNotFound(name.clone()));
+        }
+        let pose = PoseInFrame {
+            reference_frame: self.reference_frame.clone(),
+            pose: Some(self.get_pose()?),
+        };
+        Ok(HashMap::from([(self.body_name.clone(), pose)]))
+    }
+}
+
+impl<S> Status for MovementSensorPoseTracker<S>
+where
+    S: MovementSensor,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        Ok(Some(google::protobuf::Struct {
+            fields: HashMap::new(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::movement_sensor::{FakeMovementSensor, GeoPosition};
+    use crate::common::movement_sensor_pose_tracker::MovementSensorPoseTracker;
+    use crate::common::pose_tracker::{PoseTracker, PoseTrackerError};
+
+    #[test_log::test]
+    fn test_movement_sensor_pose_tracker() -> Result<(), PoseTrackerError> {
+        // the fake sensor is at lat 27.33, lon 29.45
+        let mut tracker =
+            MovementSensorPoseTracker::new(FakeMovementSensor::new(), "rover".to_string())?;
+        let poses = tracker.get_poses(&[])?;
+        let pose = poses["rover"].pose.clone().unwrap();
+        assert_eq!(poses["rover"].reference_frame, "world");
+        assert_eq!((pose.x, pose.y, pose.z), (0.0, 0.0, 0.0));
+        assert_eq!((pose.o_z, pose.theta), (1.0, 0.0));
+
+        let mut tracker =
+            MovementSensorPoseTracker::new(FakeMovementSensor::new(), "rover".to_string())?
+                .with_origin(GeoPosition {
+                    lat: 27.32,
+                    lon: 29.45,
+                    alt: 4572.2,
+                });
+        let pose = tracker.get_poses(&["rover".to_string()])?["rover"]
+            .pose
+            .clone()
+            .unwrap();
+        // 0.01 degree of latitude is about 1112 meters
+        assert!(pose.x.abs() < 0.001);
+        assert!((pose.y - 1_111_949.0).abs() < 1.0);
+
+        assert!(matches!(
+            tracker.get_poses(&["boat".to_string()]),
+            Err(PoseTrackerError::PoseTrackerBodyNotFound(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,1028 @@
+//! A [DataStore] persisted to flash (ESP32) or to a file (native) so that collected data
+//! survives reboots.
+//!
+//! Each collector owns a region of whole sectors, up to a quota of sectors in proportion to the
+//! capacity requested by each collector. A region is used as a circular log:
+//! - every sector starts with a header carrying a tag identifying the owning collector and a
+//!   sequence number incremented each time a sector is (re)started, so the sectors of each
+//!   region and their order can be recovered after a reboot
+//! - a region grows into free sectors (erased or owned by a collector that no longer exists) up
+//!   to its quota, then reuses its oldest sector
+//! - records are appended after the header as `[state][length][crc32][message]`, a record is
+//!   never written across two sectors. A message larger than a sector (e.g. an image) is split
+//!   into fragments, one record each, flagged in their length as continuing the previous
+//!   record and/or continuing in the next one
+//! - reading a record never consumes it, flushing a reader clears the state byte of the records
+//!   it read (a 1 -> 0 bit transition that flash supports without an erase)
+//! - a sector is only erased when the log wraps around to it, sectors are therefore erased in
+//!   a round-robin fashion spreading wear evenly across the region
+//!
+//! Ownership is only recorded in the sector headers, so adding, removing or resizing collectors
+//! never moves the data of the others. A region left with more sectors than its new quota gives
+//! its oldest sectors back to the free sectors once their records are consumed (or immediately
+//! when writing with [WriteMode::OverwriteOldest]).
+//!
+//! A record interrupted by a power loss fails its crc check, the rest of its sector is then
+//! ignored and the next write starts a new sector. The fragments of a message missing some of
+//! its fragments (torn write, failed write or oldest sector reused) are never read and are
+//! marked as consumed by the next flush.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use bytes::BytesMut;
+use prost::Message;
+
+use super::{
+    data_collector::ResourceMethodKey,
+    data_store::{DataStore, DataStoreError, DataStoreReader, WriteMode},
+};
+use crate::proto::app::data_sync::v1::SensorData;
+
+/// Byte addressable storage organised in erasable sectors, the erased state of a byte is 0xFF
+pub trait DataStoreStorage {
+    /// Size in bytes of the smallest erasable unit
+    fn sector_size(&self) -> usize;
+    /// Total size in bytes of the storage
+    fn capacity(&self) -> usize;
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), DataStoreError>;
+    /// Writes `data` at `offset`, the area is expected to have been erased beforehand with the
+    /// exception of the state byte of a record
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), DataStoreError>;
+    /// Erases the sector starting at `offset`
+    fn erase_sector(&mut self, offset: usize) -> Result<(), DataStoreError>;
+    /// Opens the storage used when the store is built through
+    /// [DataStore::from_resource_method_settings]
+    fn open_default() -> Result<Self, DataStoreError>
+    where
+        Self: Sized;
+}
+
+#[cfg(feature = "native")]
+pub type DefaultPersistentDataStore =
+    PersistentDataStore<crate::native::data_storage::FileDataStorage>;
+#[cfg(feature = "esp32")]
+pub type DefaultPersistentDataStore =
+    PersistentDataStore<crate::esp32::data_storage::PartitionDataStorage>;
+
+const SECTOR_MAGIC: u32 = 0x5644_5331;
+// magic, tag, sequence, crc32 of the preceding fields
+const SECTOR_HEADER_LEN: usize = 16;
+// state, length, crc32 of the message
+const RECORD_HEADER_LEN: usize = 9;
+const RECORD_LIVE: u8 = 0xFF;
+const RECORD_CONSUMED: u8 = 0x00;
+const ERASED_LEN: u32 = u32::MAX;
+// the record continues the message of the previous record
+const RECORD_CONTINUATION: u32 = 1 << 31;
+// the message continues in the next record
+const RECORD_MORE: u32 = 1 << 30;
+const RECORD_LEN_MASK: u32 = RECORD_MORE - 1;
+const MIN_SECTORS_PER_REGION: usize = 2;
+
+type SharedStorage<S> = Rc<RefCell<S>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Record {
+    offset: usize,
+    len: usize,
+    live: bool,
+    continuation: bool,
+    more: bool,
+}
+
+impl Record {
+    fn end(&self) -> usize {
+        self.offset + RECORD_HEADER_LEN + self.len
+    }
+}
+
+struct Region {
+    tag: u32,
+    sector_size: usize,
+    // number of sectors the region grows to, it may own more after a change of the collectors
+    quota: usize,
+    // storage sectors owned by this region with their sequence number
+    sectors: Vec<(usize, u32)>,
+    write_sector: Option<usize>,
+    write_offset: usize,
+}
+
+impl Region {
+    fn mount<S: DataStoreStorage>(
+        storage: &mut S,
+        tag: u32,
+        sectors: Vec<(usize, u32)>,
+        quota: usize,
+    ) -> Result<Self, DataStoreError> {
+        let sector_size = storage.sector_size();
+        let mut region = Self {
+            tag,
+            sector_size,
+            quota,
+            sectors,
+            write_sector: None,
+            write_offset: 0,
+        };
+        region.write_sector = region
+            .sectors
+            .iter()
+            .max_by_key(|(_, seq)| *seq)
+            .map(|(sector, _)| *sector);
+        if let Some(sector) = region.write_sector {
+            let mut offset = SECTOR_HEADER_LEN;
+            let mut sealed = false;
+            loop {
+                match region.record_at(storage, sector, offset)? {
+                    RecordSlot::Record(record) => {
+                        offset = record.end() - region.sector_offset(sector)
+                    }
+                    RecordSlot::Erased => break,
+                    RecordSlot::Corrupted => {
+                        sealed = true;
+                        break;
+                    }
+                }
+            }
+            // nothing can be safely appended after a torn write, the next write will start a
+            // new sector
+            region.write_offset = if sealed { sector_size } else { offset };
+        }
+        Ok(region)
+    }
+
+    fn sector_offset(&self, sector: usize) -> usize {
+        sector * self.sector_size
+    }
+
+    // largest message written as a single record
+    fn max_record_len(&self) -> usize {
+        self.sector_size - SECTOR_HEADER_LEN - RECORD_HEADER_LEN
+    }
+
+    // largest message whose fragments fit in the region without reusing the sector of the first
+    // fragment, which starts part-way through a sector
+    fn max_message_len(&self) -> usize {
+        (self.quota - 1) * self.max_record_len()
+    }
+
+    /// Sectors holding data ordered from the oldest to the newest
+    fn ordered_sectors(&self) -> Vec<usize> {
+        let mut sectors = self.sectors.clone();
+        sectors.sort_by_key(|(_, seq)| *seq);
+        sectors.into_iter().map(|(sector, _)| sector).collect()
+    }
+
+    fn has_live_records<S: DataStoreStorage>(
+        &self,
+        storage: &mut S,
+        sector: usize,
+    ) -> Result<bool, DataStoreError> {
+        Ok(self
+            .sector_records(storage, sector)?
+            .iter()
+            .any(|record| record.live))
+    }
+
+    /// Gives the oldest sectors above the quota back to `free_sectors`, the sectors holding
+    /// records not consumed yet are only released when `write_mode` allows discarding data
+    fn release_excess_sectors<S: DataStoreStorage>(
+        &mut self,
+        storage: &mut S,
+        free_sectors: &mut Vec<usize>,
+        collector_key: &ResourceMethodKey,
+        write_mode: WriteMode,
+    ) -> Result<(), DataStoreError> {
+        while self.sectors.len() > self.quota {
+            // the quota is at least 2, the oldest sector is never the write sector
+            let oldest = self.ordered_sectors()[0];
+            if self.has_live_records(storage, oldest)? {
+                if !matches!(write_mode, WriteMode::OverwriteOldest) {
+                    break;
+                }
+                log::warn!(
+                    "persistent store region shrunk, discarding oldest data for {}",
+                    collector_key
+                );
+            }
+            self.sectors.retain(|(sector, _)| *sector != oldest);
+            free_sectors.push(oldest);
+        }
+        Ok(())
+    }
+
+    /// Picks the sector to start once the write sector is full: a free sector while the region
+    /// is below its quota, its oldest sector otherwise
+    fn next_sector<S: DataStoreStorage>(
+        &mut self,
+        storage: &mut S,
+        free_sectors: &mut Vec<usize>,
+        collector_key: &ResourceMethodKey,
+        write_mode: WriteMode,
+    ) -> Result<usize, DataStoreError> {
+        self.release_excess_sectors(storage, free_sectors, collector_key, write_mode)?;
+        if self.sectors.len() < self.quota && !free_sectors.is_empty() {
+            return Ok(free_sectors.remove(0));
+        }
+        // a region without sectors waits for other regions to release theirs
+        let oldest = *self
+            .ordered_sectors()
+            .first()
+            .ok_or_else(|| DataStoreError::DataBufferFull(collector_key.clone()))?;
+        if self.has_live_records(storage, oldest)? {
+            if !matches!(write_mode, WriteMode::OverwriteOldest) {
+                return Err(DataStoreError::DataBufferFull(collector_key.clone()));
+            }
+            log::warn!(
+                "persistent store full, discarding oldest data for {}",
+                collector_key
+            );
+        }
+        Ok(oldest)
+    }
+
+    fn record_at<S: DataStoreStorage>(
+        &self,
+        storage: &mut S,
+        sector: usize,
+        offset: usize,
+    ) -> Result<RecordSlot, DataStoreError> {
+        if offset + RECORD_HEADER_LEN > self.sector_size {
+            return Ok(RecordSlot::Erased);
+        }
+        let abs_offset = self.sector_offset(sector) + offset;
+        let mut header = [0_u8; RECORD_HEADER_LEN];
+        storage.read(abs_offset, &mut header)?;
+        let raw_len = u32::from_le_bytes(header[1..5].try_into().unwrap());
+        if raw_len == ERASED_LEN {
+            return Ok(RecordSlot::Erased);
+        }
+        let len = (raw_len & RECORD_LEN_MASK) as usize;
+        if offset + RECORD_HEADER_LEN + len > self.sector_size {
+            return Ok(RecordSlot::Corrupted);
+        }
+        let crc = u32::from_le_bytes(header[5..9].try_into().unwrap());
+        let mut message = vec![0_u8; len];
+        storage.read(abs_offset + RECORD_HEADER_LEN, &mut message)?;
+        if crc32fast::hash(&message) != crc {
+            return Ok(RecordSlot::Corrupted);
+        }
+        Ok(RecordSlot::Record(Record {
+            offset: abs_offset,
+            len,
+            live: header[0] == RECORD_LIVE,
+            continuation: raw_len & RECORD_CONTINUATION != 0,
+            more: raw_len & RECORD_MORE != 0,
+        }))
+    }
+
+    /// Returns the records of a sector, stopping at the first erased or corrupted slot
+    fn sector_records<S: DataStoreStorage>(
+        &self,
+        storage: &mut S,
+        sector: usize,
+    ) -> Result<Vec<Record>, DataStoreError> {
+        let mut records = vec![];
+        let mut offset = SECTOR_HEADER_LEN;
+        while let RecordSlot::Record(record) = self.record_at(storage, sector, offset)? {
+            offset = record.end() - self.sector_offset(sector);
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    fn start_sector<S: DataStoreStorage>(
+        &mut self,
+        storage: &mut S,
+        sector: usize,
+    ) -> Result<(), DataStoreError> {
+        let sequence = self
+            .sectors
+            .iter()
+            .map(|(_, seq)| *seq)
+            .max()
+            .map_or(0, |seq| seq.wrapping_add(1));
+        let offset = self.sector_offset(sector);
+        // nothing is written to the sector before it is erased: an erase or a header write
+        // interrupted by a power loss leaves either a header failing its crc check, the sector
+        // is then mounted as a free sector, or the previous header with some of its records,
+        // which are read up to the first damaged one
+        self.sectors.retain(|(owned, _)| *owned != sector);
+        storage.erase_sector(offset)?;
+        storage.write(offset, &sector_header(self.tag, sequence))?;
+        self.sectors.push((sector, sequence));
+        self.write_sector = Some(sector);
+        self.write_offset = SECTOR_HEADER_LEN;
+        Ok(())
+    }
+
+    fn append<S: DataStoreStorage>(
+        &mut self,
+        storage: &mut S,
+        collector_key: &ResourceMethodKey,
+        message: &[u8],
+        write_mode: WriteMode,
+        free_sectors: &mut Vec<usize>,
+    ) -> Result<(), DataStoreError> {
+        let mut remaining = message;
+        let mut flags = 0;
+        loop {
+            let room = match self.write_sector {
+                Some(_) => self.sector_size.saturating_sub(self.write_offset),
+                None => 0,
+            };
+            // a message fitting in a sector is never split, a larger one fills the write sector
+            let fits = RECORD_HEADER_LEN + remaining.len() <= room;
+            if !fits && (remaining.len() <= self.max_record_len() || room <= RECORD_HEADER_LEN) {
+                let next = self.next_sector(storage, free_sectors, collector_key, write_mode)?;
+                self.start_sector(storage, next)?;
+                continue;
+            }
+            let (fragment, rest) =
+                remaining.split_at(remaining.len().min(room - RECORD_HEADER_LEN));
+            if !rest.is_empty() {
+                flags |= RECORD_MORE;
+            }
+            self.write_record(storage, fragment, flags)?;
+            if rest.is_empty() {
+                return Ok(());
+            }
+            remaining = rest;
+            flags = RECORD_CONTINUATION;
+        }
+    }
+
+    fn write_record<S: DataStoreStorage>(
+        &mut self,
+        storage: &mut S,
+        message: &[u8],
+        flags: u32,
+    ) -> Result<(), DataStoreError> {
+        let record_len = RECORD_HEADER_LEN + message.len();
+        let mut record = Vec::with_capacity(record_len);
+        record.push(RECORD_LIVE);
+        record.extend_from_slice(&(message.len() as u32 | flags).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(message).to_le_bytes());
+        record.extend_from_slice(message);
+        // write_sector is always set at this point
+        let offset = self.sector_offset(self.write_sector.unwrap()) + self.write_offset;
+        storage.write(offset, &record)?;
+        self.write_offset += record_len;
+        Ok(())
+    }
+
+    /// Returns the records of each message not consumed yet, from the oldest to the newest, and
+    /// the live records of the messages that can't be read since some of their fragments are
+    /// missing or consumed
+    fn live_messages<S: DataStoreStorage>(
+        &self,
+        storage: &mut S,
+    ) -> Result<(Vec<Vec<Record>>, Vec<Record>), DataStoreError> {
+        let mut messages = vec![];
+        let mut unreadable = vec![];
+        let mut fragments: Vec<Record> = vec![];
+        for sector in self.ordered_sectors() {
+            for record in self.sector_records(storage, sector)? {
+                if !record.continuation {
+                    // the previous message never got its last fragment
+                    unreadable.append(&mut fragments);
+                } else if fragments.is_empty() {
+                    // the first fragments of the message were discarded
+                    unreadable.push(record);
+                    continue;
+                }
+                fragments.push(record);
+                if !record.more {
+                    if fragments.iter().all(|record| record.live) {
+                        messages.push(std::mem::take(&mut fragments));
+                    } else {
+                        unreadable.append(&mut fragments);
+                    }
+                }
+            }
+        }
+        unreadable.append(&mut fragments);
+        unreadable.retain(|record| record.live);
+        Ok((messages, unreadable))
+    }
+
+    fn live_records<S: DataStoreStorage>(
+        &self,
+        storage: &mut S,
+    ) -> Result<Vec<Record>, DataStoreError> {
+        let mut records = vec![];
+        for sector in self.ordered_sectors() {
+            records.extend(
+                self.sector_records(storage, sector)?
+                    .into_iter()
+                    .filter(|record| record.live),
+            );
+        }
+        Ok(records)
+    }
+}
+
+enum RecordSlot {
+    Record(Record),
+    Erased,
+    Corrupted,
+}
+
+fn sector_header(tag: u32, sequence: u32) -> [u8; SECTOR_HEADER_LEN] {
+    let mut header = [0_u8; SECTOR_HEADER_LEN];
+    header[0..4].copy_from_slice(&SECTOR_MAGIC.to_le_bytes());
+    header[4..8].copy_from_slice(&tag.to_le_bytes());
+    header[8..12].copy_from_slice(&sequence.to_le_bytes());
+    let crc = crc32fast::hash(&header[0..12]);
+    header[12..16].copy_from_slice(&crc.to_le_bytes());
+    header
+}
+
+// returns the tag and the sequence number of a valid header
+fn parse_sector_header(header: &[u8; SECTOR_HEADER_LEN]) -> Option<(u32, u32)> {
+    let field = |idx: usize| u32::from_le_bytes(header[idx..idx + 4].try_into().unwrap());
+    if field(0) != SECTOR_MAGIC || field(12) != crc32fast::hash(&header[0..12]) {
+        return None;
+    }
+    Some((field(4), field(8)))
+}
+
+fn region_tag(collector_key: &ResourceMethodKey) -> u32 {
+    crc32fast::hash(collector_key.to_string().as_bytes())
+}
+
+/// A [DataStore] keeping collected data in a [DataStoreStorage]. Like
+/// [DefaultDataStore](super::data_store::DefaultDataStore) it is not thread-safe and should only
+/// be initialized once.
+pub struct PersistentDataStore<S> {
+    storage: SharedStorage<S>,
+    regions: Vec<Rc<RefCell<Region>>>,
+    region_usages: Vec<Rc<AtomicBool>>,
+    collector_keys: Vec<ResourceMethodKey>,
+    // sectors not owned by any region, in the order they are handed out
+    free_sectors: Vec<usize>,
+}
+
+impl<S: DataStoreStorage> PersistentDataStore<S> {
+    /// Shares `storage` among the collectors in proportion to their requested capacity and
+    /// recovers the data they already own
+    pub fn new(
+        mut storage: S,
+        collector_settings: Vec<(ResourceMethodKey, usize)>,
+    ) -> Result<Self, DataStoreError> {
+        if collector_settings.is_empty() {
+            return Err(DataStoreError::NoCollectors);
+        }
+        let sector_size = storage.sector_size();
+        let total_sectors = storage.capacity() / sector_size;
+        if total_sectors < MIN_SECTORS_PER_REGION * collector_settings.len() {
+            return Err(DataStoreError::InsufficientStorage(
+                storage.capacity(),
+                collector_settings.len(),
+            ));
+        }
+        let spare_sectors = total_sectors - MIN_SECTORS_PER_REGION * collector_settings.len();
+        let total_requested: usize = collector_settings
+            .iter()
+            .map(|(_, capacity)| *capacity)
+            .sum::<usize>()
+            .max(1);
+
+        let tags: Vec<u32> = collector_settings
+            .iter()
+            .map(|(collector_key, _)| region_tag(collector_key))
+            .collect();
+        let mut owned_sectors: HashMap<u32, Vec<(usize, u32)>> = HashMap::new();
+        let mut free_sectors = vec![];
+        for sector in 0..total_sectors {
+            let mut header = [0_u8; SECTOR_HEADER_LEN];
+            storage.read(sector * sector_size, &mut header)?;
+            match parse_sector_header(&header) {
+                Some((tag, sequence)) if tags.contains(&tag) => owned_sectors
+                    .entry(tag)
+                    .or_default()
+                    .push((sector, sequence)),
+                _ => free_sectors.push(sector),
+            }
+        }
+
+        let mut regions = Vec::with_capacity(collector_settings.len());
+        let mut region_usages = Vec::with_capacity(collector_settings.len());
+        let mut collector_keys = Vec::with_capacity(collector_settings.len());
+        for ((collector_key, capacity), tag) in collector_settings.into_iter().zip(tags) {
+            let quota = MIN_SECTORS_PER_REGION + spare_sectors * capacity / total_requested;
+            let sectors = owned_sectors.remove(&tag).unwrap_or_default();
+            let mut region = Region::mount(&mut storage, tag, sectors, quota)?;
+            region.release_excess_sectors(
+                &mut storage,
+                &mut free_sectors,
+                &collector_key,
+                WriteMode::PreserveOrFail,
+            )?;
+            regions.push(Rc::new(RefCell::new(region)));
+            region_usages.push(Rc::new(AtomicBool::new(false)));
+            collector_keys.push(collector_key);
+        }
+        Ok(Self {
+            storage: Rc::new(RefCell::new(storage)),
+            regions,
+            region_usages,
+            collector_keys,
+            free_sectors,
+        })
+    }
+
+    fn get_index_for_collector(
+        &self,
+        collector_key: &ResourceMethodKey,
+    ) -> Result<usize, DataStoreError> {
+        self.collector_keys
+            .iter()
+            .position(|key| key == collector_key)
+            .ok_or(DataStoreError::UnknownCollectorKey(collector_key.clone()))
+    }
+}
+
+impl<S: DataStoreStorage> DataStore for PersistentDataStore<S> {
+    type Reader = PersistentDataStoreReader<S>;
+
+    fn write_message(
+        &mut self,
+        collector_key: &ResourceMethodKey,
+        message: SensorData,
+        write_mode: WriteMode,
+    ) -> Result<(), DataStoreError> {
+        let index = self.get_index_for_collector(collector_key)?;
+        if self.region_usages[index].load(Ordering::Relaxed) {
+            return Err(DataStoreError::BufferInUse(collector_key.clone()));
+        }
+        let mut region = self.regions[index].borrow_mut();
+        let encoded = message.encode_to_vec();
+        if encoded.len() > region.max_message_len() {
+            return Err(DataStoreError::DataTooLarge(
+                collector_key.clone(),
+                encoded.len(),
+                region.max_message_len(),
+            ));
+        }
+        region.append(
+            &mut *self.storage.borrow_mut(),
+            collector_key,
+            &encoded,
+            write_mode,
+            &mut self.free_sectors,
+        )
+    }
+
+    fn from_resource_method_settings(
+        settings: Vec<(ResourceMethodKey, usize)>,
+    ) -> Result<Self, DataStoreError> {
+        Self::new(S::open_default()?, settings)
+    }
+
+    fn get_reader(
+        &self,
+        collector_key: &ResourceMethodKey,
+    ) -> Result<PersistentDataStoreReader<S>, DataStoreError> {
+        let index = self.get_index_for_collector(collector_key)?;
+        if self.region_usages[index].load(Ordering::Relaxed) {
+            return Err(DataStoreError::BufferInUse(collector_key.clone()));
+        }
+        let (messages, unreadable) = self.regions[index]
+            .borrow()
+            .live_messages(&mut *self.storage.borrow_mut())?;
+        self.region_usages[index].store(true, Ordering::Relaxed);
+        Ok(PersistentDataStoreReader {
+            storage: self.storage.clone(),
+            messages,
+            unreadable,
+            read: 0,
+            region_usage: self.region_usages[index].clone(),
+        })
+    }
+    fn region_usage(
+        &self,
+        collector_key: &ResourceMethodKey,
+    ) -> Result<(usize, usize), DataStoreError> {
+        let region = self.regions[self.get_index_for_collector(collector_key)?].borrow();
+        let used = region
+            .live_records(&mut *self.storage.borrow_mut())?
+            .iter()
+            .map(|record| RECORD_HEADER_LEN + record.len)
+            .sum();
+        let sectors = region.quota.max(region.sectors.len());
+        Ok((used, sectors * region.sector_size))
+    }
+}
+
+/// Reader over the messages present in a region when it was created. Flushing marks the records
+/// of the messages read so far (and of the messages that can't be read) as consumed, dropping
+/// the reader without flushing leaves them in place.
+pub struct PersistentDataStoreReader<S> {
+    storage: SharedStorage<S>,
+    // records of each message, a single one unless the message was split
+    messages: Vec<Vec<Record>>,
+    unreadable: Vec<Record>,
+    read: usize,
+    region_usage: Rc<AtomicBool>,
+}
+
+impl<S: DataStoreStorage> DataStoreReader for PersistentDataStoreReader<S> {
+    fn read_next_message(&mut self) -> Result<BytesMut, DataStoreError> {
+        let Some(records) = self.messages.get(self.read) else {
+            return Ok(BytesMut::with_capacity(0));
+        };
+        let mut message = BytesMut::zeroed(records.iter().map(|record| record.len).sum());
+        let mut storage = self.storage.borrow_mut();
+        let mut start = 0;
+        for record in records {
+            storage.read(
+                record.offset + RECORD_HEADER_LEN,
+                &mut message[start..start + record.len],
+            )?;
+            start += record.len;
+        }
+        self.read += 1;
+        Ok(message)
+    }
+    fn messages_remaining(&self) -> Result<usize, DataStoreError> {
+        Ok(self.messages.len() - self.read)
+    }
+    fn flush(self) {
+        let mut storage = self.storage.borrow_mut();
+        let read = self.messages.iter().take(self.read).flatten();
+        for record in self.unreadable.iter().chain(read) {
+            if let Err(err) = storage.write(record.offset, &[RECORD_CONSUMED]) {
+                log::error!("failed to mark persisted data as consumed: {:?}", err);
+                break;
+            }
+        }
+    }
+}
+
+impl<S> Drop for PersistentDataStoreReader<S> {
+    fn drop(&mut self) {
+        self.region_usage.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use prost::Message;
+
+    use crate::common::data_collector::{CollectionMethod, ResourceMethodKey};
+    use crate::common::data_store::{DataStore, DataStoreError, DataStoreReader, WriteMode};
+    use crate::common::persistent_data_store::{DataStoreStorage, PersistentDataStore};
+    use crate::google::protobuf::{value::Kind, Struct, Value};
+    use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData};
+
+    const SECTOR_SIZE: usize = 256;
+
+    /// Emulates NOR flash, a write can only clear bits
+    #[derive(Clone)]
+    struct MemoryStorage(Vec<u8>);
+
+    impl DataStoreStorage for MemoryStorage {
+        fn sector_size(&self) -> usize {
+            SECTOR_SIZE
+        }
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+        fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), DataStoreError> {
+            buf.copy_from_slice(&self.0[offset..offset + buf.len()]);
+            Ok(())
+        }
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), DataStoreError> {
+            for (dst, src) in self.0[offset..offset + data.len()].iter_mut().zip(data) {
+                *dst &= *src;
+            }
+            Ok(())
+        }
+        fn erase_sector(&mut self, offset: usize) -> Result<(), DataStoreError> {
+            self.0[offset..offset + SECTOR_SIZE].fill(0xFF);
+            Ok(())
+        }
+        fn open_default() -> Result<Self, DataStoreError> {
+            Ok(Self(vec![0xFF; SECTOR_SIZE * 8]))
+        }
+    }
+
+    fn collector_key(name: &str) -> ResourceMethodKey {
+        ResourceMethodKey {
+            r_name: name.to_string(),
+            component_type: "rdk:component:sensor".to_string(),
+            method: CollectionMethod::Readings,
+        }
+    }
+
+    fn reading(value: f64) -> SensorData {
+        SensorData {
+            metadata: None,
+            data: Some(Data::Struct(Struct {
+                fields: HashMap::from([(
+                    "thing".to_string(),
+                    Value {
+                        kind: Some(Kind::NumberValue(value)),
+                    },
+                )]),
+            })),
+        }
+    }
+
+    fn read_all<R: DataStoreReader>(reader: &mut R) -> Vec<SensorData> {
+        let mut messages = vec![];
+        loop {
+            let msg = reader.read_next_message().unwrap();
+            if msg.is_empty() {
+                break;
+            }
+            messages.push(SensorData::decode(msg).unwrap());
+        }
+        messages
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_read_and_flush() {
+        let key_1 = collector_key("r1");
+        let key_2 = collector_key("r2");
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key_1.clone(), 100), (key_2.clone(), 100)],
+        )
+        .unwrap();
+
+        for i in 0..3 {
+            store
+                .write_message(&key_1, reading(i as f64), WriteMode::PreserveOrFail)
+                .unwrap();
+        }
+        store
+            .write_message(&key_2, reading(42.0), WriteMode::PreserveOrFail)
+            .unwrap();
+
+        let mut reader = store.get_reader(&key_1).unwrap();
+        assert!(matches!(
+            store.write_message(&key_1, reading(3.0), WriteMode::PreserveOrFail),
+            Err(DataStoreError::BufferInUse(_))
+        ));
+        assert!(store.get_reader(&key_1).is_err());
+        assert_eq!(reader.messages_remaining().unwrap(), 3);
+        let msg = SensorData::decode(reader.read_next_message().unwrap()).unwrap();
+        assert_eq!(msg, reading(0.0));
+        assert_eq!(reader.messages_remaining().unwrap(), 2);
+        // only the first message is consumed
+        reader.flush();
+
+        let mut reader = store.get_reader(&key_1).unwrap();
+        assert_eq!(read_all(&mut reader), vec![reading(1.0), reading(2.0)]);
+        // not flushing keeps the messages
+        drop(reader);
+        let mut reader = store.get_reader(&key_1).unwrap();
+        assert_eq!(reader.messages_remaining().unwrap(), 2);
+        read_all(&mut reader);
+        reader.flush();
+        let reader = store.get_reader(&key_1).unwrap();
+        assert_eq!(reader.messages_remaining().unwrap(), 0);
+        drop(reader);
+
+        let mut reader = store.get_reader(&key_2).unwrap();
+        assert_eq!(read_all(&mut reader), vec![reading(42.0)]);
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_survives_reopen() {
+        let key = collector_key("r1");
+        let mut storage = MemoryStorage::open_default().unwrap();
+        {
+            let mut store =
+                PersistentDataStore::new(storage.clone(), vec![(key.clone(), 100)]).unwrap();
+            for i in 0..20 {
+                store
+                    .write_message(&key, reading(i as f64), WriteMode::PreserveOrFail)
+                    .unwrap();
+            }
+            let mut reader = store.get_reader(&key).unwrap();
+            reader.read_next_message().unwrap();
+            reader.flush();
+            storage = store.storage.borrow().clone();
+        }
+        let mut store = PersistentDataStore::new(storage, vec![(key.clone(), 100)]).unwrap();
+        store
+            .write_message(&key, reading(20.0), WriteMode::PreserveOrFail)
+            .unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        let expected: Vec<SensorData> = (1..21).map(|i| reading(i as f64)).collect();
+        assert_eq!(read_all(&mut reader), expected);
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_collectors_change() {
+        let key_1 = collector_key("r1");
+        let key_2 = collector_key("r2");
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key_1.clone(), 100)],
+        )
+        .unwrap();
+        for i in 0..3 {
+            store
+                .write_message(&key_1, reading(i as f64), WriteMode::PreserveOrFail)
+                .unwrap();
+        }
+        let storage = store.storage.borrow().clone();
+        drop(store);
+
+        // a collector added in front of the existing one doesn't move its data
+        let mut store =
+            PersistentDataStore::new(storage, vec![(key_2.clone(), 100), (key_1.clone(), 100)])
+                .unwrap();
+        let mut written = 0;
+        loop {
+            match store.write_message(&key_2, reading(written as f64), WriteMode::PreserveOrFail) {
+                Ok(()) => written += 1,
+                Err(DataStoreError::DataBufferFull(_)) => break,
+                Err(err) => panic!("unexpected error {:?}", err),
+            }
+        }
+        assert!(written > 0);
+        assert_eq!(store.region_usage(&key_2).unwrap().1, 4 * SECTOR_SIZE);
+        let mut reader = store.get_reader(&key_1).unwrap();
+        let expected: Vec<SensorData> = (0..3).map(|i| reading(i as f64)).collect();
+        assert_eq!(read_all(&mut reader), expected);
+        reader.flush();
+        let storage = store.storage.borrow().clone();
+        drop(store);
+
+        // the sectors of a removed collector are reused, the others keep their data
+        let mut store = PersistentDataStore::new(storage, vec![(key_2.clone(), 100)]).unwrap();
+        store
+            .write_message(&key_2, reading(-1.0), WriteMode::PreserveOrFail)
+            .unwrap();
+        let mut reader = store.get_reader(&key_2).unwrap();
+        let messages = read_all(&mut reader);
+        assert_eq!(messages.len(), written + 1);
+        assert_eq!(messages.last(), Some(&reading(-1.0)));
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_torn_write() {
+        let key = collector_key("r1");
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key.clone(), 100)],
+        )
+        .unwrap();
+        for i in 0..2 {
+            store
+                .write_message(&key, reading(i as f64), WriteMode::PreserveOrFail)
+                .unwrap();
+        }
+        let mut storage = store.storage.borrow().clone();
+        drop(store);
+        // corrupt the payload of the second record as an interrupted write would
+        let record_len = super::RECORD_HEADER_LEN + reading(0.0).encoded_len();
+        let offset = super::SECTOR_HEADER_LEN + record_len + super::RECORD_HEADER_LEN;
+        storage.0[offset] = 0;
+
+        let mut store = PersistentDataStore::new(storage, vec![(key.clone(), 100)]).unwrap();
+        store
+            .write_message(&key, reading(2.0), WriteMode::PreserveOrFail)
+            .unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        assert_eq!(read_all(&mut reader), vec![reading(0.0), reading(2.0)]);
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_full() {
+        let key = collector_key("r1");
+        // a single collector gets every sector of the storage
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key.clone(), 100)],
+        )
+        .unwrap();
+        let mut written = 0;
+        loop {
+            match store.write_message(&key, reading(written as f64), WriteMode::PreserveOrFail) {
+                Ok(()) => written += 1,
+                Err(DataStoreError::DataBufferFull(_)) => break,
+                Err(err) => panic!("unexpected error {:?}", err),
+            }
+        }
+        let reader = store.get_reader(&key).unwrap();
+        assert_eq!(reader.messages_remaining().unwrap(), written);
+        drop(reader);
+
+        store
+            .write_message(&key, reading(-1.0), WriteMode::OverwriteOldest)
+            .unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        let messages = read_all(&mut reader);
+        assert!(messages.len() < written);
+        assert_eq!(messages.last(), Some(&reading(-1.0)));
+        assert_ne!(messages.first(), Some(&reading(0.0)));
+        reader.flush();
+
+        // once consumed, the space can be reclaimed without discarding data
+        for i in 0..written {
+            store
+                .write_message(&key, reading(i as f64), WriteMode::PreserveOrFail)
+                .unwrap();
+        }
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_too_large() {
+        let key = collector_key("r1");
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key.clone(), 100)],
+        )
+        .unwrap();
+        // larger than the whole region
+        let large = SensorData {
+            metadata: None,
+            data: Some(Data::Binary(vec![0; SECTOR_SIZE * 8])),
+        };
+        assert!(matches!(
+            store.write_message(&key, large, WriteMode::OverwriteOldest),
+            Err(DataStoreError::DataTooLarge(..))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_persistent_store_split_message() {
+        let key = collector_key("camera");
+        let image = |fill: u8| SensorData {
+            metadata: None,
+            data: Some(Data::Binary(vec![fill; SECTOR_SIZE * 3 / 2])),
+        };
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key.clone(), 100)],
+        )
+        .unwrap();
+        store
+            .write_message(&key, reading(0.0), WriteMode::OverwriteOldest)
+            .unwrap();
+        // each image spans more than one sector
+        for fill in 1..4 {
+            store
+                .write_message(&key, image(fill), WriteMode::OverwriteOldest)
+                .unwrap();
+        }
+        let mut storage = store.storage.borrow().clone();
+        drop(store);
+
+        let mut store =
+            PersistentDataStore::new(storage.clone(), vec![(key.clone(), 100)]).unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        assert_eq!(
+            read_all(&mut reader),
+            vec![reading(0.0), image(1), image(2), image(3)]
+        );
+        drop(reader);
+
+        // the region wraps around, the oldest image loses its first fragments
+        for fill in 4..7 {
+            store
+                .write_message(&key, image(fill), WriteMode::OverwriteOldest)
+                .unwrap();
+        }
+        let mut reader = store.get_reader(&key).unwrap();
+        let messages = read_all(&mut reader);
+        assert_eq!(messages.last(), Some(&image(6)));
+        assert!(messages.iter().all(|msg| msg.encoded_len() > SECTOR_SIZE));
+        reader.flush();
+        // the fragments that couldn't be read were consumed with the messages
+        assert_eq!(store.region_usage(&key).unwrap().0, 0);
+        drop(store);
+
+        // a power loss while writing the last fragment of an image
+        let mut store = PersistentDataStore::new(
+            MemoryStorage::open_default().unwrap(),
+            vec![(key.clone(), 100)],
+        )
+        .unwrap();
+        store
+            .write_message(&key, image(1), WriteMode::PreserveOrFail)
+            .unwrap();
+        storage = store.storage.borrow().clone();
+        drop(store);
+        let last_fragment = SECTOR_SIZE + super::SECTOR_HEADER_LEN + super::RECORD_HEADER_LEN;
+        storage.0[last_fragment] = 0;
+        let mut store = PersistentDataStore::new(storage, vec![(key.clone(), 100)]).unwrap();
+        store
+            .write_message(&key, image(2), WriteMode::PreserveOrFail)
+            .unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        assert_eq!(read_all(&mut reader), vec![image(2)]);
+    }
+}
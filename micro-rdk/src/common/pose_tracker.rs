@@ -0,0 +1,54 @@
+use super::{config::AttributeError, generic::DoCommand, sensor::SensorError, status::Status};
+use crate::proto::common::v1::PoseInFrame;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "pose_tracker";
+
+#[derive(Debug, Error)]
+pub enum PoseTrackerError {
+    #[error(transparent)]
+    PoseTrackerSensorError(#[from] SensorError),
+    #[error(transparent)]
+    PoseTrackerConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    PoseTrackerConfigurationError(&'static str),
+    #[error("body {0} isn't tracked")]
+    PoseTrackerBodyNotFound(String),
+}
+
+pub trait PoseTracker: Status + DoCommand {
+    /// Gets the pose of each body named in `body_names`, or of every tracked body if
+    /// `body_names` is empty
+    fn get_poses(
+        &mut self,
+        body_names: &[String],
+    ) -> Result<HashMap<String, PoseInFrame>, PoseTrackerError>;
+}
+
+pub type PoseTrackerType = Arc<Mutex<dyn PoseTracker>>;
+
+impl<L> PoseTracker for Mutex<L>
+where
+    L: ?Sized + PoseTracker,
+{
+    fn get_poses(
+        &mut self,
+        body_names: &[String],
+    ) -> Result<HashMap<String, PoseInFrame>, PoseTrackerError> {
+        self.get_mut().unwrap().get_poses(body_names)
+    }
+}
+
+impl<A> PoseTracker for Arc<Mutex<A>>
+where
+    A: ?Sized + PoseTracker,
+{
+    fn get_poses(
+        &mut self,
+        body_names: &[String],
+    ) -> Result<HashMap<String, PoseInFrame>, PoseTrackerError> {
+        self.lock().unwrap().get_poses(body_names)
+    }
+}
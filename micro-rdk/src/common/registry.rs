@@ -3,17 +3,24 @@ use std::collections::HashMap as Map;
 use thiserror::Error;
 
 use super::{
+    arm::{ArmError, ArmType},
     base::{BaseError, BaseType},
     board::{BoardError, BoardType},
+    button::{ButtonError, ButtonType},
     config::ConfigType,
     encoder::{EncoderError, EncoderType},
+    gantry::{GantryError, GantryType},
     generic::{GenericComponentType, GenericError},
+    gripper::{GripperError, GripperType},
+    input_controller::{InputControllerError, InputControllerType},
     motor::{MotorError, MotorType},
     movement_sensor::MovementSensorType,
+    pose_tracker::{PoseTrackerError, PoseTrackerType},
     power_sensor::PowerSensorType,
     robot::Resource,
     sensor::{SensorError, SensorType},
     servo::{ServoError, ServoType},
+    switch::{SwitchError, SwitchType},
 };
 
 #[cfg(feature = "camera")]
@@ -61,6 +68,13 @@ impl TryFrom<ResourceName> for ResourceKey {
     fn try_from(value: ResourceName) -> Result<Self, Self::Error> {
         let comp_type: &str = &value.subtype;
         let comp_name = match comp_type {
+            "arm" => crate::common::arm::COMPONENT_NAME,
+            "pose_tracker" => crate::common::pose_tracker::COMPONENT_NAME,
+            "input_controller" => crate::common::input_controller::COMPONENT_NAME,
+            "switch" => crate::common::switch::COMPONENT_NAME,
+            "button" => crate::common::button::COMPONENT_NAME,
+            "gantry" => crate::common::gantry::COMPONENT_NAME,
+            "gripper" => crate::common::gripper::COMPONENT_NAME,
             "motor" => crate::common::motor::COMPONENT_NAME,
             "sensor" => crate::common::sensor::COMPONENT_NAME,
             #[cfg(feature = "camera")]
@@ -107,6 +121,29 @@ type CameraConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<CameraTyp
 /// Fn that returns a `ServoType`, `Arc<Mutex<dyn Servo>>`
 type ServoConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<ServoType, ServoError>;
 
+/// Fn that returns an `ArmType`, `Arc<Mutex<dyn Arm>>`
+type ArmConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<ArmType, ArmError>;
+
+/// Fn that returns a `PoseTrackerType`, `Arc<Mutex<dyn PoseTracker>>`
+type PoseTrackerConstructor =
+    dyn Fn(ConfigType, Vec<Dependency>) -> Result<PoseTrackerType, PoseTrackerError>;
+
+/// Fn that returns an `InputControllerType`, `Arc<Mutex<dyn InputController>>`
+type InputControllerConstructor =
+    dyn Fn(ConfigType, Vec<Dependency>) -> Result<InputControllerType, InputControllerError>;
+
+/// Fn that returns a `SwitchType`, `Arc<Mutex<dyn Switch>>`
+type SwitchConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<SwitchType, SwitchError>;
+
+/// Fn that returns a `ButtonType`, `Arc<Mutex<dyn Button>>`
+type ButtonConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<ButtonType, ButtonError>;
+
+/// Fn that returns a `GantryType`, `Arc<Mutex<dyn Gantry>>`
+type GantryConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<GantryType, GantryError>;
+
+/// Fn that returns a `GripperType`, `Arc<Mutex<dyn Gripper>>`
+type GripperConstructor = dyn Fn(ConfigType, Vec<Dependency>) -> Result<GripperType, GripperError>;
+
 /// Fn that returns a `PowerSensorType`, `Arc<Mutex<dyn PowerSensor>>`
 type PowerSensorConstructor =
     dyn Fn(ConfigType, Vec<Dependency>) -> Result<PowerSensorType, SensorError>;
@@ -128,6 +165,13 @@ pub struct ComponentRegistry {
     encoders: Map<String, &'static EncoderConstructor>,
     bases: Map<String, &'static BaseConstructor>,
     servos: Map<String, &'static ServoConstructor>,
+    arms: Map<String, &'static ArmConstructor>,
+    pose_trackers: Map<String, &'static PoseTrackerConstructor>,
+    input_controllers: Map<String, &'static InputControllerConstructor>,
+    switches: Map<String, &'static SwitchConstructor>,
+    buttons: Map<String, &'static ButtonConstructor>,
+    gantries: Map<String, &'static GantryConstructor>,
+    grippers: Map<String, &'static GripperConstructor>,
     power_sensors: Map<String, &'static PowerSensorConstructor>,
     generic_components: Map<String, &'static GenericComponentConstructor>,
     dependencies: Map<String, Map<String, &'static DependenciesFromConfig>>,
@@ -150,6 +194,13 @@ impl Default for ComponentRegistry {
             crate::common::generic::register_models(&mut r);
             crate::common::ina::register_models(&mut r);
             crate::common::wheeled_base::register_models(&mut r);
+            crate::common::servo_arm::register_models(&mut r);
+            crate::common::movement_sensor_pose_tracker::register_models(&mut r);
+            crate::common::gpio_input_controller::register_models(&mut r);
+            crate::common::gpio_switch::register_models(&mut r);
+            crate::common::gpio_button::register_models(&mut r);
+            crate::common::gpio_gantry::register_models(&mut r);
+            crate::common::servo_gripper::register_models(&mut r);
             #[cfg(feature = "camera")]
             crate::common::camera::register_models(&mut r);
         }
@@ -182,6 +233,19 @@ impl ComponentRegistry {
         #[cfg(feature = "camera")]
         dependency_func_map.insert(crate::common::camera::COMPONENT_NAME.into(), Map::new());
         dependency_func_map.insert(crate::common::servo::COMPONENT_NAME.into(), Map::new());
+        dependency_func_map.insert(crate::common::arm::COMPONENT_NAME.into(), Map::new());
+        dependency_func_map.insert(
+            crate::common::pose_tracker::COMPONENT_NAME.into(),
+            Map::new(),
+        );
+        dependency_func_map.insert(
+            crate::common::input_controller::COMPONENT_NAME.into(),
+            Map::new(),
+        );
+        dependency_func_map.insert(crate::common::switch::COMPONENT_NAME.into(), Map::new());
+        dependency_func_map.insert(crate::common::button::COMPONENT_NAME.into(), Map::new());
+        dependency_func_map.insert(crate::common::gantry::COMPONENT_NAME.into(), Map::new());
+        dependency_func_map.insert(crate::common::gripper::COMPONENT_NAME.into(), Map::new());
         dependency_func_map.insert(
             crate::common::power_sensor::COMPONENT_NAME.into(),
             Map::new(),
@@ -197,6 +261,13 @@ impl ComponentRegistry {
             encoders: Map::new(),
             bases: Map::new(),
             servos: Map::new(),
+            arms: Map::new(),
+            pose_trackers: Map::new(),
+            input_controllers: Map::new(),
+            switches: Map::new(),
+            buttons: Map::new(),
+            gantries: Map::new(),
+            grippers: Map::new(),
             power_sensors: Map::new(),
             generic_components: Map::new(),
             dependencies: dependency_func_map,
@@ -319,6 +390,97 @@ impl ComponentRegistry {
         Ok(())
     }
 
+    pub fn register_arm(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static ArmConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.arms.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.arms.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_gripper(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static GripperConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.grippers.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.grippers.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_gantry(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static GantryConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.gantries.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.gantries.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_button(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static ButtonConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.buttons.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.buttons.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_switch(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static SwitchConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.switches.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.switches.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_input_controller(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static InputControllerConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.input_controllers.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.input_controllers.insert(model, constructor);
+        Ok(())
+    }
+
+    pub fn register_pose_tracker(
+        &mut self,
+        model: impl Into<String>,
+        constructor: &'static PoseTrackerConstructor,
+    ) -> Result<(), RegistryError> {
+        let model = model.into();
+        if self.pose_trackers.contains_key(&model) {
+            return Err(RegistryError::ModelAlreadyRegistered(model));
+        }
+        let _ = self.pose_trackers.insert(model, constructor);
+        Ok(())
+    }
+
     pub fn register_generic_component(
         &mut self,
         model: impl Into<String>,
@@ -463,6 +625,76 @@ impl ComponentRegistry {
         Err(RegistryError::ModelNotFound(model.to_string()))
     }
 
+    pub(crate) fn get_arm_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static ArmConstructor, RegistryError> {
+        if let Some(ctor) = self.arms.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_gripper_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static GripperConstructor, RegistryError> {
+        if let Some(ctor) = self.grippers.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_gantry_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static GantryConstructor, RegistryError> {
+        if let Some(ctor) = self.gantries.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_button_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static ButtonConstructor, RegistryError> {
+        if let Some(ctor) = self.buttons.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_switch_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static SwitchConstructor, RegistryError> {
+        if let Some(ctor) = self.switches.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_input_controller_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static InputControllerConstructor, RegistryError> {
+        if let Some(ctor) = self.input_controllers.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
+    pub(crate) fn get_pose_tracker_constructor(
+        &self,
+        model: &str,
+    ) -> Result<&'static PoseTrackerConstructor, RegistryError> {
+        if let Some(ctor) = self.pose_trackers.get(model) {
+            return Ok(*ctor);
+        }
+        Err(RegistryError::ModelNotFound(model.to_string()))
+    }
+
     pub(crate) fn get_generic_component_constructor(
         &self,
         model: &str,
@@ -1,12 +1,13 @@
 #![allow(dead_code)]
 
 use async_executor::Task;
+use async_io::Timer;
 
 use chrono::{DateTime, FixedOffset};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 #[cfg(feature = "camera")]
@@ -19,7 +20,7 @@ use crate::{
     },
     google,
     proto::{
-        app::v1::RobotConfig,
+        app::v1::{ComponentConfig, RobotConfig},
         common::{self, v1::ResourceName},
         robot,
     },
@@ -28,29 +29,42 @@ use log::*;
 
 #[cfg(feature = "data")]
 use super::{
+    capture_policy::CaptureTriggers,
     data_collector::{DataCollectionError, DataCollector, DataCollectorConfig},
-    data_manager::DataManager,
-    data_store::DefaultDataStore,
+    data_manager::{CaptureSources, DataManager, DataManagerError, DataManagerService},
+    data_store::{DataStore, DefaultDataStore},
+};
+#[cfg(all(feature = "data", any(feature = "native", feature = "esp32")))]
+use super::{
+    data_manager::uses_persistent_store, persistent_data_store::DefaultPersistentDataStore,
 };
 
 use super::{
     actuator::ActuatorError,
     app_client::PeriodicAppClientTask,
+    arm::{Arm, ArmType},
     base::BaseType,
     board::BoardType,
+    button::{Button, ButtonType},
     config::{AttributeError, Component, ConfigType, DynamicComponentConfig},
     encoder::EncoderType,
     exec::Executor,
+    gantry::{Gantry, GantryType},
     generic::{GenericComponent, GenericComponentType},
+    gripper::{Gripper, GripperType},
+    input_controller::{InputController, InputControllerType},
     motor::MotorType,
     movement_sensor::MovementSensorType,
+    pose_tracker::{PoseTracker, PoseTrackerType},
     power_sensor::{PowerSensor, PowerSensorType},
     registry::{
         get_board_from_dependencies, ComponentRegistry, Dependency, RegistryError, ResourceKey,
     },
     sensor::SensorType,
+    sensors_service::{is_sensors_service, SensorsService, SensorsServiceType},
     servo::{Servo, ServoType},
     status::StatusError,
+    switch::{Switch, SwitchType},
 };
 
 use thiserror::Error;
@@ -67,6 +81,13 @@ pub enum ResourceType {
     Encoder(EncoderType),
     PowerSensor(PowerSensorType),
     Servo(ServoType),
+    Arm(ArmType),
+    PoseTracker(PoseTrackerType),
+    InputController(InputControllerType),
+    Switch(SwitchType),
+    Button(ButtonType),
+    Gantry(GantryType),
+    Gripper(GripperType),
     Generic(GenericComponentType),
     #[cfg(feature = "camera")]
     Camera(CameraType),
@@ -77,6 +98,13 @@ pub type ResourceMap = HashMap<ResourceName, Resource>;
 impl ResourceType {
     pub fn component_type(&self) -> String {
         match self {
+            Self::Arm(_) => "rdk:component:arm",
+            Self::PoseTracker(_) => "rdk:component:pose_tracker",
+            Self::InputController(_) => "rdk:component:input_controller",
+            Self::Switch(_) => "rdk:component:switch",
+            Self::Button(_) => "rdk:component:button",
+            Self::Gantry(_) => "rdk:component:gantry",
+            Self::Gripper(_) => "rdk:component:gripper",
             Self::Base(_) => "rdk:component:base",
             Self::Board(_) => "rdk:component:board",
             Self::Encoder(_) => "rdk:component:encoder",
@@ -93,6 +121,33 @@ impl ResourceType {
     }
 }
 
+/// A long running operation (e.g. a motor moving for a number of revolutions) started on a
+/// resource with [`LocalRobot::start_operation`]. The operation is cancelled by
+/// [`LocalRobot::cancel_operation`] or when another operation is started on the same resource.
+pub struct Operation(async_channel::Receiver<()>);
+
+impl Operation {
+    /// Waits for `duration` to elapse, returns false if the operation was cancelled first.
+    pub async fn wait_for(&self, duration: Duration) -> bool {
+        futures_lite::future::or(
+            async {
+                Timer::after(duration).await;
+                true
+            },
+            async {
+                // nothing is ever sent on the channel, recv only returns once it is closed
+                let _ = self.0.recv().await;
+                false
+            },
+        )
+        .await
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CloudMetadata {
     org_id: String,
@@ -104,9 +159,17 @@ pub struct LocalRobot {
     pub(crate) part_id: String,
     resources: ResourceMap,
     build_time: Option<DateTime<FixedOffset>>,
+    // Revision of the configuration the robot was built from, empty when unknown
+    config_revision: String,
     executor: Executor,
     #[cfg(feature = "data")]
     data_collector_configs: Vec<(ResourceName, DataCollectorConfig)>,
+    #[cfg(feature = "data")]
+    capture_triggers: CaptureTriggers,
+    #[cfg(feature = "data")]
+    data_manager_service: Option<DataManagerService>,
+    #[cfg(feature = "data")]
+    data_manager_capture_updates: Option<async_channel::Sender<CaptureSources>>,
     data_manager_sync_task: Option<Box<dyn PeriodicAppClientTask>>,
     data_manager_collection_task: Option<Task<()>>,
     // Used for time correcting stored data before upload, see DataSyncTask::run. WARNING: This
@@ -114,6 +177,8 @@ pub struct LocalRobot {
     // at some point using settimeofday (or something equivalent) and referenced thereof.
     pub(crate) start_time: Instant,
     cloud_metadata: Option<CloudMetadata>,
+    operations: HashMap<ResourceKey, async_channel::Sender<()>>,
+    sensors_services: HashMap<String, SensorsServiceType>,
 }
 
 #[derive(Error, Debug)]
@@ -140,11 +205,25 @@ pub enum RobotError {
     ResourceNotFound(String, String),
     #[error("missing cloud metadata")]
     RobotMissingCloudMetadata,
+    #[error("configuration change requires a restart: {0}")]
+    RobotReconfigureRequiresRestart(String),
     #[cfg(feature = "data")]
     #[error(transparent)]
     DataCollectorInitError(#[from] DataCollectionError),
 }
 
+// Stops the resource if it is an actuator
+fn stop_resource(resource: &mut ResourceType) -> Result<(), ActuatorError> {
+    match resource {
+        ResourceType::Base(b) => b.stop(),
+        ResourceType::Motor(m) => m.stop(),
+        ResourceType::Arm(a) => a.stop(),
+        ResourceType::Gantry(a) => a.stop(),
+        ResourceType::Gripper(a) => a.stop(),
+        _ => Ok(()),
+    }
+}
+
 fn resource_name_from_component_cfg(cfg: &DynamicComponentConfig) -> ResourceName {
     ResourceName {
         namespace: cfg.namespace.to_string(),
@@ -185,10 +264,19 @@ impl LocalRobot {
             cloud_metadata: None,
             resources: Default::default(),
             build_time: Default::default(),
+            config_revision: Default::default(),
             data_manager_collection_task: Default::default(),
             data_manager_sync_task: Default::default(),
             #[cfg(feature = "data")]
             data_collector_configs: Default::default(),
+            #[cfg(feature = "data")]
+            capture_triggers: Default::default(),
+            #[cfg(feature = "data")]
+            data_manager_service: None,
+            #[cfg(feature = "data")]
+            data_manager_capture_updates: None,
+            operations: Default::default(),
+            sensors_services: Default::default(),
         }
     }
     // Inserts components in order of dependency. If a component's dependencies are not satisfied it is
@@ -218,6 +306,19 @@ impl LocalRobot {
         } else {
             (None, None)
         };
+        self.build_resources(components, board, board_key, registry);
+        Ok(())
+    }
+
+    // Builds components in no particular order, a component whose dependencies are not built
+    // yet is retried later.
+    fn build_resources(
+        &mut self,
+        mut components: Vec<Option<DynamicComponentConfig>>,
+        board: Option<BoardType>,
+        board_key: Option<ResourceKey>,
+        registry: &mut ComponentRegistry,
+    ) {
         let mut resource_to_build = components.len();
         let max_iteration = resource_to_build * 2;
         let mut num_iteration = 0;
@@ -251,7 +352,6 @@ impl LocalRobot {
                     .collect::<Vec<String>>()
             )
         }
-        Ok(())
     }
 
     // Creates a robot from the response of a gRPC call to acquire the robot configuration. The individual
@@ -276,12 +376,21 @@ impl LocalRobot {
             // Use date time pulled off gRPC header as the `build_time` returned in the status of
             // every resource as `last_reconfigured`.
             build_time,
+            config_revision: config.revision.clone(),
 
             #[cfg(feature = "data")]
             data_collector_configs: vec![],
+            #[cfg(feature = "data")]
+            capture_triggers: Default::default(),
+            #[cfg(feature = "data")]
+            data_manager_service: None,
+            #[cfg(feature = "data")]
+            data_manager_capture_updates: None,
             data_manager_sync_task: None,
             data_manager_collection_task: None,
             start_time: Instant::now(),
+            operations: HashMap::new(),
+            sensors_services: HashMap::new(),
         };
 
         let components: Result<Vec<Option<DynamicComponentConfig>>, AttributeError> = config
@@ -294,32 +403,305 @@ impl LocalRobot {
             registry,
         )?;
 
+        for svc_cfg in config.services.iter().filter(|cfg| is_sensors_service(cfg)) {
+            let service = SensorsService::new(&robot.resources);
+            let _ = robot
+                .sensors_services
+                .insert(svc_cfg.name.clone(), Arc::new(Mutex::new(service)));
+        }
+
         // TODO: When cfg's on expressions are valid, remove the outer scope.
         #[cfg(feature = "data")]
         {
-            // TODO(RSDK-8125): Support selection of the DataStore trait other than
-            // DefaultDataStore in a way that is configurable
-            match DataManager::<DefaultDataStore>::from_robot_and_config(&robot, config) {
-                Ok(Some(mut data_manager)) => {
-                    if let Some(task) = data_manager.get_sync_task(robot.start_time) {
-                        let _ = robot.data_manager_sync_task.insert(Box::new(task));
+            #[cfg(any(feature = "native", feature = "esp32"))]
+            let res = if uses_persistent_store(config) {
+                match robot.start_data_manager::<DefaultPersistentDataStore>(config) {
+                    // e.g. the data partition is missing from the partition table, data is
+                    // still captured but lost on reboot
+                    Err(DataManagerError::StoreError(err)) => {
+                        log::error!(
+                            "persistent data store unavailable ({:?}), buffering data in RAM",
+                            err
+                        );
+                        robot.start_data_manager::<DefaultDataStore>(config)
                     }
-                    let _ = robot
-                        .data_manager_collection_task
-                        .replace(robot.executor.spawn(async move {
-                            data_manager.data_collection_task(robot.start_time).await;
-                        }));
-                }
-                Ok(None) => {}
-                Err(err) => {
-                    log::error!("Error configuring data management: {:?}", err);
+                    res => res,
                 }
+            } else {
+                robot.start_data_manager::<DefaultDataStore>(config)
             };
+            #[cfg(not(any(feature = "native", feature = "esp32")))]
+            let res = robot.start_data_manager::<DefaultDataStore>(config);
+            if let Err(err) = res {
+                log::error!("Error configuring data management: {:?}", err);
+            }
         }
 
         Ok(robot)
     }
 
+    #[cfg(feature = "data")]
+    fn start_data_manager<StoreType: DataStore + 'static>(
+        &mut self,
+        config: &RobotConfig,
+    ) -> Result<(), DataManagerError> {
+        if let Some(mut data_manager) =
+            DataManager::<StoreType>::from_robot_and_config(self, config)?
+        {
+            if let Some(task) = data_manager.get_sync_task(self.start_time) {
+                let _ = self.data_manager_sync_task.insert(Box::new(task));
+            }
+            let _ = self
+                .data_manager_service
+                .insert(data_manager.get_service(self.start_time, self.capture_triggers.clone()));
+            let _ = self
+                .data_manager_capture_updates
+                .insert(data_manager.capture_updates());
+            let start_time = self.start_time;
+            let _ = self
+                .data_manager_collection_task
+                .replace(self.executor.spawn(async move {
+                    data_manager.data_collection_task(start_time).await;
+                }));
+        }
+        Ok(())
+    }
+
+    /// Applies `new_config` to a robot built from `old_config` while it keeps running. Removed
+    /// components are stopped and dropped, added and changed components as well as the
+    /// components depending on them are built, every other resource is left untouched. The
+    /// sensors services and the data collectors are rebuilt against the new resources.
+    /// Changes that cannot be applied in place (board, cloud or network settings, other services
+    /// and data collectors capturing different methods or capacities, as the data store is laid
+    /// out for them) return `RobotError::RobotReconfigureRequiresRestart`, the robot is not
+    /// modified in that case unless the data manager fails to capture from the rebuilt
+    /// components.
+    pub fn reconfigure(
+        &mut self,
+        old_config: &RobotConfig,
+        new_config: &RobotConfig,
+        registry: &mut Box<ComponentRegistry>,
+        build_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), RobotError> {
+        // the cloud connection and the data manager are set up when the robot starts
+        let other_services = |cfg: &RobotConfig| {
+            cfg.services
+                .iter()
+                .filter(|svc| !is_sensors_service(svc))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        if old_config.cloud != new_config.cloud
+            || old_config.auth != new_config.auth
+            || old_config.network != new_config.network
+            || other_services(old_config) != other_services(new_config)
+        {
+            return Err(RobotError::RobotReconfigureRequiresRestart(
+                "cloud, network or services configuration changed".to_string(),
+            ));
+        }
+
+        let old_components: HashMap<&str, &ComponentConfig> = old_config
+            .components
+            .iter()
+            .map(|cfg| (cfg.name.as_str(), cfg))
+            .collect();
+        let old_dynamic = old_config
+            .components
+            .iter()
+            .map(DynamicComponentConfig::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // removed and changed components, then the components depending on them
+        let mut stale: HashSet<String> = new_config
+            .components
+            .iter()
+            .filter(|cfg| {
+                old_components
+                    .get(cfg.name.as_str())
+                    .is_some_and(|old| old != cfg)
+            })
+            .map(|cfg| cfg.name.clone())
+            .collect();
+        stale.extend(
+            old_config
+                .components
+                .iter()
+                .filter(|cfg| !new_config.components.iter().any(|c| c.name == cfg.name))
+                .map(|cfg| cfg.name.clone()),
+        );
+        loop {
+            let dependents: Vec<String> = old_dynamic
+                .iter()
+                .filter(|cfg| !stale.contains(&cfg.name))
+                .filter(|cfg| {
+                    Self::get_config_dependency_names(cfg, registry)
+                        .iter()
+                        .any(|dep| stale.contains(dep))
+                })
+                .map(|cfg| cfg.name.clone())
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+            stale.extend(dependents);
+        }
+
+        let to_build = new_config
+            .components
+            .iter()
+            .filter(|cfg| {
+                stale.contains(&cfg.name) || !old_components.contains_key(cfg.name.as_str())
+            })
+            .map(DynamicComponentConfig::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let to_drop: Vec<&DynamicComponentConfig> = old_dynamic
+            .iter()
+            .filter(|cfg| stale.contains(&cfg.name))
+            .collect();
+
+        // every resource depends on the board
+        if to_drop
+            .iter()
+            .copied()
+            .chain(to_build.iter())
+            .any(|cfg| cfg.r#type == "board")
+        {
+            return Err(RobotError::RobotReconfigureRequiresRestart(
+                "board configuration changed".to_string(),
+            ));
+        }
+        // the data store keeps a region per collector, rebuilt components must keep capturing
+        // the same methods with the same capacities
+        #[cfg(feature = "data")]
+        {
+            let store_layout = |cfg: &DynamicComponentConfig| {
+                let collectors: Vec<_> = cfg
+                    .data_collector_configs
+                    .iter()
+                    .filter(|conf| !conf.disabled)
+                    .map(|conf| (conf.method.clone(), conf.capacity))
+                    .collect();
+                (!collectors.is_empty())
+                    .then(|| (cfg.name.clone(), (cfg.r#type.clone(), collectors)))
+            };
+            let old_layout: HashMap<_, _> =
+                to_drop.iter().copied().filter_map(store_layout).collect();
+            let new_layout: HashMap<_, _> = to_build.iter().filter_map(store_layout).collect();
+            if old_layout != new_layout {
+                return Err(RobotError::RobotReconfigureRequiresRestart(
+                    "data collectors changed".to_string(),
+                ));
+            }
+        }
+        #[cfg(feature = "data")]
+        let rebuilt = !to_drop.is_empty() || !to_build.is_empty();
+
+        for cfg in to_drop {
+            self.cancel_operation(&ResourceKey::new(cfg.r#type.clone(), cfg.name.clone()));
+            let name = resource_name_from_component_cfg(cfg);
+            // collectors of the rebuilt component are added back when it is built
+            #[cfg(feature = "data")]
+            self.data_collector_configs
+                .retain(|(r_name, _)| *r_name != name);
+            if let Some(mut resource) = self.resources.remove(&name) {
+                if let Err(err) = stop_resource(&mut resource) {
+                    log::warn!("failed to stop removed resource `{}`: {:?}", cfg.name, err);
+                }
+            }
+        }
+
+        let (board, board_key) = self
+            .resources
+            .iter()
+            .find_map(|(name, resource)| match resource {
+                ResourceType::Board(board) => Some((
+                    Some(board.clone()),
+                    Some(ResourceKey::new(
+                        crate::common::board::COMPONENT_NAME,
+                        &name.name,
+                    )),
+                )),
+                _ => None,
+            })
+            .unwrap_or((None, None));
+        log::info!(
+            "reconfiguring machine: {} resources dropped, {} resources to build",
+            stale.len(),
+            to_build.len()
+        );
+        self.build_resources(
+            to_build.into_iter().map(Some).collect(),
+            board,
+            board_key,
+            registry,
+        );
+
+        // sensors services only know about the sensors that existed when they were created
+        self.sensors_services.retain(|name, _| {
+            new_config
+                .services
+                .iter()
+                .any(|svc| is_sensors_service(svc) && svc.name == *name)
+        });
+        for svc_cfg in new_config
+            .services
+            .iter()
+            .filter(|cfg| is_sensors_service(cfg))
+        {
+            let service = SensorsService::new(&self.resources);
+            match self.sensors_services.get(&svc_cfg.name) {
+                Some(existing) => *existing.lock().unwrap() = service,
+                None => {
+                    let _ = self
+                        .sensors_services
+                        .insert(svc_cfg.name.clone(), Arc::new(Mutex::new(service)));
+                }
+            }
+        }
+
+        // likewise the collectors and capture triggers hold on to the resources they read from
+        #[cfg(feature = "data")]
+        if rebuilt {
+            if let Some(updates) = self.data_manager_capture_updates.as_ref() {
+                match CaptureSources::from_robot_and_config(self, new_config) {
+                    Ok(Some(sources)) => {
+                        let _ = updates.try_send(sources);
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        return Err(RobotError::RobotReconfigureRequiresRestart(format!(
+                            "data capture cannot be rebuilt: {}",
+                            err
+                        )));
+                    }
+                }
+            }
+        }
+        self.config_revision = new_config.revision.clone();
+        self.build_time = build_time;
+        Ok(())
+    }
+
+    fn get_config_dependency_names(
+        config: &DynamicComponentConfig,
+        registry: &ComponentRegistry,
+    ) -> Vec<String> {
+        get_model_without_namespace_prefix(&mut config.get_model().to_owned()).map_or(
+            Vec::new(),
+            |model| {
+                registry
+                    .get_dependency_function(config.get_type(), &model)
+                    .map_or(Vec::new(), |dep_fn| {
+                        dep_fn(ConfigType::Dynamic(config))
+                            .into_iter()
+                            .map(|key| key.1)
+                            .collect()
+                    })
+            },
+        )
+    }
+
     fn build_resource(
         &mut self,
         config: &DynamicComponentConfig,
@@ -473,6 +855,62 @@ impl LocalRobot {
                     ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
                 )
             }
+            "arm" => {
+                let ctor = registry
+                    .get_arm_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::Arm(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "gripper" => {
+                let ctor = registry
+                    .get_gripper_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::Gripper(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "gantry" => {
+                let ctor = registry
+                    .get_gantry_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::Gantry(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "button" => {
+                let ctor = registry
+                    .get_button_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::Button(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "switch" => {
+                let ctor = registry
+                    .get_switch_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::Switch(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "input_controller" => {
+                let ctor = registry
+                    .get_input_controller_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::InputController(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
+            "pose_tracker" => {
+                let ctor = registry
+                    .get_pose_tracker_constructor(&model)
+                    .map_err(RobotError::RobotRegistryError)?;
+                ResourceType::PoseTracker(
+                    ctor(cfg, deps).map_err(|e| RobotError::RobotResourceBuildError(e.into()))?,
+                )
+            }
             "generic" => {
                 let ctor = registry
                     .get_generic_component_constructor(&model)
@@ -502,11 +940,26 @@ impl LocalRobot {
                 r_name.name.clone(),
                 resource.clone(),
                 conf,
+                self.capture_triggers.clone(),
             )?);
         }
         Ok(res)
     }
 
+    /// Named triggers of the triggered capture policies, fired by sensor triggers or
+    /// through the data manager service DoCommand
+    #[cfg(feature = "data")]
+    pub fn capture_triggers(&self) -> CaptureTriggers {
+        self.capture_triggers.clone()
+    }
+
+    #[cfg(feature = "data")]
+    pub fn get_data_manager_service_by_name(&self, name: &str) -> Option<&DataManagerService> {
+        self.data_manager_service
+            .as_ref()
+            .filter(|svc| svc.name() == name)
+    }
+
     pub fn get_periodic_app_client_tasks(&mut self) -> Vec<Box<dyn PeriodicAppClientTask>> {
         #[allow(unused_mut)]
         let mut tasks = Vec::<Box<dyn PeriodicAppClientTask>>::new();
@@ -595,6 +1048,62 @@ impl LocalRobot {
                             status,
                         });
                     }
+                    ResourceType::Arm(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::Gripper(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::Gantry(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::Button(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::Switch(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::InputController(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
+                    ResourceType::PoseTracker(b) => {
+                        let status = b.get_status()?;
+                        vec.push(robot::v1::Status {
+                            name: Some(name.clone()),
+                            last_reconfigured: last_reconfigured_proto.clone(),
+                            status,
+                        });
+                    }
                     ResourceType::Generic(b) => {
                         let status = b.get_status()?;
                         vec.push(robot::v1::Status {
@@ -686,6 +1195,62 @@ impl LocalRobot {
                                 status,
                             });
                         }
+                        ResourceType::Arm(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::Gripper(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::Gantry(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::Button(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::Switch(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::InputController(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
+                        ResourceType::PoseTracker(b) => {
+                            let status = b.get_status()?;
+                            vec.push(robot::v1::Status {
+                                name: Some(name),
+                                last_reconfigured: last_reconfigured_proto.clone(),
+                                status,
+                            });
+                        }
                         ResourceType::Generic(b) => {
                             let status = b.get_status()?;
                             vec.push(robot::v1::Status {
@@ -711,10 +1276,31 @@ impl LocalRobot {
         Ok(vec)
     }
     pub fn get_resource_names(&self) -> Result<Vec<common::v1::ResourceName>, RobotError> {
-        let mut name = Vec::with_capacity(self.resources.len());
+        let mut name = Vec::with_capacity(self.resources.len() + self.sensors_services.len());
         for k in self.resources.keys() {
             name.push(k.clone());
         }
+        for k in self.sensors_services.keys() {
+            name.push(ResourceName {
+                namespace: "rdk".to_string(),
+                r#type: "service".to_string(),
+                subtype: crate::common::sensors_service::SERVICE_NAME.to_string(),
+                local_name: k.clone(),
+                remote_path: vec![],
+                name: k.clone(),
+            });
+        }
+        #[cfg(feature = "data")]
+        if let Some(svc) = self.data_manager_service.as_ref() {
+            name.push(ResourceName {
+                namespace: "rdk".to_string(),
+                r#type: "service".to_string(),
+                subtype: crate::common::data_manager::SERVICE_NAME.to_string(),
+                local_name: svc.name().to_string(),
+                remote_path: vec![],
+                name: svc.name().to_string(),
+            });
+        }
         Ok(name)
     }
     pub fn get_motor_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Motor>>> {
@@ -861,6 +1447,121 @@ impl LocalRobot {
         }
     }
 
+    pub fn get_arm_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Arm>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "arm".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::Arm(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_gripper_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Gripper>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "gripper".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::Gripper(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_gantry_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Gantry>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "gantry".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::Gantry(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_button_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Button>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "button".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::Button(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_switch_by_name(&self, name: String) -> Option<Arc<Mutex<dyn Switch>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "switch".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::Switch(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_input_controller_by_name(
+        &self,
+        name: String,
+    ) -> Option<Arc<Mutex<dyn InputController>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "input_controller".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::InputController(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
+    pub fn get_pose_tracker_by_name(&self, name: String) -> Option<Arc<Mutex<dyn PoseTracker>>> {
+        let name = ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: "pose_tracker".to_string(),
+            local_name: name.clone(),
+            remote_path: vec![],
+            name,
+        };
+        match self.resources.get(&name) {
+            Some(ResourceType::PoseTracker(r)) => Some(r.clone()),
+            Some(_) => None,
+            None => None,
+        }
+    }
+
     pub fn get_generic_component_by_name(
         &self,
         name: String,
@@ -880,27 +1581,36 @@ impl LocalRobot {
         }
     }
 
+    /// Registers a long running operation on the resource identified by `key`, cancelling the
+    /// one that may already be running on it.
+    pub fn get_sensors_service_by_name(&self, name: String) -> Option<SensorsServiceType> {
+        self.sensors_services.get(&name).cloned()
+    }
+
+    pub fn start_operation(&mut self, key: ResourceKey) -> Operation {
+        // operations that ran to completion have dropped their receiver
+        self.operations.retain(|_, op| !op.is_closed());
+        let (sender, receiver) = async_channel::bounded(1);
+        if let Some(previous) = self.operations.insert(key, sender) {
+            previous.close();
+        }
+        Operation(receiver)
+    }
+
+    pub fn cancel_operation(&mut self, key: &ResourceKey) {
+        if let Some(op) = self.operations.remove(key) {
+            op.close();
+        }
+    }
+
     pub fn stop_all(&mut self) -> Result<(), RobotError> {
+        for (_, op) in self.operations.drain() {
+            op.close();
+        }
         let mut stop_errors: Vec<ActuatorError> = vec![];
         for resource in self.resources.values_mut() {
-            match resource {
-                ResourceType::Base(b) => {
-                    match b.stop() {
-                        Ok(_) => {}
-                        Err(err) => {
-                            stop_errors.push(err);
-                        }
-                    };
-                }
-                ResourceType::Motor(m) => {
-                    match m.stop() {
-                        Ok(_) => {}
-                        Err(err) => {
-                            stop_errors.push(err);
-                        }
-                    };
-                }
-                _ => continue,
+            if let Err(err) = stop_resource(resource) {
+                stop_errors.push(err);
             }
         }
         if !stop_errors.is_empty() {
@@ -909,6 +1619,40 @@ impl LocalRobot {
         Ok(())
     }
 
+    pub fn config_revision(&self) -> &str {
+        &self.config_revision
+    }
+
+    // Reports every resource as ready since a resource failing to build prevents the robot from
+    // being built. The config's last update is only known when it was obtained from app during
+    // this boot, it is None when the robot was built from the cached configuration.
+    pub fn get_machine_status(&self) -> robot::v1::GetMachineStatusResponse {
+        let last_updated = self.build_time.map(|bt| google::protobuf::Timestamp {
+            seconds: bt.timestamp(),
+            nanos: bt.timestamp_subsec_nanos() as i32,
+        });
+        let cloud_metadata = self.get_cloud_metadata().ok();
+        robot::v1::GetMachineStatusResponse {
+            resources: self
+                .resources
+                .keys()
+                .map(|name| robot::v1::ResourceStatus {
+                    name: Some(name.clone()),
+                    state: robot::v1::resource_status::State::Ready.into(),
+                    last_updated: last_updated.clone(),
+                    revision: self.config_revision.clone(),
+                    error: Default::default(),
+                    cloud_metadata: cloud_metadata.clone(),
+                })
+                .collect(),
+            config: Some(robot::v1::ConfigStatus {
+                revision: self.config_revision.clone(),
+                last_updated,
+            }),
+            state: robot::v1::get_machine_status_response::State::Running.into(),
+        }
+    }
+
     pub fn get_cloud_metadata(&self) -> Result<robot::v1::GetCloudMetadataResponse, RobotError> {
         self.cloud_metadata
             .as_ref()
@@ -935,7 +1679,7 @@ impl Drop for LocalRobot {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, sync::Arc};
 
     use crate::{
         common::{
@@ -947,11 +1691,12 @@ mod tests {
             i2c::I2CHandle,
             motor::Motor,
             movement_sensor::MovementSensor,
-            robot::LocalRobot,
+            registry::ResourceKey,
+            robot::{LocalRobot, RobotError},
             sensor::Readings,
         },
         google::{self, protobuf::Struct},
-        proto::app::v1::{ComponentConfig, RobotConfig},
+        proto::app::v1::{ComponentConfig, RobotConfig, ServiceConfig},
     };
 
     #[cfg(feature = "data")]
@@ -1428,6 +2173,13 @@ mod tests {
 
         let robot_cfg = RobotConfig {
             components: component_cfgs,
+            services: vec![ServiceConfig {
+                name: "sensors".to_string(),
+                namespace: "rdk".to_string(),
+                r#type: "sensors".to_string(),
+                api: "rdk:service:sensors".to_string(),
+                ..Default::default()
+            }],
             ..Default::default()
         };
 
@@ -1462,6 +2214,303 @@ mod tests {
         assert!(position.is_ok());
 
         assert_eq!(position.ok().unwrap(), 180);
+
+        assert!(robot
+            .get_sensors_service_by_name("sensors".to_string())
+            .is_some());
+        assert!(robot
+            .get_resource_names()
+            .unwrap()
+            .iter()
+            .any(|name| name.r#type == "service" && name.subtype == "sensors"));
+    }
+
+    #[test_log::test]
+    fn test_machine_status() {
+        let robot_cfg = RobotConfig {
+            components: vec![ComponentConfig {
+                name: "enc1".to_string(),
+                model: "rdk:builtin:fake".to_string(),
+                r#type: "encoder".to_string(),
+                namespace: "rdk".to_string(),
+                api: "blah".to_string(),
+                ..Default::default()
+            }],
+            revision: "rev-42".to_string(),
+            ..Default::default()
+        };
+
+        let robot = LocalRobot::from_cloud_config(
+            Executor::new(),
+            "".to_string(),
+            &robot_cfg,
+            &mut Box::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(robot.config_revision(), "rev-42");
+
+        let status = robot.get_machine_status();
+        let config = status.config.unwrap();
+        assert_eq!(config.revision, "rev-42");
+        // built without contacting app, as when booting from the cached configuration
+        assert!(config.last_updated.is_none());
+        assert_eq!(status.resources.len(), 1);
+        assert_eq!(status.resources[0].revision, "rev-42");
+        assert_eq!(
+            status.resources[0].name.as_ref().unwrap().name,
+            "enc1".to_string()
+        );
+    }
+
+    #[test_log::test]
+    fn test_reconfigure() {
+        let component = |name: &str, r#type: &str, model: &str, attribute: (&str, Kind)| {
+            let value = match attribute.1 {
+                Kind::NumberValue(n) => google::protobuf::value::Kind::NumberValue(n),
+                Kind::StringValue(s) => google::protobuf::value::Kind::StringValue(s),
+                _ => unreachable!(),
+            };
+            ComponentConfig {
+                name: name.to_string(),
+                model: model.to_string(),
+                r#type: r#type.to_string(),
+                namespace: "rdk".to_string(),
+                api: "blah".to_string(),
+                attributes: Some(Struct {
+                    fields: HashMap::from([(
+                        attribute.0.to_string(),
+                        google::protobuf::Value { kind: Some(value) },
+                    )]),
+                }),
+                ..Default::default()
+            }
+        };
+        let enc1 = |deg: f64| {
+            component(
+                "enc1",
+                "encoder",
+                "rdk:builtin:fake",
+                ("fake_deg", Kind::NumberValue(deg)),
+            )
+        };
+        let m1 = component(
+            "m1",
+            "motor",
+            "rdk:builtin:fake_with_dep",
+            ("encoder", Kind::StringValue("enc1".to_string())),
+        );
+        let s1 = component(
+            "s1",
+            "sensor",
+            "rdk:builtin:fake",
+            ("fake_value", Kind::NumberValue(1.0)),
+        );
+
+        let old_config = RobotConfig {
+            components: vec![enc1(90.0), m1.clone(), s1.clone()],
+            revision: "1".to_string(),
+            ..Default::default()
+        };
+        let mut registry = Box::default();
+        let mut robot = LocalRobot::from_cloud_config(
+            Executor::new(),
+            "".to_string(),
+            &old_config,
+            &mut registry,
+            None,
+        )
+        .unwrap();
+        let sensor = robot.get_sensor_by_name("s1".to_string()).unwrap();
+        assert_eq!(
+            robot
+                .get_motor_by_name("m1".to_string())
+                .unwrap()
+                .get_position()
+                .unwrap(),
+            90
+        );
+
+        // changing the encoder rebuilds the motor depending on it and keeps the sensor
+        let new_config = RobotConfig {
+            components: vec![enc1(180.0), m1.clone(), s1],
+            revision: "2".to_string(),
+            ..Default::default()
+        };
+        assert!(robot
+            .reconfigure(&old_config, &new_config, &mut registry, None)
+            .is_ok());
+        assert_eq!(robot.config_revision(), "2");
+        assert_eq!(
+            robot
+                .get_motor_by_name("m1".to_string())
+                .unwrap()
+                .get_position()
+                .unwrap(),
+            180
+        );
+        assert!(Arc::ptr_eq(
+            &sensor,
+            &robot.get_sensor_by_name("s1".to_string()).unwrap()
+        ));
+
+        // removing the sensor drops it
+        let old_config = new_config;
+        let new_config = RobotConfig {
+            components: vec![enc1(180.0), m1.clone()],
+            revision: "3".to_string(),
+            ..Default::default()
+        };
+        assert!(robot
+            .reconfigure(&old_config, &new_config, &mut registry, None)
+            .is_ok());
+        assert!(robot.get_sensor_by_name("s1".to_string()).is_none());
+        assert!(robot.get_motor_by_name("m1".to_string()).is_some());
+
+        // sensors services are added in place
+        let old_config = new_config;
+        let sensors_service = ServiceConfig {
+            name: "sensors".to_string(),
+            namespace: "rdk".to_string(),
+            r#type: "sensors".to_string(),
+            api: "rdk:service:sensors".to_string(),
+            ..Default::default()
+        };
+        let new_config = RobotConfig {
+            components: vec![enc1(90.0), m1.clone()],
+            services: vec![sensors_service.clone()],
+            revision: "4".to_string(),
+            ..Default::default()
+        };
+        assert!(robot
+            .reconfigure(&old_config, &new_config, &mut registry, None)
+            .is_ok());
+        assert!(robot
+            .get_sensors_service_by_name("sensors".to_string())
+            .is_some());
+
+        // other services cannot be reconfigured in place, the robot is left untouched
+        let old_config = new_config;
+        let new_config = RobotConfig {
+            components: vec![enc1(180.0), m1],
+            services: vec![
+                sensors_service,
+                ServiceConfig {
+                    name: "data".to_string(),
+                    namespace: "rdk".to_string(),
+                    r#type: "data_manager".to_string(),
+                    ..Default::default()
+                },
+            ],
+            revision: "5".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            robot.reconfigure(&old_config, &new_config, &mut registry, None),
+            Err(RobotError::RobotReconfigureRequiresRestart(_))
+        ));
+        assert_eq!(robot.config_revision(), "4");
+    }
+
+    #[cfg(feature = "data")]
+    #[test_log::test]
+    fn test_reconfigure_data_collectors() {
+        use crate::{
+            google::protobuf::{value::Kind as PKind, ListValue, Value},
+            proto::app::v1::ResourceLevelServiceConfig,
+        };
+
+        let value = |kind: PKind| Value { kind: Some(kind) };
+        let sensor = |fake_value: f64, capture_frequency_hz: f64, cache_size_kb: f64| {
+            let capture_method = Struct {
+                fields: HashMap::from([
+                    (
+                        "method".to_string(),
+                        value(PKind::StringValue("Readings".to_string())),
+                    ),
+                    (
+                        "capture_frequency_hz".to_string(),
+                        value(PKind::NumberValue(capture_frequency_hz)),
+                    ),
+                    (
+                        "cache_size_kb".to_string(),
+                        value(PKind::NumberValue(cache_size_kb)),
+                    ),
+                ]),
+            };
+            ComponentConfig {
+                name: "s1".to_string(),
+                model: "rdk:builtin:fake".to_string(),
+                r#type: "sensor".to_string(),
+                namespace: "rdk".to_string(),
+                api: "blah".to_string(),
+                attributes: Some(Struct {
+                    fields: HashMap::from([(
+                        "fake_value".to_string(),
+                        value(PKind::NumberValue(fake_value)),
+                    )]),
+                }),
+                service_configs: vec![ResourceLevelServiceConfig {
+                    r#type: "rdk:service:data_manager".to_string(),
+                    attributes: Some(Struct {
+                        fields: HashMap::from([(
+                            "capture_methods".to_string(),
+                            value(PKind::ListValue(ListValue {
+                                values: vec![value(PKind::StructValue(capture_method))],
+                            })),
+                        )]),
+                    }),
+                }],
+                ..Default::default()
+            }
+        };
+        let config = |sensor: ComponentConfig, revision: &str| RobotConfig {
+            components: vec![sensor],
+            services: vec![ServiceConfig {
+                name: "data".to_string(),
+                namespace: "rdk".to_string(),
+                r#type: "data_manager".to_string(),
+                attributes: Some(Struct {
+                    fields: HashMap::from([(
+                        "sync_disabled".to_string(),
+                        value(PKind::BoolValue(true)),
+                    )]),
+                }),
+                ..Default::default()
+            }],
+            revision: revision.to_string(),
+            ..Default::default()
+        };
+
+        let old_config = config(sensor(1.0, 1.0, 1.0), "1");
+        let mut registry = Box::default();
+        let mut robot = LocalRobot::from_cloud_config(
+            Executor::new(),
+            "".to_string(),
+            &old_config,
+            &mut registry,
+            None,
+        )
+        .unwrap();
+        assert!(robot.get_data_manager_service_by_name("data").is_some());
+
+        // the collector keeps its region in the store and captures from the rebuilt sensor
+        let new_config = config(sensor(2.0, 2.0, 1.0), "2");
+        assert!(robot
+            .reconfigure(&old_config, &new_config, &mut registry, None)
+            .is_ok());
+        let collectors = robot.data_collectors().unwrap();
+        assert_eq!(collectors.len(), 1);
+        assert_eq!(collectors[0].time_interval(), Duration::from_millis(500));
+
+        // a different capacity changes the layout of the store
+        let old_config = new_config;
+        let new_config = config(sensor(2.0, 2.0, 2.0), "3");
+        assert!(matches!(
+            robot.reconfigure(&old_config, &new_config, &mut registry, None),
+            Err(RobotError::RobotReconfigureRequiresRestart(_))
+        ));
+        assert_eq!(robot.config_revision(), "2");
     }
 
     #[test_log::test]
@@ -1564,4 +2613,30 @@ mod tests {
 
         assert!(enc.is_some());
     }
+
+    #[test_log::test]
+    fn test_robot_operations() {
+        let exec = Executor::new();
+        let mut robot = LocalRobot::new();
+        let key = ResourceKey::new("motor", "m1");
+
+        let op = robot.start_operation(key.clone());
+        assert!(!op.is_cancelled());
+        assert!(exec.block_on(op.wait_for(std::time::Duration::from_millis(10))));
+
+        // starting a new operation on the same resource cancels the previous one
+        let newer_op = robot.start_operation(key.clone());
+        assert!(op.is_cancelled());
+        assert!(!exec.block_on(op.wait_for(std::time::Duration::from_secs(60))));
+        assert!(!newer_op.is_cancelled());
+
+        // operations on other resources are left untouched
+        let other_op = robot.start_operation(ResourceKey::new("motor", "m2"));
+        robot.cancel_operation(&key);
+        assert!(newer_op.is_cancelled());
+        assert!(!other_op.is_cancelled());
+
+        assert!(robot.stop_all().is_ok());
+        assert!(other_op.is_cancelled());
+    }
 }
@@ -0,0 +1,173 @@
+//! Builtin implementation of the sensors service.
+//!
+//! The service lets a client discover every sensor of a machine and fetch all their readings
+//! in a single request rather than issuing one GetReadings call per sensor. Sensors,
+//! movement sensors and power sensors are all considered.
+
+#![allow(deprecated)]
+
+use std::sync::{Arc, Mutex};
+
+use crate::proto::{
+    app::v1::ServiceConfig,
+    common::v1::ResourceName,
+    service::sensors::v1::{GetReadingsResponse, GetSensorsResponse, Readings as SensorReadings},
+};
+
+use super::{
+    robot::{ResourceMap, ResourceType},
+    sensor::{GenericReadingsResult, Readings, SensorError},
+};
+use thiserror::Error;
+
+pub static SERVICE_NAME: &str = "sensors";
+
+pub type SensorsServiceType = Arc<Mutex<SensorsService>>;
+
+#[derive(Error, Debug)]
+pub enum SensorsServiceError {
+    #[error("sensor {0} not found")]
+    SensorNotFound(String),
+    #[error("failed to get readings of sensor {0}: {1}")]
+    SensorReadingsError(String, SensorError),
+}
+
+/// Returns true if the service config describes a sensors service
+pub fn is_sensors_service(cfg: &ServiceConfig) -> bool {
+    cfg.r#type == SERVICE_NAME || cfg.api == "rdk:service:sensors"
+}
+
+pub struct SensorsService {
+    sensors: Vec<(ResourceName, ResourceType)>,
+}
+
+impl SensorsService {
+    /// Builds a service over every sensor, movement sensor and power sensor in `resources`
+    pub fn new(resources: &ResourceMap) -> Self {
+        let mut sensors: Vec<(ResourceName, ResourceType)> = resources
+            .iter()
+            .filter(|(_, res)| {
+                matches!(
+                    res,
+                    ResourceType::Sensor(_)
+                        | ResourceType::MovementSensor(_)
+                        | ResourceType::PowerSensor(_)
+                )
+            })
+            .map(|(name, res)| (name.clone(), res.clone()))
+            .collect();
+        sensors.sort_by(|(a, _), (b, _)| (&a.subtype, &a.name).cmp(&(&b.subtype, &b.name)));
+        Self { sensors }
+    }
+
+    pub fn get_sensors(&self) -> GetSensorsResponse {
+        GetSensorsResponse {
+            sensor_names: self.sensors.iter().map(|(name, _)| name.clone()).collect(),
+        }
+    }
+
+    /// Reads every sensor in `sensor_names`, or every sensor of the machine when the list is
+    /// empty
+    pub fn get_readings(
+        &self,
+        sensor_names: Vec<ResourceName>,
+    ) -> Result<GetReadingsResponse, SensorsServiceError> {
+        let mut readings = Vec::with_capacity(self.sensors.len());
+        if sensor_names.is_empty() {
+            for (name, res) in self.sensors.iter() {
+                readings.push(Self::read_sensor(name, res)?);
+            }
+        } else {
+            for name in sensor_names.iter() {
+                let (name, res) = self
+                    .sensors
+                    .iter()
+                    .find(|(n, _)| n.subtype == name.subtype && n.name == name.name)
+                    .ok_or_else(|| SensorsServiceError::SensorNotFound(name.name.clone()))?;
+                readings.push(Self::read_sensor(name, res)?);
+            }
+        }
+        Ok(GetReadingsResponse { readings })
+    }
+
+    fn read_sensor(
+        name: &ResourceName,
+        res: &ResourceType,
+    ) -> Result<SensorReadings, SensorsServiceError> {
+        let values: Result<GenericReadingsResult, SensorError> = match res {
+            ResourceType::Sensor(s) => s.lock().unwrap().get_generic_readings(),
+            ResourceType::MovementSensor(s) => s.lock().unwrap().get_generic_readings(),
+            ResourceType::PowerSensor(s) => s.lock().unwrap().get_generic_readings(),
+            _ => return Err(SensorsServiceError::SensorNotFound(name.name.clone())),
+        };
+        Ok(SensorReadings {
+            name: Some(name.clone()),
+            readings: values
+                .map_err(|err| SensorsServiceError::SensorReadingsError(name.name.clone(), err))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::common::{
+        motor::FakeMotor,
+        movement_sensor::FakeMovementSensor,
+        robot::{ResourceMap, ResourceType},
+        sensor::FakeSensor,
+        sensors_service::{SensorsService, SensorsServiceError},
+    };
+    use crate::proto::common::v1::ResourceName;
+
+    fn resource_name(subtype: &str, name: &str) -> ResourceName {
+        ResourceName {
+            namespace: "rdk".to_string(),
+            r#type: "component".to_string(),
+            subtype: subtype.to_string(),
+            name: name.to_string(),
+            local_name: name.to_string(),
+            remote_path: vec![],
+        }
+    }
+
+    #[test_log::test]
+    #[allow(deprecated)]
+    fn test_sensors_service() {
+        let mut resources = ResourceMap::new();
+        resources.insert(
+            resource_name("sensor", "sensor"),
+            ResourceType::Sensor(Arc::new(Mutex::new(FakeSensor::new()))),
+        );
+        resources.insert(
+            resource_name("movement_sensor", "imu"),
+            ResourceType::MovementSensor(Arc::new(Mutex::new(FakeMovementSensor::new()))),
+        );
+        resources.insert(
+            resource_name("motor", "motor"),
+            ResourceType::Motor(Arc::new(Mutex::new(FakeMotor::new()))),
+        );
+        let service = SensorsService::new(&resources);
+
+        let sensors = service.get_sensors().sensor_names;
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].subtype, "movement_sensor");
+        assert_eq!(sensors[1].subtype, "sensor");
+
+        let all = service.get_readings(vec![]).unwrap();
+        assert_eq!(all.readings.len(), 2);
+
+        let one = service
+            .get_readings(vec![resource_name("sensor", "sensor")])
+            .unwrap();
+        assert_eq!(one.readings.len(), 1);
+        assert_eq!(one.readings[0].name, Some(sensors[1].clone()));
+        assert!(one.readings[0].readings.contains_key("fake_sensor"));
+
+        assert!(matches!(
+            service.get_readings(vec![resource_name("motor", "motor")]),
+            Err(SensorsServiceError::SensorNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+//! An arm made of a chain of servos, one per joint, such as the small desk arms
+//! commonly built around hobby servos.
+//!
+//! The servos are configured as their own components and referenced in order, from the
+//! base of the arm to its end, by the `joints` attribute of the arm. The servos don't report
+//! when they reach their position, the arm is considered moving for as long as its slowest
+//! joint takes to get there at `speed_degs_per_sec` (300 degrees per second by default)
+//!
+//! ```json
+//! {
+//!   "name": "arm",
+//!   "type": "arm",
+//!   "model": "servo_arm",
+//!   "attributes": {
+//!     "joints": ["shoulder", "elbow", "wrist"],
+//!     "speed_degs_per_sec": 300
+//!   }
+//! }
+//! ```
+
+use super::actuator::{Actuator, ActuatorError};
+use super::arm::{Arm, ArmError, ArmType, COMPONENT_NAME as ArmCompName};
+use super::config::ConfigType;
+use super::registry::{ComponentRegistry, Dependency, ResourceKey};
+use super::robot::Resource;
+use super::servo::{Servo, ServoType, COMPONENT_NAME as ServoCompName};
+use super::status::{Status, StatusError};
+use crate::google;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Speed of the joints when `speed_degs_per_sec` isn't configured, typical of hobby servos
+const DEFAULT_SPEED_DEGS_PER_SEC: f64 = 300.0;
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_arm("servo_arm", &ServoArm::<ServoType>::from_config)
+        .is_err()
+    {
+        log::error!("servo_arm model is already registered")
+    }
+    if registry
+        .register_dependency_getter(
+            ArmCompName,
+            "servo_arm",
+            &ServoArm::<ServoType>::dependencies_from_config,
+        )
+        .is_err()
+    {
+        log::error!("failed to register dependency getter for servo_arm model")
+    }
+}
+
+#[derive(DoCommand)]
+pub struct ServoArm<S> {
+    joints: Vec<S>,
+    speed_degs_per_sec: f64,
+    // start and expected duration of the last move, cleared when the arm is stopped. Status
+    // readers can't query the joints themselves
+    last_move: Option<(Instant, Duration)>,
+}
+
+impl<S> ServoArm<S>
+where
+    S: Servo,
+{
+    pub fn new(joints: Vec<S>) -> Self {
+        ServoArm {
+            joints,
+            speed_degs_per_sec: DEFAULT_SPEED_DEGS_PER_SEC,
+            last_move: None,
+        }
+    }
+
+    pub fn with_speed(mut self, speed_degs_per_sec: f64) -> Self {
+        self.speed_degs_per_sec = speed_degs_per_sec;
+        self
+    }
+
+    fn moving(&self) -> bool {
+        self.last_move
+            .is_some_and(|(start, duration)| start.elapsed() < duration)
+    }
+
+    pub(crate) fn from_config(cfg: ConfigType, deps: Vec<Dependency>) -> Result<ArmType, ArmError> {
+        let joint_names = cfg.get_attribute::<Vec<String>>("joints")?;
+        if joint_names.is_empty() {
+            return Err(ArmError::ArmConfigurationError(
+                "servo_arm requires at least one joint",
+            ));
+        }
+        let mut servos: HashMap<String, ServoType> = HashMap::new();
+        for Dependency(key, res) in deps {
+            if let Resource::Servo(servo) = res {
+                let _ = servos.insert(key.1, servo);
+            }
+        }
+        let joints = joint_names
+            .iter()
+            .map(|name| {
+                servos
+                    .get(name)
+                    .cloned()
+                    .ok_or(ArmError::ArmConfigurationError(
+                        "joint servo couldn't be found",
+                    ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut arm = ServoArm::new(joints);
+        if let Ok(speed) = cfg.get_attribute::<f64>("speed_degs_per_sec") {
+            if !(speed.is_finite() && speed > 0.0) {
+                return Err(ArmError::ArmConfigurationError(
+                    "speed_degs_per_sec must be positive",
+                ));
+            }
+            arm = arm.with_speed(speed);
+        }
+        Ok(Arc::new(Mutex::new(arm)))
+    }
+
+    pub(crate) fn dependencies_from_config(cfg: ConfigType) -> Vec<ResourceKey> {
+        cfg.get_attribute::<Vec<String>>("joints")
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| ResourceKey::new(ServoCompName, name))
+            .collect()
+    }
+}
+
+impl<S> Arm for ServoArm<S>
+where
+    S: Servo,
+{
+    fn get_joint_positions(&mut self) -> Result<Vec<f64>, ArmError> {
+        self.joints
+            .iter_mut()
+            .map(|joint| {
+                joint
+                    .get_position()
+                    .map(|pos| pos as f64)
+                    .map_err(ArmError::from)
+            })
+            .collect()
+    }
+
+    fn move_to_joint_positions(&mut self, positions: &[f64]) -> Result<(), ArmError> {
+        if positions.len() != self.joints.len() {
+            return Err(ArmError::ArmInvalidArgument(
+                "expected one position per joint",
+            ));
+        }
+        // validate every position before moving any joint so the arm isn't left half way
+        if positions.iter().any(|pos| !pos.is_finite() || *pos < 0.0) {
+            return Err(ArmError::ArmInvalidArgument(
+                "joint positions must be finite and non-negative",
+            ));
+        }
+        // the move lasts as long as the joint travelling the furthest, a joint whose position
+        // is unknown is assumed to cross its whole range
+        let start = Instant::now();
+        let mut travel_degs: f64 = 0.0;
+        for (joint, pos) in self.joints.iter_mut().zip(positions) {
+            let target = pos.round() as u32;
+            let travel = joint
+                .get_position()
+                .map(|current| current.abs_diff(target) as f64)
+                .unwrap_or(180.0);
+            travel_degs = travel_degs.max(travel);
+            self.last_move = Some((
+                start,
+                Duration::from_secs_f64(travel_degs / self.speed_degs_per_sec),
+            ));
+            joint.move_to(target)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> Actuator for ServoArm<S>
+where
+    S: Servo,
+{
+    fn is_moving(&mut self) -> Result<bool, ActuatorError> {
+        Ok(self.moving())
+    }
+    fn stop(&mut self) -> Result<(), ActuatorError> {
+        // every joint is stopped even if one of them fails
+        let mut result = Ok(());
+        for joint in self.joints.iter_mut() {
+            if let Err(err) = joint.stop() {
+                result = Err(err);
+            }
+        }
+        if result.is_ok() {
+            self.last_move = None;
+        }
+        result
+    }
+}
+
+impl<S> Status for ServoArm<S>
+where
+    S: Servo,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "is_moving".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::BoolValue(self.moving())),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::actuator::Actuator;
+    use crate::common::arm::{Arm, ArmError};
+    use crate::common::board::{Board, FakeBoard};
+    use crate::common::gpio_servo::{GpioServo, GpioServoSettings};
+    use crate::common::servo_arm::ServoArm;
+    use crate::common::status::Status;
+    use crate::google::protobuf::value::Kind;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn servo_settings() -> GpioServoSettings {
+        GpioServoSettings {
+            min_angle_deg: 0,
+            max_angle_deg: 180,
+            min_period_us: 500,
+            max_period_us: 2500,
+            frequency: 300,
+            pwm_resolution: 0,
+        }
+    }
+
+    #[test_log::test]
+    fn test_servo_arm() -> Result<(), ArmError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        let joints = vec![
+            GpioServo::new(board.clone(), 2, servo_settings())?,
+            GpioServo::new(board.clone(), 3, servo_settings())?,
+        ];
+        let mut arm = ServoArm::new(joints);
+
+        let status_is_moving = |arm: &ServoArm<_>| {
+            arm.get_status().unwrap().unwrap().fields["is_moving"].kind
+                == Some(Kind::BoolValue(true))
+        };
+        assert!(!status_is_moving(&arm));
+
+        arm.move_to_joint_positions(&[45.0, 135.0])?;
+        assert!(arm.is_moving()?);
+        assert!(status_is_moving(&arm));
+        let positions = arm.get_joint_positions()?;
+        assert_eq!(positions.len(), 2);
+        assert!((positions[0] - 45.0).abs() <= 1.0);
+        assert!((positions[1] - 135.0).abs() <= 1.0);
+
+        // no joint moves if any of the positions is invalid
+        assert!(arm.move_to_joint_positions(&[90.0]).is_err());
+        assert!(arm.move_to_joint_positions(&[90.0, -10.0]).is_err());
+        assert!((arm.get_joint_positions()?[0] - 45.0).abs() <= 1.0);
+
+        assert!(arm.get_end_position().is_err());
+
+        arm.stop()?;
+        assert!(!arm.is_moving()?);
+        assert!(!status_is_moving(&arm));
+        assert_eq!(board.get_pwm_duty(2), 0.0);
+        assert_eq!(board.get_pwm_duty(3), 0.0);
+
+        // the arm stops moving once its slowest joint had the time to reach its position
+        let joints = vec![GpioServo::new(board.clone(), 2, servo_settings())?];
+        let mut arm = ServoArm::new(joints).with_speed(10000.0);
+        arm.move_to_joint_positions(&[90.0])?;
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!arm.is_moving()?);
+        assert!(!status_is_moving(&arm));
+        Ok(())
+    }
+}
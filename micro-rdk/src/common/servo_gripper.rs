@@ -0,0 +1,316 @@
+//! A gripper whose jaws are driven by a servo, moving between an open and a closed angle.
+//!
+//! Optionally a power sensor measuring the current drawn by the servo can be used to detect
+//! whether an object was grabbed: when the jaws close on an object the servo stalls and draws
+//! more than `current_threshold_amps`.
+//!
+//! ```json
+//! {
+//!   "name": "gripper",
+//!   "type": "gripper",
+//!   "model": "servo_gripper",
+//!   "attributes": {
+//!     "servo": "jaw",
+//!     "open_angle_deg": 10,
+//!     "closed_angle_deg": 120,
+//!     "travel_time_ms": 500,
+//!     "power_sensor": "jaw_current",
+//!     "current_threshold_amps": 0.4
+//!   }
+//! }
+//! ```
+
+use super::actuator::{Actuator, ActuatorError};
+use super::config::ConfigType;
+use super::gripper::{Gripper, GripperError, GripperType, COMPONENT_NAME as GripperCompName};
+use super::power_sensor::{PowerSensor, PowerSensorType, COMPONENT_NAME as PowerSensorCompName};
+use super::registry::{ComponentRegistry, Dependency, ResourceKey};
+use super::robot::Resource;
+use super::servo::{Servo, ServoType, COMPONENT_NAME as ServoCompName};
+use super::status::{Status, StatusError};
+use crate::google;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Time given to the servo to move between the open and closed positions when
+/// `travel_time_ms` isn't configured
+const DEFAULT_TRAVEL_TIME: Duration = Duration::from_millis(500);
+
+pub(crate) fn register_models(registry: &mut ComponentRegistry) {
+    if registry
+        .register_gripper(
+            "servo_gripper",
+            &ServoGripper::<ServoType, PowerSensorType>::from_config,
+        )
+        .is_err()
+    {
+        log::error!("servo_gripper model is already registered")
+    }
+    if registry
+        .register_dependency_getter(
+            GripperCompName,
+            "servo_gripper",
+            &ServoGripper::<ServoType, PowerSensorType>::dependencies_from_config,
+        )
+        .is_err()
+    {
+        log::error!("failed to register dependency getter for servo_gripper model")
+    }
+}
+
+#[derive(DoCommand)]
+pub struct ServoGripper<S, P> {
+    servo: S,
+    open_angle_deg: u32,
+    closed_angle_deg: u32,
+    travel_time: Duration,
+    // power sensor and current (in amperes) above which an object is considered grabbed
+    current_threshold: Option<(P, f64)>,
+    moving_until: Option<Instant>,
+}
+
+impl<S, P> ServoGripper<S, P>
+where
+    S: Servo,
+    P: PowerSensor,
+{
+    pub fn new(
+        servo: S,
+        open_angle_deg: u32,
+        closed_angle_deg: u32,
+        travel_time: Duration,
+    ) -> Self {
+        Self {
+            servo,
+            open_angle_deg,
+            closed_angle_deg,
+            travel_time,
+            current_threshold: None,
+            moving_until: None,
+        }
+    }
+
+    /// Uses `power_sensor` to detect a grab, an object is held when the current
+    /// drawn by the servo exceeds `threshold_amps`
+    pub fn with_current_threshold(mut self, power_sensor: P, threshold_amps: f64) -> Self {
+        self.current_threshold = Some((power_sensor, threshold_amps));
+        self
+    }
+
+    pub(crate) fn from_config(
+        cfg: ConfigType,
+        deps: Vec<Dependency>,
+    ) -> Result<GripperType, GripperError> {
+        let servo_name = cfg.get_attribute::<String>("servo")?;
+        let power_sensor_name = cfg.get_attribute::<String>("power_sensor").ok();
+        let open_angle_deg = cfg.get_attribute::<u32>("open_angle_deg").unwrap_or(0);
+        let closed_angle_deg = cfg.get_attribute::<u32>("closed_angle_deg")?;
+        let travel_time = cfg
+            .get_attribute::<u32>("travel_time_ms")
+            .map_or(DEFAULT_TRAVEL_TIME, |ms| Duration::from_millis(ms.into()));
+
+        let mut servo: Option<ServoType> = None;
+        let mut power_sensor: Option<PowerSensorType> = None;
+        for Dependency(key, res) in deps {
+            match res {
+                Resource::Servo(found) if key.1 == servo_name => {
+                    servo = Some(found);
+                }
+                Resource::PowerSensor(found) if Some(&key.1) == power_sensor_name.as_ref() => {
+                    power_sensor = Some(found);
+                }
+                _ => {}
+            }
+        }
+        let servo = servo.ok_or(GripperError::GripperConfigurationError(
+            "servo couldn't be found",
+        ))?;
+        let mut gripper = ServoGripper::new(servo, open_angle_deg, closed_angle_deg, travel_time);
+        if power_sensor_name.is_some() {
+            let power_sensor = power_sensor.ok_or(GripperError::GripperConfigurationError(
+                "power sensor couldn't be found",
+            ))?;
+            let threshold = cfg.get_attribute::<f64>("current_threshold_amps")?;
+            gripper = gripper.with_current_threshold(power_sensor, threshold);
+        }
+        Ok(Arc::new(Mutex::new(gripper)))
+    }
+
+    pub(crate) fn dependencies_from_config(cfg: ConfigType) -> Vec<ResourceKey> {
+        let mut r_keys = Vec::new();
+        if let Ok(servo_name) = cfg.get_attribute::<String>("servo") {
+            r_keys.push(ResourceKey::new(ServoCompName, servo_name));
+        }
+        if let Ok(power_sensor_name) = cfg.get_attribute::<String>("power_sensor") {
+            r_keys.push(ResourceKey::new(PowerSensorCompName, power_sensor_name));
+        }
+        r_keys
+    }
+
+    fn move_to(&mut self, angle_deg: u32) -> Result<(), GripperError> {
+        self.servo.move_to(angle_deg)?;
+        self.moving_until = Some(Instant::now() + self.travel_time);
+        Ok(())
+    }
+}
+
+impl<S, P> Gripper for ServoGripper<S, P>
+where
+    S: Servo,
+    P: PowerSensor,
+{
+    fn open(&mut self) -> Result<(), GripperError> {
+        self.move_to(self.open_angle_deg)
+    }
+
+    fn grab(&mut self) -> Result<Option<Duration>, GripperError> {
+        self.move_to(self.closed_angle_deg)?;
+        Ok(Some(self.travel_time))
+    }
+
+    // without a power sensor there is no way to tell, the grab is assumed to be successful
+    fn is_holding(&mut self) -> Result<bool, GripperError> {
+        match self.current_threshold.as_mut() {
+            Some((power_sensor, threshold)) => {
+                Ok(power_sensor.get_current()?.amperes.abs() >= *threshold)
+            }
+            None => Ok(true),
+        }
+    }
+}
+
+impl<S, P> Actuator for ServoGripper<S, P>
+where
+    S: Servo,
+    P: PowerSensor,
+{
+    fn is_moving(&mut self) -> Result<bool, ActuatorError> {
+        Ok(self
+            .moving_until
+            .is_some_and(|until| Instant::now() < until))
+    }
+    fn stop(&mut self) -> Result<(), ActuatorError> {
+        self.moving_until = None;
+        self.servo.stop()
+    }
+}
+
+impl<S, P> Status for ServoGripper<S, P>
+where
+    S: Servo,
+    P: PowerSensor,
+{
+    fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+        let mut hm = HashMap::new();
+        hm.insert(
+            "is_moving".to_string(),
+            google::protobuf::Value {
+                kind: Some(google::protobuf::value::Kind::BoolValue(
+                    self.moving_until
+                        .is_some_and(|until| Instant::now() < until),
+                )),
+            },
+        );
+        Ok(Some(google::protobuf::Struct { fields: hm }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::actuator::Actuator;
+    use crate::common::board::{Board, FakeBoard};
+    use crate::common::gpio_servo::{GpioServo, GpioServoSettings};
+    use crate::common::gripper::{Gripper, GripperError};
+    use crate::common::power_sensor::{Current, PowerSensor, PowerSupplyType, Voltage};
+    use crate::common::sensor::SensorError;
+    use crate::common::servo_gripper::ServoGripper;
+    use crate::common::status::{Status, StatusError};
+    use crate::google;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(DoCommand, PowerSensorReadings)]
+    struct TestPowerSensor {
+        amperes: f64,
+    }
+
+    impl PowerSensor for TestPowerSensor {
+        fn get_voltage(&mut self) -> Result<Voltage, SensorError> {
+            Ok(Voltage {
+                volts: 5.0,
+                power_supply_type: PowerSupplyType::DC,
+            })
+        }
+        fn get_current(&mut self) -> Result<Current, SensorError> {
+            Ok(Current {
+                amperes: self.amperes,
+                power_supply_type: PowerSupplyType::DC,
+            })
+        }
+        fn get_power(&mut self) -> Result<f64, SensorError> {
+            Ok(self.amperes * 5.0)
+        }
+    }
+
+    impl Status for TestPowerSensor {
+        fn get_status(&self) -> Result<Option<google::protobuf::Struct>, StatusError> {
+            Ok(None)
+        }
+    }
+
+    fn servo(board: Arc<Mutex<FakeBoard>>) -> GpioServo<Arc<Mutex<FakeBoard>>> {
+        let settings = GpioServoSettings {
+            min_angle_deg: 0,
+            max_angle_deg: 180,
+            min_period_us: 500,
+            max_period_us: 2500,
+            frequency: 300,
+            pwm_resolution: 0,
+        };
+        GpioServo::new(board, 2, settings).unwrap()
+    }
+
+    #[test_log::test]
+    fn test_servo_gripper() -> Result<(), GripperError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        let mut gripper = ServoGripper::<_, TestPowerSensor>::new(
+            servo(board.clone()),
+            0,
+            90,
+            Duration::from_millis(200),
+        );
+
+        assert!(!gripper.is_moving()?);
+        assert_eq!(gripper.grab()?, Some(Duration::from_millis(200)));
+        assert!(gripper.is_moving()?);
+        // angle: 90 -> duty: 0.45
+        assert!((board.get_pwm_duty(2) - 0.45).abs() < 0.0001);
+        assert!(gripper.is_holding()?);
+
+        // angle: 0 -> duty: 0.15
+        gripper.open()?;
+        assert!((board.get_pwm_duty(2) - 0.15).abs() < 0.0001);
+
+        gripper.stop()?;
+        assert!(!gripper.is_moving()?);
+        assert_eq!(board.get_pwm_duty(2), 0.0);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_servo_gripper_current_threshold() -> Result<(), GripperError> {
+        let board = Arc::new(Mutex::new(FakeBoard::new(vec![])));
+        let power_sensor = Arc::new(Mutex::new(TestPowerSensor { amperes: 0.1 }));
+        let mut gripper = ServoGripper::new(servo(board), 0, 90, Duration::from_millis(200))
+            .with_current_threshold(power_sensor.clone(), 0.5);
+
+        gripper.grab()?;
+        assert!(!gripper.is_holding()?);
+
+        // the servo stalls on an object
+        power_sensor.lock().unwrap().amperes = 0.8;
+        assert!(gripper.is_holding()?);
+        Ok(())
+    }
+}
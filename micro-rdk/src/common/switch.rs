@@ -0,0 +1,60 @@
+use super::{board::BoardError, config::AttributeError, generic::DoCommand, status::Status};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+pub static COMPONENT_NAME: &str = "switch";
+
+#[derive(Debug, Error)]
+pub enum SwitchError {
+    #[error(transparent)]
+    SwitchBoardError(#[from] BoardError),
+    #[error(transparent)]
+    SwitchConfigAttributeError(#[from] AttributeError),
+    #[error("config error {0}")]
+    SwitchConfigurationError(&'static str),
+    #[error("invalid position {0}")]
+    SwitchInvalidPosition(u32),
+}
+
+pub trait Switch: Status + DoCommand {
+    /// Sets the switch to `position`, positions are numbered from 0
+    fn set_position(&mut self, position: u32) -> Result<(), SwitchError>;
+
+    /// Gets the current position of the switch
+    fn get_position(&mut self) -> Result<u32, SwitchError>;
+
+    /// Gets the number of positions the switch can be set to
+    fn get_number_of_positions(&mut self) -> Result<u32, SwitchError>;
+}
+
+pub type SwitchType = Arc<Mutex<dyn Switch>>;
+
+impl<L> Switch for Mutex<L>
+where
+    L: ?Sized + Switch,
+{
+    fn set_position(&mut self, position: u32) -> Result<(), SwitchError> {
+        self.get_mut().unwrap().set_position(position)
+    }
+    fn get_position(&mut self) -> Result<u32, SwitchError> {
+        self.get_mut().unwrap().get_position()
+    }
+    fn get_number_of_positions(&mut self) -> Result<u32, SwitchError> {
+        self.get_mut().unwrap().get_number_of_positions()
+    }
+}
+
+impl<A> Switch for Arc<Mutex<A>>
+where
+    A: ?Sized + Switch,
+{
+    fn set_position(&mut self, position: u32) -> Result<(), SwitchError> {
+        self.lock().unwrap().set_position(position)
+    }
+    fn get_position(&mut self) -> Result<u32, SwitchError> {
+        self.lock().unwrap().get_position()
+    }
+    fn get_number_of_positions(&mut self) -> Result<u32, SwitchError> {
+        self.lock().unwrap().get_number_of_positions()
+    }
+}
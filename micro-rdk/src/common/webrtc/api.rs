@@ -2,7 +2,7 @@
 use std::{
     fmt::Debug,
     io::{self, Cursor},
-    net::{Ipv4Addr, UdpSocket},
+    net::{Ipv4Addr, Ipv6Addr, UdpSocket},
     pin::Pin,
     rc::Rc,
     sync::{atomic::AtomicBool, Arc, Mutex},
@@ -13,7 +13,7 @@ use std::{
 use crate::{
     common::{
         app_client::{AppClient, AppClientError, AppSignaling, PeriodicAppClientTask},
-        conn::{errors::ServerError, server::WebRTCConnection},
+        conn::{errors::ServerError, network::NetworkAddresses, server::WebRTCConnection},
         grpc::{GrpcError, GrpcServer},
         grpc_client::{GrpcClientError, GrpcMessageStream},
         robot::LocalRobot,
@@ -52,12 +52,19 @@ use super::{
     dtls::DtlsConnector,
     exec::WebRtcExecutor,
     grpc::{WebRtcGrpcBody, WebRtcGrpcServer},
-    ice::{ICEAgent, ICECredentials},
+    ice::{ice_servers_from_proto, ICEAgent, ICECredentials, IceServer},
     io::WebRtcTransport,
     sctp::{Channel, SctpConnector, SctpHandle},
     signaling_server::LocalSignaling,
 };
 
+#[cfg(feature = "camera")]
+use super::{
+    media::{MediaSession, StreamServer},
+    srtp::SrtpContext,
+    udp_mux::BundledSender,
+};
+
 #[derive(Error, Debug)]
 pub enum WebRtcError {
     #[error("signaling server disconnected")]
@@ -86,6 +93,8 @@ pub enum WebRtcError {
     CannotParseCandidate,
     #[error("Operation timeout")]
     OperationTimeout,
+    #[error("ice connection lost")]
+    IceDisconnected,
     #[error(transparent)]
     GrpcClientError(#[from] GrpcClientError),
 }
@@ -96,6 +105,7 @@ pub(crate) struct WebRtcSignalingChannel {
     signaling: WebRtcSignaling,
     engine: Box<general_purpose::GeneralPurpose>,
     sdp: Box<WebRtcSdp>,
+    ice_servers: Vec<IceServer>,
 }
 
 impl WebRtcSignalingChannel {
@@ -104,8 +114,17 @@ impl WebRtcSignalingChannel {
             signaling,
             engine: general_purpose::STANDARD.into(),
             sdp,
+            ice_servers: vec![],
         }
     }
+    /// ICE servers handed out by the signaling server for this call
+    pub(crate) fn with_ice_servers(mut self, ice_servers: Vec<IceServer>) -> Self {
+        self.ice_servers = ice_servers;
+        self
+    }
+    pub(crate) fn ice_servers(&self) -> &[IceServer] {
+        &self.ice_servers
+    }
     pub(crate) fn offer(&self) -> &WebRtcSdp {
         &self.sdp
     }
@@ -291,7 +310,21 @@ impl PeriodicAppClientTask for SignalingTask {
                 }
             }
             let sdp = sdp?;
-            let sig = Box::new(WebRtcSignalingChannel::new(Either::Left(sig_pair), sdp));
+            // TURN credentials are short lived, they are fetched for every call
+            let ice_servers = match app_client.get_webrtc_config(self.rpc_host.clone()).await {
+                Ok(resp) => resp
+                    .config
+                    .map(|c| ice_servers_from_proto(&c.additional_ice_servers))
+                    .unwrap_or_default(),
+                Err(e) => {
+                    log::warn!("failed to get ice servers from signaling: {}", e);
+                    vec![]
+                }
+            };
+            let sig = Box::new(
+                WebRtcSignalingChannel::new(Either::Left(sig_pair), sdp)
+                    .with_ice_servers(ice_servers),
+            );
             let _ret = self.sender.send(sig).await; // TODO deal with result, sending on a close channel will never succeed. The limit here is that SignalingTask will be allocated for the lifetime of the ViamServer.
             Ok(None)
         })
@@ -538,9 +571,31 @@ pub struct WebRtcApi<C, E> {
     certificate: Rc<C>,
     local_creds: ICECredentials,
     remote_creds: Option<ICECredentials>,
-    local_ip: Ipv4Addr,
+    addresses: NetworkAddresses,
+    // the ice agent re-gathers its candidates when the addresses of the device change
+    address_changes: Option<async_channel::Receiver<NetworkAddresses>>,
     dtls: Option<Box<dyn DtlsConnector>>,
     ice_agent: AtomicSync,
+    // set once the ice agent stopped, the connection is lost at that point
+    ice_disconnected: AtomicSync,
+    ice_servers: Vec<IceServer>,
+    // negotiation channel and SRTP session of the video tracks, set once the data channels are
+    // open if the DTLS handshake exported the SRTP keys
+    #[cfg(feature = "camera")]
+    media: Option<(Channel, SrtpContext, BundledSender)>,
+}
+
+// binds an IPv6 socket that also accepts IPv4 traffic as v4-mapped addresses, the default
+// for IPV6_V6ONLY differs between platforms so it's set explicitly
+fn bind_dual_stack() -> std::io::Result<UdpSocket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::IPV6,
+        socket2::Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+    socket.set_only_v6(false)?;
+    socket.bind(&std::net::SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)).into())?;
+    Ok(socket.into())
 }
 
 impl<'a, C, E> WebRtcApi<C, E>
@@ -552,10 +607,19 @@ where
         executor: E,
         signaling: Box<WebRtcSignalingChannel>,
         certificate: Rc<C>,
-        local_ip: Ipv4Addr,
+        addresses: NetworkAddresses,
         dtls: Box<dyn DtlsConnector>,
+        ice_servers: Vec<IceServer>,
+        address_changes: Option<async_channel::Receiver<NetworkAddresses>>,
     ) -> Self {
-        let udp = Arc::new(async_io::Async::<UdpSocket>::bind(([0, 0, 0, 0], 0)).unwrap());
+        // when IPv6 is available a single dual stack socket carries both address families
+        let udp = addresses
+            .ipv6
+            .and_then(|_| bind_dual_stack().and_then(async_io::Async::new).ok())
+            .unwrap_or_else(|| {
+                async_io::Async::<UdpSocket>::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap()
+            });
+        let udp = Arc::new(udp);
 
         let transport = WebRtcTransport::new(udp);
 
@@ -566,9 +630,14 @@ where
             certificate,
             remote_creds: None,
             local_creds: Default::default(),
-            local_ip,
+            addresses,
+            address_changes,
             dtls: Some(dtls),
             ice_agent: AtomicSync::default(),
+            ice_disconnected: AtomicSync::default(),
+            ice_servers,
+            #[cfg(feature = "camera")]
+            media: None,
         }
     }
 
@@ -582,7 +651,9 @@ where
             ice_transport,
             self.local_creds.clone(),
             self.remote_creds.as_ref().unwrap().clone(),
-            self.local_ip,
+            self.addresses,
+            self.ice_servers.clone(),
+            self.address_changes.take(),
         );
 
         self.signaling.send_sdp_answer(answer).await?;
@@ -614,8 +685,10 @@ where
         let sync = AtomicSync::default();
         let sync_clone = sync.clone();
         let die_clone = self.ice_agent.clone();
+        let disconnected = self.ice_disconnected.clone();
         self.executor.execute(Box::pin(async move {
             ice_agent.run(sync, die_clone).await;
+            disconnected.done();
         }));
 
         while !sync_clone.get() {
@@ -644,8 +717,16 @@ where
             }
         }
 
-        sync_clone.await;
-        Ok(())
+        let disconnected = self.ice_disconnected.clone();
+        async {
+            sync_clone.await;
+            Ok(())
+        }
+        .or(async {
+            disconnected.await;
+            Err(WebRtcError::IceDisconnected)
+        })
+        .await
     }
 
     async fn open_data_channel(&mut self) -> Result<(Channel, SctpHandle), WebRtcError> {
@@ -654,6 +735,9 @@ where
         // TODO(NPM) consider returning an error? We should not take the channel more than once....
         let dtls_transport = self.transport.get_dtls_channel().unwrap();
 
+        #[cfg(feature = "camera")]
+        let media_sender = dtls_transport.bundled_sender();
+
         dtls.set_transport(dtls_transport);
 
         if let Ok(dtls_stream) = dtls
@@ -661,6 +745,20 @@ where
             .map_err(|e| WebRtcError::DtlsError(Box::new(e)))?
             .await
         {
+            // video is sent with the keys exported by the handshake, without them the
+            // connection only carries data channels
+            #[cfg(feature = "camera")]
+            let srtp = dtls_stream
+                .srtp_keying_material()
+                .map_err(|e| WebRtcError::DtlsError(Box::new(e)))
+                .and_then(|material| {
+                    let (key, salt) = material.server_master();
+                    SrtpContext::new(&key, &salt, dtls_stream.srtp_cipher())
+                        .map_err(|e| WebRtcError::DtlsError(Box::new(e)))
+                })
+                .inspect_err(|e| log::warn!("video streaming unavailable: {}", e))
+                .ok();
+
             let (c_tx, c_rx) = async_channel::unbounded();
 
             let sctp = Box::new(SctpConnector::new(dtls_stream, c_tx));
@@ -672,10 +770,19 @@ where
             self.executor.execute(Box::pin(async move {
                 sctp.run().await;
             }));
+            // the gRPC channel is opened first, followed by the negotiation channel
             let channel = c_rx
                 .recv()
                 .await
                 .map_err(|_| WebRtcError::DataChannelOpenError())?;
+            let _negotiation = c_rx
+                .recv()
+                .await
+                .map_err(|_| WebRtcError::DataChannelOpenError())?;
+            #[cfg(feature = "camera")]
+            if let Some(srtp) = srtp {
+                let _ = self.media.insert((_negotiation, srtp, media_sender));
+            }
             return Ok((channel, hnd));
         }
 
@@ -713,13 +820,39 @@ where
                 WebRtcError::OperationTimeout => ServerError::ServerConnectionTimeout,
                 _ => ServerError::Other(e.into()),
             })?;
-        let srv = WebRtcGrpcServer::new(c.0, GrpcServer::new(robot, WebRtcGrpcBody::default()));
-        Ok(WebRTCConnection::new(
+        let grpc = GrpcServer::new(robot, WebRtcGrpcBody::default());
+        #[cfg(feature = "camera")]
+        let stream_server = self.start_media_session(&answer);
+        #[cfg(feature = "camera")]
+        let grpc = match stream_server.clone() {
+            Some(stream_server) => grpc.with_stream_server(stream_server),
+            None => grpc,
+        };
+        let srv = WebRtcGrpcServer::new(c.0, grpc);
+        let connection = WebRTCConnection::new(
             srv,
             self.transport,
             ScopeGuard::into_inner(ice_done_guard),
+            self.ice_disconnected,
             c.1,
-        ))
+        );
+        #[cfg(feature = "camera")]
+        let connection = match stream_server {
+            Some(stream_server) => connection.with_stream_server(stream_server),
+            None => connection,
+        };
+        Ok(connection)
+    }
+
+    /// Spawns the task sending the video tracks of the connection, they are added later on
+    /// through the StreamService
+    #[cfg(feature = "camera")]
+    fn start_media_session(&mut self, answer: &WebRtcSdp) -> Option<StreamServer> {
+        let (negotiation, srtp, sender) = self.media.take()?;
+        let (session, stream_server) =
+            MediaSession::new(negotiation, answer.sdp.clone(), srtp, sender);
+        self.executor.execute(Box::pin(session.run()));
+        Some(stream_server)
     }
 
     pub async fn answer(
@@ -1,13 +1,22 @@
 #![allow(dead_code)]
 use std::{
     fmt::Display,
-    net::{Ipv4Addr, SocketAddrV4},
+    net::{IpAddr, SocketAddr},
     time::{Duration, Instant},
 };
 
 use stun_codec::TransactionId;
 use thiserror::Error;
 
+// Ta, interval between retransmissions of a connectivity check
+const CHECK_RETRANSMIT_INTERVAL: Duration = Duration::from_millis(500);
+// RFC7675 consent is refreshed every 5s on average, the actual interval is randomized between
+// 4s and 6s
+const CONSENT_CHECK_INTERVAL_MS: u64 = 4000;
+const CONSENT_CHECK_JITTER_MS: u64 = 2000;
+// RFC7675 consent is lost when no check succeeded for 30s
+pub(crate) const CONSENT_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum CandidateError {
     #[error("cannot parse candidate")]
@@ -77,7 +86,7 @@ pub struct Candidate {
     pub network_type: NetworkType,
     pub candidate_type: CandidateType,
     pub component: u16,
-    pub address: SocketAddrV4, //Socket addr??
+    pub address: SocketAddr,
     pub raddr: Option<String>,
     pub rport: Option<u16>,
     /// The foundation is an identifier, scoped within a session
@@ -91,13 +100,12 @@ pub struct Candidate {
 
 impl Candidate {
     /// Creates a new server reflexive candidate
-    /// Only supports IpV4
-    pub fn new_srflx_candidate(ip_v4: SocketAddrV4, _base: SocketAddrV4) -> Self {
+    pub fn new_srflx_candidate(addr: SocketAddr, _base: SocketAddr) -> Self {
         Self {
             network_type: NetworkType::UDP,
             candidate_type: CandidateType::ServerReflexive,
             component: 1,
-            address: ip_v4,
+            address: addr,
             raddr: Some("0.0.0.0".to_owned()),
             rport: Some(0),
             foundation: None,
@@ -105,27 +113,38 @@ impl Candidate {
         }
     }
     /// Creates a new host candidate
-    /// Only supports IpV4
-    pub fn new_host_candidate(ip_v4: SocketAddrV4) -> Self {
+    pub fn new_host_candidate(addr: SocketAddr) -> Self {
         Self {
             network_type: NetworkType::UDP, //Always UDP
             candidate_type: CandidateType::Host,
             component: 1, // Always a single strem
-            address: ip_v4,
+            address: addr,
             raddr: None,
             rport: None,
             foundation: None,
             priority: None,
         }
     }
+    /// Creates a new relayed candidate from the address allocated on a TURN server
+    pub fn new_relay_candidate(addr: SocketAddr, _base: SocketAddr) -> Self {
+        Self {
+            network_type: NetworkType::UDP,
+            candidate_type: CandidateType::Relay,
+            component: 1,
+            address: addr,
+            raddr: Some("0.0.0.0".to_owned()),
+            rport: Some(0),
+            foundation: None,
+            priority: None,
+        }
+    }
     /// Creates a new peer reflexive candidate
-    /// Only supports IpV4
-    pub fn new_peer_reflexive(ip_v4: SocketAddrV4, _priority: Option<u32>) -> Self {
+    pub fn new_peer_reflexive(addr: SocketAddr, _priority: Option<u32>) -> Self {
         Self {
             network_type: NetworkType::UDP,
             candidate_type: CandidateType::PeerReflexive,
             component: 1,
-            address: ip_v4,
+            address: addr,
             raddr: None,
             rport: None,
             foundation: None,
@@ -153,7 +172,7 @@ impl Candidate {
         "UDP".to_owned()
     }
 
-    pub(crate) fn address(&self) -> &SocketAddrV4 {
+    pub(crate) fn address(&self) -> &SocketAddr {
         &self.address
     }
 
@@ -229,10 +248,10 @@ impl TryFrom<String> for Candidate {
 
         let address = split[4].to_owned();
 
-        // if the candidate we receive is Ipv6 mDNS we reject it
+        // if the candidate we receive is mDNS we reject it
         // mDNS candidate will be discovered as peer reflexive during connectivity check
         let address = address
-            .parse::<Ipv4Addr>()
+            .parse::<IpAddr>()
             .map_err(|_| CandidateError::CannotParseCandidate)?;
 
         let port = split[5]
@@ -257,7 +276,7 @@ impl TryFrom<String> for Candidate {
             "host" => Ok(Candidate {
                 foundation: Some(fondation),
                 component,
-                address: SocketAddrV4::new(address, port),
+                address: SocketAddr::new(address, port),
                 priority: Some(priority),
                 raddr,
                 rport,
@@ -267,7 +286,7 @@ impl TryFrom<String> for Candidate {
             "srflx" => Ok(Candidate {
                 foundation: Some(fondation),
                 component,
-                address: SocketAddrV4::new(address, port),
+                address: SocketAddr::new(address, port),
                 priority: Some(priority),
                 raddr,
                 rport,
@@ -277,7 +296,7 @@ impl TryFrom<String> for Candidate {
             "prflx" => Ok(Candidate {
                 foundation: Some(fondation),
                 component,
-                address: SocketAddrV4::new(address, port),
+                address: SocketAddr::new(address, port),
                 priority: Some(priority),
                 raddr,
                 rport,
@@ -287,7 +306,7 @@ impl TryFrom<String> for Candidate {
             "relay" => Ok(Candidate {
                 foundation: Some(fondation),
                 component,
-                address: SocketAddrV4::new(address, port),
+                address: SocketAddr::new(address, port),
                 priority: Some(priority),
                 raddr,
                 rport,
@@ -337,6 +356,9 @@ pub struct CandidatePair {
     binding_req_sent: u32,
     /// successful binding requests on this pair
     pub(crate) binding_resp_recv: u32,
+    /// last time a check on this pair succeeded, see RFC7675
+    consent_refreshed: Option<Instant>,
+    consent_interval: Duration,
 }
 
 impl CandidatePair {
@@ -346,8 +368,10 @@ impl CandidatePair {
         local_idx: usize,
         remote_idx: usize,
     ) -> Result<Self, CandidateError> {
-        // Only support ipv4 & udp so just need to check component id is correct
-        if local.component() != remote.component() {
+        // Only support udp so just need to check component id and address family are correct
+        if local.component() != remote.component()
+            || local.address().is_ipv4() != remote.address().is_ipv4()
+        {
             return Err(CandidateError::CannotFormCandidatePair);
         }
         // Remote is always the controlling agent
@@ -366,13 +390,41 @@ impl CandidatePair {
             current_binding_request: None, // store last 4 attempts
             binding_resp_recv: 0,
             binding_req_sent: 0,
+            consent_refreshed: None,
+            consent_interval: Self::next_consent_interval(),
         })
     }
+    fn next_consent_interval() -> Duration {
+        Duration::from_millis(
+            CONSENT_CHECK_INTERVAL_MS + rand::random::<u64>() % CONSENT_CHECK_JITTER_MS,
+        )
+    }
     pub(crate) fn state(&self) -> &CandidatePairState {
         &self.state
     }
+    /// Prevents connectivity checks on this pair until it is unfrozen
+    pub(crate) fn freeze(&mut self) {
+        if self.state == CandidatePairState::Waiting {
+            self.state = CandidatePairState::Frozen;
+        }
+    }
+    pub(crate) fn unfreeze(&mut self) {
+        if self.state == CandidatePairState::Frozen {
+            self.state = CandidatePairState::Waiting;
+        }
+    }
+    /// Marks the pair as nominated by the controlling agent, the pair is selected once it
+    /// also succeeded
+    pub(crate) fn nominate(&mut self) {
+        self.nominated = true;
+    }
+    pub(crate) fn is_nominated(&self) -> bool {
+        self.nominated
+    }
     /// create a new binding request if None have been created already other returns the
-    /// TransactionId of the last request
+    /// TransactionId of the last request.
+    /// Once the pair succeeded a new request is made every consent interval to keep the
+    /// consent fresh
     pub(crate) fn create_new_binding_request(&mut self, now: Instant) -> Option<TransactionId> {
         match self.state {
             CandidatePairState::Frozen => {
@@ -384,10 +436,10 @@ impl CandidatePair {
             CandidatePairState::Waiting => {
                 self.state = CandidatePairState::InProgress;
             }
-            CandidatePairState::InProgress | CandidatePairState::Succeeded => {
+            CandidatePairState::InProgress => {
                 if let Some(req) = self.current_binding_request.as_mut() {
                     // Retry while pair is InProgress, Ta is set a 500ms.
-                    if now - req.req_time < Duration::from_millis(500) {
+                    if now - req.req_time < CHECK_RETRANSMIT_INTERVAL {
                         return None;
                     }
                     if !req.resp_recv {
@@ -397,6 +449,14 @@ impl CandidatePair {
                     }
                 }
             }
+            CandidatePairState::Succeeded => {
+                if let Some(req) = self.current_binding_request.as_ref() {
+                    if now - req.req_time < self.consent_interval {
+                        return None;
+                    }
+                }
+                self.consent_interval = Self::next_consent_interval();
+            }
         }
         let id = TransactionId::new(rand::random());
         let _ = self.current_binding_request.insert(BindingRequests {
@@ -409,22 +469,37 @@ impl CandidatePair {
         Some(id)
     }
 
-    /// Check if the CandidatePair should be set to fail
-    pub fn update_pair_status(&mut self) {
-        if self.state != CandidatePairState::Failed
-            && self.binding_req_sent > self.binding_req_recv
-            && self.binding_req_sent - self.binding_req_recv > 50
-        {
-            // after 20 failed attempts mark the pair as failed
-            self.state = CandidatePairState::Failed;
+    /// Check if the CandidatePair should be set to fail, a pair that succeeded fails when its
+    /// consent expired
+    pub fn update_pair_status(&mut self, now: Instant) {
+        match self.state {
+            CandidatePairState::Failed => {}
+            CandidatePairState::Succeeded => {
+                if self
+                    .consent_refreshed
+                    .is_some_and(|t| now.duration_since(t) > CONSENT_TIMEOUT)
+                {
+                    log::debug!("consent expired for pair {:?}", self);
+                    self.state = CandidatePairState::Failed;
+                }
+            }
+            _ => {
+                if self.binding_req_sent > self.binding_req_recv
+                    && self.binding_req_sent - self.binding_req_recv > 50
+                {
+                    // after 20 failed attempts mark the pair as failed
+                    self.state = CandidatePairState::Failed;
+                }
+            }
         }
     }
     /// Check if a binding response belongs to this Pair
-    pub fn binding_response(&mut self, _now: &Instant, id: &TransactionId) -> bool {
+    pub fn binding_response(&mut self, now: &Instant, id: &TransactionId) -> bool {
         if let Some(req) = self.current_binding_request.as_mut() {
             if req.id == *id {
                 req.resp_recv = true;
                 self.binding_req_recv += 1;
+                let _ = self.consent_refreshed.insert(*now);
                 self.state = CandidatePairState::Succeeded;
                 log::debug!("Pair succeeded {:?}", self);
                 return true;
@@ -468,10 +543,16 @@ impl PartialEq for BindingRequests {
 
 #[cfg(test)]
 mod tests {
-    use std::net::SocketAddrV4;
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
 
     use super::Candidate;
+    use super::CandidatePair;
+    use super::CandidatePairState;
     use super::CandidateType;
+    use super::CONSENT_TIMEOUT;
 
     #[test_log::test]
     fn test_parse_candidate_string() {
@@ -483,7 +564,11 @@ mod tests {
             "candidate:830412194 1 udp 1694498815 ::1 49701 typ host raddr 0.0.0.0 rport 49701"
                 .to_owned();
         let ret = TryInto::<Candidate>::try_into(c1);
-        assert!(ret.is_err());
+        assert!(ret.is_ok());
+        assert_eq!(
+            ret.unwrap().address,
+            SocketAddr::new("::1".parse().unwrap(), 49701)
+        );
 
         let c1 = "candidate:2230659787 1 udp 2130706431 10.1.2.3 54182 typ host".to_owned();
         let ret = TryInto::<Candidate>::try_into(c1);
@@ -493,7 +578,7 @@ mod tests {
         assert_eq!(c1.candidate_type, CandidateType::Host);
         assert_eq!(
             c1.address,
-            SocketAddrV4::new("10.1.2.3".parse().unwrap(), 54182)
+            SocketAddr::new("10.1.2.3".parse().unwrap(), 54182)
         );
         assert_eq!(c1.priority.unwrap(), 2130706431);
         assert_eq!(c1.component, 1);
@@ -509,7 +594,7 @@ mod tests {
         assert_eq!(c1.candidate_type, CandidateType::ServerReflexive);
         assert_eq!(
             c1.address,
-            SocketAddrV4::new("71.167.39.185".parse().unwrap(), 49701)
+            SocketAddr::new("71.167.39.185".parse().unwrap(), 49701)
         );
         assert_eq!(c1.priority.unwrap(), 1694498815);
         assert_eq!(c1.component, 1);
@@ -521,15 +606,15 @@ mod tests {
     #[test_log::test]
     fn test_candidate_to_string() {
         let c1 =
-            Candidate::new_host_candidate(SocketAddrV4::new("127.0.0.1".parse().unwrap(), 61322));
+            Candidate::new_host_candidate(SocketAddr::new("127.0.0.1".parse().unwrap(), 61322));
 
         let r = format!("{c1}");
 
         assert_eq!("candidate:0 1 UDP 2130706431 127.0.0.1 61322 typ host", r);
 
         let c1 = Candidate::new_srflx_candidate(
-            SocketAddrV4::new("89.72.32.132".parse().unwrap(), 61322),
-            SocketAddrV4::new("127.0.0.1".parse().unwrap(), 61322),
+            SocketAddr::new("89.72.32.132".parse().unwrap(), 61322),
+            SocketAddr::new("127.0.0.1".parse().unwrap(), 61322),
         );
 
         let r = format!("{c1}");
@@ -538,5 +623,54 @@ mod tests {
             "candidate:1 1 UDP 1694498815 89.72.32.132 61322 typ srflx raddr 0.0.0.0 rport 0",
             r
         );
+
+        let c1 = Candidate::new_relay_candidate(
+            SocketAddr::new("34.72.32.10".parse().unwrap(), 50122),
+            SocketAddr::new("127.0.0.1".parse().unwrap(), 61322),
+        );
+
+        let r = format!("{c1}");
+
+        assert_eq!(
+            "candidate:2 1 UDP 16777215 34.72.32.10 50122 typ relay raddr 0.0.0.0 rport 0",
+            r
+        );
+
+        let c1 =
+            Candidate::new_host_candidate(SocketAddr::new("2001:db8::12".parse().unwrap(), 61322));
+
+        let r = format!("{c1}");
+
+        assert_eq!(
+            "candidate:0 1 UDP 2130706431 2001:db8::12 61322 typ host",
+            r
+        );
+    }
+
+    #[test_log::test]
+    fn test_pair_consent() {
+        let local = Candidate::new_host_candidate(SocketAddr::new("10.1.2.4".parse().unwrap(), 1));
+        let remote = Candidate::new_host_candidate(SocketAddr::new("10.1.2.3".parse().unwrap(), 2));
+        let mut pair = CandidatePair::new(&local, &remote, 0, 0).unwrap();
+        let now = Instant::now();
+
+        let id = pair.create_new_binding_request(now).unwrap();
+        assert_eq!(*pair.state(), CandidatePairState::InProgress);
+        assert!(pair.binding_response(&now, &id));
+        assert_eq!(*pair.state(), CandidatePairState::Succeeded);
+
+        // consent checks are spaced by 4 to 6 seconds once the pair succeeded
+        assert!(pair
+            .create_new_binding_request(now + Duration::from_secs(1))
+            .is_none());
+        let consent = pair
+            .create_new_binding_request(now + Duration::from_secs(6))
+            .unwrap();
+        assert_ne!(consent, id);
+
+        pair.update_pair_status(now + Duration::from_secs(6));
+        assert_eq!(*pair.state(), CandidatePairState::Succeeded);
+        pair.update_pair_status(now + CONSENT_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(*pair.state(), CandidatePairState::Failed);
     }
 }
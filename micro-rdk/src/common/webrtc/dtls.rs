@@ -4,7 +4,10 @@ use futures_lite::{AsyncRead, AsyncWrite, Future};
 use crate::esp32::dtls::SSLError;
 use thiserror::Error;
 
-use super::udp_mux::UdpMux;
+use super::{
+    srtp::{SrtpCipher, SrtpKeyingMaterial},
+    udp_mux::UdpMux,
+};
 
 #[derive(Error, Debug)]
 pub enum DtlsError {
@@ -15,8 +18,13 @@ pub enum DtlsError {
     DtlsSslError(#[from] SSLError),
 }
 
-pub trait DtlsStream: AsyncRead + AsyncWrite + Send + Unpin {}
-impl<T> DtlsStream for T where T: AsyncRead + AsyncWrite + Send + Unpin {}
+pub trait DtlsStream: AsyncRead + AsyncWrite + Send + Unpin {
+    /// Keying material of the SRTP session negotiated with the use_srtp extension (RFC 5764),
+    /// only available once the handshake completed
+    fn srtp_keying_material(&self) -> Result<SrtpKeyingMaterial, DtlsError>;
+    /// Primitives protecting SRTP packets, backed by the same crypto library as DTLS
+    fn srtp_cipher(&self) -> Box<dyn SrtpCipher>;
+}
 pub trait IntoDtlsStream: Future<Output = Result<Box<dyn DtlsStream>, DtlsError>> {}
 
 pub trait DtlsConnector {
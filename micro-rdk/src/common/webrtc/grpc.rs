@@ -2,15 +2,17 @@
 #![allow(clippy::read_zero_byte_vec)]
 use std::{
     collections::HashMap,
+    pin::Pin,
     time::{Duration, Instant},
 };
 
 use bytes::{Bytes, BytesMut};
-use futures_lite::AsyncReadExt;
+use futures_lite::{AsyncReadExt, Future, StreamExt};
+use futures_util::stream::FuturesUnordered;
 use prost::Message;
 
 use crate::{
-    common::grpc::{GrpcResponse, ServerError},
+    common::grpc::{DeferredResponse, GrpcResponse, ServerError},
     google::rpc::Status,
     proto::rpc::webrtc::{
         self,
@@ -63,11 +65,20 @@ impl GrpcResponse for WebRtcGrpcBody {
     }
 }
 
+type PendingRpc = Pin<Box<dyn Future<Output = (u32, Result<Bytes, ServerError>)>>>;
+
+enum NextRpc {
+    Call(u32),
+    Deferred(u32, Result<Bytes, ServerError>),
+}
+
+// the last field is the position of a server stream, see `WebRtcGrpcService::server_stream_rpc`
 #[derive(Debug)]
 struct RpcCall(
     webrtc::v1::RequestHeaders,
     Option<Instant>,
     Option<RequestMessage>,
+    Option<u64>,
 );
 
 pub struct WebRtcGrpcServer<S> {
@@ -76,16 +87,47 @@ pub struct WebRtcGrpcServer<S> {
     stream: Option<webrtc::v1::Stream>,
     headers: Option<RequestHeaders>,
     streams: HashMap<u32, RpcCall>,
+    // unary calls waiting on the robot to respond, the connection keeps serving other
+    // calls in the meantime (e.g. a Stop interrupting a GoFor)
+    deferred: FuturesUnordered<PendingRpc>,
     buffer: BytesMut,
 }
 
 pub trait WebRtcGrpcService {
     fn unary_rpc(&mut self, method: &str, data: &Bytes) -> Result<Bytes, ServerError>;
+    /// Returns the pending response of a unary RPC which completes once the robot is done
+    /// acting on it, or None if `method` should be served by `unary_rpc`
+    fn deferred_unary_rpc(&mut self, method: &str, data: &Bytes) -> Option<DeferredResponse>;
+    /// Returns the next message of a server stream, if any, and when to call again.
+    /// `position` is kept between the calls of a stream for the service to track what
+    /// the stream already sent
     fn server_stream_rpc(
         &mut self,
         method: &str,
         data: &Bytes,
-    ) -> Result<(Bytes, Instant), ServerError>;
+        position: &mut Option<u64>,
+    ) -> Result<(Option<Bytes>, Instant), ServerError>;
+    /// Returns true if `method` is a server stream served by `server_stream_rpc`
+    fn is_server_stream(&self, method: &str) -> bool;
+}
+
+// calls `service` with a message of `method`, returns the response message if any and, for
+// server streams, when to call again
+fn call_service<S: WebRtcGrpcService>(
+    service: &mut S,
+    method: &str,
+    data: &Bytes,
+    position: &mut Option<u64>,
+) -> Result<(Option<Bytes>, Option<Instant>), ServerError> {
+    if service.is_server_stream(method) {
+        service
+            .server_stream_rpc(method, data, position)
+            .map(|(message, next)| (message, Some(next)))
+    } else {
+        service
+            .unary_rpc(method, data)
+            .map(|message| (Some(message), None))
+    }
 }
 
 impl<S> WebRtcGrpcServer<S>
@@ -99,6 +141,7 @@ where
             stream: None,
             headers: None,
             streams: HashMap::new(),
+            deferred: FuturesUnordered::new(),
             buffer: BytesMut::zeroed(WEBRTC_GRPC_BUFFER_SIZE),
         }
     }
@@ -118,38 +161,25 @@ where
         stream: Stream,
         msg: &RequestMessage,
         hdr: &RequestHeaders,
+        position: &mut Option<u64>,
     ) -> Result<(Status, Option<Instant>), WebRtcError> {
         let method = &hdr.method;
         log::debug!("processing req {:?}", method);
         let ret = if let Some(pkt) = msg.packet_message.as_ref() {
-            if method.contains("Stream") {
-                match self.service.server_stream_rpc(method, &pkt.data) {
-                    Ok(data) => {
-                        self.send_rpc_response(data.0, stream).await?;
-                        (
-                            Status {
-                                code: 0,
-                                ..Default::default()
-                            },
-                            Some(data.1),
-                        )
-                    }
-                    Err(e) => (e.to_status(), None),
-                }
-            } else {
-                match self.service.unary_rpc(method, &pkt.data) {
-                    Ok(data) => {
-                        self.send_rpc_response(data, stream).await?;
-                        (
-                            Status {
-                                code: 0,
-                                ..Default::default()
-                            },
-                            None,
-                        )
+            match call_service(&mut self.service, method, &pkt.data, position) {
+                Ok((message, next)) => {
+                    if let Some(message) = message {
+                        self.send_rpc_response(message, stream).await?;
                     }
-                    Err(e) => (e.to_status(), None),
+                    (
+                        Status {
+                            code: 0,
+                            ..Default::default()
+                        },
+                        next,
+                    )
                 }
+                Err(e) => (e.to_status(), None),
             }
         } else {
             (
@@ -206,7 +236,7 @@ where
                         };
                         let _ = self.streams.insert(
                             req.stream.as_ref().unwrap().id as u32,
-                            RpcCall(hdr, None, None),
+                            RpcCall(hdr, None, None, None),
                         );
 
                         self.send_response(header_response).await?;
@@ -265,14 +295,60 @@ where
             })
             .unwrap_or((0, async_io::Timer::never()));
 
-        let id = futures_lite::future::or(async { self.next_rpc_call().await }, async {
-            next_stream.1.await;
-            Ok(next_stream.0)
-        })
-        .await?;
+        // the pending calls are moved out so they can be polled alongside next_rpc_call
+        let mut deferred = std::mem::take(&mut self.deferred);
+        let next = futures_lite::future::or(
+            futures_lite::future::or(
+                async { self.next_rpc_call().await.map(NextRpc::Call) },
+                async {
+                    next_stream.1.await;
+                    Ok(NextRpc::Call(next_stream.0))
+                },
+            ),
+            async {
+                match deferred.next().await {
+                    Some((id, resp)) => Ok(NextRpc::Deferred(id, resp)),
+                    None => futures_lite::future::pending().await,
+                }
+            },
+        )
+        .await;
+        self.deferred = deferred;
+        let id = match next? {
+            NextRpc::Call(id) => id,
+            NextRpc::Deferred(id, resp) => {
+                let stream = Stream { id: id as u64 };
+                let status = match resp {
+                    Ok(data) => {
+                        self.send_rpc_response(data, stream.clone()).await?;
+                        Status {
+                            code: 0,
+                            ..Default::default()
+                        }
+                    }
+                    Err(e) => e.to_status(),
+                };
+                return self.send_trailers(stream, status).await;
+            }
+        };
         if let Some(mut call) = self.streams.remove(&id) {
+            let pending = call
+                .2
+                .as_ref()
+                .and_then(|msg| msg.packet_message.as_ref())
+                .and_then(|pkt| self.service.deferred_unary_rpc(&call.0.method, &pkt.data));
+            if let Some(pending) = pending {
+                self.deferred
+                    .push(Box::pin(async move { (id, pending.await) }));
+                return Ok(());
+            }
             let r = self
-                .process_rpc_request(Stream { id: id as u64 }, call.2.as_ref().unwrap(), &call.0)
+                .process_rpc_request(
+                    Stream { id: id as u64 },
+                    call.2.as_ref().unwrap(),
+                    &call.0,
+                    &mut call.3,
+                )
                 .await?;
             if let Some(next) = r.1 {
                 let _ = call.1.insert(next);
@@ -284,3 +360,48 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(feature = "camera", feature = "builtin-components"))]
+    #[test_log::test]
+    fn test_list_streams_is_unary() {
+        use std::sync::{Arc, Mutex};
+
+        use bytes::Bytes;
+        use prost::Message;
+
+        use super::{call_service, WebRtcGrpcBody, WebRtcGrpcService};
+        use crate::{
+            common::{config::DynamicComponentConfig, grpc::GrpcServer, robot::LocalRobot},
+            proto::stream::v1::{ListStreamsRequest, ListStreamsResponse},
+        };
+
+        let mut robot = LocalRobot::default();
+        robot
+            .process_components(
+                vec![Some(DynamicComponentConfig {
+                    name: "camera".to_owned(),
+                    namespace: "rdk".to_owned(),
+                    r#type: "camera".to_owned(),
+                    model: "rdk:builtin:fake".to_owned(),
+                    ..Default::default()
+                })],
+                &mut Box::default(),
+            )
+            .unwrap();
+        let mut grpc = GrpcServer::new(Arc::new(Mutex::new(robot)), WebRtcGrpcBody::default());
+
+        assert!(grpc.is_server_stream("/viam.robot.v1.RobotService/StreamStatus"));
+        let (message, next) = call_service(
+            &mut grpc,
+            "/proto.stream.v1.StreamService/ListStreams",
+            &Bytes::from(ListStreamsRequest {}.encode_to_vec()),
+            &mut None,
+        )
+        .unwrap();
+        assert!(next.is_none());
+        let response = ListStreamsResponse::decode(message.unwrap()).unwrap();
+        assert_eq!(response.names, vec!["camera".to_owned()]);
+    }
+}
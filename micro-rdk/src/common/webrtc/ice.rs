@@ -1,14 +1,24 @@
+//! ICE agent of a WebRTC connection, micro-RDK is always the controlled agent.
+//!
+//! The agent doesn't perform ICE restarts: credentials never change and no new offer or
+//! candidate reaches the peer once connected. When the addresses of the device change it
+//! re-gathers its local candidates, checks them against the remote candidates it knows and
+//! waits for the peer, see `ICEAgent::regather_candidates`. The connection only recovers if the
+//! controlling agent accepts our checks from the new address as peer reflexive candidates and
+//! nominates one of these pairs, it fails with `IceError::IceConsentExpired` otherwise.
 #![allow(dead_code)]
 use std::{
+    collections::VecDeque,
+    fmt::Display,
     io,
-    net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
     pin::Pin,
     time::{Duration, Instant},
 };
 
 use async_io::Timer;
 use bytecodec::{DecodeExt, EncodeExt};
-use bytes::{Bytes, BytesMut};
+use bytes::BytesMut;
 use thiserror::Error;
 
 use futures_lite::{Future, FutureExt};
@@ -23,14 +33,32 @@ use stun_codec::{
     Message, MessageClass, TransactionId,
 };
 
-use crate::{common::webrtc::candidates::CandidatePairState, IceAttribute};
+use crate::{
+    common::{conn::network::NetworkAddresses, webrtc::candidates::CandidatePairState},
+    proto::rpc::webrtc::v1::IceServer as IceServerProto,
+    IceAttribute,
+};
 
 use super::{
     api::AtomicSync,
-    candidates::{Candidate, CandidateError, CandidatePair, CandidateType},
+    candidates::{Candidate, CandidateError, CandidatePair, CandidateType, CONSENT_TIMEOUT},
+    turn::{
+        decode_channel_data, encode_channel_data, is_channel_data, is_turn_method, TurnAttribute,
+        TurnClient, TurnEvent,
+    },
     udp_mux::UdpMux,
 };
 
+const DEFAULT_STUN_PORT: u16 = 3478;
+// number of attempts made to reach a STUN or TURN server while gathering candidates
+const SERVER_REQUEST_ATTEMPTS: usize = 3;
+// number of packets from peers kept while waiting on a STUN or TURN server
+const MAX_PENDING_PACKETS: usize = 16;
+// relayed pairs are only checked after this delay, unless all the other pairs failed already
+const RELAY_CHECK_DELAY: Duration = Duration::from_secs(2);
+// upper bound of the checklist size, lowest priority pairs are dropped beyond it
+const MAX_CANDIDATE_PAIRS: usize = 32;
+
 #[derive(Clone, Debug)]
 pub struct ICECredentials {
     pub(crate) u_frag: String,
@@ -52,6 +80,123 @@ impl ICECredentials {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IceServerKind {
+    Stun,
+    Turn,
+}
+
+/// A STUN or TURN server used to gather server reflexive and relayed candidates.
+/// Only UDP is supported to reach the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IceServer {
+    kind: IceServerKind,
+    host: String,
+    port: u16,
+    username: Option<String>,
+    credential: Option<String>,
+}
+
+impl IceServer {
+    pub fn new_stun(host: String, port: u16) -> Self {
+        Self {
+            kind: IceServerKind::Stun,
+            host,
+            port,
+            username: None,
+            credential: None,
+        }
+    }
+    pub fn new_turn(host: String, port: u16, username: String, credential: String) -> Self {
+        Self {
+            kind: IceServerKind::Turn,
+            host,
+            port,
+            username: Some(username),
+            credential: Some(credential),
+        }
+    }
+    /// Parses a `stun:host[:port]` or `turn:host[:port][?transport=udp]` url
+    pub fn from_url(
+        url: &str,
+        username: Option<String>,
+        credential: Option<String>,
+    ) -> Result<Self, IceError> {
+        let unsupported = || IceError::IceUnsupportedIceServer(url.to_owned());
+        let (scheme, rest) = url.split_once(':').ok_or_else(unsupported)?;
+        let kind = match scheme {
+            "stun" => IceServerKind::Stun,
+            "turn" => IceServerKind::Turn,
+            _ => return Err(unsupported()),
+        };
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+        if query.is_some_and(|q| q != "transport=udp") {
+            return Err(unsupported());
+        }
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>().map_err(|_| unsupported())?),
+            None => (address, DEFAULT_STUN_PORT),
+        };
+        if host.is_empty() {
+            return Err(unsupported());
+        }
+        Ok(Self {
+            kind,
+            host: host.to_owned(),
+            port,
+            username,
+            credential,
+        })
+    }
+    pub fn kind(&self) -> IceServerKind {
+        self.kind
+    }
+    // resolves the server to an address of the requested family
+    fn resolve(&self, ipv6: bool) -> Result<SocketAddr, IceError> {
+        (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|_| IceError::IceServerUnreachable)?
+            .find(|addr| addr.is_ipv6() == ipv6)
+            .ok_or(IceError::IceServerNoAddressForFamily)
+    }
+}
+
+impl Display for IceServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            IceServerKind::Stun => write!(f, "stun:{}:{}", self.host, self.port),
+            IceServerKind::Turn => write!(f, "turn:{}:{}", self.host, self.port),
+        }
+    }
+}
+
+/// Converts the ice servers received from signaling, urls that can't be used are skipped
+pub(crate) fn ice_servers_from_proto(servers: &[IceServerProto]) -> Vec<IceServer> {
+    servers
+        .iter()
+        .flat_map(|server| {
+            server.urls.iter().filter_map(|url| {
+                let username = (!server.username.is_empty()).then(|| server.username.clone());
+                let credential = (!server.credential.is_empty()).then(|| server.credential.clone());
+                IceServer::from_url(url, username, credential)
+                    .inspect_err(|e| log::debug!("skipping ice server: {}", e))
+                    .ok()
+            })
+        })
+        .collect()
+}
+
+/// Servers used when none were configured
+pub(crate) fn default_ice_servers() -> Vec<IceServer> {
+    vec![IceServer::new_stun(
+        "global.stun.twilio.com".to_owned(),
+        DEFAULT_STUN_PORT,
+    )]
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum IceError {
     #[error("candidate channel closed")]
@@ -82,13 +227,30 @@ pub enum IceError {
     IceStunDecodingError,
     #[error("ice operation timeout")]
     IceTimeout,
+    #[error("unsupported ice server {0}")]
+    IceUnsupportedIceServer(String),
+    #[error("ice server unreachable")]
+    IceServerUnreachable,
+    #[error("ice server has no address for this address family")]
+    IceServerNoAddressForFamily,
+    #[error("turn server answered with error {0}")]
+    IceTurnError(u16),
+    #[error("turn allocation failed")]
+    IceTurnAllocationFailed,
+    #[error("no turn channel available")]
+    IceTurnNoChannelAvailable,
+    #[error("missing ice-controlling attribute")]
+    IceMissingControllingAttribute,
+    #[error("consent to send expired")]
+    IceConsentExpired,
     #[error(transparent)]
     IceCandidateError(#[from] CandidateError),
 }
 
 enum IceEvent {
     CandidateReceived(Candidate),
-    StunPacketReceived((usize, SocketAddrV4)),
+    StunPacketReceived((usize, SocketAddr)),
+    AddressChanged(NetworkAddresses),
 }
 
 /// ICE Agent implementation for micro-RDK, the goal is to keep it lightweight. Therefore it doesn't
@@ -97,10 +259,15 @@ enum IceEvent {
 /// * Only support ICE-CONTROLLED
 /// * Doesn't resolve local mDNS candidate presented
 /// * Doesn't do a best effort to find a better pair once one was nominated
-/// * Doesn't support Ice Restart
-/// * Doesn't support freeing candidates
+/// * Doesn't support Ice Restart, candidates are gathered again in place when the addresses of
+///   the device change
+/// * Only the relayed candidate is freed, when another pair is selected
 /// * Can only do trickle ice
 /// * Adding/Removing tracks
+/// * Only one relayed candidate is gathered, from the first TURN server that grants an allocation
+///
+/// Once a nominated pair succeeded it becomes the only pair of the checklist, its consent is then
+/// refreshed as described in RFC7675 and the agent stops when the consent expires
 pub struct ICEAgent {
     pub(crate) local_candidates: Vec<Candidate>,
     remote_candidates: Vec<Candidate>,
@@ -110,7 +277,15 @@ pub struct ICEAgent {
     local_credentials: ICECredentials,
     remote_credentials: ICECredentials,
     state: ICEAgentState,
-    local_ip: Ipv4Addr,
+    addresses: NetworkAddresses,
+    address_changes: Option<async_channel::Receiver<NetworkAddresses>>,
+    ice_servers: Vec<IceServer>,
+    turn_client: Option<TurnClient>,
+    checks_started: Option<Instant>,
+    regathered: Option<Instant>,
+    // packets from peers received while waiting on a STUN or TURN server, they are processed
+    // once gathering is done
+    pending_packets: VecDeque<(Vec<u8>, SocketAddr)>,
 }
 
 impl Drop for ICEAgent {
@@ -131,7 +306,9 @@ impl ICEAgent {
         transport: UdpMux,
         local_credentials: ICECredentials,
         remote_credentials: ICECredentials,
-        local_ip: Ipv4Addr,
+        addresses: NetworkAddresses,
+        ice_servers: Vec<IceServer>,
+        address_changes: Option<async_channel::Receiver<NetworkAddresses>>,
     ) -> Self {
         Self {
             local_candidates: vec![],
@@ -139,76 +316,110 @@ impl ICEAgent {
             remote_candidates_chan,
             transport,
             candidate_pairs: vec![],
-            local_ip,
+            addresses,
+            address_changes,
             local_credentials,
             remote_credentials,
             state: ICEAgentState::Checking,
+            ice_servers,
+            turn_client: None,
+            checks_started: None,
+            regathered: None,
+            pending_packets: VecDeque::new(),
         }
     }
 
-    /// Gather local candidates, it will only generate one host and one server reflexive,
-    /// relay candidates are not supported yet
+    /// Gather local candidates, it will generate one host candidate per address family, one
+    /// server reflexive candidate per address family from the first STUN server answering and
+    /// one relayed candidate from the first TURN server granting an allocation
     pub async fn local_candidates(&mut self) -> Result<(), IceError> {
         if !self.local_candidates.is_empty() {
             return Ok(());
         }
 
-        log::debug!("local_candidates: registering intrinsic local candidate");
-        let our_ip = SocketAddrV4::new(
-            self.local_ip,
-            self.transport
-                .local_address()
-                .map_err(|_| IceError::IceIoError)?
-                .port(),
-        );
-        let local_cand = Candidate::new_host_candidate(our_ip);
-        self.local_candidates.push(local_cand);
-
-        log::debug!("local_candidates: looking for srv reflexive candidate");
-
-        let message = stun_codec::Message::<stun_codec::rfc5389::Attribute>::new(
-            stun_codec::MessageClass::Request,
-            stun_codec::rfc5389::methods::BINDING,
-            stun_codec::TransactionId::new(rand::random()),
-        );
+        log::debug!("local_candidates: registering intrinsic local candidates");
+        let local_address = self
+            .transport
+            .local_address()
+            .map_err(|_| IceError::IceIoError)?;
+        let port = local_address.port();
+        // the IPv4 address is unspecified on IPv6 only networks, an IPv6 address obtained after
+        // the socket was bound can't be used if the socket is IPv4 only
+        let ipv4 = self.addresses.ipv4;
+        let bases: Vec<SocketAddr> = (!ipv4.is_unspecified())
+            .then_some(IpAddr::V4(ipv4))
+            .into_iter()
+            .chain(
+                self.addresses
+                    .ipv6
+                    .filter(|_| local_address.is_ipv6())
+                    .map(IpAddr::V6),
+            )
+            .map(|ip| SocketAddr::new(ip, port))
+            .collect();
+        if bases.is_empty() {
+            return Err(IceError::IceNoLocalCandidates);
+        }
+        for base in &bases {
+            self.local_candidates
+                .push(Candidate::new_host_candidate(*base));
+        }
 
-        let mut encoder = stun_codec::MessageEncoder::new();
-        let bytes = Bytes::from(encoder.encode_into_bytes(message).unwrap());
+        let ice_servers = std::mem::take(&mut self.ice_servers);
 
-        // TODO(RSDK-3063) Twilio address is hard-coded, we should support additional server via WebRTCOptions
-        let mut stun_ip = match "global.stun.twilio.com:3478".to_socket_addrs() {
-            Ok(stun_ip) => stun_ip,
-            Err(err) => {
-                log::warn!("Failed trying to resolve STUN server address; no reflexive candidate will be generated: {}", err);
-                return Ok(());
+        log::debug!("local_candidates: looking for srv reflexive candidates");
+        for base in &bases {
+            for server in ice_servers
+                .iter()
+                .filter(|s| s.kind() == IceServerKind::Stun)
+            {
+                match self.server_reflexive_candidate(server, *base).await {
+                    Ok(candidate) => {
+                        self.local_candidates.push(candidate);
+                        break;
+                    }
+                    Err(err) => log::warn!(
+                        "failed to get a reflexive candidate from {}: {}",
+                        server,
+                        err
+                    ),
+                }
             }
-        };
+        }
 
-        let stun_ip = match stun_ip.next() {
-            Some(stun_ip) => stun_ip,
-            None => {
-                log::warn!("STUN server address resolution found no records; no reflexive candidate will be generated");
-                return Ok(());
+        log::debug!("local_candidates: looking for relay candidate");
+        // IPv4 is preferred to reach the TURN server, it's what relays are most likely to offer
+        let base = bases[0];
+        for server in ice_servers
+            .iter()
+            .filter(|s| s.kind() == IceServerKind::Turn)
+        {
+            match self.relay_candidate(server, base).await {
+                Ok(()) => break,
+                Err(err) => log::warn!("failed to get a relay candidate from {}: {}", server, err),
             }
-        };
+        }
+        self.ice_servers = ice_servers;
 
-        let stun_ip = match stun_ip {
-            SocketAddr::V4(v4) => v4,
-            _ => {
-                return Err(IceError::IceStunServerNotIPV4);
-            }
-        };
+        Ok(())
+    }
 
-        let mut buf = BytesMut::zeroed(256);
-        let (buf_len, _addr) = loop {
-            let _r = self
-                .transport
-                .send_to(&bytes, stun_ip.into())
+    // sends a request to a STUN or TURN server and waits for the server to answer, the request
+    // is sent again if no answer is received within a second
+    async fn server_transaction(
+        &mut self,
+        request: &[u8],
+        server: SocketAddr,
+        buf: &mut [u8],
+    ) -> Result<usize, IceError> {
+        for _ in 0..SERVER_REQUEST_ATTEMPTS {
+            self.transport
+                .send_to(request, server)
                 .await
-                .unwrap();
+                .map_err(|_| IceError::IceIoError)?;
             let response = self
                 .transport
-                .recv_from(&mut buf)
+                .recv_from(buf)
                 .or(async {
                     Timer::after(Duration::from_secs(1)).await;
                     Err(io::Error::new(io::ErrorKind::TimedOut, ""))
@@ -216,32 +427,102 @@ impl ICEAgent {
                 .await;
 
             match response {
-                Ok(rsp) => break rsp,
+                Ok((len, from)) if from == server => return Ok(len),
+                // e.g. checks and consent refreshes of the peer while we re-gather candidates
+                Ok((len, from)) => {
+                    if self.pending_packets.len() < MAX_PENDING_PACKETS {
+                        self.pending_packets.push_back((buf[..len].to_vec(), from));
+                    }
+                    continue;
+                }
                 Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
                 Err(_) => return Err(IceError::IceIoError),
             };
-        };
+        }
+        Err(IceError::IceServerUnreachable)
+    }
+
+    async fn server_reflexive_candidate(
+        &mut self,
+        server: &IceServer,
+        base: SocketAddr,
+    ) -> Result<Candidate, IceError> {
+        let server = server.resolve(base.is_ipv6())?;
+        let message = stun_codec::Message::<stun_codec::rfc5389::Attribute>::new(
+            stun_codec::MessageClass::Request,
+            stun_codec::rfc5389::methods::BINDING,
+            stun_codec::TransactionId::new(rand::random()),
+        );
+
+        let mut encoder = stun_codec::MessageEncoder::new();
+        let bytes = encoder
+            .encode_into_bytes(message)
+            .map_err(|_| IceError::IceStunEncodingError)?;
+
+        let mut buf = BytesMut::zeroed(256);
+        let buf_len = self.server_transaction(&bytes, server, &mut buf).await?;
         let mut decoder = stun_codec::MessageDecoder::<stun_codec::rfc5389::Attribute>::new();
 
         let decoded = decoder
             .decode_from_bytes(&buf[..buf_len])
             .map_err(|_| IceError::IceStunDecodingError)?
-            .unwrap();
+            .map_err(|_| IceError::IceStunDecodingError)?;
 
-        let xor_mapped_addr =
+        let rflx_addr =
             match decoded.get_attribute::<stun_codec::rfc5389::attributes::XorMappedAddress>() {
                 Some(addr) => addr.address(),
                 None => return Err(IceError::IceMissingXorMappedAddress),
             };
 
-        let rflx_addr = match xor_mapped_addr {
-            SocketAddr::V4(v4) => v4,
-            SocketAddr::V6(_) => return Err(IceError::IceXorMappedAddressIsIPV6),
-        };
-
-        let srflx_candidate = Candidate::new_srflx_candidate(rflx_addr, our_ip);
-        self.local_candidates.push(srflx_candidate);
+        Ok(Candidate::new_srflx_candidate(rflx_addr, base))
+    }
 
+    // makes an allocation on a TURN server, the relayed address becomes our relay candidate.
+    // The mapped address returned by the server is used as a reflexive candidate if no STUN
+    // server gave us one
+    async fn relay_candidate(
+        &mut self,
+        server: &IceServer,
+        base: SocketAddr,
+    ) -> Result<(), IceError> {
+        let address = server.resolve(base.is_ipv6())?;
+        let mut client = TurnClient::new(
+            address,
+            server.username.clone().unwrap_or_default(),
+            server.credential.clone().unwrap_or_default(),
+        );
+        let mut request = client.allocate_request()?;
+        let mut buf = BytesMut::zeroed(512);
+        // the first request is expected to be rejected until it carries the credentials
+        for _ in 0..SERVER_REQUEST_ATTEMPTS {
+            let len = self.server_transaction(&request, address, &mut buf).await?;
+            let response = stun_codec::MessageDecoder::<TurnAttribute>::new()
+                .decode_from_bytes(&buf[..len])
+                .map_err(|_| IceError::IceStunDecodingError)?
+                .map_err(|_| IceError::IceStunDecodingError)?;
+            match client.handle_response(&response, Instant::now())? {
+                TurnEvent::Allocated => break,
+                TurnEvent::Resend(next) => request = next,
+                _ => continue,
+            }
+        }
+        let relayed = client
+            .relayed_address()
+            .ok_or(IceError::IceTurnAllocationFailed)?;
+
+        if let Some(mapped) = client.mapped_address() {
+            if !self.local_candidates.iter().any(|c| {
+                c.candidate_type() == CandidateType::ServerReflexive
+                    && c.address().is_ipv6() == mapped.is_ipv6()
+            }) {
+                self.local_candidates
+                    .push(Candidate::new_srflx_candidate(mapped, base));
+            }
+        }
+        log::debug!("obtained relayed address {} from {}", relayed, server);
+        self.local_candidates
+            .push(Candidate::new_relay_candidate(relayed, base));
+        let _ = self.turn_client.insert(client);
         Ok(())
     }
 
@@ -252,32 +533,48 @@ impl ICEAgent {
 
         let error = loop {
             let stop = stop.clone();
-            for pair in &mut self.candidate_pairs {
-                pair.update_pair_status();
-                // TODO(npm) check for nomination flag before we are actually connected
-                // note: nomitation flag isn't set yet
-                if self.state != ICEAgentState::Connected
-                    && *pair.state() == CandidatePairState::Succeeded
-                {
-                    // When at least one pair is succeeded we go in the connected state
-                    // we will not attempt to find a better candidate pair
-                    self.state = ICEAgentState::Connected;
+            match self.update_state(Instant::now()).await {
+                Ok(true) => {
                     // this is a work around to tell the WebRTCAPI that signaling can be
                     // stopped and DTLS should be started
                     done.done();
                 }
+                Ok(false) => {}
+                Err(e) => break e,
+            }
+
+            if let Some(server) = self.turn_client.as_ref().map(|t| t.server()) {
+                let mut closed = false;
+                for req in self.turn_requests(Instant::now()) {
+                    if self.transport.send_to(&req, server).await.is_err() {
+                        closed = true;
+                        break;
+                    }
+                }
+                if closed {
+                    break IceError::IceTransportClosed;
+                }
             }
 
             let req = self.next_stun_request();
-            if let Some(req) = req {
-                if let Ok(msg) = self.make_stun_request(req.0) {
-                    if self.transport.send_to(&msg, req.1.into()).await.is_err() {
+            if let Some((id, remote, via_relay)) = req {
+                if let Ok(msg) = self.make_stun_request(id) {
+                    if let Err(IceError::IceTransportClosed) =
+                        self.send_to_remote(&msg, remote, via_relay).await
+                    {
                         break IceError::IceTransportClosed;
                     }
                 }
             }
 
-            let mut buf = BytesMut::zeroed(256);
+            let mut buf = BytesMut::zeroed(512);
+
+            if let Some((packet, from)) = self.pending_packets.pop_front() {
+                if let Some(e) = self.process_packet(&packet, from).await {
+                    break e;
+                }
+                continue;
+            }
 
             let f1: Pin<Box<dyn Future<Output = Result<IceEvent, IceError>> + Send>> =
                 if !self.remote_candidates_chan.is_closed() {
@@ -298,18 +595,22 @@ impl ICEAgent {
                 self.transport
                     .recv_from(&mut buf)
                     .await
-                    .map(|(len, addr)| {
-                        //TODO deal with IpV6
-                        let addr = match addr {
-                            SocketAddr::V4(addr) => addr,
-                            _ => panic!(),
-                        };
-                        IceEvent::StunPacketReceived((len, addr))
-                    })
+                    .map(IceEvent::StunPacketReceived)
                     .map_err(|_| IceError::IceTransportClosed)
             });
 
+            let f3 = Box::pin(async {
+                match self.address_changes.as_ref() {
+                    Some(changes) => match changes.recv().await {
+                        Ok(addresses) => Ok(IceEvent::AddressChanged(addresses)),
+                        Err(_) => futures_lite::future::pending().await,
+                    },
+                    None => futures_lite::future::pending().await,
+                }
+            });
+
             let event = futures_lite::future::or(f1, f2)
+                .or(f3)
                 .or(async {
                     // TODO we should take the min time for next candidate pair check
                     Timer::after(Duration::from_millis(500)).await;
@@ -327,6 +628,11 @@ impl ICEAgent {
                 }
             };
             match event {
+                IceEvent::AddressChanged(addresses) => {
+                    if let Err(e) = self.regather_candidates(addresses).await {
+                        break e;
+                    }
+                }
                 IceEvent::CandidateReceived(c) => {
                     self.remote_candidates.push(c);
                     self.form_pairs(self.remote_candidates.len() - 1);
@@ -339,47 +645,287 @@ impl ICEAgent {
                     }
                 }
                 IceEvent::StunPacketReceived((len, addr)) => {
-                    let mut decoder = stun_codec::MessageDecoder::<IceAttribute>::new();
-                    let decoded = match decoder.decode_from_bytes(&buf[..len]).unwrap() {
-                        Ok(e) => e,
-                        Err(e) => {
-                            log::error!("dropping stun msg {:?}", e);
-                            buf.clear();
-                            continue;
-                        }
-                    };
-                    buf.clear();
-
-                    match decoded.class() {
-                        MessageClass::Request => {
-                            log::debug!("processing a stun request");
-                            if let Ok(msg) = self.process_stun_request(&decoded, &addr) {
-                                if self.transport.send_to(&msg, addr.into()).await.is_err() {
-                                    break IceError::IceTransportClosed;
-                                }
-                            }
-                        }
-                        MessageClass::SuccessResponse => {
-                            if let Err(e) = self.process_stun_response(Instant::now(), decoded) {
-                                // could be caused by multiple response for one request
-                                log::error!("unable to properly process stun response {:?}", e);
-                            }
-                        }
+                    if let Some(e) = self.process_packet(&buf[..len], addr).await {
+                        break e;
+                    }
+                }
+            }
+        };
+
+        if let Some(turn) = self.turn_client.as_mut() {
+            if let Ok(req) = turn.deallocate_request() {
+                let _ = self.transport.send_to(&req, turn.server()).await;
+            }
+        }
 
-                        MessageClass::ErrorResponse => {
-                            //TODO(RSDK-3064)
-                            log::error!("received a stun error");
+        log::error!("closing ice agent with error {:?}", error);
+    }
+
+    // handles a packet received from `addr`, returns an error when the agent must stop
+    async fn process_packet(&mut self, buf: &[u8], addr: SocketAddr) -> Option<IceError> {
+        let turn_server = self.turn_client.as_ref().map(|t| t.server());
+        // packets coming from the TURN server are either answers to our requests or
+        // relayed packets wrapped in a channel
+        let (packet, from, via_relay) = if turn_server == Some(addr) {
+            if !is_channel_data(buf[0]) {
+                match self.process_turn_message(buf) {
+                    Ok(Some(req)) => {
+                        if self.transport.send_to(&req, addr).await.is_err() {
+                            return Some(IceError::IceTransportClosed);
                         }
-                        MessageClass::Indication => {
-                            //TODO(RSDK-3064)
-                            log::error!("received a stun indication")
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::error!("turn request failed {:?}", e),
+                }
+                return None;
+            }
+            let relayed = decode_channel_data(buf).and_then(|(n, data)| {
+                self.turn_client
+                    .as_ref()
+                    .and_then(|t| t.peer_for_channel(n))
+                    .map(|peer| (data, peer))
+            });
+            match relayed {
+                Some((data, peer)) => (data, peer, true),
+                None => return None,
+            }
+        } else {
+            (buf, addr, false)
+        };
+        let mut decoder = stun_codec::MessageDecoder::<IceAttribute>::new();
+        let decoded = match decoder.decode_from_bytes(packet) {
+            Ok(Ok(e)) => e,
+            Ok(Err(e)) => {
+                log::error!("dropping stun msg {:?}", e);
+                return None;
+            }
+            Err(e) => {
+                log::error!("dropping stun msg {:?}", e);
+                return None;
+            }
+        };
+
+        match decoded.class() {
+            MessageClass::Request => {
+                log::debug!("processing a stun request");
+                match self.process_stun_request(&decoded, &from, via_relay) {
+                    Ok(msg) => {
+                        if let Err(IceError::IceTransportClosed) =
+                            self.send_to_remote(&msg, from, via_relay).await
+                        {
+                            return Some(IceError::IceTransportClosed);
                         }
                     }
+                    Err(e) => {
+                        log::debug!("rejecting stun request from {}: {}", from, e)
+                    }
+                }
+            }
+            MessageClass::SuccessResponse => {
+                if let Err(e) = self.process_stun_response(Instant::now(), decoded) {
+                    // could be caused by multiple response for one request
+                    log::error!("unable to properly process stun response {:?}", e);
                 }
             }
+
+            MessageClass::ErrorResponse => {
+                //TODO(RSDK-3064)
+                log::error!("received a stun error");
+            }
+            MessageClass::Indication => {
+                //TODO(RSDK-3064)
+                log::error!("received a stun indication")
+            }
+        }
+        None
+    }
+
+    /// update_state refreshes the status of every pair and returns true when the agent becomes
+    /// connected. The agent is connected once a nominated pair succeeded, this pair is selected
+    /// and the other ones are pruned since we will not attempt to find a better one. Once
+    /// connected an error is returned when the consent of the selected pair expired
+    async fn update_state(&mut self, now: Instant) -> Result<bool, IceError> {
+        for pair in &mut self.candidate_pairs {
+            pair.update_pair_status(now);
+        }
+        // the remote agent has as much time to move to our new addresses as it would have to
+        // refresh its consent
+        if self
+            .regathered
+            .is_some_and(|t| now.duration_since(t) > CONSENT_TIMEOUT)
+        {
+            return Err(IceError::IceConsentExpired);
+        }
+        if self.state == ICEAgentState::Connected {
+            return match self.candidate_pairs.first().map(|p| p.state()) {
+                Some(CandidatePairState::Succeeded) => Ok(false),
+                _ => Err(IceError::IceConsentExpired),
+            };
+        }
+        // pairs are sorted by priority so the first match is the best nominated pair
+        let Some(selected) = self
+            .candidate_pairs
+            .iter()
+            .position(|p| p.is_nominated() && *p.state() == CandidatePairState::Succeeded)
+        else {
+            return Ok(false);
         };
+        let selected = self.candidate_pairs.swap_remove(selected);
+        log::info!(
+            "selected pair {} -> {}",
+            self.local_candidates[selected.local],
+            self.remote_candidates[selected.remote]
+        );
+        let relayed =
+            self.local_candidates[selected.local].candidate_type() == CandidateType::Relay;
+        self.candidate_pairs = vec![selected];
+        self.state = ICEAgentState::Connected;
+        self.regathered = None;
+
+        // the allocation is useless when the selected pair doesn't go through the relay
+        if !relayed {
+            if let Some(mut turn) = self.turn_client.take() {
+                if let Ok(req) = turn.deallocate_request() {
+                    self.transport
+                        .send_to(&req, turn.server())
+                        .await
+                        .map_err(|_| IceError::IceTransportClosed)?;
+                }
+            }
+        }
+        Ok(true)
+    }
 
-        log::error!("closing ice agent with error {:?}", error);
+    /// regather_candidates gathers new local candidates once the addresses of the device changed
+    /// and checks them against the remote candidates already known, this isn't an ICE restart.
+    /// The credentials are kept and no new offer or candidate can be sent once connected, the
+    /// remote agent can only learn our new addresses through our checks as peer reflexive
+    /// candidates. Whether a pair gets nominated again is up to the remote agent, see the module
+    /// documentation
+    async fn regather_candidates(&mut self, addresses: NetworkAddresses) -> Result<(), IceError> {
+        log::info!(
+            "re-gathering ice candidates, addresses changed to {} {:?}",
+            addresses.ipv4,
+            addresses.ipv6
+        );
+        // the allocation was made from our previous address
+        if let Some(mut turn) = self.turn_client.take() {
+            if let Ok(req) = turn.deallocate_request() {
+                let _ = self.transport.send_to(&req, turn.server()).await;
+            }
+        }
+        self.transport.clear_relay_channels();
+        self.addresses = addresses;
+        self.local_candidates.clear();
+        self.candidate_pairs.clear();
+        self.checks_started = None;
+        self.state = ICEAgentState::Checking;
+        let _ = self.regathered.insert(Instant::now());
+
+        if let Err(e) = self.local_candidates().await {
+            log::warn!("failed to gather candidates after an address change: {}", e);
+        }
+        for remote_idx in 0..self.remote_candidates.len() {
+            self.form_pairs(remote_idx);
+        }
+        Ok(())
+    }
+
+    // sends a packet to a remote candidate, when the packet is sent from our relayed candidate
+    // it goes through the channel bound to the remote candidate on the TURN server
+    async fn send_to_remote(
+        &self,
+        buf: &[u8],
+        to: SocketAddr,
+        via_relay: bool,
+    ) -> Result<usize, IceError> {
+        if via_relay {
+            let turn = self
+                .turn_client
+                .as_ref()
+                .ok_or(IceError::IceNoLocalCandidates)?;
+            let channel = turn
+                .channel_for_peer(&to)
+                .ok_or(IceError::IceTurnNoChannelAvailable)?;
+            return self
+                .transport
+                .send_to(&encode_channel_data(channel, buf), turn.server())
+                .await
+                .map_err(|_| IceError::IceTransportClosed);
+        }
+        self.transport
+            .send_to(buf, to)
+            .await
+            .map_err(|_| IceError::IceTransportClosed)
+    }
+
+    // handles a message sent by the TURN server outside of a channel, returns the request to
+    // send back to the server if one is needed
+    fn process_turn_message(&mut self, packet: &[u8]) -> Result<Option<Vec<u8>>, IceError> {
+        let Some(turn) = self.turn_client.as_mut() else {
+            return Ok(None);
+        };
+        let message = stun_codec::MessageDecoder::<TurnAttribute>::new()
+            .decode_from_bytes(packet)
+            .map_err(|_| IceError::IceStunDecodingError)?
+            .map_err(|_| IceError::IceStunDecodingError)?;
+        if !is_turn_method(message.method()) {
+            return Ok(None);
+        }
+        match turn.handle_response(&message, Instant::now())? {
+            TurnEvent::Resend(req) => Ok(Some(req)),
+            TurnEvent::ChannelBound(peer) => {
+                if let Some(number) = turn.channel_for_peer(&peer) {
+                    log::debug!("bound turn channel {} to {}", number, peer);
+                    self.transport
+                        .add_relay_channel(turn.server(), number, peer);
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// turn_requests returns the requests keeping the TURN allocation alive and binding a
+    /// channel for every remote candidate paired with our relayed candidate.
+    /// Relayed pairs are kept frozen until their channel is bound and either RELAY_CHECK_DELAY
+    /// elapsed or every other pair failed, so a direct path is preferred when one exists
+    fn turn_requests(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let Some(turn) = self.turn_client.as_mut() else {
+            return vec![];
+        };
+        let mut requests = turn.refresh_requests(now);
+        let direct_failed = self
+            .candidate_pairs
+            .iter()
+            .filter(|p| self.local_candidates[p.local].candidate_type() != CandidateType::Relay)
+            .all(|p| *p.state() == CandidatePairState::Failed);
+        let delay_elapsed = self
+            .checks_started
+            .is_some_and(|t| now.duration_since(t) > RELAY_CHECK_DELAY);
+
+        for pair in self
+            .candidate_pairs
+            .iter_mut()
+            .filter(|p| *p.state() == CandidatePairState::Frozen)
+        {
+            if self.local_candidates[pair.local].candidate_type() != CandidateType::Relay {
+                continue;
+            }
+            let peer = *self.remote_candidates[pair.remote].address();
+            if turn.channel_for_peer(&peer).is_some() {
+                if direct_failed || delay_elapsed {
+                    pair.unfreeze();
+                }
+            } else {
+                match turn.channel_bind_request(peer) {
+                    Ok(Some(req)) => requests.push(req),
+                    Ok(None) => {}
+                    Err(e) => log::error!("cannot bind a channel to {}: {:?}", peer, e),
+                }
+            }
+        }
+        requests
     }
 
     /// next_stun_request finds the next suitable pair to do a connection check on
@@ -388,7 +934,9 @@ impl ICEAgent {
     /// 2) If a pair has a pending STUN request and its timeout is elapsed it will resend
     ///    the generated TransactionId
     /// 3) Otherwise it moves to the next candidate pair
-    fn next_stun_request(&mut self) -> Option<(TransactionId, SocketAddrV4)> {
+    ///
+    /// Along with the remote address it returns whether the request has to go through the relay
+    fn next_stun_request(&mut self) -> Option<(TransactionId, SocketAddr, bool)> {
         let instant = Instant::now();
         for pair in &mut self.candidate_pairs {
             log::debug!("processing pair {:?}", pair);
@@ -399,13 +947,21 @@ impl ICEAgent {
                     self.local_candidates[pair.local],
                     self.remote_candidates[pair.remote]
                 );
-                return Some((id, *self.remote_candidates[pair.remote].address()));
+                return Some((
+                    id,
+                    *self.remote_candidates[pair.remote].address(),
+                    self.local_candidates[pair.local].candidate_type() == CandidateType::Relay,
+                ));
             }
         }
         None
     }
 
     fn form_pairs(&mut self, remote_idx: usize) {
+        // the checklist only holds the selected pair once connected
+        if self.state == ICEAgentState::Connected {
+            return;
+        }
         for (local_idx, local) in self.local_candidates.iter().enumerate() {
             // Assumption, ipv6 candidates are rejected by default
             let remote = &self.remote_candidates[remote_idx];
@@ -416,13 +972,25 @@ impl ICEAgent {
                 continue;
             }
 
-            let pair = match CandidatePair::new(local, remote, local_idx, remote_idx) {
+            let mut pair = match CandidatePair::new(local, remote, local_idx, remote_idx) {
                 Err(e) => {
                     log::error!("Couldn't form pair {:?}", e);
                     continue;
                 }
                 Ok(c) => c,
             };
+            // 6.1.2.4 Pruning the Pairs, a pair is redundant when one with the same local
+            // candidate and remote address exists already
+            if self.candidate_pairs.iter().any(|other| {
+                other.local == local_idx
+                    && self.remote_candidates[other.remote].address() == remote.address()
+            }) {
+                continue;
+            }
+            // relayed pairs wait for a channel to be bound, see turn_requests
+            if local.candidate_type == CandidateType::Relay {
+                pair.freeze();
+            }
             let _ = match self
                 .candidate_pairs
                 .binary_search_by(|other| pair.cmp(other))
@@ -442,7 +1010,11 @@ impl ICEAgent {
                     idx
                 }
             };
-            // TODO(RSDK-3066) prune the pairs
+        }
+        // pairs are sorted by decreasing priority
+        self.candidate_pairs.truncate(MAX_CANDIDATE_PAIRS);
+        if self.checks_started.is_none() && !self.candidate_pairs.is_empty() {
+            let _ = self.checks_started.insert(Instant::now());
         }
 
         log::debug!(
@@ -476,7 +1048,7 @@ impl ICEAgent {
         if let BINDING = stun.method() {
             let mut creds = stun
                 .get_attribute::<rfc5389::attributes::Username>()
-                .unwrap()
+                .ok_or(IceError::IceMissingUserName("local"))?
                 .name()
                 .split(':');
             let local_u = creds.next().ok_or(IceError::IceMissingUserName("local"))?;
@@ -494,10 +1066,13 @@ impl ICEAgent {
         Err(IceError::IceInvalidStunMessage)
     }
 
+    /// Answers a binding request, when the request was relayed by the TURN server the pair is
+    /// formed with our relayed candidate
     fn process_stun_request(
         &mut self,
         stun: &Message<IceAttribute>,
-        from: &SocketAddrV4,
+        from: &SocketAddr,
+        via_relay: bool,
     ) -> Result<Vec<u8>, IceError> {
         let use_candidate = if stun
             .get_attribute::<rfc5245::attributes::UseCandidate>()
@@ -509,13 +1084,14 @@ impl ICEAgent {
             false
         };
 
+        self.validate_stun_message(stun)?;
         let id = stun.transaction_id();
+        // we are always the controlled agent
         if stun
             .get_attribute::<rfc5245::attributes::IceControlling>()
             .is_none()
         {
-            log::debug!("we should have had the controlling attribute")
-            // TODO(RSDK-3067) probably should error out here
+            return Err(IceError::IceMissingControllingAttribute);
         };
 
         let have_as_remote_candidate = match self
@@ -538,11 +1114,18 @@ impl ICEAgent {
             }
         };
 
+        let local_type = if via_relay {
+            CandidateType::Relay
+        } else {
+            CandidateType::Host
+        };
         let local_host = self
             .local_candidates
             .iter()
             .enumerate()
-            .position(|(_, c)| c.candidate_type() == CandidateType::Host)
+            .position(|(_, c)| {
+                c.candidate_type() == local_type && c.address().is_ipv4() == from.is_ipv4()
+            })
             .ok_or(IceError::IceNoLocalCandidates)?;
         let pair_idx = match self
             .candidate_pairs
@@ -550,6 +1133,11 @@ impl ICEAgent {
             .position(|c| c.local == local_host && c.remote == have_as_remote_candidate)
         {
             Some(idx) => Some(idx),
+            None if self.state == ICEAgentState::Connected => {
+                // checks on other pairs are still answered but no new pair is formed
+                log::debug!("ignoring check from {} on a non selected pair", from);
+                return self.stun_success_response(*from, id);
+            }
             None => {
                 let local_c = &self.local_candidates[local_host];
                 let remote_c = &self.remote_candidates[have_as_remote_candidate];
@@ -562,17 +1150,18 @@ impl ICEAgent {
             }
         };
         if let Some(pair_idx) = pair_idx {
-            if use_candidate {
+            if use_candidate && !self.candidate_pairs[pair_idx].is_nominated() {
                 log::debug!(
-                    "should nominate Pair {:?} L:{:?} R:{:?}",
+                    "nominating Pair {:?} L:{:?} R:{:?}",
                     self.candidate_pairs[pair_idx],
                     &self.local_candidates[local_host],
                     &self.remote_candidates[have_as_remote_candidate]
                 );
+                self.candidate_pairs[pair_idx].nominate();
             }
             self.candidate_pairs[pair_idx].binding_req_recv += 1;
 
-            return self.stun_success_response((*from).into(), id);
+            return self.stun_success_response(*from, id);
         }
         Err(IceError::IceNoPairForThisStunResponse)
     }
@@ -662,12 +1251,17 @@ mod tests {
     use async_executor::Executor;
     use async_io::Async;
     use futures_lite::future::block_on;
-    use std::net::UdpSocket;
+    use std::net::{Ipv4Addr, UdpSocket};
     use std::sync::Arc;
 
-    use crate::common::webrtc::ice::{ICEAgent, ICECredentials};
+    use crate::common::webrtc::ice::{
+        default_ice_servers, ICEAgent, ICECredentials, IceServer, IceServerKind,
+    };
 
-    use crate::common::webrtc::{candidates::Candidate, io::WebRtcTransport};
+    use crate::common::{
+        conn::network::NetworkAddresses,
+        webrtc::{candidates::Candidate, io::WebRtcTransport},
+    };
 
     use super::IceError;
 
@@ -703,7 +1297,9 @@ mod tests {
             ice_transport,
             ICECredentials::default(),
             ICECredentials::default(),
-            our_ip,
+            NetworkAddresses::new(our_ip, None),
+            default_ice_servers(),
+            None,
         );
         let ret = block_on(executor.run(async { ice_agent.local_candidates().await }));
 
@@ -717,4 +1313,82 @@ mod tests {
 
         Ok(())
     }
+
+    #[test_log::test]
+    fn test_peer_packets_kept_while_gathering() {
+        let executor = Executor::new();
+        let udp = block_on(
+            executor.run(async { Async::new(UdpSocket::bind("127.0.0.1:0").unwrap()).unwrap() }),
+        );
+        let agent_address = udp.get_ref().local_addr().unwrap();
+        let transport = WebRtcTransport::new(Arc::new(udp));
+        let (_tx, rx) = async_channel::unbounded();
+        let mut ice_agent = ICEAgent::new(
+            rx,
+            transport.get_stun_channel().unwrap(),
+            ICECredentials::default(),
+            ICECredentials::default(),
+            NetworkAddresses::new(Ipv4Addr::LOCALHOST, None),
+            vec![],
+            None,
+        );
+
+        // binding request carrying a SOFTWARE attribute
+        let mut request = vec![0x00, 0x01, 0x00, 0x08, 0x21, 0x12, 0xa4, 0x42];
+        request.extend_from_slice(&[7; 12]);
+        request.extend_from_slice(&[0x80, 0x22, 0x00, 0x04, b't', b'e', b's', b't']);
+
+        // a check of the peer arrives before the answer of the server
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(&request, agent_address).unwrap();
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_address = server.local_addr().unwrap();
+        let echo = std::thread::spawn(move || {
+            let mut buf = [0; 64];
+            let (len, from) = server.recv_from(&mut buf).unwrap();
+            server.send_to(&buf[..len], from).unwrap();
+        });
+
+        let mut buf = [0; 64];
+        let len = block_on(executor.run(ice_agent.server_transaction(
+            &request,
+            server_address,
+            &mut buf,
+        )))
+        .unwrap();
+        echo.join().unwrap();
+        assert_eq!(&buf[..len], &request[..]);
+        assert_eq!(
+            ice_agent.pending_packets.pop_front(),
+            Some((request, peer.local_addr().unwrap()))
+        );
+        assert!(ice_agent.pending_packets.is_empty());
+    }
+
+    #[test_log::test]
+    fn test_ice_server_url() -> Result<(), IceError> {
+        let server = IceServer::from_url("stun:global.stun.twilio.com:3478", None, None)?;
+        assert_eq!(server.kind(), IceServerKind::Stun);
+        assert_eq!(server.to_string(), "stun:global.stun.twilio.com:3478");
+
+        let server = IceServer::from_url(
+            "turn:turn.viam.com?transport=udp",
+            Some("user".to_owned()),
+            Some("pass".to_owned()),
+        )?;
+        assert_eq!(
+            server,
+            IceServer::new_turn(
+                "turn.viam.com".to_owned(),
+                3478,
+                "user".to_owned(),
+                "pass".to_owned()
+            )
+        );
+
+        assert!(IceServer::from_url("turn:turn.viam.com:443?transport=tcp", None, None).is_err());
+        assert!(IceServer::from_url("turns:turn.viam.com:443", None, None).is_err());
+        assert!(IceServer::from_url("stun:", None, None).is_err());
+        Ok(())
+    }
 }
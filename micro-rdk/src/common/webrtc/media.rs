@@ -0,0 +1,381 @@
+//! Video tracks sending camera frames to the peer of a WebRTC connection
+//!
+//! Tracks are added and removed through the StreamService, each change is negotiated with the
+//! peer by sending it a new offer over the negotiation data channel. Frames are sent as MJPEG
+//! (RFC 2435) in SRTP packets bundled with the DTLS transport of the connection.
+use std::{
+    io::{self, Cursor},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_channel::{Receiver, Sender};
+use async_io::Timer;
+use base64::{engine::general_purpose, Engine};
+use futures_lite::{AsyncReadExt, FutureExt};
+use sdp::{description::common::Attribute, MediaDescription, SessionDescription};
+use thiserror::Error;
+
+use crate::common::camera::{CameraType, MIME_TYPE_JPEG};
+
+use super::{
+    api::SdpOffer,
+    rtp::{JpegFrame, RtpStream, RTP_JPEG_PAYLOAD_TYPE, RTP_VIDEO_CLOCK_RATE},
+    sctp::Channel,
+    srtp::{SrtpContext, SrtpError},
+    udp_mux::BundledSender,
+};
+
+/// Maximum number of cameras streamed on a single connection
+pub(crate) const MAX_VIDEO_TRACKS: usize = 2;
+// cameras are polled at most at this rate, slower cameras set the actual frame rate
+const FRAME_INTERVAL: Duration = Duration::from_millis(1000 / 15);
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(10);
+// a whole message has to fit, an answer describing a handful of tracks is a few kilobytes
+const NEGOTIATION_BUFFER_SIZE: usize = 16 * 1024;
+// transport attributes shared by every media section of the bundle
+const BUNDLE_ATTRIBUTES: [&str; 4] = ["ice-ufrag", "ice-pwd", "fingerprint", "setup"];
+
+#[derive(Error, Debug)]
+pub enum StreamError {
+    #[error("stream {0} is already added")]
+    StreamAlreadyAdded(String),
+    #[error("stream {0} isn't added")]
+    StreamNotAdded(String),
+    #[error("cannot stream more than {0} cameras")]
+    StreamTooManyTracks(usize),
+    #[error("renegotiation failed: {0}")]
+    StreamNegotiationError(String),
+    #[error("peer rejected the video track")]
+    StreamRejected,
+    #[error("connection closed")]
+    StreamConnectionClosed,
+    #[error(transparent)]
+    StreamSrtpError(#[from] SrtpError),
+    #[error(transparent)]
+    StreamIoError(#[from] io::Error),
+}
+
+pub(crate) enum StreamRequest {
+    Add(String, CameraType),
+    Remove(String),
+}
+
+type StreamReply = Sender<Result<(), StreamError>>;
+
+/// Handle used by the StreamService to add and remove the video tracks of a connection
+#[derive(Clone)]
+pub(crate) struct StreamServer {
+    requests: Sender<(StreamRequest, StreamReply)>,
+    active_tracks: Arc<AtomicUsize>,
+}
+
+impl StreamServer {
+    /// Resolves once the peer accepted the track
+    pub(crate) async fn add_stream(
+        &self,
+        name: String,
+        camera: CameraType,
+    ) -> Result<(), StreamError> {
+        self.request(StreamRequest::Add(name, camera)).await
+    }
+    pub(crate) async fn remove_stream(&self, name: String) -> Result<(), StreamError> {
+        self.request(StreamRequest::Remove(name)).await
+    }
+    async fn request(&self, request: StreamRequest) -> Result<(), StreamError> {
+        let (tx, rx) = async_channel::bounded(1);
+        self.requests
+            .send((request, tx))
+            .await
+            .map_err(|_| StreamError::StreamConnectionClosed)?;
+        rx.recv()
+            .await
+            .map_err(|_| StreamError::StreamConnectionClosed)?
+    }
+    /// A connection sending video isn't idle even if no request comes in
+    pub(crate) fn is_streaming(&self) -> bool {
+        self.active_tracks.load(Ordering::Relaxed) > 0
+    }
+}
+
+struct VideoTrack {
+    name: String,
+    mid: String,
+    // None once the track is removed, its media section stays in later offers with a zero
+    // port since mids can't be reused (RFC 8843 section 7.1.3)
+    camera: Option<CameraType>,
+    rtp: RtpStream,
+    timestamp_offset: u32,
+    // frames that can't be sent are reported once until the camera recovers
+    failing: bool,
+}
+
+impl VideoTrack {
+    fn media_description(&self, bundle_attributes: &[Attribute]) -> MediaDescription {
+        let mut media = MediaDescription::new_jsep_media_description("video".to_owned(), vec![])
+            .with_codec(
+                RTP_JPEG_PAYLOAD_TYPE,
+                "JPEG".to_owned(),
+                RTP_VIDEO_CLOCK_RATE,
+                0,
+                String::new(),
+            );
+        media.attributes.extend_from_slice(bundle_attributes);
+        let media = media
+            .with_value_attribute("mid".to_owned(), self.mid.clone())
+            .with_property_attribute("rtcp-mux".to_owned());
+        if self.camera.is_none() {
+            let mut media = media.with_property_attribute("inactive".to_owned());
+            media.media_name.port.value = 0;
+            return media;
+        }
+        // clients find the camera a track belongs to through its stream id
+        media
+            .with_property_attribute("sendonly".to_owned())
+            .with_value_attribute("msid".to_owned(), format!("{} {}", self.name, self.name))
+            .with_media_source(
+                self.rtp.ssrc(),
+                "micro-rdk".to_owned(),
+                self.name.clone(),
+                self.name.clone(),
+            )
+    }
+}
+
+/// Sends the frames of the video tracks of a connection, runs until the connection is dropped
+pub(crate) struct MediaSession {
+    negotiation: Channel,
+    // the answer the connection was established with, offers add the video tracks to it
+    description: SessionDescription,
+    engine: general_purpose::GeneralPurpose,
+    srtp: SrtpContext,
+    sender: BundledSender,
+    tracks: Vec<VideoTrack>,
+    requests: Receiver<(StreamRequest, StreamReply)>,
+    active_tracks: Arc<AtomicUsize>,
+    started: Instant,
+}
+
+impl MediaSession {
+    pub(crate) fn new(
+        negotiation: Channel,
+        description: SessionDescription,
+        srtp: SrtpContext,
+        sender: BundledSender,
+    ) -> (Self, StreamServer) {
+        let (tx, rx) = async_channel::unbounded();
+        let active_tracks = Arc::new(AtomicUsize::new(0));
+        let server = StreamServer {
+            requests: tx,
+            active_tracks: active_tracks.clone(),
+        };
+        let session = Self {
+            negotiation,
+            description,
+            engine: general_purpose::STANDARD,
+            srtp,
+            sender,
+            tracks: vec![],
+            requests: rx,
+            active_tracks,
+            started: Instant::now(),
+        };
+        (session, server)
+    }
+
+    pub(crate) async fn run(mut self) {
+        let mut next_frame = Instant::now();
+        loop {
+            let streaming = self.tracks.iter().any(|t| t.camera.is_some());
+            let event = async { self.requests.recv().await.map(Some) }
+                .or(async {
+                    if !streaming {
+                        futures_lite::future::pending::<()>().await;
+                    }
+                    Timer::at(next_frame).await;
+                    Ok(None)
+                })
+                .await;
+            match event {
+                Ok(Some((request, reply))) => {
+                    let result = self.handle_request(request).await;
+                    let _ = reply.send(result).await;
+                }
+                Ok(None) => {
+                    next_frame = Instant::now() + FRAME_INTERVAL;
+                    if let Err(e) = self.send_frames().await {
+                        log::error!("failed to send video frames: {}", e);
+                    }
+                }
+                // the connection is gone along with every handle of the session
+                Err(_) => break,
+            }
+            self.active_tracks.store(
+                self.tracks.iter().filter(|t| t.camera.is_some()).count(),
+                Ordering::Relaxed,
+            );
+        }
+        log::debug!("media session closed");
+    }
+
+    async fn handle_request(&mut self, request: StreamRequest) -> Result<(), StreamError> {
+        match request {
+            StreamRequest::Add(name, camera) => {
+                let active = self.tracks.iter().filter(|t| t.camera.is_some());
+                if active.clone().any(|t| t.name == name) {
+                    return Err(StreamError::StreamAlreadyAdded(name));
+                }
+                if active.count() >= MAX_VIDEO_TRACKS {
+                    return Err(StreamError::StreamTooManyTracks(MAX_VIDEO_TRACKS));
+                }
+                let mid = (self.tracks.len() + 1).to_string();
+                self.tracks.push(VideoTrack {
+                    name,
+                    mid: mid.clone(),
+                    camera: Some(camera),
+                    rtp: RtpStream::new(rand::random(), RTP_JPEG_PAYLOAD_TYPE),
+                    timestamp_offset: rand::random(),
+                    failing: false,
+                });
+                let accepted = self.renegotiate().await.and_then(|answer| {
+                    answer
+                        .media_descriptions
+                        .iter()
+                        .find(|m| m.attribute("mid").flatten() == Some(mid.as_str()))
+                        .filter(|m| m.media_name.port.value != 0)
+                        .map(|_| ())
+                        .ok_or(StreamError::StreamRejected)
+                });
+                if accepted.is_err() {
+                    // the peer may have applied the offer, the mid stays taken
+                    let _ = self.tracks.last_mut().unwrap().camera.take();
+                }
+                accepted
+            }
+            StreamRequest::Remove(name) => {
+                let track = self
+                    .tracks
+                    .iter_mut()
+                    .find(|t| t.camera.is_some() && t.name == name)
+                    .ok_or(StreamError::StreamNotAdded(name))?;
+                let _ = track.camera.take();
+                // the track is stopped either way, the peer only misses that it ended
+                if let Err(e) = self.renegotiate().await {
+                    log::warn!("failed to renegotiate after removing a track: {}", e);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn offer(&mut self) -> SessionDescription {
+        self.description.origin.session_version += 1;
+        let mut offer = self.description.clone();
+        let bundle_attributes: Vec<Attribute> = offer
+            .media_descriptions
+            .first()
+            .map(|m| {
+                m.attributes
+                    .iter()
+                    .filter(|a| BUNDLE_ATTRIBUTES.contains(&a.key.as_str()))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut bundle = "BUNDLE 0".to_owned();
+        for track in self.tracks.iter().filter(|t| t.camera.is_some()) {
+            bundle.push(' ');
+            bundle.push_str(&track.mid);
+        }
+        for attribute in offer.attributes.iter_mut().filter(|a| a.key == "group") {
+            attribute.value = Some(bundle.clone());
+        }
+        for track in &self.tracks {
+            offer = offer.with_media(track.media_description(&bundle_attributes));
+        }
+        offer
+    }
+
+    /// Sends an offer describing the current tracks and waits for the peer to answer
+    async fn renegotiate(&mut self) -> Result<SessionDescription, StreamError> {
+        let offer = SdpOffer {
+            sdp_type: "offer".to_owned(),
+            sdp: self.offer().marshal(),
+        };
+        let offer = serde_json::to_string(&offer)
+            .map_err(|e| StreamError::StreamNegotiationError(e.to_string()))?;
+        self.negotiation
+            .write(self.engine.encode(offer).as_bytes())
+            .await?;
+
+        let mut buffer = vec![0; NEGOTIATION_BUFFER_SIZE];
+        let len = self
+            .negotiation
+            .read(&mut buffer)
+            .or(async {
+                Timer::after(NEGOTIATION_TIMEOUT).await;
+                Err(io::ErrorKind::TimedOut.into())
+            })
+            .await?;
+        let answer = self
+            .engine
+            .decode(&buffer[..len])
+            .map_err(|e| StreamError::StreamNegotiationError(e.to_string()))?;
+        let answer: SdpOffer = serde_json::from_slice(&answer)
+            .map_err(|e| StreamError::StreamNegotiationError(e.to_string()))?;
+        // the peer never sends offers of its own since it has nothing to send
+        if answer.sdp_type != "answer" {
+            return Err(StreamError::StreamNegotiationError(format!(
+                "unexpected type {}",
+                answer.sdp_type
+            )));
+        }
+        SessionDescription::unmarshal(&mut Cursor::new(answer.sdp))
+            .map_err(|e| StreamError::StreamNegotiationError(e.to_string()))
+    }
+
+    async fn send_frames(&mut self) -> Result<(), StreamError> {
+        // rfc3550 section 5.1, the timestamp of a frame is its sampling instant
+        let elapsed = self.started.elapsed().as_micros() as u64;
+        let timestamp = (elapsed * RTP_VIDEO_CLOCK_RATE as u64 / 1_000_000) as u32;
+        for track in self.tracks.iter_mut() {
+            let Some(camera) = track.camera.as_ref() else {
+                continue;
+            };
+            let image = match camera.lock().unwrap().get_image() {
+                Ok(image) if image.mime_type == MIME_TYPE_JPEG => Ok(image),
+                Ok(image) => Err(format!("unsupported format {}", image.mime_type)),
+                Err(e) => Err(e.to_string()),
+            };
+            let payloads = image.and_then(|image| {
+                JpegFrame::parse(&image.data)
+                    .map(|frame| frame.payloads())
+                    .map_err(|e| e.to_string())
+            });
+            let payloads = match payloads {
+                Ok(payloads) => payloads,
+                Err(e) => {
+                    if !track.failing {
+                        log::warn!("cannot stream camera {}: {}", track.name, e);
+                    }
+                    track.failing = true;
+                    continue;
+                }
+            };
+            track.failing = false;
+
+            let timestamp = timestamp.wrapping_add(track.timestamp_offset);
+            let last = payloads.len() - 1;
+            for (i, payload) in payloads.iter().enumerate() {
+                let (roc, mut packet) = track.rtp.packet(timestamp, i == last, payload);
+                self.srtp.protect(roc, &mut packet)?;
+                self.sender.send(&packet).await?;
+            }
+        }
+        Ok(())
+    }
+}
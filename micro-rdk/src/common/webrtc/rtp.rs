@@ -0,0 +1,367 @@
+//! RTP packets (RFC 3550) carrying JPEG frames (RFC 2435)
+use thiserror::Error;
+
+use super::srtp::SRTP_AUTH_TAG_LEN;
+use super::turn::CHANNEL_DATA_HEADER_LEN;
+
+/// Static payload type assigned to JPEG (RFC 3551)
+pub(crate) const RTP_JPEG_PAYLOAD_TYPE: u8 = 26;
+/// RTP clock rate of video payloads
+pub(crate) const RTP_VIDEO_CLOCK_RATE: u32 = 90000;
+// keeps SRTP packets, possibly relayed by a TURN server, clear of fragmentation
+const RTP_MAX_PACKET_SIZE: usize = 1200;
+const RTP_HEADER_LEN: usize = 12;
+const RTP_VERSION: u8 = 2;
+
+// rfc2435 section 3.1
+const JPEG_HEADER_LEN: usize = 8;
+const RESTART_MARKER_HEADER_LEN: usize = 4;
+const QUANTIZATION_TABLE_HEADER_LEN: usize = 4;
+// tables are sent in every frame's first packet
+const JPEG_DYNAMIC_Q: u8 = 255;
+const JPEG_TYPE_RESTART_MARKERS: u8 = 64;
+const JPEG_MAX_DIMENSION: u16 = 2040;
+
+const MARKER_SOI: u8 = 0xd8;
+const MARKER_EOI: u8 = 0xd9;
+const MARKER_SOF0: u8 = 0xc0;
+const MARKER_DQT: u8 = 0xdb;
+const MARKER_DRI: u8 = 0xdd;
+const MARKER_SOS: u8 = 0xda;
+
+#[derive(Error, Debug)]
+pub enum RtpError {
+    #[error("malformed jpeg")]
+    RtpMalformedJpeg,
+    #[error("jpeg cannot be sent over rtp: {0}")]
+    RtpUnsupportedJpeg(&'static str),
+}
+
+/// Sequence of RTP packets sent with the same SSRC
+pub(crate) struct RtpStream {
+    ssrc: u32,
+    payload_type: u8,
+    sequence: u16,
+    roc: u32,
+}
+
+impl RtpStream {
+    pub(crate) fn new(ssrc: u32, payload_type: u8) -> Self {
+        Self {
+            ssrc,
+            payload_type,
+            sequence: rand::random::<u16>() & 0x7fff,
+            roc: 0,
+        }
+    }
+    pub(crate) fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+    /// Builds the next packet of the stream along with its rollover counter, room is left for
+    /// the SRTP authentication tag
+    pub(crate) fn packet(
+        &mut self,
+        timestamp: u32,
+        marker: bool,
+        payload: &[u8],
+    ) -> (u32, Vec<u8>) {
+        let mut packet = Vec::with_capacity(RTP_HEADER_LEN + payload.len() + SRTP_AUTH_TAG_LEN);
+        packet.push(RTP_VERSION << 6);
+        packet.push(((marker as u8) << 7) | self.payload_type);
+        packet.extend_from_slice(&self.sequence.to_be_bytes());
+        packet.extend_from_slice(&timestamp.to_be_bytes());
+        packet.extend_from_slice(&self.ssrc.to_be_bytes());
+        packet.extend_from_slice(payload);
+
+        let roc = self.roc;
+        self.sequence = self.sequence.wrapping_add(1);
+        if self.sequence == 0 {
+            self.roc = self.roc.wrapping_add(1);
+        }
+        (roc, packet)
+    }
+}
+
+/// The parts of a baseline JPEG needed to send it as described by RFC 2435, the decoder is
+/// expected to rebuild the headers using the standard Huffman tables
+#[derive(Debug)]
+pub(crate) struct JpegFrame<'a> {
+    typ: u8,
+    width: u8,
+    height: u8,
+    restart_interval: u16,
+    precision: u8,
+    tables: Vec<&'a [u8]>,
+    scan: &'a [u8],
+}
+
+impl<'a> JpegFrame<'a> {
+    pub(crate) fn parse(jpeg: &'a [u8]) -> Result<Self, RtpError> {
+        if !jpeg.starts_with(&[0xff, MARKER_SOI]) {
+            return Err(RtpError::RtpMalformedJpeg);
+        }
+        let mut tables: [Option<(bool, &[u8])>; 4] = [None; 4];
+        let mut frame = None;
+        let mut restart_interval = 0;
+        let mut pos = 2;
+        let scan = loop {
+            let (marker, segment) = match jpeg.get(pos..pos + 4) {
+                Some([0xff, marker, len @ ..]) => {
+                    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+                    let segment = jpeg
+                        .get(pos + 4..pos + 2 + len)
+                        .ok_or(RtpError::RtpMalformedJpeg)?;
+                    pos += 2 + len;
+                    (*marker, segment)
+                }
+                _ => return Err(RtpError::RtpMalformedJpeg),
+            };
+            match marker {
+                MARKER_DQT => {
+                    let mut segment = segment;
+                    while let Some((pq_tq, rest)) = segment.split_first() {
+                        let wide = pq_tq >> 4 != 0;
+                        let len = if wide { 128 } else { 64 };
+                        let table = rest.get(..len).ok_or(RtpError::RtpMalformedJpeg)?;
+                        let slot = tables
+                            .get_mut((pq_tq & 0x0f) as usize)
+                            .ok_or(RtpError::RtpMalformedJpeg)?;
+                        let _ = slot.insert((wide, table));
+                        segment = &rest[len..];
+                    }
+                }
+                MARKER_SOF0 => {
+                    let _ = frame.insert(segment);
+                }
+                0xc1..=0xcf if marker != 0xc4 && marker != 0xc8 && marker != 0xcc => {
+                    return Err(RtpError::RtpUnsupportedJpeg("not a baseline jpeg"));
+                }
+                MARKER_DRI => {
+                    let interval = segment.get(..2).ok_or(RtpError::RtpMalformedJpeg)?;
+                    restart_interval = u16::from_be_bytes([interval[0], interval[1]]);
+                }
+                MARKER_SOS => {
+                    let end = jpeg
+                        .windows(2)
+                        .rposition(|m| m == [0xff, MARKER_EOI])
+                        .filter(|end| *end >= pos)
+                        .ok_or(RtpError::RtpMalformedJpeg)?;
+                    break &jpeg[pos..end];
+                }
+                _ => {}
+            }
+        };
+
+        let frame = frame.ok_or(RtpError::RtpMalformedJpeg)?;
+        if frame.len() < 15 {
+            return Err(RtpError::RtpUnsupportedJpeg("expected YCbCr components"));
+        }
+        let height = u16::from_be_bytes([frame[1], frame[2]]);
+        let width = u16::from_be_bytes([frame[3], frame[4]]);
+        if width > JPEG_MAX_DIMENSION || height > JPEG_MAX_DIMENSION {
+            return Err(RtpError::RtpUnsupportedJpeg(
+                "frame larger than 2040 pixels",
+            ));
+        }
+        if frame[5] != 3 || frame[10] != 0x11 || frame[13] != 0x11 {
+            return Err(RtpError::RtpUnsupportedJpeg("expected YCbCr components"));
+        }
+        // rfc2435 section 4.1
+        let typ = match frame[7] {
+            0x21 => 0,
+            0x22 => 1,
+            _ => {
+                return Err(RtpError::RtpUnsupportedJpeg(
+                    "expected 4:2:2 or 4:2:0 sampling",
+                ))
+            }
+        };
+        // luma uses the first table, both chroma components share the second one
+        let luma = tables[frame[8] as usize & 0x03];
+        let chroma = tables[frame[11] as usize & 0x03];
+        let (luma, chroma) = luma.zip(chroma).ok_or(RtpError::RtpMalformedJpeg)?;
+
+        Ok(Self {
+            typ: if restart_interval != 0 {
+                typ + JPEG_TYPE_RESTART_MARKERS
+            } else {
+                typ
+            },
+            width: width.div_ceil(8) as u8,
+            height: height.div_ceil(8) as u8,
+            restart_interval,
+            precision: (luma.0 as u8) | ((chroma.0 as u8) << 1),
+            tables: vec![luma.1, chroma.1],
+            scan,
+        })
+    }
+
+    fn quantization_table_header(&self) -> Vec<u8> {
+        let len: usize = self.tables.iter().map(|t| t.len()).sum();
+        let mut header = Vec::with_capacity(QUANTIZATION_TABLE_HEADER_LEN + len);
+        header.push(0);
+        header.push(self.precision);
+        header.extend_from_slice(&(len as u16).to_be_bytes());
+        for table in &self.tables {
+            header.extend_from_slice(table);
+        }
+        header
+    }
+
+    /// Splits the frame into RTP payloads, the last one should be sent with the marker bit set
+    pub(crate) fn payloads(&self) -> Vec<Vec<u8>> {
+        let mut payloads = vec![];
+        let mut offset = 0;
+        let available = RTP_MAX_PACKET_SIZE - CHANNEL_DATA_HEADER_LEN - RTP_HEADER_LEN;
+        let available = available - SRTP_AUTH_TAG_LEN - JPEG_HEADER_LEN;
+        let available = if self.restart_interval != 0 {
+            available - RESTART_MARKER_HEADER_LEN
+        } else {
+            available
+        };
+        while offset < self.scan.len() || payloads.is_empty() {
+            let mut payload = Vec::with_capacity(RTP_MAX_PACKET_SIZE);
+            payload.push(0);
+            payload.extend_from_slice(&(offset as u32).to_be_bytes()[1..]);
+            payload.extend_from_slice(&[self.typ, JPEG_DYNAMIC_Q, self.width, self.height]);
+            if self.restart_interval != 0 {
+                // fragments aren't aligned on restart intervals, rfc2435 section 3.1.7
+                payload.extend_from_slice(&self.restart_interval.to_be_bytes());
+                payload.extend_from_slice(&0xffff_u16.to_be_bytes());
+            }
+            let mut room = available;
+            if offset == 0 {
+                let tables = self.quantization_table_header();
+                room -= tables.len();
+                payload.extend_from_slice(&tables);
+            }
+            let end = self.scan.len().min(offset + room);
+            payload.extend_from_slice(&self.scan[offset..end]);
+            offset = end;
+            payloads.push(payload);
+        }
+        payloads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JpegFrame, RtpError, RtpStream, RTP_JPEG_PAYLOAD_TYPE, RTP_MAX_PACKET_SIZE};
+
+    fn jpeg(sampling: u8, restart_interval: Option<u16>, scan: &[u8]) -> Vec<u8> {
+        let mut jpeg = vec![0xff, 0xd8];
+        // JFIF header
+        jpeg.extend_from_slice(&[0xff, 0xe0, 0x00, 0x04, 0x4a, 0x46]);
+        for table in 0..2_u8 {
+            jpeg.extend_from_slice(&[0xff, 0xdb, 0x00, 0x43, table]);
+            jpeg.extend((0..64).map(|v| v + table));
+        }
+        jpeg.extend_from_slice(&[
+            0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0xf0, 0x01, 0x40, 0x03, 0x01, sampling, 0x00, 0x02,
+            0x11, 0x01, 0x03, 0x11, 0x01,
+        ]);
+        if let Some(interval) = restart_interval {
+            jpeg.extend_from_slice(&[0xff, 0xdd, 0x00, 0x04]);
+            jpeg.extend_from_slice(&interval.to_be_bytes());
+        }
+        // a truncated huffman table, they are skipped
+        jpeg.extend_from_slice(&[0xff, 0xc4, 0x00, 0x03, 0x00]);
+        jpeg.extend_from_slice(&[
+            0xff, 0xda, 0x00, 0x0c, 0x03, 0x01, 0x00, 0x02, 0x11, 0x03, 0x11, 0x00, 0x3f, 0x00,
+        ]);
+        jpeg.extend_from_slice(scan);
+        jpeg.extend_from_slice(&[0xff, 0xd9]);
+        jpeg
+    }
+
+    #[test_log::test]
+    fn test_parse_jpeg() {
+        let scan: Vec<u8> = (0..100).collect();
+        let image = jpeg(0x21, None, &scan);
+        let frame = JpegFrame::parse(&image);
+        assert!(frame.is_ok());
+        let frame = frame.unwrap();
+        assert_eq!(frame.typ, 0);
+        assert_eq!(frame.width, 40);
+        assert_eq!(frame.height, 30);
+        assert_eq!(frame.precision, 0);
+        assert_eq!(frame.tables.len(), 2);
+        assert_eq!(frame.tables[1][0], 1);
+        assert_eq!(frame.scan, &scan);
+
+        let image = jpeg(0x22, Some(4), &scan);
+        let frame = JpegFrame::parse(&image).unwrap();
+        assert_eq!(frame.typ, 65);
+        assert_eq!(frame.restart_interval, 4);
+
+        let image = jpeg(0x11, None, &scan);
+        assert!(matches!(
+            JpegFrame::parse(&image),
+            Err(RtpError::RtpUnsupportedJpeg(_))
+        ));
+        assert!(matches!(
+            JpegFrame::parse(&image[..image.len() - 2]),
+            Err(RtpError::RtpMalformedJpeg)
+        ));
+        assert!(matches!(
+            JpegFrame::parse(&image[2..]),
+            Err(RtpError::RtpMalformedJpeg)
+        ));
+
+        // the fake camera image is 4:4:4
+        let fake = include_bytes!("../camera/fake_image.jpg");
+        assert!(matches!(
+            JpegFrame::parse(fake),
+            Err(RtpError::RtpUnsupportedJpeg(_))
+        ));
+    }
+
+    #[test_log::test]
+    fn test_jpeg_payloads() {
+        let scan: Vec<u8> = (0..5000).map(|v| v as u8).collect();
+        let image = jpeg(0x21, None, &scan);
+        let frame = JpegFrame::parse(&image).unwrap();
+        let payloads = frame.payloads();
+        assert_eq!(payloads.len(), 5);
+
+        let mut reassembled = vec![];
+        for (i, payload) in payloads.iter().enumerate() {
+            assert!(payload.len() + 12 + 10 + 4 <= RTP_MAX_PACKET_SIZE);
+            let offset = u32::from_be_bytes([0, payload[1], payload[2], payload[3]]) as usize;
+            assert_eq!(offset, reassembled.len());
+            assert_eq!(&payload[4..8], &[0, 255, 40, 30]);
+            let data = if i == 0 {
+                // quantization table header
+                assert_eq!(&payload[8..12], &[0, 0, 0, 128]);
+                assert_eq!(payload[12], 0);
+                assert_eq!(payload[12 + 64], 1);
+                &payload[12 + 128..]
+            } else {
+                &payload[8..]
+            };
+            reassembled.extend_from_slice(data);
+        }
+        assert_eq!(reassembled, scan);
+
+        let image = jpeg(0x21, Some(8), &scan[..10]);
+        let payloads = JpegFrame::parse(&image).unwrap().payloads();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0][4], 64);
+        assert_eq!(&payloads[0][8..12], &[0, 8, 0xff, 0xff]);
+    }
+
+    #[test_log::test]
+    fn test_rtp_stream() {
+        let mut stream = RtpStream::new(0xdeadbeef, RTP_JPEG_PAYLOAD_TYPE);
+        stream.sequence = 0xffff;
+        let (roc, packet) = stream.packet(90000, true, &[1, 2, 3]);
+        assert_eq!(roc, 0);
+        assert_eq!(
+            packet,
+            [0x80, 0x9a, 0xff, 0xff, 0x00, 0x01, 0x5f, 0x90, 0xde, 0xad, 0xbe, 0xef, 1, 2, 3]
+        );
+        let (roc, packet) = stream.packet(90000, false, &[]);
+        assert_eq!(roc, 1);
+        assert_eq!(&packet[..4], &[0x80, 0x1a, 0x00, 0x00]);
+    }
+}
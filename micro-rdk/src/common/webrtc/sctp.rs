@@ -16,9 +16,14 @@ use thiserror::Error;
 use futures_lite::{future::poll_fn, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use sctp_proto::{
     Association, AssociationHandle, ClientConfig, DatagramEvent, Endpoint, EndpointConfig, Event,
-    Payload, ServerConfig, StreamEvent, StreamId, Transmit,
+    Payload, PayloadProtocolIdentifier, ServerConfig, StreamEvent, StreamId, Transmit,
 };
 
+/// Both data channels are negotiated out of band, the first one carries gRPC requests
+pub(crate) const DATA_CHANNEL_ID: StreamId = 0;
+/// Session descriptions renegotiating the connection are exchanged on this channel
+pub(crate) const NEGOTIATION_CHANNEL_ID: StreamId = 1;
+
 //#[derive(Clone)]
 struct SctpStream {
     waker: Option<Waker>,
@@ -42,6 +47,9 @@ pub struct Channel {
 }
 
 impl Channel {
+    pub(crate) fn id(&self) -> StreamId {
+        self.tx_stream_id
+    }
     pub async fn write(&self, buf: &[u8]) -> std::io::Result<()> {
         if *self.closed.lock().unwrap() {
             return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
@@ -305,22 +313,26 @@ where
                     break;
                 }
                 Event::Connected => {
-                    match association.open_stream(0, sctp_proto::PayloadProtocolIdentifier::Binary)
-                    {
-                        Err(e) => {
-                            log::error!(" cannot open stream {:?}", e);
-                        }
-                        Ok(s) => {
-                            let c = Channel {
-                                tx_event: self.sctp_event_tx.clone(),
-                                tx_stream_id: s.stream_identifier(),
-                                rx_channel: Arc::new(Mutex::new(SctpStream { waker: None })),
-                                closed: Arc::new(Mutex::new(false)),
-                                association: self.association.clone(),
-                            };
-                            self.channels.insert(ChannelId(0), c.clone());
-                            if let Err(e) = self.channels_rx.try_send(c) {
-                                log::error!("Failed to send opened channel {:?}", e);
+                    for (id, ppi) in [
+                        (DATA_CHANNEL_ID, PayloadProtocolIdentifier::Binary),
+                        (NEGOTIATION_CHANNEL_ID, PayloadProtocolIdentifier::String),
+                    ] {
+                        match association.open_stream(id, ppi) {
+                            Err(e) => {
+                                log::error!(" cannot open stream {:?}", e);
+                            }
+                            Ok(s) => {
+                                let c = Channel {
+                                    tx_event: self.sctp_event_tx.clone(),
+                                    tx_stream_id: s.stream_identifier(),
+                                    rx_channel: Arc::new(Mutex::new(SctpStream { waker: None })),
+                                    closed: Arc::new(Mutex::new(false)),
+                                    association: self.association.clone(),
+                                };
+                                self.channels.insert(ChannelId(id), c.clone());
+                                if let Err(e) = self.channels_rx.try_send(c) {
+                                    log::error!("Failed to send opened channel {:?}", e);
+                                }
                             }
                         }
                     }
@@ -0,0 +1,274 @@
+//! SRTP (RFC 3711) protection of outgoing RTP packets with the SRTP_AES128_CM_HMAC_SHA1_80
+//! profile, the master key and salt are exported from the DTLS handshake (RFC 5764)
+use thiserror::Error;
+
+pub(crate) const SRTP_MASTER_KEY_LEN: usize = 16;
+pub(crate) const SRTP_MASTER_SALT_LEN: usize = 14;
+const SRTP_AUTH_KEY_LEN: usize = 20;
+pub(crate) const SRTP_AUTH_TAG_LEN: usize = 10;
+
+/// Label of the DTLS exporter producing the SRTP keying material (RFC 5764 section 4.2)
+pub const SRTP_EXPORTER_LABEL: &str = "EXTRACTOR-dtls_srtp";
+/// Size of the exported keying material, a key and a salt for each side of the connection
+pub const SRTP_KEYING_MATERIAL_LEN: usize = 2 * (SRTP_MASTER_KEY_LEN + SRTP_MASTER_SALT_LEN);
+
+// rfc3711 section 4.3.2
+const LABEL_RTP_ENCRYPTION: u8 = 0x00;
+const LABEL_RTP_AUTHENTICATION: u8 = 0x01;
+const LABEL_RTP_SALT: u8 = 0x02;
+
+const RTP_HEADER_LEN: usize = 12;
+
+#[derive(Error, Debug)]
+pub enum SrtpError {
+    #[error(transparent)]
+    SrtpCipherError(#[from] Box<dyn std::error::Error + Send + Sync>),
+    #[error("malformed rtp packet")]
+    SrtpMalformedPacket,
+    #[error("srtp keying material unavailable")]
+    SrtpNoKeyingMaterial,
+}
+
+/// Cryptographic primitives used by SRTP, they are provided by the platform's DTLS implementation
+pub trait SrtpCipher: Send {
+    /// XORs `data` with the AES-128 counter mode keystream starting at counter block `iv`
+    fn aes_128_ctr(
+        &mut self,
+        key: &[u8; SRTP_MASTER_KEY_LEN],
+        iv: &[u8; 16],
+        data: &mut [u8],
+    ) -> Result<(), SrtpError>;
+    /// HMAC-SHA1 of the concatenation of `data`
+    fn hmac_sha1(&mut self, key: &[u8], data: &[&[u8]]) -> Result<[u8; 20], SrtpError>;
+}
+
+/// Keying material exported once the DTLS handshake completes, laid out as
+/// client key | server key | client salt | server salt (RFC 5764 section 4.2)
+pub struct SrtpKeyingMaterial([u8; SRTP_KEYING_MATERIAL_LEN]);
+
+impl SrtpKeyingMaterial {
+    pub fn new(material: [u8; SRTP_KEYING_MATERIAL_LEN]) -> Self {
+        Self(material)
+    }
+    /// Master key and salt protecting the packets sent by the DTLS server, the role micro-rdk
+    /// always takes
+    pub fn server_master(&self) -> ([u8; SRTP_MASTER_KEY_LEN], [u8; SRTP_MASTER_SALT_LEN]) {
+        let mut key = [0; SRTP_MASTER_KEY_LEN];
+        let mut salt = [0; SRTP_MASTER_SALT_LEN];
+        key.copy_from_slice(&self.0[SRTP_MASTER_KEY_LEN..2 * SRTP_MASTER_KEY_LEN]);
+        salt.copy_from_slice(&self.0[2 * SRTP_MASTER_KEY_LEN + SRTP_MASTER_SALT_LEN..]);
+        (key, salt)
+    }
+}
+
+// rfc3711 section 4.3.1, the key derivation rate is 0 so x is the label XORed into the salt
+fn derive_session_key<const N: usize>(
+    cipher: &mut dyn SrtpCipher,
+    master_key: &[u8; SRTP_MASTER_KEY_LEN],
+    master_salt: &[u8; SRTP_MASTER_SALT_LEN],
+    label: u8,
+) -> Result<[u8; N], SrtpError> {
+    let mut iv = [0; 16];
+    iv[..SRTP_MASTER_SALT_LEN].copy_from_slice(master_salt);
+    iv[7] ^= label;
+    let mut key = [0; N];
+    cipher.aes_128_ctr(master_key, &iv, &mut key)?;
+    Ok(key)
+}
+
+// rfc3711 section 4.1.1, IV = (salt * 2^16) XOR (SSRC * 2^64) XOR (index * 2^16)
+fn packet_iv(salt: &[u8; SRTP_MASTER_SALT_LEN], ssrc: u32, roc: u32, seq: u16) -> [u8; 16] {
+    let mut iv = [0; 16];
+    iv[..SRTP_MASTER_SALT_LEN].copy_from_slice(salt);
+    for (b, s) in iv[4..8].iter_mut().zip(ssrc.to_be_bytes()) {
+        *b ^= s;
+    }
+    for (b, i) in iv[8..14]
+        .iter_mut()
+        .zip(roc.to_be_bytes().into_iter().chain(seq.to_be_bytes()))
+    {
+        *b ^= i;
+    }
+    iv
+}
+
+// length of the fixed header, CSRCs and header extension of an RTP packet
+fn rtp_header_len(packet: &[u8]) -> Result<usize, SrtpError> {
+    if packet.len() < RTP_HEADER_LEN {
+        return Err(SrtpError::SrtpMalformedPacket);
+    }
+    let mut len = RTP_HEADER_LEN + 4 * (packet[0] & 0x0f) as usize;
+    if packet[0] & 0x10 != 0 {
+        let extension = packet
+            .get(len + 2..len + 4)
+            .ok_or(SrtpError::SrtpMalformedPacket)?;
+        len += 4 + 4 * u16::from_be_bytes([extension[0], extension[1]]) as usize;
+    }
+    if len > packet.len() {
+        return Err(SrtpError::SrtpMalformedPacket);
+    }
+    Ok(len)
+}
+
+/// Session keys protecting the RTP packets we send
+pub struct SrtpContext {
+    cipher: Box<dyn SrtpCipher>,
+    session_key: [u8; SRTP_MASTER_KEY_LEN],
+    session_salt: [u8; SRTP_MASTER_SALT_LEN],
+    auth_key: [u8; SRTP_AUTH_KEY_LEN],
+}
+
+impl SrtpContext {
+    pub fn new(
+        master_key: &[u8; SRTP_MASTER_KEY_LEN],
+        master_salt: &[u8; SRTP_MASTER_SALT_LEN],
+        mut cipher: Box<dyn SrtpCipher>,
+    ) -> Result<Self, SrtpError> {
+        let session_key = derive_session_key(
+            cipher.as_mut(),
+            master_key,
+            master_salt,
+            LABEL_RTP_ENCRYPTION,
+        )?;
+        let auth_key = derive_session_key(
+            cipher.as_mut(),
+            master_key,
+            master_salt,
+            LABEL_RTP_AUTHENTICATION,
+        )?;
+        let session_salt =
+            derive_session_key(cipher.as_mut(), master_key, master_salt, LABEL_RTP_SALT)?;
+        Ok(Self {
+            cipher,
+            session_key,
+            session_salt,
+            auth_key,
+        })
+    }
+
+    /// Encrypts the payload of an RTP packet in place and appends its authentication tag,
+    /// `roc` is the number of times the sequence number of the packet's SSRC wrapped around
+    pub fn protect(&mut self, roc: u32, packet: &mut Vec<u8>) -> Result<(), SrtpError> {
+        let header_len = rtp_header_len(packet)?;
+        let seq = u16::from_be_bytes([packet[2], packet[3]]);
+        let ssrc = u32::from_be_bytes([packet[8], packet[9], packet[10], packet[11]]);
+
+        let iv = packet_iv(&self.session_salt, ssrc, roc, seq);
+        self.cipher
+            .aes_128_ctr(&self.session_key, &iv, &mut packet[header_len..])?;
+
+        // rfc3711 section 4.2, the authenticated portion is followed by the ROC
+        let tag = self
+            .cipher
+            .hmac_sha1(&self.auth_key, &[packet.as_slice(), &roc.to_be_bytes()])?;
+        packet.extend_from_slice(&tag[..SRTP_AUTH_TAG_LEN]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{packet_iv, rtp_header_len, SrtpError};
+
+    #[test_log::test]
+    fn test_packet_iv() {
+        let salt = [
+            0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd,
+        ];
+        assert_eq!(
+            packet_iv(&salt, 0, 0, 0),
+            [
+                0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd,
+                0x00, 0x00
+            ]
+        );
+        assert_eq!(
+            packet_iv(&salt, 0x11223344, 0x55667788, 0x99aa),
+            [
+                0xf0, 0xf1, 0xf2, 0xf3, 0xe5, 0xd7, 0xc5, 0xb3, 0xad, 0x9f, 0x8d, 0x73, 0x65, 0x57,
+                0x00, 0x00
+            ]
+        );
+    }
+
+    #[test_log::test]
+    fn test_rtp_header_len() {
+        let mut packet = vec![0x80, 26, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0xaa];
+        assert_eq!(rtp_header_len(&packet).unwrap(), 12);
+        assert!(matches!(
+            rtp_header_len(&packet[..11]),
+            Err(SrtpError::SrtpMalformedPacket)
+        ));
+        // one CSRC
+        packet[0] = 0x81;
+        assert!(rtp_header_len(&packet).is_err());
+        packet.extend_from_slice(&[0; 4]);
+        assert_eq!(rtp_header_len(&packet).unwrap(), 16);
+        // a one word header extension
+        packet[0] = 0x90;
+        packet.truncate(12);
+        packet.extend_from_slice(&[0xbe, 0xde, 0, 1, 1, 2, 3, 4]);
+        assert_eq!(rtp_header_len(&packet).unwrap(), 20);
+    }
+
+    #[cfg(feature = "native")]
+    #[test_log::test]
+    fn test_key_derivation() {
+        use super::{derive_session_key, SrtpCipher};
+        use crate::native::dtls::NativeSrtpCipher;
+
+        let mut cipher = NativeSrtpCipher;
+
+        // rfc3711 appendix B.2
+        let key = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let iv = [
+            0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd,
+            0x00, 0x00,
+        ];
+        let mut keystream = [0; 16];
+        assert!(cipher.aes_128_ctr(&key, &iv, &mut keystream).is_ok());
+        assert_eq!(
+            keystream,
+            [
+                0xe0, 0x3e, 0xad, 0x09, 0x35, 0xc9, 0x5e, 0x80, 0xe1, 0x66, 0xb1, 0x6d, 0xd9, 0x2b,
+                0x4e, 0xb4
+            ]
+        );
+
+        // rfc3711 appendix B.3
+        let master_key = [
+            0xe1, 0xf9, 0x7a, 0x0d, 0x3e, 0x01, 0x8b, 0xe0, 0xd6, 0x4f, 0xa3, 0x2c, 0x06, 0xde,
+            0x41, 0x39,
+        ];
+        let master_salt = [
+            0x0e, 0xc6, 0x75, 0xad, 0x49, 0x8a, 0xfe, 0xeb, 0xb6, 0x96, 0x0b, 0x3a, 0xab, 0xe6,
+        ];
+        let session_key: [u8; 16] =
+            derive_session_key(&mut cipher, &master_key, &master_salt, 0x00).unwrap();
+        assert_eq!(
+            session_key,
+            [
+                0xc6, 0x1e, 0x7a, 0x93, 0x74, 0x4f, 0x39, 0xee, 0x10, 0x73, 0x4a, 0xfe, 0x3f, 0xf7,
+                0xa0, 0x87
+            ]
+        );
+        let session_salt: [u8; 14] =
+            derive_session_key(&mut cipher, &master_key, &master_salt, 0x02).unwrap();
+        assert_eq!(
+            session_salt,
+            [0x30, 0xcb, 0xbc, 0x08, 0x86, 0x3d, 0x8c, 0x85, 0xd4, 0x9d, 0xb3, 0x4a, 0x9a, 0xe1]
+        );
+        let auth_key: [u8; 20] =
+            derive_session_key(&mut cipher, &master_key, &master_salt, 0x01).unwrap();
+        assert_eq!(
+            auth_key,
+            [
+                0xce, 0xbe, 0x32, 0x1f, 0x6f, 0xf7, 0x71, 0x6b, 0x6f, 0xd4, 0xab, 0x49, 0xaf, 0x25,
+                0x6a, 0x15, 0x6d, 0x38, 0xba, 0xa4
+            ]
+        );
+    }
+}
@@ -0,0 +1,536 @@
+#![allow(dead_code)]
+//! Minimal TURN client (RFC5766) used by the ICE agent to obtain a relayed candidate.
+//! It doesn't do any IO, requests are returned as bytes for the agent to send to the TURN
+//! server and responses are handed back to it.
+//! Notable omissions:
+//! * Only UDP allocations are supported
+//! * Data is only relayed through channels, Send and Data indications are not used
+//! * Permissions are only installed through channel bindings
+//! * The relayed address family isn't requested (RFC6156), the server's default is used
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use bytecodec::EncodeExt;
+use stun_codec::{
+    rfc5389::attributes::{
+        ErrorCode, Fingerprint, MessageIntegrity, Nonce, Realm, Username, XorMappedAddress,
+    },
+    rfc5766::{
+        attributes::{
+            ChannelNumber, Data, Lifetime, RequestedTransport, XorPeerAddress, XorRelayAddress,
+        },
+        methods::{ALLOCATE, CHANNEL_BIND, REFRESH},
+    },
+    Message, MessageClass, Method, TransactionId,
+};
+
+use super::ice::IceError;
+
+stun_codec::define_attribute_enums!(
+    TurnAttribute,
+    TurnAttributeDecoder,
+    TurnAttributeEncoder,
+    [
+        Username,
+        MessageIntegrity,
+        ErrorCode,
+        XorMappedAddress,
+        Fingerprint,
+        Realm,
+        Nonce,
+        ChannelNumber,
+        Data,
+        Lifetime,
+        RequestedTransport,
+        XorPeerAddress,
+        XorRelayAddress
+    ]
+);
+
+// protocol number of UDP, the only transport that can be requested
+const UDP_TRANSPORT: u8 = 17;
+const FIRST_CHANNEL_NUMBER: u16 = 0x4000;
+const LAST_CHANNEL_NUMBER: u16 = 0x7FFF;
+pub(crate) const CHANNEL_DATA_HEADER_LEN: usize = 4;
+// channel bindings last 10 minutes and the permission they install 5 minutes
+const CHANNEL_REFRESH_INTERVAL: Duration = Duration::from_secs(240);
+const DEFAULT_LIFETIME: Duration = Duration::from_secs(600);
+const ERROR_UNAUTHORIZED: u16 = 401;
+const ERROR_STALE_NONCE: u16 = 438;
+
+/// Returns true when the first byte of a datagram is the one of a ChannelData message
+pub(crate) fn is_channel_data(first_byte: u8) -> bool {
+    (0x40..=0x7F).contains(&first_byte)
+}
+
+/// Frames `data` as a ChannelData message for `channel`
+pub(crate) fn encode_channel_data(channel: u16, data: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(CHANNEL_DATA_HEADER_LEN + data.len());
+    framed.extend_from_slice(&channel.to_be_bytes());
+    framed.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    framed.extend_from_slice(data);
+    framed
+}
+
+/// Returns the channel number and the payload of a ChannelData message
+pub(crate) fn decode_channel_data(buf: &[u8]) -> Option<(u16, &[u8])> {
+    if buf.len() < CHANNEL_DATA_HEADER_LEN || !is_channel_data(buf[0]) {
+        return None;
+    }
+    let channel = u16::from_be_bytes([buf[0], buf[1]]);
+    let len = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+    buf.get(CHANNEL_DATA_HEADER_LEN..CHANNEL_DATA_HEADER_LEN + len)
+        .map(|data| (channel, data))
+}
+
+/// Outcome of processing a message received from the TURN server
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TurnEvent {
+    Allocated,
+    Refreshed,
+    ChannelBound(SocketAddr),
+    /// The request had to be authenticated again, the new request should be sent to the server
+    Resend(Vec<u8>),
+    Ignored,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TurnRequest {
+    Allocate,
+    Refresh,
+    ChannelBind(u16),
+}
+
+#[derive(Debug)]
+struct TurnChannel {
+    number: u16,
+    peer: SocketAddr,
+    bound_at: Option<Instant>,
+}
+
+pub(crate) struct TurnClient {
+    server: SocketAddr,
+    username: String,
+    password: String,
+    realm: Option<Realm>,
+    nonce: Option<Nonce>,
+    relayed_address: Option<SocketAddr>,
+    mapped_address: Option<SocketAddr>,
+    lifetime: Duration,
+    allocated_at: Option<Instant>,
+    channels: Vec<TurnChannel>,
+    pending: Vec<(TransactionId, TurnRequest)>,
+}
+
+impl TurnClient {
+    pub(crate) fn new(server: SocketAddr, username: String, password: String) -> Self {
+        Self {
+            server,
+            username,
+            password,
+            realm: None,
+            nonce: None,
+            relayed_address: None,
+            mapped_address: None,
+            lifetime: DEFAULT_LIFETIME,
+            allocated_at: None,
+            channels: vec![],
+            pending: vec![],
+        }
+    }
+
+    pub(crate) fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    pub(crate) fn relayed_address(&self) -> Option<SocketAddr> {
+        self.relayed_address
+    }
+
+    pub(crate) fn mapped_address(&self) -> Option<SocketAddr> {
+        self.mapped_address
+    }
+
+    /// Returns the channel bound to `peer` if any
+    pub(crate) fn channel_for_peer(&self, peer: &SocketAddr) -> Option<u16> {
+        self.channels
+            .iter()
+            .find(|c| c.peer == *peer && c.bound_at.is_some())
+            .map(|c| c.number)
+    }
+
+    /// Returns the peer a channel is bound to
+    pub(crate) fn peer_for_channel(&self, number: u16) -> Option<SocketAddr> {
+        self.channels
+            .iter()
+            .find(|c| c.number == number && c.bound_at.is_some())
+            .map(|c| c.peer)
+    }
+
+    pub(crate) fn allocate_request(&mut self) -> Result<Vec<u8>, IceError> {
+        self.request(TurnRequest::Allocate)
+    }
+
+    /// Returns a ChannelBind request for `peer` unless a channel was already requested for it
+    pub(crate) fn channel_bind_request(
+        &mut self,
+        peer: SocketAddr,
+    ) -> Result<Option<Vec<u8>>, IceError> {
+        if self.channels.iter().any(|c| c.peer == peer) {
+            return Ok(None);
+        }
+        let number = FIRST_CHANNEL_NUMBER + self.channels.len() as u16;
+        if number > LAST_CHANNEL_NUMBER {
+            return Err(IceError::IceTurnNoChannelAvailable);
+        }
+        self.channels.push(TurnChannel {
+            number,
+            peer,
+            bound_at: None,
+        });
+        self.request(TurnRequest::ChannelBind(number)).map(Some)
+    }
+
+    /// Returns the requests needed to keep the allocation and the channel bindings alive
+    pub(crate) fn refresh_requests(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let mut requests = vec![];
+        if self
+            .allocated_at
+            .is_some_and(|at| now.duration_since(at) > self.lifetime / 2)
+        {
+            // avoid sending a refresh at every call while waiting for the response
+            self.allocated_at = Some(now);
+            if let Ok(request) = self.request(TurnRequest::Refresh) {
+                requests.push(request);
+            }
+        }
+        let expired: Vec<u16> = self
+            .channels
+            .iter_mut()
+            .filter(|c| {
+                c.bound_at
+                    .is_some_and(|at| now.duration_since(at) > CHANNEL_REFRESH_INTERVAL)
+            })
+            .map(|c| {
+                c.bound_at = Some(now);
+                c.number
+            })
+            .collect();
+        for number in expired {
+            if let Ok(request) = self.request(TurnRequest::ChannelBind(number)) {
+                requests.push(request);
+            }
+        }
+        requests
+    }
+
+    /// Returns the request releasing the allocation
+    pub(crate) fn deallocate_request(&mut self) -> Result<Vec<u8>, IceError> {
+        self.lifetime = Duration::ZERO;
+        self.request(TurnRequest::Refresh)
+    }
+
+    fn request(&mut self, request: TurnRequest) -> Result<Vec<u8>, IceError> {
+        let id = TransactionId::new(rand::random());
+        let method = match request {
+            TurnRequest::Allocate => ALLOCATE,
+            TurnRequest::Refresh => REFRESH,
+            TurnRequest::ChannelBind(_) => CHANNEL_BIND,
+        };
+        let mut message = Message::<TurnAttribute>::new(MessageClass::Request, method, id);
+        match request {
+            TurnRequest::Allocate => {
+                message.add_attribute(TurnAttribute::RequestedTransport(RequestedTransport::new(
+                    UDP_TRANSPORT,
+                )));
+            }
+            TurnRequest::Refresh => {
+                message.add_attribute(TurnAttribute::Lifetime(
+                    Lifetime::new(self.lifetime).map_err(|_| IceError::IceStunEncodingError)?,
+                ));
+            }
+            TurnRequest::ChannelBind(number) => {
+                let peer = self
+                    .channels
+                    .iter()
+                    .find(|c| c.number == number)
+                    .ok_or(IceError::IceTurnNoChannelAvailable)?
+                    .peer;
+                message.add_attribute(TurnAttribute::ChannelNumber(
+                    ChannelNumber::new(number).map_err(|_| IceError::IceStunEncodingError)?,
+                ));
+                message.add_attribute(TurnAttribute::XorPeerAddress(XorPeerAddress::new(peer)));
+            }
+        }
+        let bytes = self.encode_authenticated(message)?;
+        self.pending.push((id, request));
+        Ok(bytes)
+    }
+
+    // adds the long term credentials once the realm and nonce are known, the first allocate
+    // request is sent without them
+    fn encode_authenticated(
+        &self,
+        mut message: Message<TurnAttribute>,
+    ) -> Result<Vec<u8>, IceError> {
+        if let (Some(realm), Some(nonce)) = (self.realm.as_ref(), self.nonce.as_ref()) {
+            let username =
+                Username::new(self.username.clone()).map_err(|_| IceError::IceStunEncodingError)?;
+            message.add_attribute(TurnAttribute::Username(username.clone()));
+            message.add_attribute(TurnAttribute::Realm(realm.clone()));
+            message.add_attribute(TurnAttribute::Nonce(nonce.clone()));
+            message.add_attribute(TurnAttribute::MessageIntegrity(
+                MessageIntegrity::new_long_term_credential(
+                    &message,
+                    &username,
+                    realm,
+                    &self.password,
+                )
+                .map_err(|_| IceError::IceStunEncodingError)?,
+            ));
+        }
+        let mut encoder = stun_codec::MessageEncoder::new();
+        encoder
+            .encode_into_bytes(message)
+            .map_err(|_| IceError::IceStunEncodingError)
+    }
+
+    /// Processes a response received from the TURN server
+    pub(crate) fn handle_response(
+        &mut self,
+        message: &Message<TurnAttribute>,
+        now: Instant,
+    ) -> Result<TurnEvent, IceError> {
+        let idx = match self
+            .pending
+            .iter()
+            .position(|(id, _)| *id == message.transaction_id())
+        {
+            Some(idx) => idx,
+            None => return Ok(TurnEvent::Ignored),
+        };
+        let (_, request) = self.pending.remove(idx);
+
+        match message.class() {
+            MessageClass::SuccessResponse => Ok(match request {
+                TurnRequest::Allocate => {
+                    self.relayed_address = message
+                        .get_attribute::<XorRelayAddress>()
+                        .map(|a| a.address());
+                    if self.relayed_address.is_none() {
+                        return Err(IceError::IceTurnAllocationFailed);
+                    }
+                    self.mapped_address = message
+                        .get_attribute::<XorMappedAddress>()
+                        .map(|a| a.address());
+                    if let Some(lifetime) = message.get_attribute::<Lifetime>() {
+                        self.lifetime = lifetime.lifetime();
+                    }
+                    self.allocated_at = Some(now);
+                    TurnEvent::Allocated
+                }
+                TurnRequest::Refresh => {
+                    if let Some(lifetime) = message.get_attribute::<Lifetime>() {
+                        self.lifetime = lifetime.lifetime();
+                    }
+                    self.allocated_at = Some(now);
+                    TurnEvent::Refreshed
+                }
+                TurnRequest::ChannelBind(number) => {
+                    match self.channels.iter_mut().find(|c| c.number == number) {
+                        Some(channel) => {
+                            channel.bound_at = Some(now);
+                            TurnEvent::ChannelBound(channel.peer)
+                        }
+                        None => TurnEvent::Ignored,
+                    }
+                }
+            }),
+            MessageClass::ErrorResponse => {
+                let code = message.get_attribute::<ErrorCode>().map_or(0, |e| e.code());
+                if code == ERROR_UNAUTHORIZED || code == ERROR_STALE_NONCE {
+                    let nonce = message.get_attribute::<Nonce>().cloned();
+                    let realm = message.get_attribute::<Realm>().cloned();
+                    // a second 401 with a nonce we already used means the credentials are wrong
+                    if nonce.is_none()
+                        || (code == ERROR_UNAUTHORIZED
+                            && self.nonce.is_some()
+                            && nonce == self.nonce)
+                    {
+                        return Err(IceError::IceTurnError(code));
+                    }
+                    self.nonce = nonce;
+                    if realm.is_some() {
+                        self.realm = realm;
+                    }
+                    return self.request(request).map(TurnEvent::Resend);
+                }
+                Err(IceError::IceTurnError(code))
+            }
+            _ => Ok(TurnEvent::Ignored),
+        }
+    }
+}
+
+/// Returns true when `method` is one of the methods of the TURN client
+pub(crate) fn is_turn_method(method: Method) -> bool {
+    method == ALLOCATE || method == REFRESH || method == CHANNEL_BIND
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
+
+    use bytecodec::{DecodeExt, EncodeExt};
+    use stun_codec::{
+        rfc5389::attributes::{ErrorCode, Nonce, Realm, XorMappedAddress},
+        rfc5766::attributes::{Lifetime, XorRelayAddress},
+        Message, MessageClass, MessageDecoder, MessageEncoder,
+    };
+
+    use super::{
+        decode_channel_data, encode_channel_data, TurnAttribute, TurnClient, TurnEvent,
+        FIRST_CHANNEL_NUMBER,
+    };
+    use crate::common::webrtc::ice::IceError;
+
+    fn decode(bytes: &[u8]) -> Message<TurnAttribute> {
+        MessageDecoder::<TurnAttribute>::new()
+            .decode_from_bytes(bytes)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn respond(
+        request: &Message<TurnAttribute>,
+        class: MessageClass,
+        attributes: Vec<TurnAttribute>,
+    ) -> Message<TurnAttribute> {
+        let mut response = Message::new(class, request.method(), request.transaction_id());
+        for attribute in attributes {
+            response.add_attribute(attribute);
+        }
+        // round trip through the codec as a real response would
+        decode(&MessageEncoder::new().encode_into_bytes(response).unwrap())
+    }
+
+    #[test_log::test]
+    fn test_channel_data() {
+        let framed = encode_channel_data(FIRST_CHANNEL_NUMBER, &[1, 2, 3]);
+        assert_eq!(framed, vec![0x40, 0x00, 0x00, 0x03, 1, 2, 3]);
+        assert_eq!(
+            decode_channel_data(&framed),
+            Some((FIRST_CHANNEL_NUMBER, &[1_u8, 2, 3][..]))
+        );
+        // truncated payload
+        assert_eq!(decode_channel_data(&framed[..5]), None);
+        // stun message
+        assert_eq!(decode_channel_data(&[0x00, 0x01, 0x00, 0x00]), None);
+    }
+
+    #[test_log::test]
+    fn test_allocation() -> Result<(), IceError> {
+        let server: SocketAddr = "10.0.0.1:3478".parse().unwrap();
+        let relayed: SocketAddr = "10.0.0.1:50000".parse().unwrap();
+        let mapped: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let peer: SocketAddr = "198.51.100.2:60000".parse().unwrap();
+        let now = Instant::now();
+
+        let mut client = TurnClient::new(server, "user".to_string(), "pass".to_string());
+        let request = decode(&client.allocate_request()?);
+        // the first request is not authenticated
+        assert!(request.get_attribute::<Nonce>().is_none());
+
+        let unauthorized = respond(
+            &request,
+            MessageClass::ErrorResponse,
+            vec![
+                TurnAttribute::ErrorCode(ErrorCode::new(401, "Unauthorized".to_string()).unwrap()),
+                TurnAttribute::Realm(Realm::new("viam".to_string()).unwrap()),
+                TurnAttribute::Nonce(Nonce::new("abcd".to_string()).unwrap()),
+            ],
+        );
+        let request = match client.handle_response(&unauthorized, now)? {
+            TurnEvent::Resend(request) => decode(&request),
+            e => panic!("unexpected event {:?}", e),
+        };
+        assert_eq!(request.get_attribute::<Nonce>().unwrap().value(), "abcd");
+
+        let allocated = respond(
+            &request,
+            MessageClass::SuccessResponse,
+            vec![
+                TurnAttribute::XorRelayAddress(XorRelayAddress::new(relayed)),
+                TurnAttribute::XorMappedAddress(XorMappedAddress::new(mapped)),
+                TurnAttribute::Lifetime(Lifetime::new(Duration::from_secs(600)).unwrap()),
+            ],
+        );
+        assert_eq!(
+            client.handle_response(&allocated, now)?,
+            TurnEvent::Allocated
+        );
+        assert_eq!(client.relayed_address(), Some(relayed));
+        assert_eq!(client.mapped_address(), Some(mapped));
+        // responses are only processed once
+        assert_eq!(client.handle_response(&allocated, now)?, TurnEvent::Ignored);
+
+        let request = decode(&client.channel_bind_request(peer)?.unwrap());
+        assert!(client.channel_bind_request(peer)?.is_none());
+        assert_eq!(client.channel_for_peer(&peer), None);
+        let bound = respond(&request, MessageClass::SuccessResponse, vec![]);
+        assert_eq!(
+            client.handle_response(&bound, now)?,
+            TurnEvent::ChannelBound(peer)
+        );
+        assert_eq!(client.channel_for_peer(&peer), Some(FIRST_CHANNEL_NUMBER));
+        assert_eq!(client.peer_for_channel(FIRST_CHANNEL_NUMBER), Some(peer));
+
+        // nothing to refresh yet, then both the allocation and the channel
+        assert!(client.refresh_requests(now).is_empty());
+        assert_eq!(
+            client
+                .refresh_requests(now + Duration::from_secs(301))
+                .len(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_allocation_wrong_credentials() -> Result<(), IceError> {
+        let mut client = TurnClient::new(
+            "10.0.0.1:3478".parse().unwrap(),
+            "user".to_string(),
+            "wrong".to_string(),
+        );
+        let unauthorized = |request: &Message<TurnAttribute>| {
+            respond(
+                request,
+                MessageClass::ErrorResponse,
+                vec![
+                    TurnAttribute::ErrorCode(
+                        ErrorCode::new(401, "Unauthorized".to_string()).unwrap(),
+                    ),
+                    TurnAttribute::Realm(Realm::new("viam".to_string()).unwrap()),
+                    TurnAttribute::Nonce(Nonce::new("abcd".to_string()).unwrap()),
+                ],
+            )
+        };
+        let request = decode(&client.allocate_request()?);
+        let request = match client.handle_response(&unauthorized(&request), Instant::now())? {
+            TurnEvent::Resend(request) => decode(&request),
+            e => panic!("unexpected event {:?}", e),
+        };
+        assert_eq!(
+            client.handle_response(&unauthorized(&request), Instant::now()),
+            Err(IceError::IceTurnError(401))
+        );
+        Ok(())
+    }
+}
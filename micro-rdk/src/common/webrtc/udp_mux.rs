@@ -1,6 +1,6 @@
 use std::{
     io::Result,
-    net::{SocketAddr, UdpSocket},
+    net::{IpAddr, SocketAddr, UdpSocket},
     ops::{Index, IndexMut},
     pin::Pin,
     sync::{Arc, Mutex},
@@ -11,11 +11,17 @@ use async_io::{Async, Readable};
 
 use futures_lite::{ready, AsyncRead, AsyncWrite, Future, FutureExt};
 
+use super::turn::{
+    decode_channel_data, encode_channel_data, is_channel_data, CHANNEL_DATA_HEADER_LEN,
+};
+
 #[derive(Clone, Copy, PartialEq)]
 #[allow(clippy::upper_case_acronyms)]
 enum MuxDirection {
     DTLS,
     STUN,
+    // RTP and RTCP, nothing listens for them so incoming packets are discarded
+    RTP,
     // This is the default value it's a placeholder so we panic if for some reason we try
     // to index with this.
     //TODO remove once testing is done
@@ -28,6 +34,7 @@ impl Index<MuxDirection> for [MuxState] {
         match index {
             MuxDirection::DTLS => &self[0],
             MuxDirection::STUN => &self[1],
+            MuxDirection::RTP => &self[2],
             MuxDirection::NODIR => panic!(),
         }
     }
@@ -37,15 +44,39 @@ impl IndexMut<MuxDirection> for [MuxState] {
         match index {
             MuxDirection::DTLS => &mut self[0],
             MuxDirection::STUN => &mut self[1],
+            MuxDirection::RTP => &mut self[2],
             MuxDirection::NODIR => panic!(),
         }
     }
 }
 
+/// A channel bound on a TURN server, packets exchanged with `peer` are relayed by `server`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RelayChannel {
+    server: SocketAddr,
+    number: u16,
+    peer: SocketAddr,
+}
+
 #[derive(Clone)]
 pub(crate) struct UdpMuxer {
     socket: Arc<Async<UdpSocket>>,
-    mux: Arc<Mutex<[MuxState; 2]>>,
+    mux: Arc<Mutex<[MuxState; 3]>>,
+    relay_channels: Arc<Mutex<Vec<RelayChannel>>>,
+    // an IPv6 socket also carries IPv4 traffic using IPv4-mapped addresses
+    dual_stack: bool,
+}
+
+// IPv4 peers reached through a dual stack socket show up as IPv4-mapped IPv6 addresses,
+// they are converted back so consumers only deal with plain IPv4 addresses
+fn from_dual_stack(addr: SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V6(v6) => v6
+            .ip()
+            .to_ipv4_mapped()
+            .map_or(addr, |v4| SocketAddr::new(IpAddr::V4(v4), v6.port())),
+        SocketAddr::V4(_) => addr,
+    }
 }
 
 impl Drop for UdpMuxer {
@@ -103,6 +134,11 @@ impl UdpMuxer {
         Self {
             socket: socket.clone(),
             mux: Default::default(),
+            relay_channels: Default::default(),
+            dual_stack: socket
+                .get_ref()
+                .local_addr()
+                .is_ok_and(|addr| addr.is_ipv6()),
         }
     }
     pub(crate) fn get_stun_mux(&self) -> Option<UdpMux> {
@@ -112,7 +148,7 @@ impl UdpMuxer {
             Some(UdpMux {
                 muxer: self.clone(),
                 direction: MuxDirection::STUN,
-                peer_addr: None,
+                destination: Default::default(),
             })
         } else {
             None
@@ -125,7 +161,7 @@ impl UdpMuxer {
             Some(UdpMux {
                 muxer: self.clone(),
                 direction: MuxDirection::DTLS,
-                peer_addr: None,
+                destination: Default::default(),
             })
         } else {
             None
@@ -141,7 +177,9 @@ impl UdpMuxer {
             if r.0 != 0 {
                 if dir == r.1 {
                     let socket = self.socket.as_ref().get_ref();
-                    return socket.recv_from(buf);
+                    return socket
+                        .recv_from(buf)
+                        .map(|(len, addr)| (len, from_dual_stack(addr)));
                 }
                 if self.yield_or_discard(r.1, r.0)? {
                     continue;
@@ -198,17 +236,38 @@ impl UdpMuxer {
 
     fn read_header(&self, hdr: [u8; 13]) -> (u16, MuxDirection) {
         let msg_type = hdr[0];
-        if msg_type < 2 {
+        if is_channel_data(msg_type) {
+            // message relayed by a TURN server, the payload tells who it is for
+            let len: u16 = u16::from_be_bytes(hdr[2..4].try_into().unwrap());
+            match hdr[CHANNEL_DATA_HEADER_LEN] {
+                0..=1 => (len, MuxDirection::STUN),
+                128..=191 => (len, MuxDirection::RTP),
+                _ => (len, MuxDirection::DTLS),
+            }
+        } else if msg_type < 2 {
             // stun message
             let len: u16 = u16::from_be_bytes(hdr[2..4].try_into().unwrap());
             (len, MuxDirection::STUN)
+        } else if (128..=191).contains(&msg_type) {
+            // RTP or RTCP (RFC 7983), their header doesn't carry a length
+            (hdr.len() as u16, MuxDirection::RTP)
         } else {
             // assume DTLS record
             let len: u16 = u16::from_be_bytes(hdr[11..13].try_into().unwrap());
             (len, MuxDirection::DTLS)
         }
     }
+    // maps an IPv4 destination when sending through a dual stack socket
+    fn to_dual_stack(&self, addr: SocketAddr) -> SocketAddr {
+        match addr {
+            SocketAddr::V4(v4) if self.dual_stack => {
+                SocketAddr::new(IpAddr::V6(v4.ip().to_ipv6_mapped()), v4.port())
+            }
+            _ => addr,
+        }
+    }
     async fn send_to(&self, buf: &[u8], peer: SocketAddr) -> Result<usize> {
+        let peer = self.to_dual_stack(peer);
         loop {
             let socket = self.socket.as_ref().get_ref();
             match socket.send_to(buf, peer) {
@@ -222,6 +281,17 @@ impl UdpMuxer {
         }
     }
 
+    // returns the channel a message was relayed through and the length of its payload
+    fn relay_channel(&self, from: SocketAddr, buf: &[u8]) -> Option<(RelayChannel, usize)> {
+        let (number, data) = decode_channel_data(buf)?;
+        self.relay_channels
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|c| c.server == from && c.number == number)
+            .map(|c| (*c, data.len()))
+    }
+
     fn poll_recv_from(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -239,7 +309,11 @@ impl UdpMuxer {
                 if dir == r.1 {
                     let socket = self.socket.as_ref().get_ref();
                     self.deregister_waker(dir);
-                    return Poll::Ready(socket.recv_from(buf));
+                    return Poll::Ready(
+                        socket
+                            .recv_from(buf)
+                            .map(|(len, addr)| (len, from_dual_stack(addr))),
+                    );
                 }
 
                 match self.yield_or_discard(r.1, r.0) {
@@ -260,6 +334,7 @@ impl UdpMuxer {
         buf: &[u8],
         peer: SocketAddr,
     ) -> Poll<Result<usize>> {
+        let peer = self.to_dual_stack(peer);
         loop {
             let socket = self.socket.as_ref().get_ref();
             match socket.send_to(buf, peer) {
@@ -281,17 +356,54 @@ struct MuxState {
     is_listening: bool,   // whether there is a consumer listening
 }
 
+/// Where a consumer's writes are sent, follows the origin of the last message it read
+#[derive(Clone, Copy, Default)]
+struct MuxDestination {
+    peer_addr: Option<SocketAddr>,
+    relay: Option<RelayChannel>, // set when the peer is reached through a TURN server
+}
+
 pub struct UdpMux {
     muxer: UdpMuxer,
     direction: MuxDirection, // symbolize the interest a consumer has on a particular message type
-    peer_addr: Option<SocketAddr>,
+    destination: Arc<Mutex<MuxDestination>>,
+}
+
+/// Sends packets to the peer of the mux it was created from, SRTP packets are bundled with
+/// DTLS on the same transport (RFC 8843)
+#[cfg(feature = "camera")]
+#[derive(Clone)]
+pub(crate) struct BundledSender {
+    muxer: UdpMuxer,
+    destination: Arc<Mutex<MuxDestination>>,
+}
+
+#[cfg(feature = "camera")]
+impl BundledSender {
+    pub(crate) async fn send(&self, buf: &[u8]) -> Result<usize> {
+        let destination = *self.destination.lock().unwrap();
+        if let Some(relay) = destination.relay {
+            let framed = encode_channel_data(relay.number, buf);
+            return self
+                .muxer
+                .send_to(&framed, relay.server)
+                .await
+                .map(|_| buf.len());
+        }
+        match destination.peer_addr {
+            Some(peer_addr) => self.muxer.send_to(buf, peer_addr).await,
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "no peer set",
+            )),
+        }
+    }
 }
 
 impl UdpMux {
     pub(crate) async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
         self.muxer.recv_from(self.direction, buf).await
     }
-    // TODO consider SocketAddrV4
     pub(crate) async fn send_to(&self, buf: &[u8], peer: SocketAddr) -> Result<usize> {
         self.muxer.send_to(buf, peer).await
     }
@@ -299,6 +411,31 @@ impl UdpMux {
     pub(crate) fn local_address(&self) -> Result<SocketAddr> {
         self.muxer.socket.get_ref().local_addr()
     }
+
+    /// Registers a channel bound on a TURN server so relayed DTLS records can be exchanged
+    /// with `peer`
+    pub(crate) fn add_relay_channel(&self, server: SocketAddr, number: u16, peer: SocketAddr) {
+        let channel = RelayChannel {
+            server,
+            number,
+            peer,
+        };
+        let mut channels = self.muxer.relay_channels.lock().unwrap();
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+    /// Forgets every channel, used when the TURN allocation is released
+    pub(crate) fn clear_relay_channels(&self) {
+        self.muxer.relay_channels.lock().unwrap().clear();
+    }
+    #[cfg(feature = "camera")]
+    pub(crate) fn bundled_sender(&self) -> BundledSender {
+        BundledSender {
+            muxer: self.muxer.clone(),
+            destination: self.destination.clone(),
+        }
+    }
 }
 
 impl Drop for UdpMux {
@@ -319,7 +456,23 @@ impl AsyncRead for UdpMux {
         let r = ready!(Pin::new(&mut self.muxer).poll_recv_from(cx, direction, buf));
         match r {
             Ok((len, peer_addr)) => {
-                let _ = self.peer_addr.insert(peer_addr);
+                if let Some((relay, payload_len)) = self.muxer.relay_channel(peer_addr, &buf[..len])
+                {
+                    // strip the channel header so the consumer only sees the relayed message
+                    buf.copy_within(
+                        CHANNEL_DATA_HEADER_LEN..CHANNEL_DATA_HEADER_LEN + payload_len,
+                        0,
+                    );
+                    *self.destination.lock().unwrap() = MuxDestination {
+                        peer_addr: Some(relay.peer),
+                        relay: Some(relay),
+                    };
+                    return Poll::Ready(Ok(payload_len));
+                }
+                *self.destination.lock().unwrap() = MuxDestination {
+                    peer_addr: Some(peer_addr),
+                    relay: None,
+                };
                 Poll::Ready(Ok(len))
             }
             Err(e) => Poll::Ready(Err(e)),
@@ -333,7 +486,15 @@ impl AsyncWrite for UdpMux {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<Result<usize>> {
-        if let Some(peer_addr) = self.peer_addr {
+        let destination = *self.destination.lock().unwrap();
+        if let Some(relay) = destination.relay {
+            let framed = encode_channel_data(relay.number, buf);
+            return match ready!(Pin::new(&mut self.muxer).poll_send_to(cx, &framed, relay.server)) {
+                Ok(_) => Poll::Ready(Ok(buf.len())),
+                Err(e) => Poll::Ready(Err(e)),
+            };
+        }
+        if let Some(peer_addr) = destination.peer_addr {
             Pin::new(&mut self.muxer).poll_send_to(cx, buf, peer_addr)
         } else {
             Poll::Ready(Err(std::io::Error::new(
@@ -363,7 +524,7 @@ mod tests {
     use futures_util::FutureExt;
     use rand::Rng;
 
-    use crate::common::webrtc::udp_mux::{MuxDirection, UdpMuxer};
+    use crate::common::webrtc::udp_mux::{from_dual_stack, MuxDirection, UdpMuxer};
 
     fn dtls_packet(len: u16, typ: u8) -> Bytes {
         let mut buf = BytesMut::with_capacity(len as usize + 13);
@@ -608,4 +769,36 @@ mod tests {
         local_ex.spawn(read_stun).detach();
         futures_lite::future::block_on(local_ex.run(client));
     }
+
+    #[test_log::test]
+    fn test_dual_stack_address() {
+        let mapped = "[::ffff:10.1.2.3]:5000".parse().unwrap();
+        assert_eq!(from_dual_stack(mapped), "10.1.2.3:5000".parse().unwrap());
+        let v6 = "[2001:db8::12]:5000".parse().unwrap();
+        assert_eq!(from_dual_stack(v6), v6);
+        let v4 = "10.1.2.3:5000".parse().unwrap();
+        assert_eq!(from_dual_stack(v4), v4);
+    }
+
+    #[test_log::test]
+    fn test_demultiplexing() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let muxer = UdpMuxer::new(Arc::new(Async::new(socket).unwrap()));
+
+        let mut hdr = [0_u8; 13];
+        hdr[0] = 22;
+        assert!(muxer.read_header(hdr).1 == MuxDirection::DTLS);
+        hdr[0] = 1;
+        assert!(muxer.read_header(hdr).1 == MuxDirection::STUN);
+        // RTCP receiver report
+        hdr[0] = 0x81;
+        hdr[1] = 201;
+        assert!(muxer.read_header(hdr).1 == MuxDirection::RTP);
+        // RTP relayed by a TURN server
+        hdr[0] = 0x40;
+        hdr[4] = 0x80;
+        assert!(muxer.read_header(hdr).1 == MuxDirection::RTP);
+        hdr[4] = 23;
+        assert!(muxer.read_header(hdr).1 == MuxDirection::DTLS);
+    }
 }
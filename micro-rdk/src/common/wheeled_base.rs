@@ -1,7 +1,7 @@
 use super::actuator::{Actuator, ActuatorError};
 use super::base::{Base, BaseError, BaseType, COMPONENT_NAME as BaseCompName};
 use super::config::ConfigType;
-use super::motor::{Motor, MotorType, COMPONENT_NAME as MotorCompName};
+use super::motor::{Motor, MotorError, MotorType, COMPONENT_NAME as MotorCompName};
 use super::registry::{ComponentRegistry, Dependency, ResourceKey};
 use super::robot::Resource;
 use super::status::{Status, StatusError};
@@ -9,6 +9,7 @@ use crate::google;
 use crate::proto::common::v1::Vector3;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub(crate) fn register_models(registry: &mut ComponentRegistry) {
     if registry
@@ -36,6 +37,9 @@ pub(crate) fn register_models(registry: &mut ComponentRegistry) {
 pub struct WheeledBase<ML, MR> {
     motor_right: MR,
     motor_left: ML,
+    wheel_circumference_mm: Option<f64>,
+    width_mm: Option<f64>,
+    spin_slip_factor: f64,
 }
 
 impl<ML, MR> WheeledBase<ML, MR>
@@ -47,8 +51,69 @@ where
         WheeledBase {
             motor_right,
             motor_left,
+            wheel_circumference_mm: None,
+            width_mm: None,
+            spin_slip_factor: 1.0,
         }
     }
+
+    /// Sets the dimensions of the base, they are required by `move_straight`, `spin`
+    /// and `set_velocity`
+    pub fn with_dimensions(mut self, wheel_circumference_mm: f64, width_mm: f64) -> Self {
+        self.wheel_circumference_mm = Some(wheel_circumference_mm);
+        self.width_mm = Some(width_mm);
+        self
+    }
+
+    fn wheel_circumference_mm(&self) -> Result<f64, BaseError> {
+        self.wheel_circumference_mm
+            .filter(|c| *c > 0.0)
+            .ok_or(BaseError::BaseConfigError(
+                "wheel_circumference_mm must be configured and positive",
+            ))
+    }
+
+    fn width_mm(&self) -> Result<f64, BaseError> {
+        self.width_mm
+            .filter(|w| *w > 0.0)
+            .ok_or(BaseError::BaseConfigError(
+                "width_mm must be configured and positive",
+            ))
+    }
+
+    // Motors reporting their position are sent to a target position so implementations
+    // relying on their encoder can correct the travel of each wheel, others are driven
+    // for a number of revolutions
+    fn drive_wheel<M: Motor>(
+        motor: &mut M,
+        rpm: f64,
+        revolutions: f64,
+    ) -> Result<Option<Duration>, MotorError> {
+        if motor.get_properties().position_reporting {
+            let position = motor.get_position_revolutions()?;
+            return motor.go_to(rpm, position + revolutions);
+        }
+        motor.go_for(rpm, revolutions)
+    }
+
+    fn drive_wheels(
+        &mut self,
+        rpm: f64,
+        left_revolutions: f64,
+        right_revolutions: f64,
+    ) -> Result<Option<Duration>, BaseError> {
+        let result =
+            Self::drive_wheel(&mut self.motor_left, rpm, left_revolutions).and_then(|left| {
+                Self::drive_wheel(&mut self.motor_right, rpm, right_revolutions)
+                    .map(|right| left.max(right))
+            });
+        if result.is_err() {
+            // don't leave one wheel running if the other one failed
+            let _ = self.stop();
+        }
+        result.map_err(BaseError::from)
+    }
+
     #[allow(clippy::only_used_in_recursion)]
     fn differential_drive(&self, forward: f64, left: f64) -> (f64, f64) {
         if forward < 0.0 {
@@ -86,7 +151,14 @@ where
         }
         if let Some(l_motor) = l_motor {
             if let Some(r_motor) = r_motor {
-                Ok(Arc::new(Mutex::new(WheeledBase::new(r_motor, l_motor))))
+                let mut base = WheeledBase::new(l_motor, r_motor);
+                base.wheel_circumference_mm =
+                    cfg.get_attribute::<f64>("wheel_circumference_mm").ok();
+                base.width_mm = cfg.get_attribute::<f64>("width_mm").ok();
+                if let Ok(spin_slip_factor) = cfg.get_attribute::<f64>("spin_slip_factor") {
+                    base.spin_slip_factor = spin_slip_factor;
+                }
+                Ok(Arc::new(Mutex::new(base)))
             } else {
                 Err(BaseError::BaseConfigError("right motor couldn't be found"))
             }
@@ -151,4 +223,156 @@ where
         self.motor_right.set_power(r)?;
         Ok(())
     }
+
+    fn move_straight(
+        &mut self,
+        distance_mm: i64,
+        mm_per_sec: f64,
+    ) -> Result<Option<Duration>, BaseError> {
+        if !mm_per_sec.is_finite() {
+            return Err(BaseError::BaseInvalidArgument("mm_per_sec must be finite"));
+        }
+        if distance_mm == 0 || mm_per_sec == 0.0 {
+            self.stop().map_err(MotorError::from)?;
+            return Ok(None);
+        }
+        let circumference = self.wheel_circumference_mm()?;
+        let direction = (distance_mm as f64).signum() * mm_per_sec.signum();
+        let revolutions = (distance_mm as f64 / circumference).abs() * direction;
+        let rpm = mm_per_sec.abs() / circumference * 60.0;
+        self.drive_wheels(rpm, revolutions, revolutions)
+    }
+
+    fn spin(&mut self, angle_deg: f64, degs_per_sec: f64) -> Result<Option<Duration>, BaseError> {
+        if !angle_deg.is_finite() || !degs_per_sec.is_finite() {
+            return Err(BaseError::BaseInvalidArgument(
+                "angle_deg and degs_per_sec must be finite",
+            ));
+        }
+        if angle_deg == 0.0 || degs_per_sec == 0.0 {
+            self.stop().map_err(MotorError::from)?;
+            return Ok(None);
+        }
+        let circumference = self.wheel_circumference_mm()?;
+        let width = self.width_mm()?;
+        // each wheel travels along the circle whose diameter is the width of the base
+        let wheel_travel_mm =
+            self.spin_slip_factor * width * std::f64::consts::PI * angle_deg.abs() / 360.0;
+        let revolutions = wheel_travel_mm / circumference;
+        let rpm = revolutions * 60.0 / (angle_deg / degs_per_sec).abs();
+        let direction = angle_deg.signum() * degs_per_sec.signum();
+        self.drive_wheels(rpm, -revolutions * direction, revolutions * direction)
+    }
+
+    fn set_velocity(&mut self, lin: &Vector3, ang: &Vector3) -> Result<(), BaseError> {
+        if !lin.y.is_finite() || !ang.z.is_finite() {
+            return Err(BaseError::BaseInvalidArgument("velocities must be finite"));
+        }
+        let circumference = self.wheel_circumference_mm()?;
+        let width = self.width_mm()?;
+        // linear speed of each wheel induced by the rotation of the base, in mm/sec
+        let turn = ang.z.to_radians() * width / 2.0;
+        let left_rpm = (lin.y - turn) / circumference * 60.0;
+        let right_rpm = (lin.y + turn) / circumference * 60.0;
+        self.motor_left.set_rpm(left_rpm)?;
+        self.motor_right.set_rpm(right_rpm)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::common::actuator::Actuator;
+    use crate::common::base::Base;
+    use crate::common::motor::{FakeMotor, Motor};
+    use crate::common::wheeled_base::WheeledBase;
+    use crate::proto::common::v1::Vector3;
+
+    type FakeWheeledBase = WheeledBase<Arc<Mutex<FakeMotor>>, Arc<Mutex<FakeMotor>>>;
+
+    fn fake_base() -> (
+        FakeWheeledBase,
+        Arc<Mutex<FakeMotor>>,
+        Arc<Mutex<FakeMotor>>,
+    ) {
+        let left = Arc::new(Mutex::new(FakeMotor::new()));
+        let right = Arc::new(Mutex::new(FakeMotor::new()));
+        let base = WheeledBase::new(left.clone(), right.clone())
+            .with_dimensions(100.0, 400.0 / std::f64::consts::PI);
+        (base, left, right)
+    }
+
+    // the sign of the power tells the direction a wheel turns in
+    fn power(motor: &mut Arc<Mutex<FakeMotor>>) -> f64 {
+        motor.is_powered().unwrap().1
+    }
+
+    #[test_log::test]
+    fn test_wheeled_base_move_straight() {
+        let (mut base, mut left, mut right) = fake_base();
+
+        let dur = base.move_straight(200, 100.0).unwrap().unwrap();
+        assert!((dur.as_secs_f64() - 2.0).abs() < 1e-6);
+        assert!(power(&mut left) > 0.0);
+        assert!(power(&mut right) > 0.0);
+
+        let dur = base.move_straight(-150, 100.0).unwrap().unwrap();
+        assert!((dur.as_secs_f64() - 1.5).abs() < 1e-6);
+        assert!(power(&mut left) < 0.0);
+        assert!(power(&mut right) < 0.0);
+        assert!(left.is_moving().unwrap());
+        assert!(right.is_moving().unwrap());
+
+        assert!(base.move_straight(0, 100.0).unwrap().is_none());
+        assert!(base.move_straight(100, f64::NAN).is_err());
+
+        let mut base = WheeledBase::new(left, right);
+        assert!(base.move_straight(100, 100.0).is_err());
+    }
+
+    #[test_log::test]
+    fn test_wheeled_base_spin() {
+        let (mut base, mut left, mut right) = fake_base();
+
+        // each wheel travels 100mm for a quarter turn
+        let dur = base.spin(90.0, 45.0).unwrap().unwrap();
+        assert!((dur.as_secs_f64() - 2.0).abs() < 1e-6);
+        assert!(power(&mut left) < 0.0);
+        assert!(power(&mut right) > 0.0);
+
+        let dur = base.spin(-90.0, 90.0).unwrap().unwrap();
+        assert!((dur.as_secs_f64() - 1.0).abs() < 1e-6);
+        assert!(power(&mut left) > 0.0);
+        assert!(power(&mut right) < 0.0);
+
+        assert!(base.spin(0.0, 90.0).unwrap().is_none());
+        assert!(!left.is_moving().unwrap());
+    }
+
+    #[test_log::test]
+    fn test_wheeled_base_set_velocity() {
+        let (mut base, mut left, mut right) = fake_base();
+
+        let lin = Vector3 {
+            y: 50.0,
+            ..Default::default()
+        };
+        base.set_velocity(&lin, &Vector3::default()).unwrap();
+        assert!(power(&mut left) > 0.0);
+        assert!(power(&mut right) > 0.0);
+
+        let ang = Vector3 {
+            z: 90.0,
+            ..Default::default()
+        };
+        base.set_velocity(&Vector3::default(), &ang).unwrap();
+        assert!(power(&mut left) < 0.0);
+        assert!(power(&mut right) > 0.0);
+
+        base.stop().unwrap();
+        assert!(!left.is_moving().unwrap());
+        assert!(!right.is_moving().unwrap());
+    }
 }
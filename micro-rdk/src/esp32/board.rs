@@ -368,6 +368,31 @@ impl Board for EspBoard {
         }
         Err(BoardError::GpioPinError(pin as u32, "not configured"))
     }
+    fn set_digital_interrupt_debounce(
+        &mut self,
+        pin: i32,
+        debounce: Duration,
+    ) -> Result<(), BoardError> {
+        let p = self.pins.iter().find(|p| p.pin() == pin);
+        if let Some(p) = p {
+            if !p.is_interrupt() {
+                return Err(BoardError::GpioPinError(pin as u32, "not an interrupt"));
+            }
+            p.set_debounce(debounce);
+            return Ok(());
+        }
+        Err(BoardError::GpioPinError(pin as u32, "not configured"))
+    }
+    fn get_debounced_interrupt_value(&self, pin: i32) -> Result<u32, BoardError> {
+        let p = self.pins.iter().find(|p| p.pin() == pin);
+        if let Some(p) = p {
+            if !p.is_interrupt() {
+                return Err(BoardError::GpioPinError(pin as u32, "not an interrupt"));
+            }
+            return Ok(p.get_debounced_event_count());
+        }
+        Err(BoardError::GpioPinError(pin as u32, "not configured"))
+    }
 }
 
 impl Status for EspBoard {
@@ -6,7 +6,10 @@ use std::{
 
 use crate::{
     common::{
-        camera::{Camera, CameraError, CameraType},
+        camera::{
+            calibration_from_config, Camera, CameraDistortion, CameraError, CameraImage,
+            CameraIntrinsics, CameraProperties, CameraType, MIME_TYPE_JPEG,
+        },
         config::ConfigType,
         registry::{ComponentRegistry, Dependency},
         status::{Status, StatusError},
@@ -81,6 +84,8 @@ enum FrameSize {
 #[derive(DoCommand)]
 pub struct Esp32Camera {
     config: camera_config_t,
+    intrinsics: Option<CameraIntrinsics>,
+    distortion: Option<CameraDistortion>,
 }
 
 impl Esp32Camera {
@@ -116,6 +121,7 @@ impl Esp32Camera {
         let jpeg_quality = cfg.get_attribute::<i32>("jpeg_quality").unwrap_or(32);
         //  If pin_sccb_sda is -1, use the already configured I2C bus by number
         let sccb_i2c_port = cfg.get_attribute::<i32>("sccb_i2c_port").unwrap_or(-1);
+        let (intrinsics, distortion) = calibration_from_config(&cfg)?;
 
         let config = camera_config_t {
             pin_pwdn,
@@ -168,14 +174,32 @@ impl Esp32Camera {
 
         *registered = true;
 
-        Ok(Arc::new(Mutex::new(Self { config })))
+        Ok(Arc::new(Mutex::new(Self {
+            config,
+            intrinsics,
+            distortion,
+        })))
     }
 }
 
 impl Camera for Esp32Camera {
-    fn get_image(&mut self) -> Result<Bytes, CameraError> {
+    fn get_image(&mut self) -> Result<CameraImage, CameraError> {
         let frame = Esp32CameraFrameBuffer::get().ok_or(CameraError::FailedToGetImage)?;
-        Ok(frame.as_bytes())
+        if frame.format() != PixelFormat::JPEG as u32 {
+            return Err(CameraError::CameraGenericError(
+                "frame buffer is not JPEG encoded",
+            ));
+        }
+        Ok(CameraImage::new(MIME_TYPE_JPEG, frame.as_bytes()))
+    }
+    fn get_properties(&mut self) -> Result<CameraProperties, CameraError> {
+        Ok(CameraProperties {
+            supports_pcd: false,
+            intrinsics: self.intrinsics,
+            distortion: self.distortion.clone(),
+            mime_types: vec![MIME_TYPE_JPEG.to_string()],
+            frame_rate: None,
+        })
     }
 }
 
@@ -1,5 +1,7 @@
 // TODO(RSDK-8993): Obtain this from the esp-idf component registry so
 // we can upgrade `esp-idf-svc`.
+use std::net::{Ipv4Addr, Ipv6Addr};
+
 use crate::esp32::esp_idf_svc::mdns::EspMdns;
 
 use crate::common::conn::mdns::{Mdns, MdnsError};
@@ -7,6 +9,7 @@ use crate::common::conn::mdns::{Mdns, MdnsError};
 pub struct Esp32Mdns {
     inner: EspMdns,
     hostname: String,
+    ipv6: Option<Ipv6Addr>,
 }
 
 impl Esp32Mdns {
@@ -14,8 +17,14 @@ impl Esp32Mdns {
         Ok(Self {
             inner: EspMdns::take().map_err(|e| MdnsError::MdnsInitServiceError(e.to_string()))?,
             hostname,
+            ipv6: None,
         })
     }
+    /// Also advertise services on an IPv6 address (AAAA record)
+    pub fn with_ipv6(mut self, ipv6: Option<Ipv6Addr>) -> Self {
+        self.ipv6 = ipv6;
+        self
+    }
     fn add_service(
         &mut self,
         instance_name: &str,
@@ -45,6 +54,19 @@ impl Esp32Mdns {
         self.hostname = hostname.to_owned();
         Ok(())
     }
+    // the responder answers A and AAAA queries with the addresses of the station interface
+    // but only announces them when its pcbs restart, setting the hostname again restarts them so
+    // the AAAA record of a newly obtained IPv6 address is announced
+    fn set_addresses(&mut self, _: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        let changed = self.ipv6 != ipv6;
+        self.ipv6 = ipv6;
+        if changed && self.ipv6.is_some() && !self.hostname.is_empty() {
+            self.inner
+                .set_hostname(self.hostname.clone())
+                .map_err(|e| MdnsError::MdnsAddServiceError(e.to_string()))?;
+        }
+        Ok(())
+    }
 }
 
 impl Mdns for Esp32Mdns {
@@ -61,6 +83,9 @@ impl Mdns for Esp32Mdns {
     fn set_hostname(&mut self, hostname: &str) -> Result<(), MdnsError> {
         self.set_hostname(hostname)
     }
+    fn set_addresses(&mut self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        self.set_addresses(ip, ipv6)
+    }
     fn remove_service(
         &mut self,
         instance_name: &str,
@@ -85,6 +110,9 @@ impl Mdns for &mut Esp32Mdns {
     fn set_hostname(&mut self, hostname: &str) -> Result<(), MdnsError> {
         (*self).set_hostname(hostname)
     }
+    fn set_addresses(&mut self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        (*self).set_addresses(ip, ipv6)
+    }
     fn remove_service(
         &mut self,
         instance_name: &str,
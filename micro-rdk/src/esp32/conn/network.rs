@@ -3,7 +3,7 @@ use std::{
     cell::RefCell,
     ffi::CString,
     fmt::Display,
-    net::Ipv4Addr,
+    net::{Ipv4Addr, Ipv6Addr},
     ops::{Index, IndexMut},
     sync::{
         atomic::{AtomicBool, AtomicU32, Ordering},
@@ -12,13 +12,13 @@ use std::{
 };
 use {
     crate::common::{
-        conn::network::{Network, NetworkError},
+        conn::network::{AddressChangeNotifier, Network, NetworkAddresses, NetworkError},
         provisioning::server::{NetworkInfo, WifiManager, WifiManagerError},
     },
     crate::esp32::esp_idf_svc::{
         eventloop::{EspSubscription, EspSystemEventLoop, System},
         handle::RawHandle,
-        netif::EspNetif,
+        netif::{EspNetif, IpEvent},
         sys,
         sys::esp_wifi_set_ps,
         wifi::{EspWifi, WifiEvent},
@@ -70,11 +70,53 @@ pub(crate) fn esp32_get_wifi() -> Result<&'static Mutex<AsyncWifi<EspWifi<'stati
     })
 }
 
+// lwip keeps at most this many IPv6 addresses per interface (LWIP_IPV6_NUM_ADDRESSES)
+const MAX_NETIF_IPV6_ADDRESSES: usize = 3;
+
+// returns a routable IPv6 address of an interface, preferring a global address over a unique
+// local one; link-local addresses are never returned since they are useless without a scope id
+fn esp32_netif_ipv6(netif: *mut sys::esp_netif_t) -> Option<Ipv6Addr> {
+    if netif.is_null() {
+        return None;
+    }
+    let mut ip6s: [sys::esp_ip6_addr_t; MAX_NETIF_IPV6_ADDRESSES] = Default::default();
+    let count = unsafe { sys::esp_netif_get_all_ip6(netif, ip6s.as_mut_ptr()) };
+    let addrs = ip6s
+        .iter()
+        .take(count.clamp(0, MAX_NETIF_IPV6_ADDRESSES as i32) as usize)
+        .map(|ip6| {
+            // lwip stores the address in network order
+            let mut octets = [0_u8; 16];
+            for (chunk, word) in octets.chunks_mut(4).zip(ip6.addr.iter()) {
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            Ipv6Addr::from(octets)
+        })
+        .collect::<Vec<_>>();
+    let is_global = |ip: &&Ipv6Addr| ip.segments()[0] & 0xe000 == 0x2000;
+    let is_unique_local = |ip: &&Ipv6Addr| ip.segments()[0] & 0xfe00 == 0xfc00;
+    addrs
+        .iter()
+        .find(is_global)
+        .or_else(|| addrs.iter().find(is_unique_local))
+        .copied()
+}
+
+// the link-local address has to be created every time the station connects, the global
+// address is then obtained through SLAAC
+fn esp32_create_ipv6_linklocal(netif: *mut sys::esp_netif_t) {
+    if let Err(e) = unsafe { sys::esp!(sys::esp_netif_create_ip6_linklocal(netif)) } {
+        log::warn!("couldn't create IPv6 link-local address {:?}", e);
+    }
+}
+
 /// A wrapper around the wifi structure available in esp-idf-svc with and adjustment to support
 /// reconnection
 #[derive(Default)]
 pub struct Esp32WifiNetwork {
     _subscription: RefCell<Option<EspSubscription<'static, System>>>,
+    _ip_subscription: RefCell<Option<EspSubscription<'static, System>>>,
+    notifier: AddressChangeNotifier,
 }
 
 impl Esp32WifiNetwork {
@@ -244,6 +286,7 @@ impl Esp32WifiNetwork {
         wifi.start().await?;
         wifi.connect().await?;
         wifi.wait_netif_up().await?;
+        esp32_create_ipv6_linklocal(wifi.wifi().sta_netif().handle());
 
         crate::esp32::esp_idf_svc::sys::esp!(unsafe {
             esp_wifi_set_ps(crate::esp32::esp_idf_svc::sys::wifi_ps_type_t_WIFI_PS_NONE)
@@ -268,9 +311,21 @@ impl Esp32WifiNetwork {
                 }
             } else if matches!(event, WifiEvent::StaConnected) {
                 log::info!("wifi connected event received");
+                if let Ok(wifi) = esp32_get_wifi() {
+                    if let Some(wifi_guard) = wifi.try_lock() {
+                        esp32_create_ipv6_linklocal(wifi_guard.wifi().sta_netif().handle());
+                    }
+                }
             }
         })?;
         let _ = self._subscription.borrow_mut().replace(subscription);
+
+        // addresses are read from the netif since the wifi may be locked when the event fires
+        let notifier = self.notifier.clone();
+        let ip_subscription = sl_stack.subscribe::<IpEvent, _>(move |_: IpEvent| {
+            notifier.notify(Esp32NetifHelper::default().get_addresses());
+        })?;
+        let _ = self._ip_subscription.borrow_mut().replace(ip_subscription);
         Ok(())
     }
     async fn scan_networks_inner(&self) -> Result<Vec<AccessPointInfo>, WifiManagerError> {
@@ -387,10 +442,17 @@ impl Network for Esp32WifiNetwork {
                 .map_or(Ipv4Addr::UNSPECIFIED, |ip_info| ip_info.ip)
         })
     }
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        let guard = esp32_get_wifi().map_or(None, |wifi| wifi.try_lock());
+        guard.and_then(|guard| esp32_netif_ipv6(guard.wifi().sta_netif().handle()))
+    }
     fn is_connected(&self) -> Result<bool, NetworkError> {
         let guard = esp32_get_wifi().map_or(None, |wifi| wifi.try_lock());
         Ok(guard.map_or(Ok(false), |guard| guard.is_connected())?)
     }
+    fn subscribe_address_changes(&self) -> Option<async_channel::Receiver<NetworkAddresses>> {
+        Some(self.notifier.subscribe())
+    }
 }
 
 #[cfg(feature = "qemu")]
@@ -420,6 +482,9 @@ impl Network for Box<BlockingEth<EspEth<'static, OpenEth>>> {
             .expect("could not get IP info")
             .ip
     }
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        esp32_netif_ipv6(self.eth().netif().handle())
+    }
     fn is_connected(&self) -> Result<bool, NetworkError> {
         Ok(BlockingEth::is_connected(self)?)
     }
@@ -508,12 +573,33 @@ impl Esp32NetifHelper {
 
         Err(NetworkError::NoIpConfigured)
     }
+    fn get_ipv6_addr(&self) -> Option<Ipv6Addr> {
+        esp32_netif_ipv6(self.netif_hnds[ESP32NetifHandle::Esp32WifiSta])
+            .or_else(|| esp32_netif_ipv6(self.netif_hnds[ESP32NetifHandle::Esp32Eth]))
+    }
+    fn get_addresses(&self) -> NetworkAddresses {
+        let ipv4 = self
+            .get_ip_addr()
+            .map_or(Ipv4Addr::UNSPECIFIED, |ip| Ipv4Addr::from(ip.to_be()));
+        NetworkAddresses::new(ipv4, self.get_ipv6_addr())
+    }
 }
 
 #[derive(Clone)]
 struct Esp32ExternallyManagerNetworkInner {
     connected: Arc<AtomicBool>,
     ipv4: Arc<AtomicU32>,
+    notifier: AddressChangeNotifier,
+}
+
+impl Esp32ExternallyManagerNetworkInner {
+    fn notify_addresses(&self) {
+        let ipv4 = Ipv4Addr::from(self.ipv4.load(Ordering::Acquire).to_be());
+        self.notifier.notify(NetworkAddresses::new(
+            ipv4,
+            Esp32NetifHelper::default().get_ipv6_addr(),
+        ));
+    }
 }
 
 pub struct Esp32ExternallyManagedNetwork {
@@ -525,9 +611,16 @@ impl Network for Esp32ExternallyManagedNetwork {
         let ip = self.inner.ipv4.load(Ordering::Acquire);
         Ipv4Addr::from(ip.to_be())
     }
+    fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        // the link-local address is managed by the external code
+        Esp32NetifHelper::default().get_ipv6_addr()
+    }
     fn is_connected(&self) -> Result<bool, NetworkError> {
         Ok(self.inner.connected.load(Ordering::Acquire))
     }
+    fn subscribe_address_changes(&self) -> Option<async_channel::Receiver<NetworkAddresses>> {
+        Some(self.inner.notifier.subscribe())
+    }
 }
 
 impl Drop for Esp32ExternallyManagedNetwork {
@@ -575,9 +668,11 @@ impl Esp32ExternallyManagedNetwork {
             AtomicBool::new(false)
         };
 
+        let notifier = AddressChangeNotifier::new(help.get_addresses());
         let data = Box::new(Esp32ExternallyManagerNetworkInner {
             connected: Arc::new(connected),
             ipv4: Arc::new(ip),
+            notifier,
         });
 
         // Would be better to instantiate the EspSystemEventLoop but since the
@@ -645,9 +740,13 @@ impl Esp32ExternallyManagedNetwork {
                 let ip_event: &mut esp_idf_svc::sys::ip_event_got_ip_t = &mut *(ev_data as *mut _);
                 if ip_event.ip_changed {
                     data.ipv4.store(ip_event.ip_info.ip.addr, Ordering::Release);
+                    data.notify_addresses();
                 }
                 data.connected.store(true, Ordering::Release);
             }
+            if ev_id == esp_idf_svc::sys::ip_event_t_IP_EVENT_GOT_IP6 {
+                data.notify_addresses();
+            }
             if ev_id == esp_idf_svc::sys::ip_event_t_IP_EVENT_STA_LOST_IP
                 || ev_id == esp_idf_svc::sys::ip_event_t_IP_EVENT_ETH_LOST_IP
             {
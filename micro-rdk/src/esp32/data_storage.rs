@@ -0,0 +1,93 @@
+//! Flash partition backed storage for the [PersistentDataStore](crate::common::persistent_data_store::PersistentDataStore)
+
+use std::ffi::CString;
+
+use crate::common::{data_store::DataStoreError, persistent_data_store::DataStoreStorage};
+use crate::esp32::esp_idf_svc::sys::{
+    esp, esp_partition_erase_range, esp_partition_find_first, esp_partition_read,
+    esp_partition_subtype_t_ESP_PARTITION_SUBTYPE_ANY, esp_partition_t,
+    esp_partition_type_t_ESP_PARTITION_TYPE_DATA, esp_partition_write,
+};
+
+/// Label of the data partition used by the default persistent data store. It is declared in the
+/// partition tables of micro-rdk-server, of the project template and of the ESP-IDF component
+/// example, custom partition tables should declare it as well, e.g.
+/// `viam_data, data, 0x40, , 0x100000,`
+pub const DATA_STORE_PARTITION_LABEL: &str = "viam_data";
+const SECTOR_SIZE: usize = 4096;
+
+/// Storage backed by a raw data partition. Flash encryption must not be enabled on this
+/// partition since records are marked as consumed by rewriting a single byte in place.
+pub struct PartitionDataStorage {
+    partition: &'static esp_partition_t,
+}
+
+// *mut esp_flash_t in esp_partition_t does not implement Send, the partition table entry is
+// never modified and remains valid for the lifetime of the program
+unsafe impl Send for PartitionDataStorage {}
+
+impl PartitionDataStorage {
+    pub fn open(label: &str) -> Result<Self, DataStoreError> {
+        let c_label = CString::new(label)
+            .map_err(|_| DataStoreError::StorageError(format!("invalid label {}", label)))?;
+        let partition = unsafe {
+            esp_partition_find_first(
+                esp_partition_type_t_ESP_PARTITION_TYPE_DATA,
+                esp_partition_subtype_t_ESP_PARTITION_SUBTYPE_ANY,
+                c_label.as_ptr(),
+            )
+            .as_ref()
+            .ok_or_else(|| {
+                DataStoreError::StorageError(format!("no data partition labeled {}", label))
+            })?
+        };
+        Ok(Self { partition })
+    }
+}
+
+impl DataStoreStorage for PartitionDataStorage {
+    fn sector_size(&self) -> usize {
+        SECTOR_SIZE
+    }
+    fn capacity(&self) -> usize {
+        let size = self.partition.size as usize;
+        size - size % SECTOR_SIZE
+    }
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), DataStoreError> {
+        esp!(unsafe {
+            esp_partition_read(
+                self.partition,
+                offset,
+                buf.as_mut_ptr() as *mut _,
+                buf.len(),
+            )
+        })
+        .map_err(|err| DataStoreError::StorageError(err.to_string()))
+    }
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), DataStoreError> {
+        esp!(unsafe {
+            esp_partition_write(
+                self.partition,
+                offset,
+                data.as_ptr() as *const _,
+                data.len(),
+            )
+        })
+        .map_err(|err| DataStoreError::StorageError(err.to_string()))
+    }
+    fn erase_sector(&mut self, offset: usize) -> Result<(), DataStoreError> {
+        esp!(unsafe { esp_partition_erase_range(self.partition, offset, SECTOR_SIZE) })
+            .map_err(|err| DataStoreError::StorageError(err.to_string()))
+    }
+    fn open_default() -> Result<Self, DataStoreError> {
+        // the partition table isn't updated over the air, devices flashed with an earlier
+        // release don't have the partition until they are flashed again
+        Self::open(DATA_STORE_PARTITION_LABEL).inspect_err(|_| {
+            log::warn!(
+                "no `{}` partition on this device, captured data won't persist across reboots \
+                 until the partition table is updated by a full flash (see OTA.md)",
+                DATA_STORE_PARTITION_LABEL
+            )
+        })
+    }
+}
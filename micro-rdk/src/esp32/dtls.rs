@@ -12,27 +12,38 @@ use std::{
 use crate::{
     common::webrtc::{
         certificate::Certificate,
-        dtls::{DtlsBuilder, DtlsConnector, DtlsError, IntoDtlsStream},
+        dtls::{DtlsBuilder, DtlsConnector, DtlsError, DtlsStream, IntoDtlsStream},
+        srtp::{
+            SrtpCipher, SrtpError, SrtpKeyingMaterial, SRTP_EXPORTER_LABEL,
+            SRTP_KEYING_MATERIAL_LEN, SRTP_MASTER_KEY_LEN,
+        },
         udp_mux::UdpMux,
     },
     esp32::tcp::TlsHandshake,
 };
 
 use crate::esp32::esp_idf_svc::sys::{
+    mbedtls_cipher_context_t, mbedtls_cipher_crypt, mbedtls_cipher_free,
+    mbedtls_cipher_info_from_type, mbedtls_cipher_init, mbedtls_cipher_setkey,
+    mbedtls_cipher_setup, mbedtls_cipher_type_t_MBEDTLS_CIPHER_AES_128_CTR,
     mbedtls_ctr_drbg_context, mbedtls_ctr_drbg_free, mbedtls_ctr_drbg_init,
     mbedtls_ctr_drbg_random, mbedtls_ctr_drbg_seed, mbedtls_entropy_context, mbedtls_entropy_free,
-    mbedtls_entropy_func, mbedtls_entropy_init, mbedtls_pk_context, mbedtls_pk_free,
-    mbedtls_pk_init, mbedtls_pk_parse_key, mbedtls_ssl_conf_ca_chain, mbedtls_ssl_conf_dbg,
-    mbedtls_ssl_conf_dtls_cookies, mbedtls_ssl_conf_dtls_srtp_protection_profiles,
+    mbedtls_entropy_func, mbedtls_entropy_init, mbedtls_md_context_t, mbedtls_md_free,
+    mbedtls_md_hmac_finish, mbedtls_md_hmac_starts, mbedtls_md_hmac_update,
+    mbedtls_md_info_from_type, mbedtls_md_init, mbedtls_md_setup,
+    mbedtls_md_type_t_MBEDTLS_MD_SHA1, mbedtls_operation_t_MBEDTLS_ENCRYPT, mbedtls_pk_context,
+    mbedtls_pk_free, mbedtls_pk_init, mbedtls_pk_parse_key, mbedtls_ssl_conf_ca_chain,
+    mbedtls_ssl_conf_dbg, mbedtls_ssl_conf_dtls_cookies,
+    mbedtls_ssl_conf_dtls_srtp_protection_profiles, mbedtls_ssl_conf_export_keys_ext_cb,
     mbedtls_ssl_conf_handshake_timeout, mbedtls_ssl_conf_own_cert, mbedtls_ssl_conf_rng,
     mbedtls_ssl_config, mbedtls_ssl_config_defaults, mbedtls_ssl_config_free,
     mbedtls_ssl_config_init, mbedtls_ssl_context, mbedtls_ssl_free, mbedtls_ssl_handshake,
     mbedtls_ssl_init, mbedtls_ssl_read, mbedtls_ssl_set_bio, mbedtls_ssl_set_timer_cb,
-    mbedtls_ssl_setup, mbedtls_ssl_write, mbedtls_x509_crt, mbedtls_x509_crt_free,
-    mbedtls_x509_crt_init, mbedtls_x509_crt_parse_der, MBEDTLS_ERR_NET_RECV_FAILED,
-    MBEDTLS_ERR_NET_SEND_FAILED, MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY, MBEDTLS_ERR_SSL_TIMEOUT,
-    MBEDTLS_ERR_SSL_WANT_READ, MBEDTLS_ERR_SSL_WANT_WRITE, MBEDTLS_SSL_IS_SERVER,
-    MBEDTLS_SSL_PRESET_DEFAULT, MBEDTLS_SSL_TRANSPORT_DATAGRAM,
+    mbedtls_ssl_setup, mbedtls_ssl_tls_prf, mbedtls_ssl_write, mbedtls_tls_prf_types,
+    mbedtls_x509_crt, mbedtls_x509_crt_free, mbedtls_x509_crt_init, mbedtls_x509_crt_parse_der,
+    MBEDTLS_ERR_NET_RECV_FAILED, MBEDTLS_ERR_NET_SEND_FAILED, MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY,
+    MBEDTLS_ERR_SSL_TIMEOUT, MBEDTLS_ERR_SSL_WANT_READ, MBEDTLS_ERR_SSL_WANT_WRITE,
+    MBEDTLS_SSL_IS_SERVER, MBEDTLS_SSL_PRESET_DEFAULT, MBEDTLS_SSL_TRANSPORT_DATAGRAM,
 };
 use async_io::Timer;
 use core::ffi::CStr;
@@ -262,6 +273,49 @@ extern "C" fn mbedtls_timing_get_delay<S>(data: *mut c_void) -> c_int {
     }
     0
 }
+
+// secrets of the handshake, the SRTP keying material is derived from them (RFC 5705)
+struct DtlsExportedSecrets {
+    master_secret: [u8; 48],
+    randbytes: [u8; 64],
+    tls_prf_type: mbedtls_tls_prf_types,
+    exported: bool,
+}
+
+impl Default for DtlsExportedSecrets {
+    fn default() -> Self {
+        Self {
+            master_secret: [0; 48],
+            randbytes: [0; 64],
+            tls_prf_type: 0,
+            exported: false,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn mbedtls_export_keys(
+    p_expkey: *mut c_void,
+    ms: *const c_uchar,
+    _kb: *const c_uchar,
+    _maclen: usize,
+    _keylen: usize,
+    _ivlen: usize,
+    client_random: *const c_uchar,
+    server_random: *const c_uchar,
+    tls_prf_type: mbedtls_tls_prf_types,
+) -> c_int {
+    let secrets = &mut *(p_expkey as *mut DtlsExportedSecrets);
+    secrets
+        .master_secret
+        .copy_from_slice(std::slice::from_raw_parts(ms, 48));
+    secrets.randbytes[..32].copy_from_slice(std::slice::from_raw_parts(client_random, 32));
+    secrets.randbytes[32..].copy_from_slice(std::slice::from_raw_parts(server_random, 32));
+    secrets.tls_prf_type = tls_prf_type;
+    secrets.exported = true;
+    0
+}
+
 #[repr(u16)]
 enum MbedTlsStrpProfile {
     MbedtlsSrtpUnsetProfile = 0,
@@ -281,6 +335,7 @@ pub(crate) struct DtlsSSLContext {
     pk_ctx: Box<mbedtls_pk_context>,
     timer_ctx: Box<Esp32DtlsDelay>,
     strp_profiles: Box<[MbedTlsStrpProfile]>,
+    exported_secrets: Box<DtlsExportedSecrets>,
 }
 
 impl Drop for DtlsSSLContext {
@@ -406,6 +461,11 @@ impl DtlsSSLContext {
                 if ret != 0 {
                     return Err(SSLError::SSLSrtpConfigFailure(ret));
                 }
+                mbedtls_ssl_conf_export_keys_ext_cb(
+                    self.ssl_config.as_mut(),
+                    Some(mbedtls_export_keys),
+                    self.exported_secrets.as_mut() as *mut DtlsExportedSecrets as *mut c_void,
+                );
             }
         }
 
@@ -428,6 +488,113 @@ impl DtlsSSLContext {
     fn set_srtp_profiles(&mut self, profiles: [MbedTlsStrpProfile; 2]) {
         self.strp_profiles = Box::new(profiles);
     }
+    fn srtp_keying_material(&self) -> Result<SrtpKeyingMaterial, SSLError> {
+        let secrets = &self.exported_secrets;
+        if !secrets.exported {
+            return Err(SSLError::SSLSrtpKeysUnavailable);
+        }
+        let label = std::ffi::CString::new(SRTP_EXPORTER_LABEL).unwrap();
+        let mut material = [0; SRTP_KEYING_MATERIAL_LEN];
+        let ret = unsafe {
+            mbedtls_ssl_tls_prf(
+                secrets.tls_prf_type,
+                secrets.master_secret.as_ptr(),
+                secrets.master_secret.len(),
+                label.as_ptr(),
+                secrets.randbytes.as_ptr(),
+                secrets.randbytes.len(),
+                material.as_mut_ptr(),
+                material.len(),
+            )
+        };
+        if ret != 0 {
+            return Err(SSLError::SSLOtherError(ret));
+        }
+        Ok(SrtpKeyingMaterial::new(material))
+    }
+}
+
+/// SRTP primitives relying on mbedtls, AES is hardware accelerated when
+/// CONFIG_MBEDTLS_HARDWARE_AES is set
+pub(crate) struct Esp32SrtpCipher;
+
+impl SrtpCipher for Esp32SrtpCipher {
+    fn aes_128_ctr(
+        &mut self,
+        key: &[u8; SRTP_MASTER_KEY_LEN],
+        iv: &[u8; 16],
+        data: &mut [u8],
+    ) -> Result<(), SrtpError> {
+        let mut ctx = mbedtls_cipher_context_t::default();
+        let mut out = vec![0; data.len()];
+        let mut out_len = 0;
+        let ret = unsafe {
+            mbedtls_cipher_init(&mut ctx);
+            let mut ret = mbedtls_cipher_setup(
+                &mut ctx,
+                mbedtls_cipher_info_from_type(mbedtls_cipher_type_t_MBEDTLS_CIPHER_AES_128_CTR),
+            );
+            if ret == 0 {
+                ret = mbedtls_cipher_setkey(
+                    &mut ctx,
+                    key.as_ptr(),
+                    (key.len() * 8) as c_int,
+                    mbedtls_operation_t_MBEDTLS_ENCRYPT,
+                );
+            }
+            if ret == 0 {
+                ret = mbedtls_cipher_crypt(
+                    &mut ctx,
+                    iv.as_ptr(),
+                    iv.len(),
+                    data.as_ptr(),
+                    data.len(),
+                    out.as_mut_ptr(),
+                    &mut out_len,
+                );
+            }
+            mbedtls_cipher_free(&mut ctx);
+            ret
+        };
+        if ret != 0 {
+            return Err(SrtpError::SrtpCipherError(Box::new(
+                SSLError::SSLOtherError(ret),
+            )));
+        }
+        data.copy_from_slice(&out[..data.len()]);
+        Ok(())
+    }
+    fn hmac_sha1(&mut self, key: &[u8], data: &[&[u8]]) -> Result<[u8; 20], SrtpError> {
+        let mut ctx = mbedtls_md_context_t::default();
+        let mut tag = [0; 20];
+        let ret = unsafe {
+            mbedtls_md_init(&mut ctx);
+            let mut ret = mbedtls_md_setup(
+                &mut ctx,
+                mbedtls_md_info_from_type(mbedtls_md_type_t_MBEDTLS_MD_SHA1),
+                1,
+            );
+            if ret == 0 {
+                ret = mbedtls_md_hmac_starts(&mut ctx, key.as_ptr(), key.len());
+            }
+            for d in data {
+                if ret == 0 {
+                    ret = mbedtls_md_hmac_update(&mut ctx, d.as_ptr(), d.len());
+                }
+            }
+            if ret == 0 {
+                ret = mbedtls_md_hmac_finish(&mut ctx, tag.as_mut_ptr());
+            }
+            mbedtls_md_free(&mut ctx);
+            ret
+        };
+        if ret != 0 {
+            return Err(SrtpError::SrtpCipherError(Box::new(
+                SSLError::SSLOtherError(ret),
+            )));
+        }
+        Ok(tag)
+    }
 }
 
 pub struct Esp32Dtls<C> {
@@ -445,6 +612,8 @@ pub enum SSLError {
     SSLConfigFailure(i32),
     #[error("srtp config failed {0}")]
     SSLSrtpConfigFailure(i32),
+    #[error("srtp keys were not exported by the handshake")]
+    SSLSrtpKeysUnavailable,
     #[error("entropy seed failed {0}")]
     SSLEntropySeedFailure(i32),
     #[error("ssl other error {0}")]
@@ -817,12 +986,12 @@ pub struct DtlsAcceptor(TlsHandshake<UdpMux>);
 impl IntoDtlsStream for DtlsAcceptor {}
 
 impl Future for DtlsAcceptor {
-    type Output = Result<Box<dyn crate::common::webrtc::dtls::DtlsStream>, DtlsError>;
+    type Output = Result<Box<dyn DtlsStream>, DtlsError>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         Pin::new(&mut self.0)
             .poll(cx)
             .map_err(DtlsError::DtlsSslError)
-            .map_ok(|s| Box::new(s) as Box<dyn crate::common::webrtc::dtls::DtlsStream>)
+            .map_ok(|s| Box::new(s) as Box<dyn DtlsStream>)
     }
 }
 
@@ -847,6 +1016,18 @@ impl<C: Certificate> DtlsConnector for Esp32Dtls<C> {
 
 unsafe impl<S> Send for AsyncSSLStream<S> {}
 
+impl DtlsStream for AsyncSSLStream<UdpMux> {
+    fn srtp_keying_material(&self) -> Result<SrtpKeyingMaterial, DtlsError> {
+        match &self.0.context {
+            SSLContext::DtlsSSLContext(context) => Ok(context.srtp_keying_material()?),
+            SSLContext::Esp32TLSContext(_) => Err(SSLError::SSLSrtpKeysUnavailable.into()),
+        }
+    }
+    fn srtp_cipher(&self) -> Box<dyn SrtpCipher> {
+        Box::new(Esp32SrtpCipher)
+    }
+}
+
 impl<S> AsyncRead for AsyncSSLStream<S>
 where
     S: AsyncRead + AsyncWrite,
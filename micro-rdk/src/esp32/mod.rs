@@ -5,6 +5,8 @@ pub mod board;
 #[cfg(all(feature = "camera", feature = "builtin-components"))]
 pub mod camera;
 pub mod certificate;
+#[cfg(feature = "data")]
+pub mod data_storage;
 pub mod dtls;
 #[cfg(feature = "builtin-components")]
 pub mod encoder;
@@ -1,15 +1,16 @@
 use super::pwm::PwmDriver;
 use crate::common::board::BoardError;
+use crate::common::digital_interrupt::InterruptEvents;
 use crate::esp32::esp_idf_svc::hal::gpio::{
     AnyIOPin, InputOutput, InterruptType, Pin, PinDriver, Pull,
 };
 use crate::esp32::esp_idf_svc::sys::{
-    esp, gpio_install_isr_service, gpio_isr_handler_add, ESP_INTR_FLAG_IRAM,
+    esp, esp_timer_get_time, gpio_install_isr_service, gpio_isr_handler_add, ESP_INTR_FLAG_IRAM,
     SOC_GPIO_VALID_OUTPUT_GPIO_MASK,
 };
 use once_cell::sync::{Lazy, OnceCell};
-use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub trait PinExt {
     fn pin(&self) -> i32;
@@ -57,7 +58,7 @@ pub struct Esp32GPIOPin {
     pin: i32,
     driver: PinDriver<'static, AnyIOPin, InputOutput>,
     interrupt_type: Option<InterruptType>,
-    event_count: Arc<AtomicU32>,
+    events: Arc<InterruptEvents>,
     pwm_driver: Option<PwmDriver<'static>>,
 }
 
@@ -75,7 +76,7 @@ impl Esp32GPIOPin {
             pin,
             driver,
             interrupt_type: None,
-            event_count: Arc::new(AtomicU32::new(0)),
+            events: Arc::new(InterruptEvents::default()),
             pwm_driver: None,
         })
     }
@@ -200,7 +201,7 @@ impl Esp32GPIOPin {
         self.driver
             .set_interrupt_type(intr_type)
             .map_err(|e| BoardError::GpioPinOtherError(self.pin as u32, Box::new(e)))?;
-        self.event_count.store(0, Ordering::Relaxed);
+        self.events.reset();
         unsafe {
             // we can't use the subscribe method on PinDriver to add the handler
             // because it requires an FnMut with a static lifetime. A possible follow-up
@@ -209,7 +210,7 @@ impl Esp32GPIOPin {
             esp!(gpio_isr_handler_add(
                 self.pin,
                 Some(Self::interrupt),
-                &mut self.event_count as *mut Arc<AtomicU32> as *mut _
+                &mut self.events as *mut Arc<InterruptEvents> as *mut _
             ))
             .map_err(|e| BoardError::GpioPinOtherError(self.pin as u32, Box::new(e)))?;
         }
@@ -217,13 +218,22 @@ impl Esp32GPIOPin {
     }
 
     pub fn get_event_count(&self) -> u32 {
-        self.event_count.load(Ordering::Relaxed)
+        self.events.count()
+    }
+
+    pub fn get_debounced_event_count(&self) -> u32 {
+        self.events.debounced_count()
+    }
+
+    pub fn set_debounce(&self, debounce: Duration) {
+        self.events.set_debounce(debounce);
     }
 
     #[inline(always)]
     #[link_section = ".iram1.intr_srv"]
     unsafe extern "C" fn interrupt(arg: *mut core::ffi::c_void) {
-        let arg: &mut Arc<AtomicU32> = &mut *(arg as *mut _);
-        arg.fetch_add(1, Ordering::Relaxed);
+        let arg: &mut Arc<InterruptEvents> = &mut *(arg as *mut _);
+        // esp_timer_get_time is safe to call from an interrupt handler
+        arg.record((esp_timer_get_time() / 1000) as u32);
     }
 }
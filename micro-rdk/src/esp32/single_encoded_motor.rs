@@ -15,11 +15,17 @@ use std::time::Duration;
 pub struct SingleEncodedMotor {
     encoder: SingleEncoderType,
     motor: MotorType,
+    // converts the ticks counted by the encoder into revolutions of the motor
+    ticks_per_rotation: u32,
 }
 
 impl SingleEncodedMotor {
-    pub fn new(motor: MotorType, encoder: SingleEncoderType) -> Self {
-        Self { encoder, motor }
+    pub fn new(motor: MotorType, encoder: SingleEncoderType, ticks_per_rotation: u32) -> Self {
+        Self {
+            encoder,
+            motor,
+            ticks_per_rotation,
+        }
     }
 }
 
@@ -64,6 +70,23 @@ impl Motor for SingleEncodedMotor {
         let pos = self.encoder.get_position(pos_type)?;
         Ok(pos.value as i32)
     }
+    fn get_position_revolutions(&mut self) -> Result<f64, MotorError> {
+        let props = self.encoder.get_properties();
+        if props.ticks_count_supported {
+            let ticks = self.encoder.get_position(EncoderPositionType::TICKS)?.value as f64;
+            return Ok(ticks / self.ticks_per_rotation as f64);
+        }
+        if props.angle_degrees_supported {
+            let degrees = self
+                .encoder
+                .get_position(EncoderPositionType::DEGREES)?
+                .value as f64;
+            return Ok(degrees / 360.0);
+        }
+        Err(MotorError::MotorMethodUnimplemented(
+            "encoder representation not supported",
+        ))
+    }
     fn go_for(&mut self, rpm: f64, revolutions: f64) -> Result<Option<Duration>, MotorError> {
         self.motor.go_for(rpm, revolutions)
     }
@@ -72,6 +95,9 @@ impl Motor for SingleEncodedMotor {
             position_reporting: true,
         }
     }
+    fn is_powered(&mut self) -> Result<(bool, f64), MotorError> {
+        self.motor.is_powered()
+    }
 }
 
 impl Actuator for SingleEncodedMotor {
@@ -123,17 +123,59 @@ pub mod proto {
         }
     }
 
+    #[cfg(feature = "camera")]
+    pub mod stream {
+        pub mod v1 {
+            include!("gen/proto.stream.v1.rs");
+        }
+    }
+
     pub mod robot {
         pub mod v1 {
             include!("gen/viam.robot.v1.rs");
         }
     }
     pub mod component {
+        pub mod arm {
+            pub mod v1 {
+                include!("gen/viam.component.arm.v1.rs");
+            }
+        }
         pub mod board {
             pub mod v1 {
                 include!("gen/viam.component.board.v1.rs");
             }
         }
+        pub mod pose_tracker {
+            pub mod v1 {
+                include!("gen/viam.component.posetracker.v1.rs");
+            }
+        }
+        pub mod input_controller {
+            pub mod v1 {
+                include!("gen/viam.component.inputcontroller.v1.rs");
+            }
+        }
+        pub mod switch {
+            pub mod v1 {
+                include!("gen/viam.component.switch.v1.rs");
+            }
+        }
+        pub mod button {
+            pub mod v1 {
+                include!("gen/viam.component.button.v1.rs");
+            }
+        }
+        pub mod gantry {
+            pub mod v1 {
+                include!("gen/viam.component.gantry.v1.rs");
+            }
+        }
+        pub mod gripper {
+            pub mod v1 {
+                include!("gen/viam.component.gripper.v1.rs");
+            }
+        }
         pub mod motor {
             pub mod v1 {
                 include!("gen/viam.component.motor.v1.rs");
@@ -174,6 +216,18 @@ pub mod proto {
             }
         }
     }
+    pub mod service {
+        pub mod datamanager {
+            pub mod v1 {
+                include!("gen/viam.service.datamanager.v1.rs");
+            }
+        }
+        pub mod sensors {
+            pub mod v1 {
+                include!("gen/viam.service.sensors.v1.rs");
+            }
+        }
+    }
 }
 
 #[macro_use]
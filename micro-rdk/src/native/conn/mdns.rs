@@ -1,5 +1,9 @@
 #![allow(dead_code)]
-use std::{collections::HashMap, net::Ipv4Addr, time::Duration};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
 
 use mdns_sd::{ServiceDaemon, ServiceInfo, UnregisterStatus};
 
@@ -9,6 +13,7 @@ pub struct NativeMdns {
     inner: ServiceDaemon,
     hostname: String,
     ip: Ipv4Addr,
+    ipv6: Option<Ipv6Addr>,
 }
 
 impl NativeMdns {
@@ -18,8 +23,21 @@ impl NativeMdns {
                 .map_err(|e| MdnsError::MdnsInitServiceError(e.to_string()))?,
             hostname,
             ip,
+            ipv6: None,
         })
     }
+    /// Also advertise services on an IPv6 address (AAAA record)
+    pub fn with_ipv6(mut self, ipv6: Option<Ipv6Addr>) -> Self {
+        self.ipv6 = ipv6;
+        self
+    }
+    // addresses of the service as a comma separated list, the IPv4 address is skipped when
+    // unspecified (IPv6 only network)
+    fn addresses(&self) -> String {
+        let ipv4 = (!self.ip.is_unspecified()).then(|| self.ip.to_string());
+        let ipv6 = self.ipv6.map(|ip| ip.to_string());
+        ipv4.into_iter().chain(ipv6).collect::<Vec<_>>().join(",")
+    }
     pub(crate) fn daemon(&self) -> ServiceDaemon {
         self.inner.clone()
     }
@@ -43,7 +61,7 @@ impl NativeMdns {
             &ty_domain,
             instance_name,
             &srv_hostname,
-            format!("{}", self.ip),
+            self.addresses(),
             port,
             props,
         )
@@ -82,6 +100,11 @@ impl NativeMdns {
         self.hostname = hostname.to_owned();
         Ok(())
     }
+    fn set_addresses(&mut self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        self.ip = ip;
+        self.ipv6 = ipv6;
+        Ok(())
+    }
 }
 
 impl Drop for NativeMdns {
@@ -103,6 +126,9 @@ impl Mdns for NativeMdns {
     fn set_hostname(&mut self, hostname: &str) -> Result<(), MdnsError> {
         self.set_hostname(hostname)
     }
+    fn set_addresses(&mut self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        self.set_addresses(ip, ipv6)
+    }
     fn remove_service(
         &mut self,
         instance_name: &str,
@@ -127,6 +153,9 @@ impl Mdns for &mut NativeMdns {
     fn set_hostname(&mut self, hostname: &str) -> Result<(), MdnsError> {
         (*self).set_hostname(hostname)
     }
+    fn set_addresses(&mut self, ip: Ipv4Addr, ipv6: Option<Ipv6Addr>) -> Result<(), MdnsError> {
+        (*self).set_addresses(ip, ipv6)
+    }
     fn remove_service(
         &mut self,
         instance_name: &str,
@@ -0,0 +1,133 @@
+//! File backed storage for the [PersistentDataStore](crate::common::persistent_data_store::PersistentDataStore)
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::common::{data_store::DataStoreError, persistent_data_store::DataStoreStorage};
+
+/// Environment variable overriding the location of the default data store file
+pub const DATA_STORE_PATH_ENV: &str = "MICRO_RDK_DATA_STORE_PATH";
+const DEFAULT_DATA_STORE_FILE: &str = "micro-rdk-data-store.bin";
+const DEFAULT_CAPACITY: usize = 4 * 1024 * 1024;
+const SECTOR_SIZE: usize = 4096;
+
+/// Emulates a flash partition with a file, erased bytes are set to 0xFF.
+pub struct FileDataStorage {
+    file: File,
+    capacity: usize,
+}
+
+impl FileDataStorage {
+    /// Opens (or creates) the file at `path`, growing it to `capacity` bytes (rounded down to
+    /// a whole number of sectors) if needed
+    pub fn open(path: impl AsRef<Path>, capacity: usize) -> Result<Self, DataStoreError> {
+        let capacity = capacity - capacity % SECTOR_SIZE;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())
+            .map_err(|err| DataStoreError::StorageError(err.to_string()))?;
+        let len = file
+            .metadata()
+            .map_err(|err| DataStoreError::StorageError(err.to_string()))?
+            .len() as usize;
+        if len < capacity {
+            file.seek(SeekFrom::Start(len as u64))
+                .and_then(|_| file.write_all(&vec![0xFF; capacity - len]))
+                .and_then(|_| file.sync_all())
+                .map_err(|err| DataStoreError::StorageError(err.to_string()))?;
+        }
+        Ok(Self { file, capacity })
+    }
+
+    fn default_path() -> PathBuf {
+        std::env::var_os(DATA_STORE_PATH_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DATA_STORE_FILE))
+    }
+}
+
+impl DataStoreStorage for FileDataStorage {
+    fn sector_size(&self) -> usize {
+        SECTOR_SIZE
+    }
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+    fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), DataStoreError> {
+        self.file
+            .seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| self.file.read_exact(buf))
+            .map_err(|err| DataStoreError::StorageError(err.to_string()))
+    }
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), DataStoreError> {
+        self.file
+            .seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| self.file.write_all(data))
+            .and_then(|_| self.file.sync_data())
+            .map_err(|err| DataStoreError::StorageError(err.to_string()))
+    }
+    fn erase_sector(&mut self, offset: usize) -> Result<(), DataStoreError> {
+        self.write(offset, &[0xFF; SECTOR_SIZE])
+    }
+    fn open_default() -> Result<Self, DataStoreError> {
+        Self::open(Self::default_path(), DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::common::data_collector::{CollectionMethod, ResourceMethodKey};
+    use crate::common::data_store::{DataStore, DataStoreReader, WriteMode};
+    use crate::common::persistent_data_store::PersistentDataStore;
+    use crate::google::protobuf::{value::Kind, Struct, Value};
+    use crate::native::data_storage::FileDataStorage;
+    use crate::proto::app::data_sync::v1::{sensor_data::Data, SensorData};
+
+    #[test_log::test]
+    fn test_file_data_storage_persists() {
+        let path =
+            std::env::temp_dir().join(format!("micro-rdk-data-store-{}.bin", uuid::Uuid::new_v4()));
+        let key = ResourceMethodKey {
+            r_name: "r1".to_string(),
+            component_type: "rdk:component:sensor".to_string(),
+            method: CollectionMethod::Readings,
+        };
+        let message = SensorData {
+            metadata: None,
+            data: Some(Data::Struct(Struct {
+                fields: HashMap::from([(
+                    "thing".to_string(),
+                    Value {
+                        kind: Some(Kind::NumberValue(1.0)),
+                    },
+                )]),
+            })),
+        };
+
+        {
+            let storage = FileDataStorage::open(&path, 64 * 1024).unwrap();
+            let mut store = PersistentDataStore::new(storage, vec![(key.clone(), 100)]).unwrap();
+            store
+                .write_message(&key, message.clone(), WriteMode::PreserveOrFail)
+                .unwrap();
+        }
+
+        let storage = FileDataStorage::open(&path, 64 * 1024).unwrap();
+        let store = PersistentDataStore::new(storage, vec![(key.clone(), 100)]).unwrap();
+        let mut reader = store.get_reader(&key).unwrap();
+        assert_eq!(reader.messages_remaining().unwrap(), 1);
+        let read = reader.read_next_message().unwrap();
+        assert_eq!(read.to_vec(), prost::Message::encode_to_vec(&message));
+        drop(reader);
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -8,8 +8,11 @@ use std::{fs::OpenOptions, rc::Rc};
 use futures_lite::Future;
 use openssl::ec::EcKey;
 
+use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
 use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use openssl::symm::{Cipher, Crypter, Mode};
 
 use openssl::ssl::{
     Ssl, SslContext, SslContextBuilder, SslMethod, SslOptions, SslRef, SslVerifyMode,
@@ -19,6 +22,10 @@ use crate::common::webrtc::certificate::Certificate;
 use crate::common::webrtc::dtls::{
     DtlsBuilder, DtlsConnector, DtlsError, DtlsStream, IntoDtlsStream,
 };
+use crate::common::webrtc::srtp::{
+    SrtpCipher, SrtpError, SrtpKeyingMaterial, SRTP_EXPORTER_LABEL, SRTP_KEYING_MATERIAL_LEN,
+    SRTP_MASTER_KEY_LEN,
+};
 use crate::common::webrtc::udp_mux::UdpMux;
 
 fn dtls_log_session_key(_: &SslRef, line: &str) {
@@ -104,6 +111,58 @@ impl Dtls {
     }
 }
 
+impl DtlsStream for async_std_openssl::SslStream<UdpMux> {
+    fn srtp_keying_material(&self) -> Result<SrtpKeyingMaterial, DtlsError> {
+        let mut material = [0; SRTP_KEYING_MATERIAL_LEN];
+        self.ssl()
+            .export_keying_material(&mut material, SRTP_EXPORTER_LABEL, None)
+            .map_err(|e| DtlsError::DtlsError(Box::new(e)))?;
+        Ok(SrtpKeyingMaterial::new(material))
+    }
+    fn srtp_cipher(&self) -> Box<dyn SrtpCipher> {
+        Box::new(NativeSrtpCipher)
+    }
+}
+
+pub struct NativeSrtpCipher;
+
+impl SrtpCipher for NativeSrtpCipher {
+    fn aes_128_ctr(
+        &mut self,
+        key: &[u8; SRTP_MASTER_KEY_LEN],
+        iv: &[u8; 16],
+        data: &mut [u8],
+    ) -> Result<(), SrtpError> {
+        let cipher = Cipher::aes_128_ctr();
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, key, Some(iv))
+            .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        let mut out = vec![0; data.len() + cipher.block_size()];
+        let len = crypter
+            .update(data, &mut out)
+            .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        let _ = crypter
+            .finalize(&mut out[len..])
+            .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        data.copy_from_slice(&out[..data.len()]);
+        Ok(())
+    }
+    fn hmac_sha1(&mut self, key: &[u8], data: &[&[u8]]) -> Result<[u8; 20], SrtpError> {
+        let key = PKey::hmac(key).map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        let mut signer = Signer::new(MessageDigest::sha1(), &key)
+            .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        for d in data {
+            signer
+                .update(d)
+                .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        }
+        let mut tag = [0; 20];
+        let _ = signer
+            .sign(&mut tag)
+            .map_err(|e| SrtpError::SrtpCipherError(Box::new(e)))?;
+        Ok(tag)
+    }
+}
+
 pub struct DtlsAcceptor(Option<async_std_openssl::SslStream<UdpMux>>);
 impl IntoDtlsStream for DtlsAcceptor {}
 
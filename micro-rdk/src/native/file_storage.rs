@@ -0,0 +1,466 @@
+//! Directory backed credentials and configuration storage.
+//!
+//! Every value is kept in its own file, named after the key `NVSStorage` uses for it. Writes
+//! go to a temporary file which is synced and then renamed over the previous value, so a crash
+//! or power loss leaves either the old or the new value on disk, never a partially written one.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use hyper::{http::uri::InvalidUri, Uri};
+use prost::Message;
+use thiserror::Error;
+
+use crate::{
+    common::{
+        config::NetworkSetting,
+        credentials_storage::{
+            EmptyStorageCollectionError, RobotConfigurationStorage, RobotCredentials,
+            StorageDiagnostic, TlsCertificate, WifiCredentialStorage,
+        },
+        grpc::{GrpcError, ServerError},
+    },
+    proto::{app::v1::RobotConfig, provisioning::v1::CloudConfig},
+};
+
+/// Environment variable overriding the directory used by [FileStorage::open_default]
+pub const STORAGE_PATH_ENV: &str = "MICRO_RDK_STORAGE_PATH";
+const DEFAULT_STORAGE_DIR: &str = "micro-rdk-storage";
+const TMP_SUFFIX: &str = ".tmp";
+
+#[derive(Error, Debug)]
+pub enum FileStorageError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("storage key {0} is absent")]
+    KeyAbsent(String),
+    #[error("storage key {0} does not hold valid utf-8")]
+    InvalidString(String),
+    #[error(transparent)]
+    ValueDecodeError(#[from] prost::DecodeError),
+    #[error(transparent)]
+    NetworkSettingsError(#[from] postcard::Error),
+    #[error(transparent)]
+    UriParseError(#[from] InvalidUri),
+    #[error("storage collection empty")]
+    CollectionEmpty(#[from] EmptyStorageCollectionError),
+}
+
+/// Storage keeping each value in a file of a directory, with the same semantics as
+/// `NVSStorage`
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    /// Opens the storage rooted at `path`, creating the directory if needed
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, FileStorageError> {
+        let root = path.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        // leftovers of a write interrupted before its rename are never valid values
+        for entry in fs::read_dir(&root)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().ends_with(TMP_SUFFIX) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        Ok(Self { root })
+    }
+
+    /// Opens the storage in the directory named by `MICRO_RDK_STORAGE_PATH`, or
+    /// `micro-rdk-storage` in the working directory when it is not set
+    pub fn open_default() -> Result<Self, FileStorageError> {
+        Self::new(
+            std::env::var_os(STORAGE_PATH_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_STORAGE_DIR)),
+        )
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    fn get_blob(&self, key: &str) -> Result<Vec<u8>, FileStorageError> {
+        fs::read(self.root.join(key)).map_err(|err| match err.kind() {
+            ErrorKind::NotFound => FileStorageError::KeyAbsent(key.to_string()),
+            _ => err.into(),
+        })
+    }
+
+    fn set_blob(&self, key: &str, bytes: &[u8]) -> Result<(), FileStorageError> {
+        if self.get_blob(key).is_ok_and(|current| current == bytes) {
+            log::debug!("no change in write to storage key {:?}, skipping", key);
+            return Ok(());
+        }
+        let tmp_path = self.root.join(format!("{}{}", key, TMP_SUFFIX));
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // values include machine secrets and private keys
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(bytes)
+            .and_then(|_| file.sync_all())
+            .and_then(|_| fs::rename(&tmp_path, self.root.join(key)))
+            .inspect_err(|_| {
+                let _ = fs::remove_file(&tmp_path);
+            })?;
+        self.sync_dir();
+        Ok(())
+    }
+
+    fn has_blob(&self, key: &str) -> bool {
+        self.root.join(key).is_file()
+    }
+
+    fn get_string(&self, key: &str) -> Result<String, FileStorageError> {
+        String::from_utf8(self.get_blob(key)?)
+            .map_err(|_| FileStorageError::InvalidString(key.to_string()))
+    }
+
+    fn set_string(&self, key: &str, string: &str) -> Result<(), FileStorageError> {
+        self.set_blob(key, string.as_bytes())
+    }
+
+    fn erase_key(&self, key: &str) -> Result<(), FileStorageError> {
+        match fs::remove_file(self.root.join(key)) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => {
+                self.sync_dir();
+                Ok(())
+            }
+        }
+    }
+
+    // persists renames and removals; directories cannot be opened for syncing on every
+    // platform so failures are ignored
+    fn sync_dir(&self) {
+        if let Ok(dir) = File::open(&self.root) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+impl StorageDiagnostic for FileStorage {
+    fn log_space_diagnostic(&self) {
+        let used_space: u64 = match fs::read_dir(&self.root) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok()?.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum(),
+            Err(err) => {
+                log::error!("could not acquire storage stats: {:?}", err);
+                return;
+            }
+        };
+        log::info!(
+            "storage stats: {:?} bytes used in {:?}",
+            used_space,
+            self.root
+        );
+    }
+}
+
+const DEFAULT_SSID_KEY: &str = "WIFI_SSID";
+const DEFAULT_PASSWORD_KEY: &str = "WIFI_PASSWORD";
+const ROBOT_SECRET_KEY: &str = "ROBOT_SECRET";
+const ROBOT_ID_KEY: &str = "ROBOT_ID";
+const ROBOT_APP_ADDRESS: &str = "ROBOT_APP_ADDR";
+
+const ROBOT_CONFIG_KEY: &str = "ROBOT_CONFIG";
+const TLS_CERTIFICATE_KEY: &str = "TLS_CERT";
+const TLS_PRIVATE_KEY_KEY: &str = "TLS_PRIV_KEY";
+const NETWORK_SETTINGS_KEY: &str = "NETWORKS";
+
+#[cfg(feature = "ota")]
+const OTA_VERSION_KEY: &str = "OTA_VERSION";
+#[cfg(feature = "ota")]
+use crate::common::{credentials_storage::OtaMetadataStorage, ota::OtaMetadata};
+
+#[cfg(feature = "ota")]
+impl OtaMetadataStorage for FileStorage {
+    type Error = FileStorageError;
+    fn has_ota_metadata(&self) -> bool {
+        self.has_blob(OTA_VERSION_KEY)
+    }
+    fn get_ota_metadata(&self) -> Result<OtaMetadata, Self::Error> {
+        let version = self.get_string(OTA_VERSION_KEY)?;
+        Ok(OtaMetadata { version })
+    }
+    fn store_ota_metadata(&self, ota_metadata: &OtaMetadata) -> Result<(), Self::Error> {
+        self.set_string(OTA_VERSION_KEY, &ota_metadata.version)
+    }
+    fn reset_ota_metadata(&self) -> Result<(), Self::Error> {
+        self.erase_key(OTA_VERSION_KEY)
+    }
+}
+
+impl RobotConfigurationStorage for FileStorage {
+    type Error = FileStorageError;
+    fn has_robot_credentials(&self) -> bool {
+        self.has_blob(ROBOT_SECRET_KEY) && self.has_blob(ROBOT_ID_KEY)
+    }
+
+    fn get_robot_credentials(&self) -> Result<RobotCredentials, Self::Error> {
+        let robot_secret = self.get_string(ROBOT_SECRET_KEY)?;
+        let robot_id = self.get_string(ROBOT_ID_KEY)?;
+        Ok(RobotCredentials {
+            robot_secret,
+            robot_id,
+        })
+    }
+
+    fn get_app_address(&self) -> Result<Uri, Self::Error> {
+        Ok(self.get_string(ROBOT_APP_ADDRESS)?.parse::<Uri>()?)
+    }
+
+    fn has_app_address(&self) -> bool {
+        self.has_blob(ROBOT_APP_ADDRESS)
+    }
+
+    fn store_app_address(&self, uri: &str) -> Result<(), Self::Error> {
+        self.set_string(ROBOT_APP_ADDRESS, uri)
+    }
+
+    fn reset_app_address(&self) -> Result<(), Self::Error> {
+        self.erase_key(ROBOT_APP_ADDRESS)
+    }
+
+    fn store_robot_credentials(&self, cfg: &CloudConfig) -> Result<(), Self::Error> {
+        self.set_string(ROBOT_SECRET_KEY, &cfg.secret)?;
+        self.set_string(ROBOT_ID_KEY, &cfg.id).inspect_err(|_| {
+            let _ = self.erase_key(ROBOT_SECRET_KEY);
+        })?;
+        self.set_string(ROBOT_APP_ADDRESS, &cfg.app_address)
+            .inspect_err(|_| {
+                let _ = self.erase_key(ROBOT_SECRET_KEY);
+                let _ = self.erase_key(ROBOT_ID_KEY);
+            })?;
+        Ok(())
+    }
+
+    fn reset_robot_credentials(&self) -> Result<(), Self::Error> {
+        self.erase_key(ROBOT_SECRET_KEY)?;
+        self.erase_key(ROBOT_ID_KEY)?;
+        Ok(())
+    }
+
+    fn has_robot_configuration(&self) -> bool {
+        self.has_blob(ROBOT_CONFIG_KEY)
+    }
+
+    fn store_robot_configuration(&self, cfg: &RobotConfig) -> Result<(), Self::Error> {
+        self.set_blob(ROBOT_CONFIG_KEY, &cfg.encode_to_vec())
+    }
+
+    fn get_robot_configuration(&self) -> Result<RobotConfig, Self::Error> {
+        let robot_config = self.get_blob(ROBOT_CONFIG_KEY)?;
+        Ok(RobotConfig::decode(&robot_config[..])?)
+    }
+
+    fn reset_robot_configuration(&self) -> Result<(), Self::Error> {
+        self.erase_key(ROBOT_CONFIG_KEY)
+    }
+
+    fn has_tls_certificate(&self) -> bool {
+        self.has_blob(TLS_CERTIFICATE_KEY) && self.has_blob(TLS_PRIVATE_KEY_KEY)
+    }
+
+    fn get_tls_certificate(&self) -> Result<TlsCertificate, Self::Error> {
+        let certificate = self.get_blob(TLS_CERTIFICATE_KEY)?;
+        let private_key = self.get_blob(TLS_PRIVATE_KEY_KEY)?;
+        Ok(TlsCertificate {
+            certificate,
+            private_key,
+        })
+    }
+
+    fn store_tls_certificate(&self, creds: &TlsCertificate) -> Result<(), Self::Error> {
+        self.set_blob(TLS_CERTIFICATE_KEY, &creds.certificate)?;
+        self.set_blob(TLS_PRIVATE_KEY_KEY, &creds.private_key)
+            .inspect_err(|_| {
+                let _ = self.erase_key(TLS_CERTIFICATE_KEY);
+            })?;
+        Ok(())
+    }
+
+    fn reset_tls_certificate(&self) -> Result<(), Self::Error> {
+        self.erase_key(TLS_CERTIFICATE_KEY)?;
+        self.erase_key(TLS_PRIVATE_KEY_KEY)?;
+        Ok(())
+    }
+}
+
+impl WifiCredentialStorage for FileStorage {
+    type Error = FileStorageError;
+    fn has_network_settings(&self) -> bool {
+        self.has_blob(NETWORK_SETTINGS_KEY)
+    }
+
+    fn get_network_settings(&self) -> Result<Vec<NetworkSetting>, Self::Error> {
+        let blob = self.get_blob(NETWORK_SETTINGS_KEY)?;
+        Ok(postcard::from_bytes(&blob)?)
+    }
+
+    fn store_network_settings(
+        &self,
+        network_settings: &[NetworkSetting],
+    ) -> Result<(), Self::Error> {
+        let bytes: Vec<u8> = postcard::to_allocvec(&network_settings)?;
+        self.set_blob(NETWORK_SETTINGS_KEY, &bytes)
+    }
+
+    fn reset_network_settings(&self) -> Result<(), Self::Error> {
+        self.erase_key(NETWORK_SETTINGS_KEY)
+    }
+
+    fn has_default_network(&self) -> bool {
+        self.has_blob(DEFAULT_SSID_KEY) && self.has_blob(DEFAULT_PASSWORD_KEY)
+    }
+
+    fn get_default_network(&self) -> Result<NetworkSetting, Self::Error> {
+        let ssid = self.get_string(DEFAULT_SSID_KEY)?;
+        let password = self.get_string(DEFAULT_PASSWORD_KEY)?;
+        Ok(NetworkSetting {
+            ssid,
+            password,
+            priority: 0,
+        })
+    }
+
+    fn get_all_networks(&self) -> Result<Vec<NetworkSetting>, Self::Error> {
+        // return error if failed to get default network (not provisioned/configured)
+        let default = self.get_default_network()?;
+        let mut networks = if self.has_network_settings() {
+            self.get_network_settings()
+                .inspect_err(|e| log::error!("failed to retrieve stored networks: {}", e))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        networks.push(default);
+        Ok(networks)
+    }
+
+    fn store_default_network(&self, ssid: &str, password: &str) -> Result<(), Self::Error> {
+        self.set_string(DEFAULT_SSID_KEY, ssid)?;
+        self.set_string(DEFAULT_PASSWORD_KEY, password)
+            .inspect_err(|_| {
+                let _ = self.erase_key(DEFAULT_SSID_KEY);
+            })?;
+        Ok(())
+    }
+
+    fn reset_default_network(&self) -> Result<(), Self::Error> {
+        self.erase_key(DEFAULT_SSID_KEY)?;
+        self.erase_key(DEFAULT_PASSWORD_KEY)?;
+        Ok(())
+    }
+}
+
+impl From<FileStorageError> for ServerError {
+    fn from(value: FileStorageError) -> Self {
+        Self::new(GrpcError::RpcUnavailable, Some(value.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::config::NetworkSetting;
+    use crate::common::credentials_storage::{
+        RobotConfigurationStorage, TlsCertificate, WifiCredentialStorage,
+    };
+    use crate::native::file_storage::{FileStorage, FileStorageError};
+    use crate::proto::{
+        app::v1::{CloudConfig as AppCloudConfig, RobotConfig},
+        provisioning::v1::CloudConfig,
+    };
+
+    #[test_log::test]
+    fn test_file_storage_persists() {
+        let path = std::env::temp_dir().join(format!("micro-rdk-storage-{}", uuid::Uuid::new_v4()));
+        {
+            let storage = FileStorage::new(&path).unwrap();
+            assert!(!storage.has_robot_credentials());
+            assert!(matches!(
+                storage.get_robot_credentials(),
+                Err(FileStorageError::KeyAbsent(_))
+            ));
+            // resetting absent keys is not an error
+            assert!(storage.reset_robot_credentials().is_ok());
+
+            storage
+                .store_robot_credentials(&CloudConfig {
+                    app_address: "http://downloadfilestorage.org".to_owned(),
+                    id: "file".to_owned(),
+                    secret: "secret".to_owned(),
+                })
+                .unwrap();
+            storage
+                .store_robot_configuration(&RobotConfig {
+                    cloud: Some(AppCloudConfig {
+                        fqdn: "file.local".to_owned(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .unwrap();
+            storage
+                .store_tls_certificate(&TlsCertificate {
+                    certificate: b"cert".to_vec(),
+                    private_key: b"key".to_vec(),
+                })
+                .unwrap();
+            storage.store_default_network("ssid", "password").unwrap();
+            storage
+                .store_network_settings(&[NetworkSetting {
+                    ssid: "other".to_owned(),
+                    password: "other_password".to_owned(),
+                    priority: 1,
+                }])
+                .unwrap();
+        }
+
+        // a write interrupted before its rename leaves a temporary file behind
+        std::fs::write(path.join("ROBOT_CONFIG.tmp"), b"garbage").unwrap();
+
+        let storage = FileStorage::new(&path).unwrap();
+        assert!(!path.join("ROBOT_CONFIG.tmp").exists());
+        let creds = storage.get_robot_credentials().unwrap();
+        assert_eq!(creds.robot_id(), "file");
+        assert_eq!(creds.robot_secret(), "secret");
+        assert_eq!(
+            storage.get_app_address().unwrap().host(),
+            Some("downloadfilestorage.org")
+        );
+        assert_eq!(
+            storage
+                .get_robot_configuration()
+                .unwrap()
+                .cloud
+                .unwrap()
+                .fqdn,
+            "file.local"
+        );
+        let cert = storage.get_tls_certificate().unwrap();
+        assert_eq!(cert.certificate, b"cert");
+        assert_eq!(cert.private_key, b"key");
+        let networks = storage.get_all_networks().unwrap();
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0].ssid, "other");
+        assert_eq!(networks[1].ssid, "ssid");
+
+        assert!(storage.reset_robot_configuration().is_ok());
+        assert!(!storage.has_robot_configuration());
+        assert!(storage.reset_tls_certificate().is_ok());
+        assert!(!storage.has_tls_certificate());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+}